@@ -124,8 +124,15 @@ pub type Executive = frame_executive::Executive<
 	frame_system::ChainContext<Runtime>,
 	Runtime,
 	AllPalletsWithSystem,
+	Migrations,
 >;
 
+/// The migrations to run on the next runtime upgrade.
+pub type Migrations = (
+	zenlink_protocol::migrations::v1::MigrateToV1<Runtime>,
+	zenlink_stable_amm::migrations::v1::MigrateToV1<Runtime>,
+);
+
 /// Handles converting a weight scalar to a fee value, based on the scale and granularity of the
 /// node's balance type.
 ///
@@ -21,7 +21,9 @@ parameter_types! {
 	];
 	pub const StringLimit: u32 = 50;
 	pub const StableAmmPalletId: PalletId = PalletId(*b"bf/stamm");
+	pub const StableAmmOracleEmaPeriod: u64 = 600;
 	pub const VaultPalletId: PalletId = PalletId(*b"bf/vault");
+	pub const VaultBlocksPerYear: BlockNumber = DAYS * 365;
 }
 
 impl zenlink_protocol::Config for Runtime {
@@ -139,6 +141,7 @@ impl zenlink_stable_amm::Config for Runtime {
 	type EnsurePoolAsset = StableAmmVerifyPoolAsset;
 	type LpGenerate = PoolLpGenerate;
 	type PoolCurrencySymbolLimit = StringLimit;
+	type OracleEmaPeriod = StableAmmOracleEmaPeriod;
 	type PalletId = StableAmmPalletId;
 	type WeightInfo = ();
 }
@@ -171,10 +174,27 @@ impl zenlink_swap_router::Config for Runtime {
 	type StableCurrencyId = CurrencyId;
 	type NormalCurrencyId = AssetId;
 	type NormalAmm = ZenlinkProtocol;
+	type CurrencyConverter = RouterCurrencyConverter;
+	type MaxHops = frame_support::traits::ConstU32<3>;
+	type MaxSplits = frame_support::traits::ConstU32<4>;
 	type StableAMM = ZenlinkStableAmm;
+	type VaultRuntime = Runtime;
+	type Vault = ZenlinkVault;
 	type WeightInfo = ();
 }
 
+pub struct RouterCurrencyConverter;
+
+impl zenlink_swap_router::CurrencyConverter<AssetId, CurrencyId> for RouterCurrencyConverter {
+	fn to_stable(asset_id: AssetId) -> Option<CurrencyId> {
+		asset_id.try_into().ok()
+	}
+
+	fn to_normal(currency_id: CurrencyId) -> Option<AssetId> {
+		currency_id.try_into().ok()
+	}
+}
+
 pub struct VaultAssetGenerator;
 
 impl VaultAssetGenerate<CurrencyId> for VaultAssetGenerator {
@@ -191,6 +211,8 @@ impl zenlink_vault::Config for Runtime {
 	type AssetId = CurrencyId;
 	type MultiAsset = Tokens;
 	type VaultAssetGenerate = VaultAssetGenerator;
+	type Strategy = ();
+	type BlocksPerYear = VaultBlocksPerYear;
 	type PalletId = VaultPalletId;
 	type WeightInfo = ();
 }
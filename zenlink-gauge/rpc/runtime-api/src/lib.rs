@@ -0,0 +1,32 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+//! Runtime API definition for gauge.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+use codec::Codec;
+use sp_std::vec::Vec;
+use zenlink_gauge::{primitives::PeriodId, GaugePeriodInfo, GaugePoolInfo, GaugeRewardEstimate};
+
+sp_api::decl_runtime_apis! {
+	pub trait GaugeApi<AccountId, PoolId, CurrencyId, Balance> where
+		AccountId: Codec,
+		PoolId: Codec,
+		CurrencyId: Codec,
+		Balance: Codec,
+	{
+		fn current_period() -> Option<GaugePeriodInfo>;
+
+		fn pool_state(period_id: PeriodId, pool_id: PoolId) -> Option<GaugePoolInfo<PoolId, Balance>>;
+
+		fn account_votes(who: AccountId) -> Vec<(PoolId, Balance)>;
+
+		fn period_results(period_id: PeriodId) -> Vec<(PoolId, Balance)>;
+
+		fn estimate_rewards(who: AccountId, pool_id: PoolId) -> Vec<GaugeRewardEstimate<CurrencyId, Balance>>;
+	}
+}
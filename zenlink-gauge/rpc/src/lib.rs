@@ -0,0 +1,205 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+//! RPC interface for the gauge pallet.
+#![allow(clippy::type_complexity)]
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::traits::{Block as BlockT, MaybeDisplay};
+use std::sync::Arc;
+
+use zenlink_gauge::{primitives::PeriodId, GaugePeriodInfo, GaugePoolInfo, GaugeRewardEstimate};
+use zenlink_gauge_runtime_api::GaugeApi as GaugeRuntimeApi;
+
+#[rpc(server)]
+pub trait GaugeApi<BlockHash, AccountId, PoolId, CurrencyId, Balance> {
+	#[method(name = "zenlinkGauge_currentPeriod")]
+	fn current_period(&self, at: Option<BlockHash>) -> RpcResult<Option<GaugePeriodInfo>>;
+
+	#[method(name = "zenlinkGauge_poolState")]
+	fn pool_state(
+		&self,
+		period_id: PeriodId,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<GaugePoolInfo<PoolId, NumberOrHex>>>;
+
+	#[method(name = "zenlinkGauge_accountVotes")]
+	fn account_votes(
+		&self,
+		who: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<(PoolId, NumberOrHex)>>;
+
+	#[method(name = "zenlinkGauge_periodResults")]
+	fn period_results(
+		&self,
+		period_id: PeriodId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<(PoolId, NumberOrHex)>>;
+
+	#[method(name = "zenlinkGauge_estimateRewards")]
+	fn estimate_rewards(
+		&self,
+		who: AccountId,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<GaugeRewardEstimate<CurrencyId, NumberOrHex>>>;
+}
+
+pub struct Gauge<C, M> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> Gauge<C, M> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, AccountId, PoolId, CurrencyId, Balance>
+	GaugeApiServer<<Block as BlockT>::Hash, AccountId, PoolId, CurrencyId, Balance>
+	for Gauge<C, Block>
+where
+	Block: BlockT,
+	AccountId: Codec,
+	PoolId: Codec,
+	CurrencyId: Codec,
+	Balance: Codec + TryInto<NumberOrHex> + std::fmt::Debug + MaybeDisplay + Copy,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block>,
+	C::Api: GaugeRuntimeApi<Block, AccountId, PoolId, CurrencyId, Balance>,
+{
+	fn current_period(
+		&self,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<GaugePeriodInfo>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.current_period(at).map_err(runtime_error_into_rpc_err)
+	}
+
+	fn pool_state(
+		&self,
+		period_id: PeriodId,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<GaugePoolInfo<PoolId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.pool_state(at, period_id, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(|info| {
+				Ok(GaugePoolInfo {
+					pool_id: info.pool_id,
+					votable: info.votable,
+					score: try_into_rpc_balance(info.score)?,
+					total_amount: try_into_rpc_balance(info.total_amount)?,
+				})
+			})
+			.transpose()
+	}
+
+	fn account_votes(
+		&self,
+		who: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<(PoolId, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.account_votes(at, who)
+			.map_err(runtime_error_into_rpc_err)?
+			.into_iter()
+			.map(|(pool_id, amount)| Ok((pool_id, try_into_rpc_balance(amount)?)))
+			.collect()
+	}
+
+	fn period_results(
+		&self,
+		period_id: PeriodId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<(PoolId, NumberOrHex)>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.period_results(at, period_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.into_iter()
+			.map(|(pool_id, score)| Ok((pool_id, try_into_rpc_balance(score)?)))
+			.collect()
+	}
+
+	fn estimate_rewards(
+		&self,
+		who: AccountId,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<GaugeRewardEstimate<CurrencyId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.estimate_rewards(at, who, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.into_iter()
+			.map(|estimate| {
+				Ok(GaugeRewardEstimate {
+					currency_id: estimate.currency_id,
+					pending: try_into_rpc_balance(estimate.pending)?,
+					projected: try_into_rpc_balance(estimate.projected)?,
+				})
+			})
+			.collect()
+	}
+}
+
+fn try_into_rpc_balance<
+	Balance: Codec + TryInto<NumberOrHex> + MaybeDisplay + Copy + std::fmt::Debug,
+>(
+	value: Balance,
+) -> RpcResult<NumberOrHex> {
+	value.try_into().map_err(|_| {
+		CallError::Custom(ErrorObject::owned(
+			Error::RuntimeError.into(),
+			"error in gauge pallet",
+			Some("transfer into rpc balance".to_string()),
+		))
+		.into()
+	})
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Display) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"error in gauge pallet",
+		Some(err.to_string()),
+	))
+	.into()
+}
@@ -0,0 +1,221 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	/// The account holding the bribes.
+	pub fn bribe_account() -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating(b"bribe")
+	}
+
+	/// The bribes of a pool in a period `who` can claim, by currency.
+	pub fn claimable_bribes(
+		who: &T::AccountId,
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+	) -> Vec<(T::CurrencyId, Balance)> {
+		let total_score = Self::period_vote_score(period_id, pool_id);
+		let score = Self::account_period_score((period_id, pool_id, who));
+		if total_score.is_zero() ||
+			score.is_zero() ||
+			Self::bribe_claimed((period_id, pool_id, who))
+		{
+			return Vec::new()
+		}
+
+		Bribes::<T>::iter_prefix((period_id, pool_id))
+			.filter_map(|(currency_id, bribe)| {
+				balance_mul_div(bribe.amount, score, total_score)
+					.filter(|amount| !amount.is_zero())
+					.map(|amount| (currency_id, amount))
+			})
+			.collect()
+	}
+
+	/// Ensure a bribe in `currency_id` may be added to a pool in a period without exceeding the
+	/// currencies allowed.
+	fn ensure_bribe_currency(
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+		currency_id: T::CurrencyId,
+	) -> DispatchResult {
+		if Bribes::<T>::contains_key((period_id, pool_id, currency_id)) {
+			return Ok(())
+		}
+		ensure!(
+			(Bribes::<T>::iter_key_prefix((period_id, pool_id)).count() as u32) <
+				T::MaxBribeCurrencies::get(),
+			Error::<T>::TooManyBribeCurrencies
+		);
+		Ok(())
+	}
+
+	pub(crate) fn add_vote_score(
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+		who: &T::AccountId,
+		score: Balance,
+	) -> DispatchResult {
+		AccountPeriodScore::<T>::try_mutate(
+			(period_id, pool_id, who),
+			|account_score| -> DispatchResult {
+				*account_score = account_score.checked_add(score).ok_or(Error::<T>::Math)?;
+				PeriodVoteScore::<T>::try_mutate(
+					period_id,
+					pool_id,
+					|total_score| -> DispatchResult {
+						*total_score = total_score.checked_add(score).ok_or(Error::<T>::Math)?;
+						Ok(())
+					},
+				)
+			},
+		)
+	}
+
+	/// Remove at most the score `who` added to a pool in a period.
+	pub(crate) fn remove_vote_score(
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+		who: &T::AccountId,
+		score: Balance,
+	) {
+		AccountPeriodScore::<T>::mutate((period_id, pool_id, who), |account_score| {
+			let removed = score.min(*account_score);
+			*account_score -= removed;
+			PeriodVoteScore::<T>::mutate(period_id, pool_id, |total_score| {
+				*total_score = total_score.saturating_sub(removed)
+			});
+		})
+	}
+
+	pub(crate) fn inner_deposit_bribe(
+		who: &T::AccountId,
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+		currency_id: T::CurrencyId,
+		amount: Balance,
+	) -> DispatchResult {
+		ensure!(amount >= T::MinBribeDeposit::get(), Error::<T>::BribeTooSmall);
+		Self::ensure_bribe_currency(period_id, pool_id, currency_id)?;
+		T::MultiCurrency::transfer(currency_id, who, &Self::bribe_account(), amount)?;
+
+		Bribes::<T>::try_mutate((period_id, pool_id, currency_id), |bribe| -> DispatchResult {
+			bribe.amount = bribe.amount.checked_add(amount).ok_or(Error::<T>::Math)?;
+			Ok(())
+		})?;
+		BribeDeposits::<T>::try_mutate(
+			(period_id, pool_id, currency_id, who),
+			|deposit| -> DispatchResult {
+				*deposit = deposit.checked_add(amount).ok_or(Error::<T>::Math)?;
+				Ok(())
+			},
+		)?;
+
+		Self::deposit_event(Event::DepositBribe {
+			depositor: who.clone(),
+			period_id,
+			pool_id,
+			currency_id,
+			amount,
+		});
+		Ok(())
+	}
+
+	pub(crate) fn inner_claim_bribes(
+		who: &T::AccountId,
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+	) -> DispatchResult {
+		let now = T::TimeProvider::now().as_secs();
+		let period = Self::vote_period(period_id).ok_or(Error::<T>::InvalidPeriodId)?;
+		ensure!(now > period.end, Error::<T>::UnexpiredPeriod);
+		let claim_end =
+			period.end.checked_add(T::BribeClaimDuration::get()).ok_or(Error::<T>::Math)?;
+		ensure!(now <= claim_end, Error::<T>::BribeClaimExpired);
+		ensure!(!Self::bribe_claimed((period_id, pool_id, who)), Error::<T>::BribeAlreadyClaimed);
+
+		let bribe_account = Self::bribe_account();
+		for (currency_id, amount) in Self::claimable_bribes(who, period_id, pool_id) {
+			// a share the account can't hold stays with the depositors
+			if amount < T::MultiCurrency::minimum_balance(currency_id) {
+				continue
+			}
+			Bribes::<T>::try_mutate(
+				(period_id, pool_id, currency_id),
+				|bribe| -> DispatchResult {
+					bribe.claimed = bribe.claimed.checked_add(amount).ok_or(Error::<T>::Math)?;
+					Ok(())
+				},
+			)?;
+			T::MultiCurrency::transfer(currency_id, &bribe_account, who, amount)?;
+
+			Self::deposit_event(Event::ClaimBribe {
+				caller: who.clone(),
+				period_id,
+				pool_id,
+				currency_id,
+				amount,
+			});
+		}
+		BribeClaimed::<T>::insert((period_id, pool_id, who), true);
+		Ok(())
+	}
+
+	pub(crate) fn inner_reclaim_bribe(
+		who: &T::AccountId,
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+		currency_id: T::CurrencyId,
+		roll_over: bool,
+		now: Timestamp,
+	) -> DispatchResult {
+		let period = Self::vote_period(period_id).ok_or(Error::<T>::InvalidPeriodId)?;
+		let claim_end =
+			period.end.checked_add(T::BribeClaimDuration::get()).ok_or(Error::<T>::Math)?;
+		ensure!(now > claim_end, Error::<T>::BribeClaimUnexpired);
+
+		let deposit = BribeDeposits::<T>::take((period_id, pool_id, currency_id, who));
+		ensure!(!deposit.is_zero(), Error::<T>::NoBribe);
+
+		let bribe = Self::bribe((period_id, pool_id, currency_id));
+		let amount = bribe
+			.amount
+			.checked_sub(bribe.claimed)
+			.and_then(|unclaimed| balance_mul_div(unclaimed, deposit, bribe.amount))
+			.ok_or(Error::<T>::Math)?;
+
+		let roll_over_period = if roll_over {
+			let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
+			Self::ensure_bribe_currency(current_period_id, pool_id, currency_id)?;
+			Bribes::<T>::try_mutate(
+				(current_period_id, pool_id, currency_id),
+				|bribe| -> DispatchResult {
+					bribe.amount = bribe.amount.checked_add(amount).ok_or(Error::<T>::Math)?;
+					Ok(())
+				},
+			)?;
+			BribeDeposits::<T>::try_mutate(
+				(current_period_id, pool_id, currency_id, who),
+				|deposit| -> DispatchResult {
+					*deposit = deposit.checked_add(amount).ok_or(Error::<T>::Math)?;
+					Ok(())
+				},
+			)?;
+			Some(current_period_id)
+		} else {
+			T::MultiCurrency::transfer(currency_id, &Self::bribe_account(), who, amount)?;
+			None
+		};
+
+		Self::deposit_event(Event::ReclaimBribe {
+			depositor: who.clone(),
+			period_id,
+			pool_id,
+			currency_id,
+			amount,
+			roll_over_period,
+		});
+		Ok(())
+	}
+}
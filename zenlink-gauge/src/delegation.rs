@@ -0,0 +1,62 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	pub(crate) fn inner_vote_batch(
+		who: &T::AccountId,
+		votes: &[(T::PoolId, Balance)],
+		now: Timestamp,
+	) -> DispatchResult {
+		for (pool_id, amounts) in votes {
+			Self::vote_pool(who, *pool_id, *amounts, now)?;
+		}
+		Self::refresh_vote_decay(who, now)
+	}
+
+	/// The amounts of the voting power left to `who` given to each pool by its share.
+	pub(crate) fn allocate_by_percent(
+		who: &T::AccountId,
+		votes: &[(T::PoolId, Perbill)],
+		now: Timestamp,
+	) -> Result<Vec<(T::PoolId, Balance)>, DispatchError> {
+		let total_parts = votes
+			.iter()
+			.try_fold(0u32, |total, (_, percent)| total.checked_add(percent.deconstruct()))
+			.ok_or(Error::<T>::InvalidPercent)?;
+		ensure!(total_parts <= Perbill::one().deconstruct(), Error::<T>::InvalidPercent);
+
+		let available = Self::voting_power(who, now).saturating_sub(Self::account_total_vote(who));
+		Ok(votes
+			.iter()
+			.map(|(pool_id, percent)| (*pool_id, percent.mul_floor(available)))
+			.collect())
+	}
+
+	pub(crate) fn inner_delegate(
+		delegator: T::AccountId,
+		delegate: T::AccountId,
+	) -> DispatchResult {
+		let period_id = Self::get_current_period().ok_or(Error::<T>::Uninitialized)?;
+		Delegations::<T>::insert(&delegator, Delegation { delegate: delegate.clone(), period_id });
+
+		Self::deposit_event(Event::Delegate { delegator, delegate, period_id });
+		Ok(())
+	}
+
+	/// Check `delegate` may vote for `delegator` in the current period.
+	pub(crate) fn ensure_delegate(
+		delegator: &T::AccountId,
+		delegate: &T::AccountId,
+	) -> DispatchResult {
+		let current_period_id = Self::get_current_period().ok_or(Error::<T>::Uninitialized)?;
+		match Self::delegation(delegator) {
+			Some(delegation)
+				if delegation.delegate == *delegate &&
+					delegation.period_id == current_period_id =>
+				Ok(()),
+			_ => Err(Error::<T>::NotDelegate.into()),
+		}
+	}
+}
@@ -8,15 +8,35 @@ mod mock;
 #[cfg(test)]
 mod test;
 
+mod bribe;
+mod delegation;
+pub mod migrations;
 pub mod primitives;
+pub mod rpc;
+mod traits;
+mod vote_escrow;
 pub use pallet::*;
 use primitives::*;
+pub use rpc::{GaugePeriodInfo, GaugePoolInfo, GaugeRewardEstimate};
+pub use traits::{DexPools, PoolValidator, StableAmmPools, ZenlinkLpPools, PAIR_POOL_TAG};
 
 use codec::{Codec, Decode, Encode};
 use sp_arithmetic::traits::{One, Zero};
-use sp_runtime::traits::{AccountIdConversion, AtLeast32BitUnsigned, StaticLookup};
-
-use frame_support::{dispatch::DispatchResult, pallet_prelude::*, traits::UnixTime, PalletId};
+use sp_runtime::{
+	traits::{AccountIdConversion, AtLeast32BitUnsigned, StaticLookup},
+	Perbill,
+};
+
+use sp_std::{collections::btree_set::BTreeSet, ops::Bound};
+
+use frame_support::{
+	dispatch::DispatchResult,
+	pallet_prelude::*,
+	storage::with_storage_layer,
+	traits::{EitherOfDiverse, UnixTime},
+	PalletId,
+};
+use frame_system::{ensure_signed, EnsureRoot};
 
 use orml_traits::MultiCurrency;
 
@@ -52,17 +72,56 @@ pub mod pallet {
 		/// The pool ID type
 		type PoolId: Parameter + Codec + Copy + Ord + AtLeast32BitUnsigned + Zero + One + Default;
 
+		/// The origin which may manage the gauge, besides the admin account. Root may always
+		/// update the admin account.
+		type AdminOrigin: EnsureOrigin<Self::RuntimeOrigin>;
+
+		/// The trait verify the pools set votable exist.
+		type PoolValidator: PoolValidator<Self::PoolId>;
+
 		/// The trait get timestamp of chain.
 		type TimeProvider: UnixTime;
 
 		/// This pallet id.
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
+
+		/// The duration after the end of a period the voters have to claim its bribes.
+		#[pallet::constant]
+		type BribeClaimDuration: Get<Duration>;
+
+		/// The longest duration vote token can be locked for, which gets the full voting power.
+		#[pallet::constant]
+		type MaxLockDuration: Get<Duration>;
+
+		/// The most pools a batch of votes may vote for.
+		#[pallet::constant]
+		type MaxVotesPerCall: Get<u32>;
+
+		/// The most pools that may have an lp currency and receive emissions.
+		#[pallet::constant]
+		type MaxEmissionPools: Get<u32>;
+
+		/// The most pools that may ever be set votable or non votable.
+		#[pallet::constant]
+		type MaxGaugePools: Get<u32>;
+
+		/// The most currencies the bribes of a pool in a period may be deposited in.
+		#[pallet::constant]
+		type MaxBribeCurrencies: Get<u32>;
+
+		/// The least amount of a bribe deposit.
+		#[pallet::constant]
+		type MinBribeDeposit: Get<Balance>;
 	}
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(3);
+
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// The next vote period id.
@@ -102,6 +161,28 @@ pub mod pallet {
 	pub type GlobalPoolState<T: Config> =
 		StorageDoubleMap<_, Twox64Concat, PeriodId, Twox64Concat, T::PoolId, PoolState>;
 
+	/// The pools ever set votable or non votable.
+	#[pallet::storage]
+	#[pallet::getter(fn gauge_pools)]
+	pub type GaugePools<T: Config> = StorageValue<_, BTreeSet<T::PoolId>, ValueQuery>;
+
+	/// The block before which `on_initialize` does not retry a failed rollover, and the
+	/// rollovers it failed in a row.
+	#[pallet::storage]
+	#[pallet::getter(fn rollover_backoff)]
+	pub type RolloverBackoff<T: Config> = StorageValue<_, (T::BlockNumber, u32)>;
+
+	/// The last pool whose state `on_idle` let inherit, and the period it did so in.
+	#[pallet::storage]
+	#[pallet::getter(fn inherit_cursor)]
+	pub type InheritCursor<T: Config> = StorageValue<_, (PeriodId, T::PoolId)>;
+
+	/// The scores of the votable pools when a period ended.
+	#[pallet::storage]
+	#[pallet::getter(fn period_scores)]
+	pub type PeriodScores<T: Config> =
+		StorageMap<_, Twox64Concat, PeriodId, Vec<(T::PoolId, Balance)>, ValueQuery>;
+
 	/// The period which the Pool last changed state.
 	#[pallet::storage]
 	#[pallet::getter(fn pool_last_update_period)]
@@ -111,6 +192,186 @@ pub mod pallet {
 	#[pallet::getter(fn admin)]
 	pub type Admin<T: Config> = StorageValue<_, Option<T::AccountId>, ValueQuery>;
 
+	/// The vote token locked by an account for voting power.
+	#[pallet::storage]
+	#[pallet::getter(fn vote_lock)]
+	pub type VoteLocks<T: Config> = StorageMap<_, Blake2_128Concat, T::AccountId, VoteLock>;
+
+	/// The voting power an account spent on all pools.
+	#[pallet::storage]
+	#[pallet::getter(fn account_total_vote)]
+	pub type AccountTotalVote<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Balance, ValueQuery>;
+
+	/// The votes of an account on a pool, capped at its voting power as it decays.
+	#[pallet::storage]
+	#[pallet::getter(fn account_vote_decay)]
+	pub type AccountVoteDecays<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::PoolId,
+		AccountVoteDecay,
+	>;
+
+	/// The value of all the votes on a pool, capped at the voting power of their accounts.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_vote_decay)]
+	pub type PoolVoteDecays<T: Config> =
+		StorageMap<_, Twox64Concat, T::PoolId, VoteDecay, ValueQuery>;
+
+	/// The changes to the value of the votes on a pool at a step.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_vote_decay_step)]
+	pub type PoolVoteDecaySteps<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, T::PoolId, Twox64Concat, Timestamp, VoteDecayStep>;
+
+	/// The account voting with the voting power of a delegator in a period.
+	#[pallet::storage]
+	#[pallet::getter(fn delegation)]
+	pub type Delegations<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AccountId, Delegation<T::AccountId>>;
+
+	/// The score a specific account added to a specific pool in a period by voting.
+	#[pallet::storage]
+	#[pallet::getter(fn account_period_score)]
+	pub type AccountPeriodScore<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, PeriodId>,
+			NMapKey<Twox64Concat, T::PoolId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+		),
+		Balance,
+		ValueQuery,
+	>;
+
+	/// The score all accounts added to a pool in a period by voting.
+	#[pallet::storage]
+	#[pallet::getter(fn period_vote_score)]
+	pub type PeriodVoteScore<T: Config> =
+		StorageDoubleMap<_, Twox64Concat, PeriodId, Twox64Concat, T::PoolId, Balance, ValueQuery>;
+
+	/// The bribes deposited for the voters of a pool in a period.
+	#[pallet::storage]
+	#[pallet::getter(fn bribe)]
+	pub type Bribes<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, PeriodId>,
+			NMapKey<Twox64Concat, T::PoolId>,
+			NMapKey<Blake2_128Concat, T::CurrencyId>,
+		),
+		BribeState,
+		ValueQuery,
+	>;
+
+	/// The amount of bribe a specific account deposited for a pool in a period.
+	#[pallet::storage]
+	#[pallet::getter(fn bribe_deposit)]
+	pub type BribeDeposits<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, PeriodId>,
+			NMapKey<Twox64Concat, T::PoolId>,
+			NMapKey<Blake2_128Concat, T::CurrencyId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+		),
+		Balance,
+		ValueQuery,
+	>;
+
+	/// Whether a specific account claimed the bribes of a pool in a period.
+	#[pallet::storage]
+	#[pallet::getter(fn bribe_claimed)]
+	pub type BribeClaimed<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, PeriodId>,
+			NMapKey<Twox64Concat, T::PoolId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+		),
+		bool,
+		ValueQuery,
+	>;
+
+	/// The amounts of reward currencies emitted to the pools at the end of every period.
+	#[pallet::storage]
+	#[pallet::getter(fn emission_budget)]
+	pub type EmissionBudget<T: Config> = StorageValue<_, Vec<(T::CurrencyId, Balance)>, ValueQuery>;
+
+	/// The lp currency staked in a pool to earn its emissions.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_lp_currency)]
+	pub type PoolLpCurrency<T: Config> = StorageMap<_, Twox64Concat, T::PoolId, T::CurrencyId>;
+
+	/// The total amount of lp token staked in a pool.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_stake_amount)]
+	pub type PoolStakeAmount<T: Config> =
+		StorageMap<_, Twox64Concat, T::PoolId, Balance, ValueQuery>;
+
+	/// The amount of lp token a specific account staked in a specific pool.
+	#[pallet::storage]
+	#[pallet::getter(fn account_stake_amount)]
+	pub type AccountStakeAmount<T: Config> = StorageDoubleMap<
+		_,
+		Blake2_128Concat,
+		T::AccountId,
+		Twox64Concat,
+		T::PoolId,
+		Balance,
+		ValueQuery,
+	>;
+
+	/// The accumulated reward per share of the lp token staked in a pool, scaled by
+	/// `REWARD_PRECISION`.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_reward_per_share)]
+	pub type PoolRewardPerShare<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		T::PoolId,
+		Blake2_128Concat,
+		T::CurrencyId,
+		Balance,
+		ValueQuery,
+	>;
+
+	/// The reward of a specific account staking in a specific pool.
+	#[pallet::storage]
+	#[pallet::getter(fn staker_reward)]
+	pub type StakerRewards<T: Config> = StorageNMap<
+		_,
+		(
+			NMapKey<Twox64Concat, T::PoolId>,
+			NMapKey<Blake2_128Concat, T::AccountId>,
+			NMapKey<Blake2_128Concat, T::CurrencyId>,
+		),
+		StakerReward,
+		ValueQuery,
+	>;
+
+	/// The emissions of a pool being released to its stakers.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_emission_stream)]
+	pub type PoolEmissionStreams<T: Config> =
+		StorageMap<_, Twox64Concat, T::PoolId, EmissionStream<T::CurrencyId>>;
+
+	/// The emissions a pool received at the end of a period.
+	#[pallet::storage]
+	#[pallet::getter(fn period_emissions)]
+	pub type PeriodEmissions<T: Config> = StorageDoubleMap<
+		_,
+		Twox64Concat,
+		PeriodId,
+		Twox64Concat,
+		T::PoolId,
+		Vec<(T::CurrencyId, Balance)>,
+		ValueQuery,
+	>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -138,6 +399,10 @@ pub mod pallet {
 			period: PeriodId,
 			pools: Vec<T::PoolId>,
 		},
+		RemoveInvalidPool {
+			period: PeriodId,
+			pool_id: T::PoolId,
+		},
 		InheritPool {
 			pool_id: T::PoolId,
 			current_period_id: PeriodId,
@@ -164,6 +429,97 @@ pub mod pallet {
 			update_period: PeriodId,
 			last_period_amount: Balance,
 		},
+		UpdateEmissionBudget {
+			budget: Vec<(T::CurrencyId, Balance)>,
+		},
+		SetPoolLpCurrency {
+			pool_id: T::PoolId,
+			lp_currency: T::CurrencyId,
+		},
+		Stake {
+			caller: T::AccountId,
+			pool_id: T::PoolId,
+			amount: Balance,
+		},
+		Unstake {
+			caller: T::AccountId,
+			pool_id: T::PoolId,
+			amount: Balance,
+		},
+		DistributeEmission {
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+		},
+		ClaimRewards {
+			caller: T::AccountId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+		},
+		CreateLock {
+			caller: T::AccountId,
+			amount: Balance,
+			end: Timestamp,
+		},
+		IncreaseLockAmount {
+			caller: T::AccountId,
+			amount: Balance,
+		},
+		ExtendLock {
+			caller: T::AccountId,
+			end: Timestamp,
+		},
+		WithdrawLock {
+			caller: T::AccountId,
+			amount: Balance,
+		},
+		Delegate {
+			delegator: T::AccountId,
+			delegate: T::AccountId,
+			period_id: PeriodId,
+		},
+		RevokeDelegation {
+			delegator: T::AccountId,
+		},
+		DepositBribe {
+			depositor: T::AccountId,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+		},
+		ClaimBribe {
+			caller: T::AccountId,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+		},
+		ReclaimBribe {
+			depositor: T::AccountId,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+			roll_over_period: Option<PeriodId>,
+		},
+		PeriodFinalized {
+			period_id: PeriodId,
+			scores: Vec<(T::PoolId, Balance)>,
+		},
+		PokeVotes {
+			account: T::AccountId,
+			voting_power: Balance,
+		},
+		/// `on_initialize` failed to start the period after `period_id`, and does not retry
+		/// before `retry_at`.
+		RolloverFailed {
+			period_id: PeriodId,
+			error: DispatchError,
+			retry_at: T::BlockNumber,
+		},
 	}
 
 	#[pallet::error]
@@ -183,6 +539,123 @@ pub mod pallet {
 		InsufficientAmount,
 		MismatchParameters,
 		NoNeedUpdate,
+		/// The pool has no lp currency to stake.
+		NoLpCurrency,
+		/// The account already locked vote token.
+		LockExisted,
+		/// The account has no lock.
+		NoLock,
+		/// The unlock time is not in the future or exceeds the max lock duration.
+		InvalidUnlockTime,
+		/// The lock has expired.
+		LockExpired,
+		/// The lock has not expired yet.
+		LockNotExpired,
+		/// The votes exceed the voting power of the account.
+		InsufficientVotingPower,
+		/// The votes of the account must be cancelled first.
+		VotesNotReleased,
+		/// The shares of a percentage allocation exceed the whole.
+		InvalidPercent,
+		/// The caller is not the delegate of the account in the current period.
+		NotDelegate,
+		/// The bribes of the period can no longer be claimed.
+		BribeClaimExpired,
+		/// The bribes of the period can still be claimed by the voters.
+		BribeClaimUnexpired,
+		/// The account already claimed the bribes.
+		BribeAlreadyClaimed,
+		/// The account deposited no bribe.
+		NoBribe,
+		/// The pool still exists.
+		ValidPool,
+		/// The account voted for more pools than the caller stated.
+		InvalidVotePoolsWitness,
+		/// Too many pools have an lp currency.
+		TooManyEmissionPools,
+		/// The bribe is below the minimum deposit.
+		BribeTooSmall,
+		/// The bribes of the pool in the period are in too many currencies.
+		TooManyBribeCurrencies,
+		/// Too many pools have been set votable or non votable.
+		TooManyGaugePools,
+	}
+
+	#[pallet::hooks]
+	impl<T: Config> Hooks<BlockNumberFor<T>> for Pallet<T> {
+		/// Start the next period once the current one has ended. A failed rollover is retried
+		/// after a number of blocks doubling with every failure in a row, up to
+		/// `MAX_ROLLOVER_BACKOFF`.
+		fn on_initialize(n: BlockNumberFor<T>) -> Weight {
+			let mut weight = T::DbWeight::get().reads(3);
+			let backoff = Self::rollover_backoff();
+			if matches!(backoff, Some((retry_at, _)) if n < retry_at) {
+				return weight
+			}
+			let (period_id, current_period) = match Self::get_current_period()
+				.and_then(|period_id| Some((period_id, Self::vote_period(period_id)?)))
+			{
+				Some(period) => period,
+				None => return weight,
+			};
+			let now = T::TimeProvider::now().as_secs();
+			if current_period.end > now {
+				return weight
+			}
+
+			weight = weight.saturating_add(Self::rollover_weight());
+			// a failed rollover must not leave a finalized period or credited emissions behind
+			if let Err(error) = with_storage_layer(|| Self::inner_update_vote_periold(now)) {
+				let failures = backoff.map_or(0, |(_, failures)| failures).saturating_add(1);
+				let delay =
+					1u32.checked_shl(failures).unwrap_or(u32::MAX).min(MAX_ROLLOVER_BACKOFF);
+				let retry_at = n.saturating_add(delay.into());
+				RolloverBackoff::<T>::put((retry_at, failures));
+				Self::deposit_event(Event::RolloverFailed { period_id, error, retry_at });
+				weight = weight.saturating_add(T::DbWeight::get().writes(1));
+			}
+			weight
+		}
+
+		/// Let the pools not touched in the current period inherit the state of their last
+		/// updated period, as far as the remaining weight allows. Each block continues after the
+		/// last pool handled in the period.
+		fn on_idle(_n: BlockNumberFor<T>, remaining_weight: Weight) -> Weight {
+			let per_pool =
+				T::DbWeight::get().reads_writes(4, 2).saturating_add(Self::vote_decay_weight());
+			let cursor_write = T::DbWeight::get().writes(1);
+			let mut used = T::DbWeight::get().reads(3);
+			if remaining_weight.any_lt(used) {
+				return Weight::zero()
+			}
+			let current_period_id = match Self::get_current_period() {
+				Some(period_id) => period_id,
+				None => return used,
+			};
+
+			let start = match Self::inherit_cursor() {
+				Some((period_id, pool_id)) if period_id == current_period_id =>
+					Bound::Excluded(pool_id),
+				_ => Bound::Unbounded,
+			};
+			let mut last_pool_id = None;
+			for pool_id in Self::gauge_pools().range((start, Bound::Unbounded)) {
+				if remaining_weight
+					.any_lt(used.saturating_add(per_pool).saturating_add(cursor_write))
+				{
+					break
+				}
+				used = used.saturating_add(per_pool);
+				Self::inherit_pool_state(current_period_id, *pool_id);
+				last_pool_id = Some(*pool_id);
+			}
+
+			if let Some(pool_id) = last_pool_id {
+				InheritCursor::<T>::put((current_period_id, pool_id));
+				used = used.saturating_add(cursor_write);
+			}
+			used
+		}
 	}
 
 	#[pallet::call]
@@ -194,7 +667,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			admin: <T::Lookup as StaticLookup>::Source,
 		) -> DispatchResult {
-			ensure_root(origin)?;
+			EitherOfDiverse::<EnsureRoot<T::AccountId>, T::AdminOrigin>::ensure_origin(origin)?;
 			let new_admin = T::Lookup::lookup(admin)?;
 
 			Admin::<T>::mutate(|old_admin| *old_admin = Some(new_admin.clone()));
@@ -214,8 +687,7 @@ pub mod pallet {
 			vote_set_window: Duration,
 			start: Timestamp,
 		) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+			Self::ensure_admin(origin)?;
 
 			ensure!(T::TimeProvider::now().as_secs() < start, Error::<T>::InvalidTimestamp);
 
@@ -247,8 +719,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			vote_set_window: Duration,
 		) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+			Self::ensure_admin(origin)?;
 			let next_period_id = Self::next_period_id();
 			ensure!(next_period_id != 0u32, Error::<T>::Uninitialized);
 
@@ -268,8 +739,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			vote_duration: Duration,
 		) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+			Self::ensure_admin(origin)?;
 			let next_period_id = Self::next_period_id();
 			ensure!(next_period_id != 0u32, Error::<T>::Uninitialized);
 
@@ -285,7 +755,9 @@ pub mod pallet {
 
 		#[pallet::call_index(4)]
 		/// Try to start a new period. If the current period has not expired, then it will fail.
-		#[pallet::weight(10000)]
+		#[pallet::weight(
+			Weight::from_parts(10000, 0).saturating_add(Pallet::<T>::rollover_weight())
+		)]
 		#[transactional]
 		pub fn update_vote_period(origin: OriginFor<T>) -> DispatchResult {
 			ensure_signed(origin)?;
@@ -297,8 +769,10 @@ pub mod pallet {
 		#[pallet::weight(10000)]
 		#[transactional]
 		pub fn set_voteable_pools(origin: OriginFor<T>, pools: Vec<T::PoolId>) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+			Self::ensure_admin(origin)?;
+			for pool_id in pools.iter() {
+				ensure!(T::PoolValidator::is_valid_pool(*pool_id), Error::<T>::InvalidPoolId);
+			}
 
 			let mut current_period_id =
 				Self::get_current_period().ok_or(Error::<T>::InvalidPeriodId)?;
@@ -326,8 +800,7 @@ pub mod pallet {
 			origin: OriginFor<T>,
 			pools: Vec<T::PoolId>,
 		) -> DispatchResult {
-			let who = ensure_signed(origin)?;
-			ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+			Self::ensure_admin(origin)?;
 
 			let mut current_period_id =
 				Self::get_current_period().ok_or(Error::<T>::InvalidPeriodId)?;
@@ -349,48 +822,23 @@ pub mod pallet {
 		}
 
 		#[pallet::call_index(7)]
-		#[pallet::weight(10000)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::rollover_weight())
+			.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
 		#[transactional]
 		pub fn vote(origin: OriginFor<T>, pool_id: T::PoolId, amounts: Balance) -> DispatchResult {
 			let who = ensure_signed(origin)?;
 			let now = T::TimeProvider::now().as_secs();
 
 			Self::inner_update_vote_periold(now)?;
-
-			let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
-
-			let vote_period =
-				Self::vote_period(current_period_id).ok_or(Error::<T>::InvalidPeriodId)?;
-			ensure!(now < vote_period.end, Error::<T>::ExpiredPeriod);
-
-			GlobalPoolState::<T>::try_mutate(
-				current_period_id,
-				pool_id,
-				|pool_state| -> DispatchResult {
-					let mut new_state = PoolState::default();
-					if let Some(state) = pool_state {
-						new_state = *state;
-					}
-
-					Self::inherit_expired_pool(pool_id, &mut new_state, current_period_id)?;
-					Self::inner_vote(
-						&mut new_state,
-						&who,
-						amounts,
-						pool_id,
-						current_period_id,
-						&vote_period,
-						now,
-					)?;
-
-					*pool_state = Some(new_state);
-					Ok(())
-				},
-			)
+			Self::vote_pool(&who, pool_id, amounts, now)?;
+			Self::refresh_vote_decay(&who, now)
 		}
 
 		#[pallet::call_index(8)]
-		#[pallet::weight(10000)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::rollover_weight())
+			.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
 		#[transactional]
 		pub fn cancel_vote(
 			origin: OriginFor<T>,
@@ -401,45 +849,8 @@ pub mod pallet {
 			let now = T::TimeProvider::now().as_secs();
 			Self::inner_update_vote_periold(now)?;
 
-			let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
-
-			GlobalPoolState::<T>::try_mutate(
-				current_period_id,
-				pool_id,
-				|pool_state| -> DispatchResult {
-					// if let Some(state) = pool_state {
-
-					// 	Self::inherit_expired_pool(pool_id, state, current_period_id)?;
-
-					// 	Self::inner_cancel_vote(
-					// 		state,
-					// 		&who,
-					// 		amounts,
-					// 		pool_id,
-					// 		current_period_id,
-					// 		now,
-					// 	)
-					// } else {
-					// 	Err(Error::<T>::InvalidPeriodId.into())
-					// }
-					let mut new_state = PoolState::default();
-					if let Some(state) = pool_state {
-						new_state = *state;
-					}
-					Self::inherit_expired_pool(pool_id, &mut new_state, current_period_id)?;
-					Self::inner_cancel_vote(
-						&mut new_state,
-						&who,
-						amounts,
-						pool_id,
-						current_period_id,
-						now,
-					)?;
-
-					*pool_state = Some(new_state);
-					Ok(())
-				},
-			)
+			Self::cancel_vote_pool(&who, pool_id, amounts, now)?;
+			Self::refresh_vote_decay(&who, now)
 		}
 
 		#[pallet::call_index(9)]
@@ -461,6 +872,10 @@ pub mod pallet {
 				if let Some(last_update_pool_state) = Self::global_pool_state(period_id, pool_id) {
 					if last_update_pool_state.inherit || period_id == 0 {
 						for try_update_period_id in period_id + 1..=need_update_period_id {
+							let start = Self::vote_period(try_update_period_id)
+								.ok_or(Error::<T>::InvalidPeriodId)?
+								.start;
+							let score = Self::vote_decay_value(pool_id, start);
 							GlobalPoolState::<T>::try_mutate(
 								try_update_period_id,
 								pool_id,
@@ -470,7 +885,7 @@ pub mod pallet {
 											return Ok(())
 										}
 										state.inherit = true;
-										state.score = last_update_pool_state.total_amount;
+										state.score = score;
 										state.total_amount = last_update_pool_state.total_amount;
 										if !state.reset_votable {
 											state.votable = last_update_pool_state.votable;
@@ -480,7 +895,7 @@ pub mod pallet {
 											inherit: true,
 											reset_votable: false,
 											votable: last_update_pool_state.votable,
-											score: last_update_pool_state.total_amount,
+											score,
 											total_amount: last_update_pool_state.total_amount,
 										})
 									}
@@ -500,15 +915,764 @@ pub mod pallet {
 			}
 			Ok(())
 		}
+
+		/// Set the amounts of reward currencies emitted at the end of every period. The emissions
+		/// are paid from the reward account, which must be funded to cover the claims.
+		#[pallet::call_index(10)]
+		#[pallet::weight(10000)]
+		#[transactional]
+		pub fn set_emission_budget(
+			origin: OriginFor<T>,
+			budget: Vec<(T::CurrencyId, Balance)>,
+		) -> DispatchResult {
+			Self::ensure_admin(origin)?;
+
+			EmissionBudget::<T>::put(budget.clone());
+
+			Self::deposit_event(Event::UpdateEmissionBudget { budget });
+			Ok(())
+		}
+
+		/// Set the lp currency staked in a pool. Only the pools with an lp currency receive
+		/// emissions.
+		#[pallet::call_index(11)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads_writes(T::MaxEmissionPools::get() as u64 + 2, 1)
+		))]
+		#[transactional]
+		pub fn set_pool_lp_currency(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			lp_currency: T::CurrencyId,
+		) -> DispatchResult {
+			Self::ensure_admin(origin)?;
+			ensure!(Self::pool_stake_amount(pool_id).is_zero(), Error::<T>::InvalidPoolState);
+			ensure!(
+				PoolLpCurrency::<T>::contains_key(pool_id) ||
+					(PoolLpCurrency::<T>::iter_keys().count() as u32) <
+						T::MaxEmissionPools::get(),
+				Error::<T>::TooManyEmissionPools
+			);
+
+			PoolLpCurrency::<T>::insert(pool_id, lp_currency);
+
+			Self::deposit_event(Event::SetPoolLpCurrency { pool_id, lp_currency });
+			Ok(())
+		}
+
+		#[pallet::call_index(12)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::staker_rewards_weight())
+			.saturating_add(T::DbWeight::get().reads_writes(4, 4)))]
+		#[transactional]
+		pub fn stake(origin: OriginFor<T>, pool_id: T::PoolId, amount: Balance) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let lp_currency = Self::pool_lp_currency(pool_id).ok_or(Error::<T>::NoLpCurrency)?;
+
+			Self::update_staker_rewards(pool_id, &who)?;
+
+			let pallet_account = T::PalletId::get().into_account_truncating();
+			T::MultiCurrency::transfer(lp_currency, &who, &pallet_account, amount)?;
+
+			AccountStakeAmount::<T>::try_mutate(&who, pool_id, |staked| -> DispatchResult {
+				*staked = staked.checked_add(amount).ok_or(Error::<T>::Math)?;
+				Ok(())
+			})?;
+			PoolStakeAmount::<T>::try_mutate(pool_id, |total| -> DispatchResult {
+				*total = total.checked_add(amount).ok_or(Error::<T>::Math)?;
+				Ok(())
+			})?;
+
+			Self::deposit_event(Event::Stake { caller: who, pool_id, amount });
+			Ok(())
+		}
+
+		#[pallet::call_index(13)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::staker_rewards_weight())
+			.saturating_add(T::DbWeight::get().reads_writes(4, 4)))]
+		#[transactional]
+		pub fn unstake(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			amount: Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let lp_currency = Self::pool_lp_currency(pool_id).ok_or(Error::<T>::NoLpCurrency)?;
+
+			Self::update_staker_rewards(pool_id, &who)?;
+
+			AccountStakeAmount::<T>::try_mutate(&who, pool_id, |staked| -> DispatchResult {
+				*staked = staked.checked_sub(amount).ok_or(Error::<T>::InsufficientAmount)?;
+				Ok(())
+			})?;
+			PoolStakeAmount::<T>::try_mutate(pool_id, |total| -> DispatchResult {
+				*total = total.checked_sub(amount).ok_or(Error::<T>::Math)?;
+				Ok(())
+			})?;
+
+			let pallet_account = T::PalletId::get().into_account_truncating();
+			T::MultiCurrency::transfer(lp_currency, &pallet_account, &who, amount)?;
+
+			Self::deposit_event(Event::Unstake { caller: who, pool_id, amount });
+			Ok(())
+		}
+
+		#[pallet::call_index(14)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::staker_rewards_weight())
+			.saturating_add(T::DbWeight::get().reads_writes(3, 3).saturating_mul(
+				Pallet::<T>::emission_budget().len() as u64
+			)))]
+		#[transactional]
+		pub fn claim_rewards(origin: OriginFor<T>, pool_id: T::PoolId) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			Self::update_staker_rewards(pool_id, &who)?;
+
+			let reward_account = Self::reward_account();
+			for (currency_id, _) in PoolRewardPerShare::<T>::iter_prefix(pool_id) {
+				let amount = StakerRewards::<T>::mutate((pool_id, &who, currency_id), |reward| {
+					sp_std::mem::take(&mut reward.pending)
+				});
+				if amount.is_zero() {
+					continue
+				}
+				T::MultiCurrency::transfer(currency_id, &reward_account, &who, amount)?;
+
+				Self::deposit_event(Event::ClaimRewards {
+					caller: who.clone(),
+					pool_id,
+					currency_id,
+					amount,
+				});
+			}
+			Ok(())
+		}
+
+		/// Lock `amount` of vote token until `unlock_time` for voting power.
+		#[pallet::call_index(15)]
+		#[pallet::weight(10000)]
+		#[transactional]
+		pub fn create_lock(
+			origin: OriginFor<T>,
+			amount: Balance,
+			unlock_time: Timestamp,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::inner_create_lock(&who, amount, unlock_time)
+		}
+
+		#[pallet::call_index(16)]
+		#[pallet::weight(
+			Weight::from_parts(10000, 0).saturating_add(Pallet::<T>::refresh_vote_decay_weight())
+		)]
+		#[transactional]
+		pub fn increase_lock_amount(origin: OriginFor<T>, amount: Balance) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::inner_increase_lock_amount(&who, amount)
+		}
+
+		#[pallet::call_index(17)]
+		#[pallet::weight(
+			Weight::from_parts(10000, 0).saturating_add(Pallet::<T>::refresh_vote_decay_weight())
+		)]
+		#[transactional]
+		pub fn extend_lock(origin: OriginFor<T>, unlock_time: Timestamp) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::inner_extend_lock(&who, unlock_time)
+		}
+
+		/// Withdraw the vote token of an expired lock. The votes of the account must have been
+		/// cancelled.
+		#[pallet::call_index(18)]
+		#[pallet::weight(10000)]
+		#[transactional]
+		pub fn withdraw_lock(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::inner_withdraw_lock(&who)
+		}
+
+		/// Vote the amount of voting power on each pool.
+		#[pallet::call_index(19)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads_writes(8, 6).saturating_mul(votes.len() as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight())
+		.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
+		#[transactional]
+		pub fn vote_batch(
+			origin: OriginFor<T>,
+			votes: BoundedVec<(T::PoolId, Balance), T::MaxVotesPerCall>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::inner_vote_batch(&who, &votes, now)
+		}
+
+		/// Vote a share of the voting power left to the caller on each pool.
+		#[pallet::call_index(20)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads_writes(8, 6).saturating_mul(votes.len() as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight())
+		.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
+		#[transactional]
+		pub fn vote_batch_by_percent(
+			origin: OriginFor<T>,
+			votes: BoundedVec<(T::PoolId, Perbill), T::MaxVotesPerCall>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			let votes = Self::allocate_by_percent(&who, &votes, now)?;
+			Self::inner_vote_batch(&who, &votes, now)
+		}
+
+		/// Let `delegate` vote and cancel votes with the voting power of the caller until the end
+		/// of the current period.
+		#[pallet::call_index(21)]
+		#[pallet::weight(
+			Weight::from_parts(10000, 0).saturating_add(Pallet::<T>::rollover_weight())
+		)]
+		#[transactional]
+		pub fn delegate(
+			origin: OriginFor<T>,
+			delegate: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let delegate = T::Lookup::lookup(delegate)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::inner_delegate(who, delegate)
+		}
+
+		#[pallet::call_index(22)]
+		#[pallet::weight(10000)]
+		#[transactional]
+		pub fn revoke_delegation(origin: OriginFor<T>) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Delegations::<T>::take(&who).ok_or(Error::<T>::NotDelegate)?;
+
+			Self::deposit_event(Event::RevokeDelegation { delegator: who });
+			Ok(())
+		}
+
+		/// Vote with the voting power of `delegator`, whose votes are recorded as its own.
+		#[pallet::call_index(23)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads_writes(8, 6).saturating_mul(votes.len() as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight())
+		.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
+		#[transactional]
+		pub fn delegated_vote_batch(
+			origin: OriginFor<T>,
+			delegator: <T::Lookup as StaticLookup>::Source,
+			votes: BoundedVec<(T::PoolId, Balance), T::MaxVotesPerCall>,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let delegator = T::Lookup::lookup(delegator)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::ensure_delegate(&delegator, &who)?;
+			Self::inner_vote_batch(&delegator, &votes, now)
+		}
+
+		#[pallet::call_index(24)]
+		#[pallet::weight(Weight::from_parts(10000, 0)
+			.saturating_add(Pallet::<T>::rollover_weight())
+			.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
+		#[transactional]
+		pub fn delegated_cancel_vote(
+			origin: OriginFor<T>,
+			delegator: <T::Lookup as StaticLookup>::Source,
+			pool_id: T::PoolId,
+			amounts: Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let delegator = T::Lookup::lookup(delegator)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::ensure_delegate(&delegator, &who)?;
+			Self::cancel_vote_pool(&delegator, pool_id, amounts, now)?;
+			Self::refresh_vote_decay(&delegator, now)
+		}
+
+		/// Deposit a bribe for the voters of a pool in the current or a future period.
+		#[pallet::call_index(25)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads(T::MaxBribeCurrencies::get() as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight()))]
+		#[transactional]
+		pub fn deposit_bribe(
+			origin: OriginFor<T>,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			amount: Balance,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
+			ensure!(period_id >= current_period_id, Error::<T>::ExpiredPeriod);
+			Self::inner_deposit_bribe(&who, period_id, pool_id, currency_id, amount)
+		}
+
+		/// Claim the bribes of a pool in an ended period, pro rata to the score the caller added
+		/// to the pool by voting in it. The shares below the existential deposit of their
+		/// currency are left to the depositors.
+		#[pallet::call_index(26)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get()
+				.reads_writes(3, 3)
+				.saturating_mul(T::MaxBribeCurrencies::get() as u64)
+				.saturating_add(T::DbWeight::get().reads_writes(4, 1))
+		))]
+		#[transactional]
+		pub fn claim_bribes(
+			origin: OriginFor<T>,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			Self::inner_claim_bribes(&who, period_id, pool_id)
+		}
+
+		/// Take back the share of the caller in the unclaimed bribes once the claim duration has
+		/// passed, or roll it over to the pool in the open period.
+		#[pallet::call_index(27)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads(T::MaxBribeCurrencies::get() as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight()))]
+		#[transactional]
+		pub fn reclaim_bribe(
+			origin: OriginFor<T>,
+			period_id: PeriodId,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			roll_over: bool,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::inner_reclaim_bribe(&who, period_id, pool_id, currency_id, roll_over, now)
+		}
+
+		/// Set a pool which no longer exists non votable, so its voters can cancel their votes
+		/// and vote for other pools.
+		#[pallet::call_index(28)]
+		#[pallet::weight(
+			Weight::from_parts(10000, 0).saturating_add(Pallet::<T>::rollover_weight())
+		)]
+		#[transactional]
+		pub fn remove_invalid_pool(origin: OriginFor<T>, pool_id: T::PoolId) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(Self::gauge_pools().contains(&pool_id), Error::<T>::InvalidPoolId);
+			ensure!(!T::PoolValidator::is_valid_pool(pool_id), Error::<T>::ValidPool);
+
+			let now = T::TimeProvider::now().as_secs();
+			Self::inner_update_vote_periold(now)?;
+			let current_period_id =
+				Self::get_current_period().ok_or(Error::<T>::InvalidPeriodId)?;
+
+			Self::update_votable(false, current_period_id, &[pool_id])?;
+
+			Self::deposit_event(Event::RemoveInvalidPool { period: current_period_id, pool_id });
+			Ok(())
+		}
+
+		/// Scale the votes of an account down to its current voting power, releasing the voting
+		/// power spent above it. Anyone can call it.
+		///
+		/// - `vote_pools`: The number of pools the account voted for, which the weight is charged
+		///   for.
+		#[pallet::call_index(29)]
+		#[pallet::weight(Weight::from_parts(10000, 0).saturating_add(
+			T::DbWeight::get().reads_writes(6, 6).saturating_mul(*vote_pools as u64)
+		)
+		.saturating_add(Pallet::<T>::rollover_weight())
+		.saturating_add(Pallet::<T>::refresh_vote_decay_weight()))]
+		#[transactional]
+		pub fn poke_votes(
+			origin: OriginFor<T>,
+			account: <T::Lookup as StaticLookup>::Source,
+			vote_pools: u32,
+		) -> DispatchResult {
+			ensure_signed(origin)?;
+			let account = T::Lookup::lookup(account)?;
+			let now = T::TimeProvider::now().as_secs();
+
+			Self::inner_update_vote_periold(now)?;
+			Self::inner_poke_votes(&account, vote_pools, now)
+		}
 	}
 }
 
 impl<T: Config> Pallet<T> {
-	fn get_current_period() -> Option<PeriodId> {
+	/// Ensure the origin is the admin origin or signed by the admin account.
+	fn ensure_admin(origin: T::RuntimeOrigin) -> DispatchResult {
+		let origin = match T::AdminOrigin::try_origin(origin) {
+			Ok(_) => return Ok(()),
+			Err(origin) => origin,
+		};
+		let who = ensure_signed(origin)?;
+		ensure!(Self::admin() == Some(who), Error::<T>::OnlyAdmin);
+		Ok(())
+	}
+
+	/// The account paying the emissions.
+	pub fn reward_account() -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating(b"reward")
+	}
+
+	/// The state of a pool in a period, with the score and amount it inherits from its last
+	/// updated period if no vote touched it yet.
+	pub fn pool_state_of(period_id: PeriodId, pool_id: T::PoolId) -> Option<PoolState> {
+		let state = Self::global_pool_state(period_id, pool_id);
+		match state {
+			Some(state) if state.inherit || period_id == 0 => return Some(state),
+			_ => {},
+		}
+
+		let last_update_period_id = Self::pool_last_update_period(pool_id).unwrap_or_default();
+		if last_update_period_id >= period_id {
+			return state
+		}
+		let last_pool_state = Self::global_pool_state(last_update_period_id, pool_id)?;
+
+		let start = Self::vote_period(period_id)?.start;
+		let mut state = state.unwrap_or_default();
+		state.score = Self::vote_decay_value(pool_id, start);
+		state.total_amount = last_pool_state.total_amount;
+		if !state.reset_votable {
+			state.votable = last_pool_state.votable;
+		}
+		Some(state)
+	}
+
+	/// Record the scores of the votable pools in the ended period.
+	fn finalize_period(period_id: PeriodId) {
+		let scores = Self::live_period_scores(period_id);
+		PeriodScores::<T>::insert(period_id, scores.clone());
+
+		Self::deposit_event(Event::PeriodFinalized { period_id, scores });
+	}
+
+	/// The scores of the votable pools in a period, computed from their effective state.
+	pub(crate) fn live_period_scores(period_id: PeriodId) -> Vec<(T::PoolId, Balance)> {
+		Self::gauge_pools()
+			.into_iter()
+			.filter_map(|pool_id| {
+				Self::pool_state_of(period_id, pool_id)
+					.filter(|state| state.votable)
+					.map(|state| (pool_id, state.score))
+			})
+			.collect()
+	}
+
+	/// The scores of the votable pools with an lp currency in a period, which split the emission
+	/// budget.
+	pub(crate) fn emission_scores(period_id: PeriodId) -> Vec<(T::PoolId, Balance)> {
+		PoolLpCurrency::<T>::iter_keys()
+			.filter_map(|pool_id| {
+				Self::pool_state_of(period_id, pool_id)
+					.filter(|state| state.votable && !state.score.is_zero())
+					.map(|state| (pool_id, state.score))
+			})
+			.collect()
+	}
+
+	/// Store the state a pool inherits in a period if no vote touched it yet. Pools with nothing
+	/// to inherit are left untouched.
+	fn inherit_pool_state(period_id: PeriodId, pool_id: T::PoolId) {
+		if Self::pool_last_update_period(pool_id).unwrap_or_default() >= period_id {
+			return
+		}
+		let _ =
+			GlobalPoolState::<T>::try_mutate(period_id, pool_id, |pool_state| -> DispatchResult {
+				let mut new_state = pool_state.unwrap_or_default();
+				if new_state.inherit {
+					return Ok(())
+				}
+				Self::inherit_expired_pool(pool_id, &mut new_state, period_id)?;
+				*pool_state = Some(new_state);
+				Ok(())
+			});
+	}
+
+	/// The weight of starting the next period, which finalizes the ended period and streams the
+	/// emissions of every pool with an lp currency.
+	pub(crate) fn rollover_weight() -> Weight {
+		let pools = Self::gauge_pools().len() as u64;
+		let emission_pools = T::MaxEmissionPools::get() as u64;
+		let steps = T::MaxLockDuration::get() / VOTE_DECAY_STEP + 2;
+		T::DbWeight::get().reads_writes(
+			pools
+				.saturating_add(emission_pools)
+				.saturating_mul(steps)
+				.saturating_add(pools.saturating_mul(3))
+				.saturating_add(emission_pools.saturating_mul(6)) +
+				6,
+			emission_pools.saturating_mul(4) + 3,
+		)
+	}
+
+	/// The weight of going through the steps of the votes on a pool, at most one per
+	/// `VOTE_DECAY_STEP` of the max lock duration.
+	pub(crate) fn vote_decay_weight() -> Weight {
+		let steps = T::MaxLockDuration::get() / VOTE_DECAY_STEP + 1;
+		T::DbWeight::get().reads_writes(steps + 1, steps + 1)
+	}
+
+	/// The weight of capping the votes of an account on every gauge pool at its voting power.
+	pub(crate) fn refresh_vote_decay_weight() -> Weight {
+		Self::vote_decay_weight()
+			.saturating_add(T::DbWeight::get().reads_writes(4, 4))
+			.saturating_mul(2 * T::MaxGaugePools::get() as u64)
+			.saturating_add(T::DbWeight::get().reads(2))
+	}
+
+	/// The weight of accruing the rewards of a staker in a pool, in every currency of the
+	/// emission budget.
+	pub(crate) fn staker_rewards_weight() -> Weight {
+		let currencies = Self::emission_budget().len() as u64;
+		T::DbWeight::get().reads_writes(
+			currencies.saturating_mul(3).saturating_add(5),
+			currencies.saturating_mul(2).saturating_add(1),
+		)
+	}
+
+	/// Split the emission budget across the votable pools with an lp currency by their score in
+	/// the ended period. The emissions of a pool are streamed to its stakers from `now` until
+	/// `stream_end`, together with what its last stream has not released yet.
+	fn distribute_emissions(
+		period_id: PeriodId,
+		now: Timestamp,
+		stream_end: Timestamp,
+	) -> DispatchResult {
+		let budget = Self::emission_budget();
+		if budget.is_empty() {
+			return Ok(())
+		}
+
+		let scores = Self::emission_scores(period_id);
+		let total_score = scores
+			.iter()
+			.try_fold(Balance::zero(), |total, (_, score)| total.checked_add(*score))
+			.ok_or(Error::<T>::Math)?;
+
+		for (pool_id, score) in scores {
+			if Self::pool_stake_amount(pool_id).is_zero() {
+				continue
+			}
+			Self::accrue_emissions(pool_id, now)?;
+
+			let mut stream = Self::pool_emission_stream(pool_id).unwrap_or_default();
+			let mut emissions = Vec::new();
+			for (currency_id, amount) in budget.iter() {
+				let emission =
+					balance_mul_div(*amount, score, total_score).ok_or(Error::<T>::Math)?;
+				if emission.is_zero() {
+					continue
+				}
+				match stream.remaining.iter_mut().find(|(id, _)| id == currency_id) {
+					Some((_, remaining)) =>
+						*remaining = remaining.checked_add(emission).ok_or(Error::<T>::Math)?,
+					None => stream.remaining.push((*currency_id, emission)),
+				}
+				emissions.push((*currency_id, emission));
+
+				Self::deposit_event(Event::DistributeEmission {
+					period_id,
+					pool_id,
+					currency_id: *currency_id,
+					amount: emission,
+				});
+			}
+			stream.last_update = now;
+			stream.end = stream_end;
+			PoolEmissionStreams::<T>::insert(pool_id, stream);
+			PeriodEmissions::<T>::insert(period_id, pool_id, emissions);
+		}
+		Ok(())
+	}
+
+	/// The emissions of a pool released by `now` and the stream left after releasing them.
+	/// Nothing is released while the pool has no stake, the emissions of that time are spread over
+	/// the rest of the stream instead.
+	pub(crate) fn released_emissions(
+		pool_id: T::PoolId,
+		now: Timestamp,
+	) -> Result<Option<(Vec<(T::CurrencyId, Balance)>, EmissionStream<T::CurrencyId>)>, DispatchError>
+	{
+		let mut stream = match Self::pool_emission_stream(pool_id) {
+			Some(stream) => stream,
+			None => return Ok(None),
+		};
+		let until = now.min(stream.end);
+		if until <= stream.last_update {
+			return Ok(None)
+		}
+		let elapsed = Balance::from(until - stream.last_update);
+		let duration = Balance::from(stream.end - stream.last_update);
+		stream.last_update = until;
+
+		let mut released = Vec::new();
+		if Self::pool_stake_amount(pool_id).is_zero() {
+			return Ok(Some((released, stream)))
+		}
+		for (currency_id, remaining) in stream.remaining.iter_mut() {
+			let amount = balance_mul_div(*remaining, elapsed, duration).ok_or(Error::<T>::Math)?;
+			*remaining = remaining.checked_sub(amount).ok_or(Error::<T>::Math)?;
+			released.push((*currency_id, amount));
+		}
+		Ok(Some((released, stream)))
+	}
+
+	/// Add the emissions of a pool released by `now` to the reward per share of its stakers.
+	fn accrue_emissions(pool_id: T::PoolId, now: Timestamp) -> DispatchResult {
+		let (released, stream) = match Self::released_emissions(pool_id, now)? {
+			Some(released) => released,
+			None => return Ok(()),
+		};
+		let staked = Self::pool_stake_amount(pool_id);
+		for (currency_id, amount) in released {
+			if amount.is_zero() {
+				continue
+			}
+			let added =
+				balance_mul_div(amount, REWARD_PRECISION, staked).ok_or(Error::<T>::Math)?;
+			PoolRewardPerShare::<T>::try_mutate(
+				pool_id,
+				currency_id,
+				|reward_per_share| -> DispatchResult {
+					*reward_per_share =
+						reward_per_share.checked_add(added).ok_or(Error::<T>::Math)?;
+					Ok(())
+				},
+			)?;
+		}
+		PoolEmissionStreams::<T>::insert(pool_id, stream);
+		Ok(())
+	}
+
+	/// Accrue the rewards of `who` in a pool up to the current reward per share.
+	fn update_staker_rewards(pool_id: T::PoolId, who: &T::AccountId) -> DispatchResult {
+		Self::accrue_emissions(pool_id, T::TimeProvider::now().as_secs())?;
+
+		let staked = Self::account_stake_amount(who, pool_id);
+		for (currency_id, reward_per_share) in PoolRewardPerShare::<T>::iter_prefix(pool_id) {
+			StakerRewards::<T>::try_mutate(
+				(pool_id, who, currency_id),
+				|reward| -> DispatchResult {
+					let accrued = reward_per_share
+						.checked_sub(reward.reward_per_share_paid)
+						.and_then(|delta| balance_mul_div(staked, delta, REWARD_PRECISION))
+						.ok_or(Error::<T>::Math)?;
+					reward.pending = reward.pending.checked_add(accrued).ok_or(Error::<T>::Math)?;
+					reward.reward_per_share_paid = reward_per_share;
+					Ok(())
+				},
+			)?;
+		}
+		Ok(())
+	}
+
+	fn vote_pool(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		amounts: Balance,
+		now: Timestamp,
+	) -> DispatchResult {
+		let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
+
+		let vote_period =
+			Self::vote_period(current_period_id).ok_or(Error::<T>::InvalidPeriodId)?;
+		ensure!(now < vote_period.end, Error::<T>::ExpiredPeriod);
+
+		GlobalPoolState::<T>::try_mutate(
+			current_period_id,
+			pool_id,
+			|pool_state| -> DispatchResult {
+				let mut new_state = PoolState::default();
+				if let Some(state) = pool_state {
+					new_state = *state;
+				}
+
+				Self::inherit_expired_pool(pool_id, &mut new_state, current_period_id)?;
+				Self::inner_vote(
+					&mut new_state,
+					who,
+					amounts,
+					pool_id,
+					current_period_id,
+					&vote_period,
+					now,
+				)?;
+
+				*pool_state = Some(new_state);
+				Ok(())
+			},
+		)
+	}
+
+	fn cancel_vote_pool(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		amounts: Balance,
+		now: Timestamp,
+	) -> DispatchResult {
+		let current_period_id = Self::get_current_period().ok_or(Error::<T>::Math)?;
+
+		GlobalPoolState::<T>::try_mutate(
+			current_period_id,
+			pool_id,
+			|pool_state| -> DispatchResult {
+				let mut new_state = PoolState::default();
+				if let Some(state) = pool_state {
+					new_state = *state;
+				}
+				Self::inherit_expired_pool(pool_id, &mut new_state, current_period_id)?;
+				Self::inner_cancel_vote(
+					&mut new_state,
+					who,
+					amounts,
+					pool_id,
+					current_period_id,
+					now,
+				)?;
+
+				*pool_state = Some(new_state);
+				Ok(())
+			},
+		)
+	}
+
+	pub(crate) fn get_current_period() -> Option<PeriodId> {
 		Self::next_period_id().checked_sub(One::one())
 	}
 
 	fn update_votable(votable: bool, period: PeriodId, pools: &[T::PoolId]) -> DispatchResult {
+		GaugePools::<T>::try_mutate(|gauge_pools| -> DispatchResult {
+			gauge_pools.extend(pools.iter().copied());
+			ensure!(
+				gauge_pools.len() as u32 <= T::MaxGaugePools::get(),
+				Error::<T>::TooManyGaugePools
+			);
+			Ok(())
+		})?;
 		for pool_id in pools {
 			GlobalPoolState::<T>::try_mutate(period, pool_id, |pool_state| -> DispatchResult {
 				if let Some(state) = pool_state {
@@ -534,6 +1698,7 @@ impl<T: Config> Pallet<T> {
 			if current_period.end > now {
 				return Ok(())
 			}
+			Self::finalize_period(current_period_id);
 			VotePeriods::<T>::try_mutate(*next_period_id, |period| -> DispatchResult {
 				let vote_set_window = Self::vote_set_window();
 				let vote_duration = Self::vote_duration();
@@ -548,6 +1713,7 @@ impl<T: Config> Pallet<T> {
 				}
 				next_period.end =
 					next_period.start.checked_add(vote_duration).ok_or(Error::<T>::Math)?;
+				Self::distribute_emissions(current_period_id, now, next_period.end)?;
 
 				Self::deposit_event(Event::UpdateVotePeriod {
 					period: *next_period_id,
@@ -561,6 +1727,7 @@ impl<T: Config> Pallet<T> {
 			})?;
 
 			*next_period_id = next_period_id.checked_add(One::one()).ok_or(Error::<T>::Math)?;
+			RolloverBackoff::<T>::kill();
 
 			Ok(())
 		})
@@ -578,9 +1745,7 @@ impl<T: Config> Pallet<T> {
 		if !pool_state.votable {
 			return Err(Error::<T>::NonVotablePool.into())
 		}
-		let vote_currency = Self::vote_currency().ok_or(Error::<T>::Uninitialized)?;
-		let pallet_account = T::PalletId::get().into_account_truncating();
-		T::MultiCurrency::transfer(vote_currency, who, &pallet_account, amount)?;
+		Self::spend_voting_power(who, amount, now)?;
 
 		if now < current_perold.start {
 			now = current_perold.start;
@@ -594,15 +1759,16 @@ impl<T: Config> Pallet<T> {
 					balance_mul_div(stake_time.into(), amount, period_len.into())
 				})
 			})
-			.and_then(|score| -> Option<(Balance, Balance)> {
+			.and_then(|score| -> Option<(Balance, Balance, Balance)> {
 				let new_score = pool_state.score.checked_add(score)?;
 				let new_vote_amount = pool_state.total_amount.checked_add(amount)?;
-				Some((new_score, new_vote_amount))
+				Some((new_score, new_vote_amount, score))
 			})
 			.ok_or(Error::<T>::Math)?;
 
 		pool_state.score = calculate_result.0;
 		pool_state.total_amount = calculate_result.1;
+		Self::add_vote_score(current_period_id, pool_id, who, calculate_result.2)?;
 
 		AccountVoteAmount::<T>::try_mutate(who, pool_id, |mutatalbe_amount| -> DispatchResult {
 			if let Some(old_amount) = mutatalbe_amount {
@@ -636,14 +1802,33 @@ impl<T: Config> Pallet<T> {
 				ensure!(*old_amount >= amount, Error::<T>::InsufficientAmount);
 
 				*old_amount = old_amount.checked_sub(amount).ok_or(Error::<T>::Math)?;
-
-				let pallet_account = T::PalletId::get().into_account_truncating();
-				let vote_currency = Self::vote_currency().ok_or(Error::<T>::Uninitialized)?;
-				T::MultiCurrency::transfer(vote_currency, &pallet_account, who, amount)?;
+				AccountTotalVote::<T>::try_mutate(who, |total| -> DispatchResult {
+					*total = total.checked_sub(amount).ok_or(Error::<T>::Math)?;
+					Ok(())
+				})?;
 
 				let current_period =
 					Self::vote_period(current_period_id).ok_or(Error::<T>::InvalidPeriodId)?;
 
+				let removed_score = if now < current_period.start {
+					amount
+				} else if now <= current_period.end {
+					current_period
+						.end
+						.checked_sub(current_period.start)
+						.and_then(|period_len| {
+							balance_mul_div(
+								(current_period.end - now).into(),
+								amount,
+								period_len.into(),
+							)
+						})
+						.ok_or(Error::<T>::Math)?
+				} else {
+					Zero::zero()
+				};
+				Self::remove_vote_score(current_period_id, pool_id, who, removed_score);
+
 				if now < current_period.start {
 					pool_state.score =
 						pool_state.score.checked_sub(amount).ok_or(Error::<T>::Math)?;
@@ -699,8 +1884,9 @@ impl<T: Config> Pallet<T> {
 		let last_pool_state = Self::global_pool_state(last_update_period_id, pool_id)
 			.ok_or(Error::<T>::InvalidPeriodId)?;
 
+		let start = Self::vote_period(current_period_id).ok_or(Error::<T>::InvalidPeriodId)?.start;
 		pool_state.inherit = true;
-		pool_state.score = last_pool_state.total_amount;
+		pool_state.score = Self::vote_decay_value(pool_id, start);
 		pool_state.total_amount = last_pool_state.total_amount;
 
 		if !pool_state.reset_votable {
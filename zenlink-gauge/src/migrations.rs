@@ -0,0 +1,181 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+use super::*;
+use frame_support::traits::OnRuntimeUpgrade;
+use sp_runtime::Saturating;
+use sp_std::marker::PhantomData;
+#[cfg(feature = "try-runtime")]
+use sp_std::vec::Vec;
+
+pub mod v1 {
+	use super::*;
+
+	/// Fill `GaugePools` with the pools whose state changed before it was added, so the period
+	/// rollover finalizes them and `on_idle` lets them inherit their state.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let pools: BTreeSet<T::PoolId> = PoolLastUpdatePerold::<T>::iter_keys().collect();
+			let count = pools.len() as u64;
+			GaugePools::<T>::mutate(|gauge_pools| gauge_pools.extend(pools));
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(count.saturating_add(2), 2)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let pools = PoolLastUpdatePerold::<T>::iter_keys().count() as u32;
+			Ok(pools.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pools = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version not updated");
+			ensure!(Pallet::<T>::gauge_pools().len() as u32 >= pools, "gauge pools missing");
+			ensure!(
+				PoolLastUpdatePerold::<T>::iter_keys()
+					.all(|pool_id| Pallet::<T>::gauge_pools().contains(&pool_id)),
+				"gauge pools missing"
+			);
+			Ok(())
+		}
+	}
+}
+
+pub mod v2 {
+	use super::*;
+	use sp_std::collections::btree_map::BTreeMap;
+
+	/// Cancel the votes paid with vote currency before voting spent the power of a lock, and
+	/// refund the currency held for them.
+	///
+	/// Such votes are the ones of accounts which spent no voting power.
+	pub struct MigrateToV2<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV2<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 1 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut legacy_votes: BTreeMap<T::AccountId, Vec<(T::PoolId, Balance)>> =
+				BTreeMap::new();
+			let mut reads = 3u64;
+			for (who, pool_id, amount) in AccountVoteAmount::<T>::iter() {
+				reads.saturating_inc();
+				if !amount.is_zero() && Pallet::<T>::account_total_vote(&who).is_zero() {
+					legacy_votes.entry(who).or_default().push((pool_id, amount));
+				}
+			}
+
+			let now = T::TimeProvider::now().as_secs();
+			let pallet_account: T::AccountId = T::PalletId::get().into_account_truncating();
+			let mut votes = 0u64;
+			for (who, pool_votes) in legacy_votes {
+				votes.saturating_accrue(pool_votes.len() as u64);
+				let result = with_storage_layer(|| -> DispatchResult {
+					let total = pool_votes
+						.iter()
+						.try_fold(Balance::zero(), |total, (_, amount)| total.checked_add(*amount))
+						.ok_or(Error::<T>::Math)?;
+					AccountTotalVote::<T>::insert(&who, total);
+					for (pool_id, amount) in pool_votes.iter() {
+						Pallet::<T>::cancel_vote_pool(&who, *pool_id, *amount, now)?;
+					}
+
+					let vote_currency =
+						Pallet::<T>::vote_currency().ok_or(Error::<T>::Uninitialized)?;
+					T::MultiCurrency::transfer(vote_currency, &pallet_account, &who, total)
+				});
+				if result.is_err() {
+					frame_support::log::warn!(
+						target: "runtime::zenlink-gauge",
+						"failed to refund the legacy votes of {:?}",
+						who
+					);
+				}
+			}
+			StorageVersion::new(2).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(
+				reads.saturating_add(votes.saturating_mul(6)),
+				votes.saturating_mul(6).saturating_add(1),
+			)
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok(Vec::new())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 2, "storage version not updated");
+			ensure!(
+				AccountVoteAmount::<T>::iter().all(|(who, _, amount)| amount.is_zero() ||
+					!Pallet::<T>::account_total_vote(&who).is_zero()),
+				"legacy votes left"
+			);
+			Ok(())
+		}
+	}
+}
+
+pub mod v3 {
+	use super::*;
+
+	/// Cap the votes of every account at its voting power as it decays, so the score the votes
+	/// cast before pass to later periods decays with it.
+	pub struct MigrateToV3<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV3<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 2 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let accounts: BTreeSet<T::AccountId> =
+				AccountVoteAmount::<T>::iter_keys().map(|(who, _)| who).collect();
+			let now = T::TimeProvider::now().as_secs();
+			for who in accounts.iter() {
+				if Pallet::<T>::refresh_vote_decay(who, now).is_err() {
+					frame_support::log::warn!(
+						target: "runtime::zenlink-gauge",
+						"failed to cap the votes of {:?}",
+						who
+					);
+				}
+			}
+			StorageVersion::new(3).put::<Pallet<T>>();
+
+			Pallet::<T>::refresh_vote_decay_weight()
+				.saturating_mul(accounts.len() as u64)
+				.saturating_add(T::DbWeight::get().reads_writes(accounts.len() as u64 + 2, 1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			Ok(Vec::new())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(_state: Vec<u8>) -> Result<(), &'static str> {
+			ensure!(Pallet::<T>::on_chain_storage_version() == 3, "storage version not updated");
+			ensure!(
+				AccountVoteAmount::<T>::iter().all(|(who, pool_id, amount)| amount.is_zero() ||
+					Pallet::<T>::account_vote_decay(&who, pool_id)
+						.map_or(false, |vote| vote.amount == amount)),
+				"votes not capped"
+			);
+			Ok(())
+		}
+	}
+}
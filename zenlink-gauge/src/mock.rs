@@ -4,7 +4,11 @@ use scale_info::TypeInfo;
 use serde::{Deserialize, Serialize};
 use std::time::SystemTime;
 
-use frame_support::{pallet_prelude::GenesisBuild, parameter_types, traits::Contains, PalletId};
+use frame_support::{
+	ord_parameter_types, pallet_prelude::GenesisBuild, parameter_types, traits::Contains,
+	weights::constants::RocksDbWeight, PalletId,
+};
+use frame_system::EnsureSignedBy;
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
@@ -31,17 +35,39 @@ parameter_types! {
 	pub const MaxReserves: u32 = 50;
 	pub const MaxLocks:u32 = 50;
 	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
+	pub const MaxLockDuration: u64 = 3600 * 24 * 28;
+	pub const MaxVotesPerCall: u32 = 10;
+	pub const MaxEmissionPools: u32 = 10;
+	pub const MaxGaugePools: u32 = 10;
+	pub const BribeClaimDuration: u64 = 3600 * 24 * 7;
+	pub const MaxBribeCurrencies: u32 = 2;
+	pub const MinBribeDeposit: Balance = TOKEN1_UNIT;
+	pub static RemovedPools: Vec<u32> = vec![];
+}
+
+ord_parameter_types! {
+	pub const GaugeCouncil: AccountId = 3;
 }
 
 parameter_type_with_key! {
-	pub ExistentialDeposits: |_currency_id: CurrencyId| -> u128 {
-		0
+	pub ExistentialDeposits: |currency_id: CurrencyId| -> u128 {
+		match currency_id {
+			CurrencyId::Token(TOKEN5_SYMBOL) => TOKEN1_UNIT,
+			_ => 0,
+		}
 	};
 }
 
 pub type AccountId = u128;
 pub type TokenSymbol = u8;
 
+pub struct MockPoolValidator;
+impl PoolValidator<u32> for MockPoolValidator {
+	fn is_valid_pool(pool_id: u32) -> bool {
+		!RemovedPools::get().contains(&pool_id)
+	}
+}
+
 pub struct MockDustRemovalWhitelist;
 impl Contains<AccountId> for MockDustRemovalWhitelist {
 	fn contains(_a: &AccountId) -> bool {
@@ -80,7 +106,7 @@ impl frame_system::Config for Test {
 	type Header = Header;
 	type RuntimeEvent = RuntimeEvent;
 	type BlockHashCount = BlockHashCount;
-	type DbWeight = ();
+	type DbWeight = RocksDbWeight;
 	type Version = ();
 	type AccountData = pallet_balances::AccountData<u128>;
 	type OnNewAccount = ();
@@ -136,8 +162,17 @@ impl Config for Test {
 	type CurrencyId = CurrencyId;
 	type MultiCurrency = Tokens;
 	type PoolId = u32;
+	type AdminOrigin = EnsureSignedBy<GaugeCouncil, AccountId>;
+	type PoolValidator = MockPoolValidator;
 	type TimeProvider = TimestampPallet;
 	type PalletId = GaugePalletId;
+	type BribeClaimDuration = BribeClaimDuration;
+	type MaxLockDuration = MaxLockDuration;
+	type MaxVotesPerCall = MaxVotesPerCall;
+	type MaxEmissionPools = MaxEmissionPools;
+	type MaxGaugePools = MaxGaugePools;
+	type MaxBribeCurrencies = MaxBribeCurrencies;
+	type MinBribeDeposit = MinBribeDeposit;
 }
 
 frame_support::construct_runtime!(
@@ -159,8 +194,13 @@ pub type GaugePallet = Pallet<Test>;
 
 pub const ALICE: u128 = 1;
 pub const BOB: u128 = 2;
+pub const CHARLIE: u128 = 3;
 
 pub const TOKEN1_SYMBOL: u8 = 1;
+pub const TOKEN2_SYMBOL: u8 = 2;
+pub const TOKEN3_SYMBOL: u8 = 3;
+pub const TOKEN4_SYMBOL: u8 = 4;
+pub const TOKEN5_SYMBOL: u8 = 5;
 pub const TOKEN1_UNIT: u128 = 1_000_000_000_000_000_000;
 
 pub fn new_test_ext() -> sp_io::TestExternalities {
@@ -172,7 +212,10 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 	orml_tokens::GenesisConfig::<Test> {
 		balances: vec![
 			(ALICE, CurrencyId::Token(TOKEN1_SYMBOL), TOKEN1_UNIT * 50),
-			(BOB, CurrencyId::Token(TOKEN1_SYMBOL), TOKEN1_UNIT * 30),
+			(BOB, CurrencyId::Token(TOKEN1_SYMBOL), TOKEN1_UNIT * 60),
+			(ALICE, CurrencyId::Token(TOKEN2_SYMBOL), TOKEN1_UNIT * 100),
+			(BOB, CurrencyId::Token(TOKEN3_SYMBOL), TOKEN1_UNIT * 100),
+			(ALICE, CurrencyId::Token(TOKEN5_SYMBOL), TOKEN1_UNIT * 100),
 		],
 	}
 	.assimilate_storage(&mut t)
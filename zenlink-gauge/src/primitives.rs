@@ -6,6 +6,12 @@ pub type PeriodId = u32;
 pub type Duration = u64;
 pub type Timestamp = u64;
 
+// The most blocks `on_initialize` waits before retrying a failed rollover.
+pub const MAX_ROLLOVER_BACKOFF: u32 = 256;
+
+// The times votes start and stop decaying are rounded down to a multiple of it.
+pub const VOTE_DECAY_STEP: Timestamp = 24 * 3600;
+
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
 pub struct VotePeriod {
 	// The start timestmap of a vote period.
@@ -28,6 +34,139 @@ pub struct PoolState {
 	pub total_amount: Balance,
 }
 
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct VoteLock {
+	// The amount of vote token locked.
+	pub amount: Balance,
+	// The timestamp the lock expires at.
+	pub end: Timestamp,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct Delegation<AccountId> {
+	// The account voting with the voting power of the delegator.
+	pub delegate: AccountId,
+	// The period the delegation is valid in.
+	pub period_id: PeriodId,
+}
+
+/// The scale of the reward per share of the lp tokens staked in a pool.
+pub const REWARD_PRECISION: Balance = 1_000_000_000_000_000_000;
+
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct BribeState {
+	// The amount of bribe deposited.
+	pub amount: Balance,
+	// The amount of bribe claimed by the voters.
+	pub claimed: Balance,
+}
+
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct StakerReward {
+	// The reward per share of the pool already accounted to the staker.
+	pub reward_per_share_paid: Balance,
+	// The reward accrued to the staker and not claimed yet.
+	pub pending: Balance,
+}
+
+/// The emissions of a pool released to its stakers over time.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct EmissionStream<CurrencyId> {
+	// The emissions not released to the stakers yet.
+	pub remaining: Vec<(CurrencyId, Balance)>,
+	// The time the emissions were last released up to.
+	pub last_update: Timestamp,
+	// The time all the remaining emissions are released by.
+	pub end: Timestamp,
+}
+
+/// The votes of an account on a pool, capped at its voting power as it decays.
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct AccountVoteDecay {
+	// The amount of the votes.
+	pub amount: Balance,
+	// The value the votes lose per second once capped.
+	pub slope: Balance,
+	// The time the voting power falls below the total votes of the account.
+	pub capped_at: Timestamp,
+	// The time the votes are worth nothing.
+	pub end: Timestamp,
+}
+
+impl AccountVoteDecay {
+	/// The value of the votes at `at`.
+	pub fn value_at(&self, at: Timestamp) -> Balance {
+		if at < self.capped_at {
+			self.amount
+		} else {
+			self.slope.saturating_mul(self.end.saturating_sub(at).into())
+		}
+	}
+}
+
+/// The value of all the votes on a pool at a checkpoint.
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct VoteDecay {
+	// The value of the votes not capped yet.
+	pub flat: Balance,
+	// The value of the capped votes.
+	pub decaying: Balance,
+	// The value the capped votes lose per second.
+	pub slope: Balance,
+	// The time the values are at.
+	pub checkpoint: Timestamp,
+}
+
+impl VoteDecay {
+	pub fn value(&self) -> Balance {
+		self.flat.saturating_add(self.decaying)
+	}
+
+	/// Move the checkpoint forward to `at`, applying the changes `step_at` returns for every
+	/// step passed.
+	pub fn advance_to(
+		&mut self,
+		at: Timestamp,
+		mut step_at: impl FnMut(Timestamp) -> VoteDecayStep,
+	) {
+		while self.checkpoint < at {
+			if self.flat.is_zero() && self.slope.is_zero() {
+				self.decaying = Zero::zero();
+				self.checkpoint = at;
+				break
+			}
+			let next_step = (self.checkpoint / VOTE_DECAY_STEP)
+				.saturating_add(1)
+				.saturating_mul(VOTE_DECAY_STEP);
+			let to = next_step.min(at);
+			self.decaying = self
+				.decaying
+				.saturating_sub(self.slope.saturating_mul((to - self.checkpoint).into()));
+			self.checkpoint = to;
+			if to == next_step {
+				let step = step_at(next_step);
+				self.flat = self.flat.saturating_sub(step.capped);
+				self.decaying = self.decaying.saturating_add(step.capped_value);
+				self.slope =
+					self.slope.saturating_add(step.slope_in).saturating_sub(step.slope_out);
+			}
+		}
+	}
+}
+
+/// The changes to the value of the votes on a pool at a step.
+#[derive(Encode, Decode, Copy, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct VoteDecayStep {
+	// The amount of the votes capped at the step.
+	pub capped: Balance,
+	// The value of the votes capped at the step.
+	pub capped_value: Balance,
+	// The slope of the votes capped at the step.
+	pub slope_in: Balance,
+	// The slope of the votes worth nothing from the step.
+	pub slope_out: Balance,
+}
+
 pub fn balance_mul_div(x: Balance, y: Balance, z: Balance) -> Option<Balance> {
 	multiply_by_rational_with_rounding(x, y, z, Rounding::Down)
 }
@@ -0,0 +1,130 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+use sp_std::collections::btree_map::BTreeMap;
+
+use super::*;
+
+/// The id and time range of a vote period.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GaugePeriodInfo {
+	pub period_id: PeriodId,
+	pub start: Timestamp,
+	pub end: Timestamp,
+}
+
+/// The effective state of a pool in a period.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GaugePoolInfo<PoolId, Balance> {
+	pub pool_id: PoolId,
+	pub votable: bool,
+	// the score the pool gets in the period
+	pub score: Balance,
+	// the amount of votes the pool holds in the period
+	pub total_amount: Balance,
+}
+
+/// The rewards of a staker in one currency of a pool.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct GaugeRewardEstimate<CurrencyId, Balance> {
+	pub currency_id: CurrencyId,
+	// the reward claimable now
+	pub pending: Balance,
+	// the reward the current stake is streamed after the current period ends with the current
+	// scores
+	pub projected: Balance,
+}
+
+impl<T: Config> Pallet<T> {
+	pub fn get_current_period_info() -> Option<GaugePeriodInfo> {
+		let period_id = Self::get_current_period()?;
+		let period = Self::vote_period(period_id)?;
+		Some(GaugePeriodInfo { period_id, start: period.start, end: period.end })
+	}
+
+	pub fn get_pool_info(
+		period_id: PeriodId,
+		pool_id: T::PoolId,
+	) -> Option<GaugePoolInfo<T::PoolId, Balance>> {
+		Self::pool_state_of(period_id, pool_id).map(|state| GaugePoolInfo {
+			pool_id,
+			votable: state.votable,
+			score: state.score,
+			total_amount: state.total_amount,
+		})
+	}
+
+	pub fn get_account_votes(who: T::AccountId) -> Vec<(T::PoolId, Balance)> {
+		AccountVoteAmount::<T>::iter_prefix(who).collect()
+	}
+
+	/// The final scores of an ended period, or the live scores of a period not ended yet.
+	pub fn get_period_results(period_id: PeriodId) -> Vec<(T::PoolId, Balance)> {
+		if PeriodScores::<T>::contains_key(period_id) {
+			Self::period_scores(period_id)
+		} else {
+			Self::live_period_scores(period_id)
+		}
+	}
+
+	pub fn estimate_rewards(
+		who: T::AccountId,
+		pool_id: T::PoolId,
+	) -> Vec<GaugeRewardEstimate<T::CurrencyId, Balance>> {
+		let staked = Self::account_stake_amount(&who, pool_id);
+		let pool_staked = Self::pool_stake_amount(pool_id);
+		let mut estimates = BTreeMap::<T::CurrencyId, (Balance, Balance)>::new();
+
+		let mut rewards_per_share: BTreeMap<T::CurrencyId, Balance> =
+			PoolRewardPerShare::<T>::iter_prefix(pool_id).collect();
+		let now = T::TimeProvider::now().as_secs();
+		if let Ok(Some((released, _))) = Self::released_emissions(pool_id, now) {
+			for (currency_id, amount) in released {
+				let added =
+					balance_mul_div(amount, REWARD_PRECISION, pool_staked).unwrap_or_default();
+				let reward_per_share = rewards_per_share.entry(currency_id).or_default();
+				*reward_per_share = reward_per_share.saturating_add(added);
+			}
+		}
+
+		for (currency_id, reward_per_share) in rewards_per_share {
+			let reward = Self::staker_reward((pool_id, &who, currency_id));
+			let accrued = reward_per_share
+				.checked_sub(reward.reward_per_share_paid)
+				.and_then(|delta| balance_mul_div(staked, delta, REWARD_PRECISION))
+				.unwrap_or_default();
+			estimates.entry(currency_id).or_default().0 = reward.pending.saturating_add(accrued);
+		}
+
+		let scores = Self::get_current_period().map(Self::emission_scores).unwrap_or_default();
+		let total_score = scores
+			.iter()
+			.fold(Balance::zero(), |total, (_, score)| total.saturating_add(*score));
+		let pool_score = scores.iter().find(|(id, _)| *id == pool_id).map(|(_, score)| *score);
+		if let Some(pool_score) = pool_score.filter(|_| !pool_staked.is_zero()) {
+			for (currency_id, amount) in Self::emission_budget() {
+				let projected = balance_mul_div(amount, pool_score, total_score)
+					.and_then(|emission| balance_mul_div(emission, staked, pool_staked))
+					.unwrap_or_default();
+				estimates.entry(currency_id).or_default().1 = projected;
+			}
+		}
+
+		estimates
+			.into_iter()
+			.map(|(currency_id, (pending, projected))| GaugeRewardEstimate {
+				currency_id,
+				pending,
+				projected,
+			})
+			.collect()
+	}
+}
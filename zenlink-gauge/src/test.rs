@@ -4,7 +4,11 @@ use crate::{
 	Error,
 };
 
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{
+	assert_noop, assert_ok, bounded_vec,
+	traits::{Hooks, OnRuntimeUpgrade},
+	weights::Weight,
+};
 use frame_system::RawOrigin;
 
 const VOTE_CURRENCY: CurrencyId = Token(TOKEN1_SYMBOL);
@@ -25,6 +29,44 @@ fn update_admin_with_root_should_work() {
 	})
 }
 
+#[test]
+fn admin_origin_manage_gauge_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(GaugePallet::admin(), None);
+		mine_block();
+		let start = now() + 10;
+
+		assert_ok!(GaugePallet::initialize(
+			RawOrigin::Signed(CHARLIE).into(),
+			VOTE_CURRENCY,
+			VOTE_DURATON,
+			VOTE_SET_WINDOW,
+			start,
+		));
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(CHARLIE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::set_non_voteable_pools(RawOrigin::Signed(CHARLIE).into(), vec![2]));
+		assert_ok!(GaugePallet::update_vote_duration(
+			RawOrigin::Signed(CHARLIE).into(),
+			VOTE_DURATON * 2
+		));
+		assert_ok!(GaugePallet::update_vote_set_window(
+			RawOrigin::Signed(CHARLIE).into(),
+			VOTE_SET_WINDOW * 2
+		));
+		assert_eq!(GaugePallet::global_pool_state(0, 1).unwrap().votable, true);
+		assert_eq!(GaugePallet::global_pool_state(0, 2).unwrap().votable, false);
+
+		assert_noop!(
+			GaugePallet::update_admin(RawOrigin::Signed(ALICE).into(), ALICE),
+			DispatchError::BadOrigin
+		);
+		assert_ok!(GaugePallet::update_admin(RawOrigin::Signed(CHARLIE).into(), ALICE));
+		assert_eq!(GaugePallet::admin(), Some(ALICE));
+		assert_ok!(GaugePallet::update_admin(RawOrigin::Root.into(), BOB));
+		assert_eq!(GaugePallet::admin(), Some(BOB));
+	})
+}
+
 #[test]
 fn initialize_with_no_admin_should_fail() {
 	new_test_ext().execute_with(|| {
@@ -386,6 +428,14 @@ fn admin_update_vote_not_overwrite_should_work() {
 	})
 }
 
+fn lock_vote_currency(who: AccountId, amount: Balance) {
+	assert_ok!(GaugePallet::create_lock(
+		RawOrigin::Signed(who).into(),
+		amount,
+		now() + MaxLockDuration::get()
+	));
+}
+
 fn calculate_score(current_timestamp: Timestamp, amount: Balance, period: &VotePeriod) -> Balance {
 	amount * (period.end - current_timestamp) as Balance / (period.end - period.start) as Balance
 }
@@ -394,6 +444,7 @@ fn calculate_score(current_timestamp: Timestamp, amount: Balance, period: &VoteP
 fn vote_should_work() {
 	new_test_ext().execute_with(|| {
 		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
 		// vote before period0 start
 		assert_noop!(
 			GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 100 * TOKEN1_UNIT),
@@ -449,6 +500,7 @@ fn vote_should_work() {
 		assert_eq!(pool_state.total_amount, 20 * TOKEN1_UNIT);
 
 		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(30 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 30 * TOKEN1_UNIT);
 		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 0);
 	})
 }
@@ -457,6 +509,7 @@ fn vote_should_work() {
 fn update_pool_histroy_should_work() {
 	new_test_ext().execute_with(|| {
 		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
 		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
 		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
 
@@ -505,6 +558,7 @@ fn update_pool_histroy_should_work() {
 fn cancel_vote_should_work() {
 	new_test_ext().execute_with(|| {
 		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
 		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
 		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
 		assert_ok!(GaugePallet::cancel_vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
@@ -512,7 +566,7 @@ fn cancel_vote_should_work() {
 
 		assert_eq!(pool_state.total_amount, 0);
 		assert_eq!(pool_state.score, 0);
-		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 30 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::account_total_vote(BOB), 0);
 
 		// cancel vote in period0
 		let period0 = GaugePallet::vote_period(0).unwrap();
@@ -530,7 +584,7 @@ fn cancel_vote_should_work() {
 
 		assert_eq!(pool_state.total_amount, 5 * TOKEN1_UNIT);
 		assert_eq!(pool_state.score, added_score - removed_score);
-		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 25 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::account_total_vote(BOB), 5 * TOKEN1_UNIT);
 
 		// cancel vote after period0
 		next_block_timestamp = period0.end + 10;
@@ -543,9 +597,934 @@ fn cancel_vote_should_work() {
 
 		assert_eq!(GaugePallet::next_period_id(), 2);
 
-		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 30 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::account_total_vote(BOB), 0);
 		pool_state = GaugePallet::global_pool_state(1, 1).unwrap();
 		assert_eq!(pool_state.total_amount, 0);
 		assert_eq!(pool_state.score, 0);
 	})
 }
+
+const LP1_CURRENCY: CurrencyId = Token(TOKEN2_SYMBOL);
+const LP2_CURRENCY: CurrencyId = Token(TOKEN3_SYMBOL);
+const REWARD_CURRENCY: CurrencyId = Token(TOKEN4_SYMBOL);
+
+fn setup_emissions() {
+	assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+	assert_ok!(GaugePallet::set_pool_lp_currency(RawOrigin::Signed(ALICE).into(), 1, LP1_CURRENCY));
+	assert_ok!(GaugePallet::set_pool_lp_currency(RawOrigin::Signed(ALICE).into(), 2, LP2_CURRENCY));
+	assert_ok!(GaugePallet::set_emission_budget(
+		RawOrigin::Signed(ALICE).into(),
+		vec![(REWARD_CURRENCY, 100 * TOKEN1_UNIT)]
+	));
+	assert_ok!(<Test as Config>::MultiCurrency::deposit(
+		REWARD_CURRENCY,
+		&GaugePallet::reward_account(),
+		1000 * TOKEN1_UNIT
+	));
+}
+
+#[test]
+fn set_emissions_with_no_admin_should_failed() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+
+		assert_noop!(
+			GaugePallet::set_emission_budget(
+				RawOrigin::Signed(BOB).into(),
+				vec![(REWARD_CURRENCY, 100 * TOKEN1_UNIT)]
+			),
+			Error::<Test>::OnlyAdmin
+		);
+		assert_noop!(
+			GaugePallet::set_pool_lp_currency(RawOrigin::Signed(BOB).into(), 1, LP1_CURRENCY),
+			Error::<Test>::OnlyAdmin
+		);
+	})
+}
+
+#[test]
+fn stake_and_unstake_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+
+		assert_noop!(
+			GaugePallet::stake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT),
+			Error::<Test>::NoLpCurrency
+		);
+		setup_emissions();
+
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_stake_amount(ALICE, 1), 10 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::pool_stake_amount(1), 10 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(LP1_CURRENCY, &ALICE), 90 * TOKEN1_UNIT);
+
+		assert_noop!(
+			GaugePallet::unstake(RawOrigin::Signed(ALICE).into(), 1, 11 * TOKEN1_UNIT),
+			Error::<Test>::InsufficientAmount
+		);
+		assert_ok!(GaugePallet::unstake(RawOrigin::Signed(ALICE).into(), 1, 4 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_stake_amount(ALICE, 1), 6 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::pool_stake_amount(1), 6 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(LP1_CURRENCY, &ALICE), 94 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn distribute_emissions_by_score_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		setup_emissions();
+		lock_vote_currency(ALICE, 50 * TOKEN1_UNIT);
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+
+		// votes before period0 start get the full score
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(ALICE).into(), 2, 30 * TOKEN1_UNIT));
+
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(BOB).into(), 2, 10 * TOKEN1_UNIT));
+
+		// nothing is emitted before period0 end
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &ALICE), 0);
+
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+
+		assert_eq!(GaugePallet::period_emissions(0, 1), vec![(REWARD_CURRENCY, 25 * TOKEN1_UNIT)]);
+		assert_eq!(GaugePallet::period_emissions(0, 2), vec![(REWARD_CURRENCY, 75 * TOKEN1_UNIT)]);
+
+		// the emissions are streamed until period1 end
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &ALICE), 0);
+
+		let period1 = GaugePallet::vote_period(1).unwrap();
+		set_block_timestamp(period1.end);
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(BOB).into(), 2));
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &ALICE), 25 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &BOB), 75 * TOKEN1_UNIT);
+
+		// claiming again pays nothing
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &ALICE), 25 * TOKEN1_UNIT);
+
+		// period1 inherits the votes of period0
+		set_block_timestamp(period1.end + 10);
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+		assert_eq!(GaugePallet::period_emissions(1, 1), vec![(REWARD_CURRENCY, 25 * TOKEN1_UNIT)]);
+
+		let period2 = GaugePallet::vote_period(2).unwrap();
+		set_block_timestamp(period2.end);
+		assert_ok!(GaugePallet::unstake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		assert_eq!(get_user_balance(REWARD_CURRENCY, &ALICE), 50 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn stake_before_period_end_should_not_get_full_emissions() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		setup_emissions();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(<Test as Config>::MultiCurrency::transfer(
+			LP1_CURRENCY,
+			&ALICE,
+			&BOB,
+			90 * TOKEN1_UNIT
+		));
+
+		// BOB stakes 90% of the pool right before the period rolls over
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end);
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(BOB).into(), 1, 90 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+		assert_eq!(GaugePallet::period_emissions(0, 1), vec![(REWARD_CURRENCY, 100 * TOKEN1_UNIT)]);
+
+		// BOB only gets the emissions released while staked
+		let period1 = GaugePallet::vote_period(1).unwrap();
+		let duration = Balance::from(period1.end - period0.end);
+		set_block_timestamp(period0.end + 6);
+		assert_ok!(GaugePallet::unstake(RawOrigin::Signed(BOB).into(), 1, 90 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(BOB).into(), 1));
+		let bob_rewards = get_user_balance(REWARD_CURRENCY, &BOB);
+		let expected = 100 * TOKEN1_UNIT * 6 / duration * 9 / 10;
+		assert!(bob_rewards <= expected && expected - bob_rewards < 1_000);
+
+		// the rest of the stream goes to ALICE
+		set_block_timestamp(period1.end);
+		assert_ok!(GaugePallet::claim_rewards(RawOrigin::Signed(ALICE).into(), 1));
+		let alice_rewards = get_user_balance(REWARD_CURRENCY, &ALICE);
+		assert!(100 * TOKEN1_UNIT - bob_rewards - alice_rewards < 1_000);
+	})
+}
+
+#[test]
+fn vote_escrow_lock_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		let start = now();
+		let max_end = start + MaxLockDuration::get();
+
+		assert_noop!(
+			GaugePallet::create_lock(RawOrigin::Signed(BOB).into(), 10 * TOKEN1_UNIT, max_end + 1),
+			Error::<Test>::InvalidUnlockTime
+		);
+		assert_ok!(GaugePallet::create_lock(
+			RawOrigin::Signed(BOB).into(),
+			10 * TOKEN1_UNIT,
+			start + MaxLockDuration::get() / 2
+		));
+		assert_noop!(
+			GaugePallet::create_lock(RawOrigin::Signed(BOB).into(), 10 * TOKEN1_UNIT, max_end),
+			Error::<Test>::LockExisted
+		);
+		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 50 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::voting_power(&BOB, start), 5 * TOKEN1_UNIT);
+
+		assert_ok!(GaugePallet::increase_lock_amount(
+			RawOrigin::Signed(BOB).into(),
+			10 * TOKEN1_UNIT
+		));
+		assert_eq!(GaugePallet::voting_power(&BOB, start), 10 * TOKEN1_UNIT);
+
+		assert_noop!(
+			GaugePallet::extend_lock(RawOrigin::Signed(BOB).into(), start + 10),
+			Error::<Test>::InvalidUnlockTime
+		);
+		assert_ok!(GaugePallet::extend_lock(RawOrigin::Signed(BOB).into(), max_end));
+		assert_eq!(
+			GaugePallet::vote_lock(BOB),
+			Some(VoteLock { amount: 20 * TOKEN1_UNIT, end: max_end })
+		);
+		assert_eq!(GaugePallet::voting_power(&BOB, start), 20 * TOKEN1_UNIT);
+
+		// the voting power decays linearly to zero at the end of the lock
+		let half = start + MaxLockDuration::get() / 2;
+		assert_eq!(GaugePallet::voting_power(&BOB, half), 10 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::voting_power(&BOB, max_end), 0);
+
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
+		assert_noop!(
+			GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 20 * TOKEN1_UNIT + 1),
+			Error::<Test>::InsufficientVotingPower
+		);
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 20 * TOKEN1_UNIT));
+
+		set_block_timestamp(max_end);
+		assert_noop!(
+			GaugePallet::withdraw_lock(RawOrigin::Signed(BOB).into()),
+			Error::<Test>::VotesNotReleased
+		);
+		assert_ok!(GaugePallet::cancel_vote(RawOrigin::Signed(BOB).into(), 1, 20 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::withdraw_lock(RawOrigin::Signed(BOB).into()));
+		assert_eq!(GaugePallet::vote_lock(BOB), None);
+		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), 60 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn vote_batch_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2, 3]));
+
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(10 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 2), Some(20 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 30 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::global_pool_state(0, 2).unwrap().total_amount, 20 * TOKEN1_UNIT);
+
+		assert_noop!(
+			GaugePallet::vote_batch(
+				RawOrigin::Signed(BOB).into(),
+				bounded_vec![(3, 10 * TOKEN1_UNIT), (1, 21 * TOKEN1_UNIT)]
+			),
+			Error::<Test>::InsufficientVotingPower
+		);
+
+		assert_noop!(
+			GaugePallet::vote_batch_by_percent(
+				RawOrigin::Signed(BOB).into(),
+				bounded_vec![(1, Perbill::from_percent(60)), (2, Perbill::from_percent(50))]
+			),
+			Error::<Test>::InvalidPercent
+		);
+
+		// the 30 left is split in half
+		assert_ok!(GaugePallet::vote_batch_by_percent(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, Perbill::from_percent(50)), (3, Perbill::from_percent(50))]
+		));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(25 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 3), Some(15 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 60 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn delegated_vote_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
+
+		assert_noop!(
+			GaugePallet::delegated_vote_batch(
+				RawOrigin::Signed(ALICE).into(),
+				BOB,
+				bounded_vec![(1, 10 * TOKEN1_UNIT)]
+			),
+			Error::<Test>::NotDelegate
+		);
+
+		assert_ok!(GaugePallet::delegate(RawOrigin::Signed(BOB).into(), ALICE));
+		assert_eq!(
+			GaugePallet::delegation(BOB),
+			Some(Delegation { delegate: ALICE, period_id: 0 })
+		);
+
+		assert_ok!(GaugePallet::delegated_vote_batch(
+			RawOrigin::Signed(ALICE).into(),
+			BOB,
+			bounded_vec![(1, 10 * TOKEN1_UNIT)]
+		));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(10 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_vote_amount(ALICE, 1), None);
+		assert_eq!(GaugePallet::account_total_vote(BOB), 10 * TOKEN1_UNIT);
+
+		assert_ok!(GaugePallet::delegated_cancel_vote(
+			RawOrigin::Signed(ALICE).into(),
+			BOB,
+			1,
+			5 * TOKEN1_UNIT
+		));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(5 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 5 * TOKEN1_UNIT);
+
+		assert_ok!(GaugePallet::revoke_delegation(RawOrigin::Signed(BOB).into()));
+		assert_noop!(
+			GaugePallet::delegated_cancel_vote(
+				RawOrigin::Signed(ALICE).into(),
+				BOB,
+				1,
+				5 * TOKEN1_UNIT
+			),
+			Error::<Test>::NotDelegate
+		);
+
+		// the delegation expires with the period
+		assert_ok!(GaugePallet::delegate(RawOrigin::Signed(BOB).into(), ALICE));
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		assert_noop!(
+			GaugePallet::delegated_vote_batch(
+				RawOrigin::Signed(ALICE).into(),
+				BOB,
+				bounded_vec![(1, 10 * TOKEN1_UNIT)]
+			),
+			Error::<Test>::NotDelegate
+		);
+	})
+}
+
+#[test]
+fn bribe_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(ALICE, 50 * TOKEN1_UNIT);
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+
+		let bribe_currency = Token(TOKEN2_SYMBOL);
+		assert_ok!(GaugePallet::deposit_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			1,
+			bribe_currency,
+			40 * TOKEN1_UNIT
+		));
+		assert_ok!(GaugePallet::deposit_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			2,
+			bribe_currency,
+			10 * TOKEN1_UNIT
+		));
+		assert_eq!(GaugePallet::bribe((0, 1, bribe_currency)).amount, 40 * TOKEN1_UNIT);
+
+		// votes before period0 start add their full amount to the score
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(ALICE).into(), 1, 30 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_period_score((0, 1, BOB)), 10 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::period_vote_score(0, 1), 40 * TOKEN1_UNIT);
+
+		assert_noop!(
+			GaugePallet::claim_bribes(RawOrigin::Signed(BOB).into(), 0, 1),
+			Error::<Test>::UnexpiredPeriod
+		);
+
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		assert_eq!(
+			GaugePallet::claimable_bribes(&BOB, 0, 1),
+			vec![(bribe_currency, 10 * TOKEN1_UNIT)]
+		);
+		assert_ok!(GaugePallet::claim_bribes(RawOrigin::Signed(BOB).into(), 0, 1));
+		assert_eq!(get_user_balance(bribe_currency, &BOB), 10 * TOKEN1_UNIT);
+		assert_noop!(
+			GaugePallet::claim_bribes(RawOrigin::Signed(BOB).into(), 0, 1),
+			Error::<Test>::BribeAlreadyClaimed
+		);
+		assert_noop!(
+			GaugePallet::reclaim_bribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				1,
+				bribe_currency,
+				false
+			),
+			Error::<Test>::BribeClaimUnexpired
+		);
+		assert_noop!(
+			GaugePallet::deposit_bribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				1,
+				bribe_currency,
+				10 * TOKEN1_UNIT
+			),
+			Error::<Test>::ExpiredPeriod
+		);
+
+		set_block_timestamp(period0.end + BribeClaimDuration::get() + 10);
+		assert_noop!(
+			GaugePallet::claim_bribes(RawOrigin::Signed(ALICE).into(), 0, 1),
+			Error::<Test>::BribeClaimExpired
+		);
+
+		// the unclaimed bribe returns to the depositor
+		assert_ok!(GaugePallet::reclaim_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			1,
+			bribe_currency,
+			false
+		));
+		assert_eq!(get_user_balance(bribe_currency, &ALICE), 80 * TOKEN1_UNIT);
+		assert_noop!(
+			GaugePallet::reclaim_bribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				1,
+				bribe_currency,
+				false
+			),
+			Error::<Test>::NoBribe
+		);
+
+		// or rolls over to the open period
+		assert_ok!(GaugePallet::reclaim_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			2,
+			bribe_currency,
+			true
+		));
+		let current_period_id = GaugePallet::next_period_id() - 1;
+		assert_eq!(
+			GaugePallet::bribe((current_period_id, 2, bribe_currency)).amount,
+			10 * TOKEN1_UNIT
+		);
+		assert_eq!(
+			GaugePallet::bribe_deposit((current_period_id, 2, bribe_currency, ALICE)),
+			10 * TOKEN1_UNIT
+		);
+		assert_eq!(get_user_balance(bribe_currency, &ALICE), 80 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn bribe_should_be_bounded() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(ALICE, 50 * TOKEN1_UNIT);
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
+
+		assert_noop!(
+			GaugePallet::deposit_bribe(
+				RawOrigin::Signed(ALICE).into(),
+				0,
+				1,
+				Token(TOKEN2_SYMBOL),
+				MinBribeDeposit::get() - 1
+			),
+			Error::<Test>::BribeTooSmall
+		);
+
+		// a pool takes bribes in at most MaxBribeCurrencies currencies in a period
+		assert_ok!(GaugePallet::deposit_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			1,
+			Token(TOKEN2_SYMBOL),
+			40 * TOKEN1_UNIT
+		));
+		assert_ok!(GaugePallet::deposit_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			1,
+			Token(TOKEN5_SYMBOL),
+			2 * TOKEN1_UNIT
+		));
+		assert_noop!(
+			GaugePallet::deposit_bribe(
+				RawOrigin::Signed(BOB).into(),
+				0,
+				1,
+				Token(TOKEN3_SYMBOL),
+				10 * TOKEN1_UNIT
+			),
+			Error::<Test>::TooManyBribeCurrencies
+		);
+		assert_ok!(GaugePallet::deposit_bribe(
+			RawOrigin::Signed(ALICE).into(),
+			1,
+			1,
+			Token(TOKEN2_SYMBOL),
+			10 * TOKEN1_UNIT
+		));
+
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(ALICE).into(), 1, 30 * TOKEN1_UNIT));
+
+		// the share below the existential deposit is left to the depositor
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		assert_ok!(GaugePallet::claim_bribes(RawOrigin::Signed(BOB).into(), 0, 1));
+		assert_eq!(get_user_balance(Token(TOKEN2_SYMBOL), &BOB), 10 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(Token(TOKEN5_SYMBOL), &BOB), 0);
+		assert_eq!(GaugePallet::bribe((0, 1, Token(TOKEN5_SYMBOL))).claimed, 0);
+
+		assert_ok!(GaugePallet::claim_bribes(RawOrigin::Signed(ALICE).into(), 0, 1));
+		assert_eq!(
+			get_user_balance(Token(TOKEN5_SYMBOL), &ALICE),
+			98 * TOKEN1_UNIT + 3 * TOKEN1_UNIT / 2
+		);
+	})
+}
+
+#[test]
+fn hooks_roll_over_period_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+
+		// period0 has not ended yet
+		GaugePallet::on_initialize(System::block_number());
+		assert_eq!(GaugePallet::next_period_id(), 1);
+
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		GaugePallet::on_initialize(System::block_number());
+		assert_eq!(GaugePallet::next_period_id(), 2);
+
+		let scores = vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)];
+		assert_eq!(GaugePallet::period_scores(0), scores);
+		System::assert_has_event(RuntimeEvent::Gauge(crate::Event::PeriodFinalized {
+			period_id: 0,
+			scores,
+		}));
+
+		// no weight left, pool states stay untouched
+		assert_eq!(GaugePallet::on_idle(System::block_number(), Weight::zero()), Weight::zero());
+		assert_eq!(GaugePallet::global_pool_state(1, 1), None);
+
+		GaugePallet::on_idle(System::block_number(), Weight::MAX);
+		let pool_state = GaugePallet::global_pool_state(1, 1).unwrap();
+		assert!(pool_state.inherit);
+		assert_eq!(pool_state.total_amount, 10 * TOKEN1_UNIT);
+		assert_eq!(pool_state.score, 10 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::pool_last_update_period(2), Some(1));
+	})
+}
+
+#[test]
+fn hooks_should_back_off_failed_roll_over() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+
+		// the end of the next period overflows
+		assert_ok!(GaugePallet::update_vote_duration(RawOrigin::Signed(ALICE).into(), u64::MAX));
+		let block = System::block_number();
+		GaugePallet::on_initialize(block);
+		assert_eq!(GaugePallet::next_period_id(), 1);
+		assert_eq!(GaugePallet::rollover_backoff(), Some((block + 2, 1)));
+		System::assert_last_event(RuntimeEvent::Gauge(crate::Event::RolloverFailed {
+			period_id: 0,
+			error: Error::<Test>::Math.into(),
+			retry_at: block + 2,
+		}));
+
+		// no retry before the backoff ends, and a longer one after another failure
+		GaugePallet::on_initialize(block + 1);
+		assert_eq!(GaugePallet::rollover_backoff(), Some((block + 2, 1)));
+		GaugePallet::on_initialize(block + 2);
+		assert_eq!(GaugePallet::rollover_backoff(), Some((block + 6, 2)));
+
+		assert_ok!(GaugePallet::update_vote_duration(
+			RawOrigin::Signed(ALICE).into(),
+			VOTE_DURATON
+		));
+		GaugePallet::on_initialize(block + 5);
+		assert_eq!(GaugePallet::next_period_id(), 1);
+		GaugePallet::on_initialize(block + 6);
+		assert_eq!(GaugePallet::next_period_id(), 2);
+		assert_eq!(GaugePallet::rollover_backoff(), None);
+	})
+}
+
+#[test]
+fn set_too_many_gauge_pools_should_not_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		assert_ok!(GaugePallet::set_voteable_pools(
+			RawOrigin::Signed(ALICE).into(),
+			(0..MaxGaugePools::get() - 1).collect()
+		));
+		assert_noop!(
+			GaugePallet::set_non_voteable_pools(
+				RawOrigin::Signed(ALICE).into(),
+				vec![MaxGaugePools::get(), MaxGaugePools::get() + 1]
+			),
+			Error::<Test>::TooManyGaugePools
+		);
+
+		// pools already counted do not count again
+		assert_ok!(GaugePallet::set_non_voteable_pools(
+			RawOrigin::Signed(ALICE).into(),
+			vec![0, MaxGaugePools::get()]
+		));
+		assert_eq!(GaugePallet::gauge_pools().len() as u32, MaxGaugePools::get());
+	})
+}
+
+#[test]
+fn on_idle_should_continue_after_last_inherited_pool() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		set_block_timestamp(period0.end + 10);
+		GaugePallet::on_initialize(System::block_number());
+
+		let db_weight = <Test as frame_system::Config>::DbWeight::get();
+		let one_pool = db_weight.reads(3) +
+			db_weight.reads_writes(4, 2) +
+			GaugePallet::vote_decay_weight() +
+			db_weight.writes(1);
+		assert_eq!(GaugePallet::on_idle(System::block_number(), one_pool), one_pool);
+		assert!(GaugePallet::global_pool_state(1, 1).unwrap().inherit);
+		assert_eq!(GaugePallet::global_pool_state(1, 2), None);
+		assert_eq!(GaugePallet::inherit_cursor(), Some((1, 1)));
+
+		assert_eq!(GaugePallet::on_idle(System::block_number(), one_pool), one_pool);
+		assert!(GaugePallet::global_pool_state(1, 2).unwrap().inherit);
+		assert_eq!(GaugePallet::inherit_cursor(), Some((1, 2)));
+
+		// every pool inherited its state in the period
+		assert_eq!(GaugePallet::on_idle(System::block_number(), one_pool), db_weight.reads(3));
+	})
+}
+
+#[test]
+fn migrate_to_v1_should_fill_gauge_pools() {
+	new_test_ext().execute_with(|| {
+		StorageVersion::new(0).put::<GaugePallet>();
+		PoolLastUpdatePerold::<Test>::insert(1, 0);
+		PoolLastUpdatePerold::<Test>::insert(2, 3);
+
+		crate::migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(GaugePallet::gauge_pools().into_iter().collect::<Vec<_>>(), vec![1, 2]);
+		assert_eq!(GaugePallet::on_chain_storage_version(), 1);
+
+		// running it again changes nothing
+		GaugePools::<Test>::kill();
+		crate::migrations::v1::MigrateToV1::<Test>::on_runtime_upgrade();
+		assert!(GaugePallet::gauge_pools().is_empty());
+	})
+}
+
+#[test]
+fn poke_votes_should_scale_votes_to_voting_power() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		let lock_end = GaugePallet::vote_lock(BOB).unwrap().end;
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+
+		// the votes fit the voting power, nothing changes
+		assert_ok!(GaugePallet::poke_votes(RawOrigin::Signed(ALICE).into(), BOB, 2));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 30 * TOKEN1_UNIT);
+
+		set_block_timestamp(lock_end - MaxLockDuration::get() / 4);
+		assert_eq!(GaugePallet::voting_power(&BOB, now()), 15 * TOKEN1_UNIT);
+		assert_noop!(
+			GaugePallet::poke_votes(RawOrigin::Signed(ALICE).into(), BOB, 1),
+			Error::<Test>::InvalidVotePoolsWitness
+		);
+		assert_ok!(GaugePallet::poke_votes(RawOrigin::Signed(ALICE).into(), BOB, 2));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(5 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 2), Some(10 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 15 * TOKEN1_UNIT);
+		System::assert_last_event(RuntimeEvent::Gauge(crate::Event::PokeVotes {
+			account: BOB,
+			voting_power: 15 * TOKEN1_UNIT,
+		}));
+
+		// the votes expire with the lock
+		set_block_timestamp(lock_end);
+		assert_ok!(GaugePallet::poke_votes(RawOrigin::Signed(ALICE).into(), BOB, 2));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 0);
+		assert_ok!(GaugePallet::withdraw_lock(RawOrigin::Signed(BOB).into()));
+	})
+}
+
+#[test]
+fn inherited_score_should_decay_with_voting_power() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		let lock_end = GaugePallet::vote_lock(BOB).unwrap().end;
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1]));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 30 * TOKEN1_UNIT));
+
+		// the voting power falls below the votes halfway through the lock, the score they pass
+		// to a period starting after that decays with it
+		set_block_timestamp(lock_end - MaxLockDuration::get() / 4);
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+		let start = GaugePallet::vote_period(1).unwrap().start;
+		let state = GaugePallet::pool_state_of(1, 1).unwrap();
+		assert_eq!(state.total_amount, 30 * TOKEN1_UNIT);
+		assert!(!state.score.is_zero());
+		assert!(state.score <= GaugePallet::voting_power(&BOB, start));
+
+		GaugePallet::on_idle(System::block_number(), Weight::MAX);
+		let inherited = GaugePallet::global_pool_state(1, 1).unwrap();
+		assert_eq!((inherited.score, inherited.total_amount), (state.score, state.total_amount));
+		assert_eq!(GaugePallet::period_scores(0), vec![(1, 30 * TOKEN1_UNIT)]);
+
+		// the votes pass nothing once the lock ends
+		set_block_timestamp(lock_end);
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+		assert_eq!(GaugePallet::pool_state_of(2, 1).unwrap().score, 0);
+		assert_eq!(GaugePallet::pool_state_of(2, 1).unwrap().total_amount, 30 * TOKEN1_UNIT);
+
+		// cancelling the votes clears their decay
+		assert_ok!(GaugePallet::cancel_vote(RawOrigin::Signed(BOB).into(), 1, 30 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_vote_decay(BOB, 1), None);
+		assert_eq!(PoolVoteDecaySteps::<Test>::iter_prefix(1).count(), 0);
+	})
+}
+
+#[test]
+fn migrate_to_v3_should_cap_votes() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+
+		// votes cast before they were capped
+		StorageVersion::new(2).put::<GaugePallet>();
+		let _ = AccountVoteDecays::<Test>::clear(u32::MAX, None);
+		let _ = PoolVoteDecays::<Test>::clear(u32::MAX, None);
+		let _ = PoolVoteDecaySteps::<Test>::clear(u32::MAX, None);
+		assert_eq!(GaugePallet::vote_decay_value(2, now()), 0);
+
+		crate::migrations::v3::MigrateToV3::<Test>::on_runtime_upgrade();
+		assert_eq!(GaugePallet::on_chain_storage_version(), 3);
+		assert_eq!(GaugePallet::account_vote_decay(BOB, 1).unwrap().amount, 10 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::vote_decay_value(2, now()), 20 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn migrate_to_v2_should_refund_legacy_votes() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote_batch(
+			RawOrigin::Signed(BOB).into(),
+			bounded_vec![(1, 10 * TOKEN1_UNIT), (2, 20 * TOKEN1_UNIT)]
+		));
+
+		// votes paid with vote currency held by the pallet spent no voting power
+		StorageVersion::new(1).put::<GaugePallet>();
+		AccountTotalVote::<Test>::remove(BOB);
+		VoteLocks::<Test>::remove(BOB);
+		let balance_before = get_user_balance(VOTE_CURRENCY, &BOB);
+
+		crate::migrations::v2::MigrateToV2::<Test>::on_runtime_upgrade();
+		assert_eq!(GaugePallet::on_chain_storage_version(), 2);
+		assert_eq!(get_user_balance(VOTE_CURRENCY, &BOB), balance_before + 30 * TOKEN1_UNIT);
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 1), Some(0));
+		assert_eq!(GaugePallet::account_vote_amount(BOB, 2), Some(0));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 0);
+		assert_eq!(GaugePallet::global_pool_state(0, 1).unwrap().total_amount, 0);
+		assert_eq!(GaugePallet::global_pool_state(0, 2).unwrap().total_amount, 0);
+	})
+}
+
+#[test]
+fn rpc_views_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(GaugePallet::get_current_period_info(), None);
+		initialize_gauge();
+		setup_emissions();
+		lock_vote_currency(ALICE, 50 * TOKEN1_UNIT);
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+
+		let period0 = GaugePallet::vote_period(0).unwrap();
+		assert_eq!(
+			GaugePallet::get_current_period_info(),
+			Some(GaugePeriodInfo { period_id: 0, start: period0.start, end: period0.end })
+		);
+
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 2, 10 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(ALICE).into(), 2, 20 * TOKEN1_UNIT));
+		assert_ok!(GaugePallet::stake(RawOrigin::Signed(ALICE).into(), 1, 10 * TOKEN1_UNIT));
+
+		let mut votes = GaugePallet::get_account_votes(BOB);
+		votes.sort();
+		assert_eq!(votes, vec![(1, 10 * TOKEN1_UNIT), (2, 10 * TOKEN1_UNIT)]);
+		assert_eq!(
+			GaugePallet::get_pool_info(0, 2),
+			Some(GaugePoolInfo {
+				pool_id: 2,
+				votable: true,
+				score: 30 * TOKEN1_UNIT,
+				total_amount: 30 * TOKEN1_UNIT
+			})
+		);
+		assert_eq!(
+			GaugePallet::get_period_results(0),
+			vec![(1, 10 * TOKEN1_UNIT), (2, 30 * TOKEN1_UNIT)]
+		);
+
+		// ALICE is the only staker of pool1, which holds a quarter of the score
+		assert_eq!(
+			GaugePallet::estimate_rewards(ALICE, 1),
+			vec![GaugeRewardEstimate {
+				currency_id: REWARD_CURRENCY,
+				pending: 0,
+				projected: 25 * TOKEN1_UNIT
+			}]
+		);
+
+		set_block_timestamp(period0.end + 10);
+		assert_ok!(GaugePallet::update_vote_period(RawOrigin::Signed(BOB).into()));
+		assert_eq!(
+			GaugePallet::get_period_results(0),
+			vec![(1, 10 * TOKEN1_UNIT), (2, 30 * TOKEN1_UNIT)]
+		);
+		let estimates = GaugePallet::estimate_rewards(ALICE, 1);
+		assert_eq!(estimates[0].pending, 0);
+
+		// the emissions of period0 are claimable once streamed
+		set_block_timestamp(GaugePallet::vote_period(1).unwrap().end);
+		let estimates = GaugePallet::estimate_rewards(ALICE, 1);
+		assert_eq!(estimates[0].pending, 25 * TOKEN1_UNIT);
+	})
+}
+
+#[test]
+fn remove_invalid_pool_should_work() {
+	new_test_ext().execute_with(|| {
+		initialize_gauge();
+		lock_vote_currency(BOB, 60 * TOKEN1_UNIT);
+		RemovedPools::set(vec![3]);
+
+		assert_noop!(
+			GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 3]),
+			Error::<Test>::InvalidPoolId
+		);
+		assert_ok!(GaugePallet::set_voteable_pools(RawOrigin::Signed(ALICE).into(), vec![1, 2]));
+		assert_ok!(GaugePallet::vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+
+		assert_noop!(
+			GaugePallet::remove_invalid_pool(RawOrigin::Signed(BOB).into(), 3),
+			Error::<Test>::InvalidPoolId
+		);
+		assert_noop!(
+			GaugePallet::remove_invalid_pool(RawOrigin::Signed(BOB).into(), 1),
+			Error::<Test>::ValidPool
+		);
+
+		RemovedPools::set(vec![1]);
+		assert_ok!(GaugePallet::remove_invalid_pool(RawOrigin::Signed(BOB).into(), 1));
+		System::assert_last_event(RuntimeEvent::Gauge(crate::Event::RemoveInvalidPool {
+			period: 0,
+			pool_id: 1,
+		}));
+		assert!(!GaugePallet::global_pool_state(0, 1).unwrap().votable);
+
+		// the votes of the removed pool can be released
+		assert_ok!(GaugePallet::cancel_vote(RawOrigin::Signed(BOB).into(), 1, 10 * TOKEN1_UNIT));
+		assert_eq!(GaugePallet::account_total_vote(BOB), 0);
+	})
+}
+
+struct StablePools;
+impl PoolValidator<u64> for StablePools {
+	fn is_valid_pool(pool_id: u64) -> bool {
+		pool_id == 1
+	}
+}
+
+struct PairPools;
+impl PoolValidator<u64> for PairPools {
+	fn is_valid_pool(asset_index: u64) -> bool {
+		asset_index == 1
+	}
+}
+
+#[test]
+fn dex_pools_should_tell_pool_kinds_apart() {
+	type Pools = DexPools<StablePools, PairPools>;
+
+	assert!(Pools::is_valid_pool(1));
+	assert!(Pools::is_valid_pool(PAIR_POOL_TAG | 1));
+	assert!(!Pools::is_valid_pool(2));
+	assert!(!Pools::is_valid_pool(PAIR_POOL_TAG | 2));
+}
@@ -0,0 +1,67 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+use super::*;
+use sp_std::marker::PhantomData;
+use zenlink_protocol::{AssetId, LOCAL};
+
+/// Tell whether a pool id refers to an existing pool.
+pub trait PoolValidator<PoolId> {
+	fn is_valid_pool(pool_id: PoolId) -> bool;
+}
+
+impl<PoolId> PoolValidator<PoolId> for () {
+	fn is_valid_pool(_pool_id: PoolId) -> bool {
+		true
+	}
+}
+
+/// The bit tagging a pool id as a zenlink-protocol pair, the rest of the id being the asset
+/// index of its lp asset. The ids without it are zenlink-stable-amm pools.
+pub const PAIR_POOL_TAG: u64 = 1 << 63;
+
+/// Validate the ids of both zenlink-stable-amm pools and zenlink-protocol pairs, telling them apart
+/// by `PAIR_POOL_TAG`. The pool id type needs 64 bits to hold pair ids.
+pub struct DexPools<Stable, Pair>(PhantomData<(Stable, Pair)>);
+
+impl<PoolId, Stable, Pair> PoolValidator<PoolId> for DexPools<Stable, Pair>
+where
+	PoolId: TryInto<u64> + Copy,
+	Stable: PoolValidator<PoolId>,
+	Pair: PoolValidator<u64>,
+{
+	fn is_valid_pool(pool_id: PoolId) -> bool {
+		match pool_id.try_into() {
+			Ok(id) if id & PAIR_POOL_TAG != 0 => Pair::is_valid_pool(id & !PAIR_POOL_TAG),
+			_ => Stable::is_valid_pool(pool_id),
+		}
+	}
+}
+
+/// Validate pool ids against the pools of zenlink-stable-amm.
+pub struct StableAmmPools<T>(PhantomData<T>);
+
+impl<T: zenlink_stable_amm::Config> PoolValidator<T::PoolId> for StableAmmPools<T> {
+	fn is_valid_pool(pool_id: T::PoolId) -> bool {
+		zenlink_stable_amm::Pools::<T>::contains_key(pool_id)
+	}
+}
+
+/// Validate pool ids as the asset index of a zenlink-protocol lp asset of this chain.
+pub struct ZenlinkLpPools<T>(PhantomData<T>);
+
+impl<T, PoolId> PoolValidator<PoolId> for ZenlinkLpPools<T>
+where
+	T: zenlink_protocol::Config<AssetId = AssetId>,
+	PoolId: TryInto<u64>,
+{
+	fn is_valid_pool(pool_id: PoolId) -> bool {
+		let asset_index = match pool_id.try_into() {
+			Ok(asset_index) => asset_index,
+			Err(_) => return false,
+		};
+		let lp_asset_id =
+			AssetId { chain_id: T::SelfParaId::get(), asset_type: LOCAL, asset_index };
+		zenlink_protocol::LpAssetPairs::<T>::contains_key(lp_asset_id)
+	}
+}
@@ -0,0 +1,270 @@
+// Copyright 2021-2022 Zenlink
+// Licensed under GPL-3.0.
+
+use super::*;
+use sp_arithmetic::{helpers_128bit::multiply_by_rational_with_rounding, Rounding};
+
+impl<T: Config> Pallet<T> {
+	/// The voting power of `who` at `now`, decaying linearly from the locked amount at the max
+	/// lock duration to zero at the end of the lock.
+	pub fn voting_power(who: &T::AccountId, now: Timestamp) -> Balance {
+		Self::vote_lock(who)
+			.filter(|lock| lock.end > now)
+			.and_then(|lock| {
+				balance_mul_div(
+					lock.amount,
+					(lock.end - now).into(),
+					T::MaxLockDuration::get().into(),
+				)
+			})
+			.unwrap_or_default()
+	}
+
+	/// Spend `amount` of the voting power of `who` on a vote. The voting power is checked when
+	/// voting only, the score votes pass to later periods is capped at it by
+	/// `refresh_vote_decay`.
+	pub(crate) fn spend_voting_power(
+		who: &T::AccountId,
+		amount: Balance,
+		now: Timestamp,
+	) -> DispatchResult {
+		let voting_power = Self::voting_power(who, now);
+		AccountTotalVote::<T>::try_mutate(who, |total| -> DispatchResult {
+			let new_total = total.checked_add(amount).ok_or(Error::<T>::Math)?;
+			ensure!(new_total <= voting_power, Error::<T>::InsufficientVotingPower);
+			*total = new_total;
+			Ok(())
+		})
+	}
+
+	/// Cap the votes of `who` on every pool at its voting power as it decays. The votes keep
+	/// their amount until the voting power falls below their total, then decay with it to zero
+	/// at the end of the lock, both times rounded down to `VOTE_DECAY_STEP`.
+	pub(crate) fn refresh_vote_decay(who: &T::AccountId, now: Timestamp) -> DispatchResult {
+		let total = Self::account_total_vote(who);
+		let lock = Self::vote_lock(who).unwrap_or_default();
+		let end = lock.end / VOTE_DECAY_STEP * VOTE_DECAY_STEP;
+		// the voting power is the total votes `total / amount` of the max lock duration before
+		// the end of the lock
+		let capped_at = if lock.amount.is_zero() {
+			end
+		} else {
+			let before_end = multiply_by_rational_with_rounding(
+				total,
+				T::MaxLockDuration::get().into(),
+				lock.amount,
+				Rounding::Up,
+			)
+			.ok_or(Error::<T>::Math)?;
+			let capped_at =
+				lock.end.saturating_sub(before_end.try_into().unwrap_or(Timestamp::MAX));
+			capped_at / VOTE_DECAY_STEP * VOTE_DECAY_STEP
+		};
+
+		for (pool_id, amount) in AccountVoteAmount::<T>::iter_prefix(who) {
+			Self::remove_vote_decay(who, pool_id, now);
+			if !amount.is_zero() {
+				let slope = match end.saturating_sub(capped_at) {
+					0 => Zero::zero(),
+					span => amount / Balance::from(span),
+				};
+				let vote = AccountVoteDecay { amount, slope, capped_at, end };
+				Self::insert_vote_decay(who, pool_id, vote, now);
+			}
+		}
+		Ok(())
+	}
+
+	/// The value of the votes on a pool at `at`, the score they pass to a period starting then.
+	pub(crate) fn vote_decay_value(pool_id: T::PoolId, at: Timestamp) -> Balance {
+		let mut decay = Self::pool_vote_decay(pool_id);
+		decay.advance_to(at, |step| Self::pool_vote_decay_step(pool_id, step).unwrap_or_default());
+		decay.value()
+	}
+
+	fn insert_vote_decay(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		vote: AccountVoteDecay,
+		now: Timestamp,
+	) {
+		let mut decay = Self::pool_vote_decay(pool_id);
+		decay.advance_to(now, |step| {
+			PoolVoteDecaySteps::<T>::take(pool_id, step).unwrap_or_default()
+		});
+		let at = decay.checkpoint;
+		if vote.end > at {
+			if vote.capped_at > at {
+				decay.flat = decay.flat.saturating_add(vote.amount);
+				Self::mutate_vote_decay_step(pool_id, vote.capped_at, |step| {
+					step.capped = step.capped.saturating_add(vote.amount);
+					step.capped_value =
+						step.capped_value.saturating_add(vote.value_at(vote.capped_at));
+					step.slope_in = step.slope_in.saturating_add(vote.slope);
+				});
+			} else {
+				decay.decaying = decay.decaying.saturating_add(vote.value_at(at));
+				decay.slope = decay.slope.saturating_add(vote.slope);
+			}
+			Self::mutate_vote_decay_step(pool_id, vote.end, |step| {
+				step.slope_out = step.slope_out.saturating_add(vote.slope)
+			});
+		}
+		PoolVoteDecays::<T>::insert(pool_id, decay);
+		AccountVoteDecays::<T>::insert(who, pool_id, vote);
+	}
+
+	fn remove_vote_decay(who: &T::AccountId, pool_id: T::PoolId, now: Timestamp) {
+		let vote = match AccountVoteDecays::<T>::take(who, pool_id) {
+			Some(vote) => vote,
+			None => return,
+		};
+		let mut decay = Self::pool_vote_decay(pool_id);
+		decay.advance_to(now, |step| {
+			PoolVoteDecaySteps::<T>::take(pool_id, step).unwrap_or_default()
+		});
+		let at = decay.checkpoint;
+		if vote.end > at {
+			if vote.capped_at > at {
+				decay.flat = decay.flat.saturating_sub(vote.amount);
+				Self::mutate_vote_decay_step(pool_id, vote.capped_at, |step| {
+					step.capped = step.capped.saturating_sub(vote.amount);
+					step.capped_value =
+						step.capped_value.saturating_sub(vote.value_at(vote.capped_at));
+					step.slope_in = step.slope_in.saturating_sub(vote.slope);
+				});
+			} else {
+				decay.decaying = decay.decaying.saturating_sub(vote.value_at(at));
+				decay.slope = decay.slope.saturating_sub(vote.slope);
+			}
+			Self::mutate_vote_decay_step(pool_id, vote.end, |step| {
+				step.slope_out = step.slope_out.saturating_sub(vote.slope)
+			});
+		}
+		PoolVoteDecays::<T>::insert(pool_id, decay);
+	}
+
+	/// Change the step of a pool at `at`, removing it once it changes nothing.
+	fn mutate_vote_decay_step(
+		pool_id: T::PoolId,
+		at: Timestamp,
+		f: impl FnOnce(&mut VoteDecayStep),
+	) {
+		PoolVoteDecaySteps::<T>::mutate_exists(pool_id, at, |maybe_step| {
+			let mut step = maybe_step.take().unwrap_or_default();
+			f(&mut step);
+			*maybe_step = Some(step).filter(|step| *step != VoteDecayStep::default());
+		});
+	}
+
+	/// Cancel the share of every vote of `who` above its voting power at `now`.
+	pub(crate) fn inner_poke_votes(
+		who: &T::AccountId,
+		vote_pools: u32,
+		now: Timestamp,
+	) -> DispatchResult {
+		let votes: Vec<(T::PoolId, Balance)> = AccountVoteAmount::<T>::iter_prefix(who)
+			.filter(|(_, amount)| !amount.is_zero())
+			.collect();
+		ensure!(votes.len() as u32 <= vote_pools, Error::<T>::InvalidVotePoolsWitness);
+
+		let total = Self::account_total_vote(who);
+		let voting_power = Self::voting_power(who, now);
+		if total > voting_power {
+			for (pool_id, amount) in votes {
+				let kept = balance_mul_div(amount, voting_power, total).ok_or(Error::<T>::Math)?;
+				let excess = amount.checked_sub(kept).ok_or(Error::<T>::Math)?;
+				if !excess.is_zero() {
+					Self::cancel_vote_pool(who, pool_id, excess, now)?;
+				}
+			}
+		}
+		Self::refresh_vote_decay(who, now)?;
+
+		Self::deposit_event(Event::PokeVotes { account: who.clone(), voting_power });
+		Ok(())
+	}
+
+	pub(crate) fn inner_create_lock(
+		who: &T::AccountId,
+		amount: Balance,
+		unlock_time: Timestamp,
+	) -> DispatchResult {
+		let now = T::TimeProvider::now().as_secs();
+		Self::ensure_unlock_time(unlock_time, now)?;
+
+		VoteLocks::<T>::try_mutate(who, |lock| -> DispatchResult {
+			ensure!(lock.is_none(), Error::<T>::LockExisted);
+			Self::transfer_in_vote_currency(who, amount)?;
+			*lock = Some(VoteLock { amount, end: unlock_time });
+			Ok(())
+		})?;
+
+		Self::deposit_event(Event::CreateLock { caller: who.clone(), amount, end: unlock_time });
+		Ok(())
+	}
+
+	pub(crate) fn inner_increase_lock_amount(
+		who: &T::AccountId,
+		amount: Balance,
+	) -> DispatchResult {
+		let now = T::TimeProvider::now().as_secs();
+		VoteLocks::<T>::try_mutate(who, |lock| -> DispatchResult {
+			let lock = lock.as_mut().ok_or(Error::<T>::NoLock)?;
+			ensure!(lock.end > now, Error::<T>::LockExpired);
+
+			Self::transfer_in_vote_currency(who, amount)?;
+			lock.amount = lock.amount.checked_add(amount).ok_or(Error::<T>::Math)?;
+			Ok(())
+		})?;
+		Self::refresh_vote_decay(who, now)?;
+
+		Self::deposit_event(Event::IncreaseLockAmount { caller: who.clone(), amount });
+		Ok(())
+	}
+
+	pub(crate) fn inner_extend_lock(who: &T::AccountId, unlock_time: Timestamp) -> DispatchResult {
+		let now = T::TimeProvider::now().as_secs();
+		Self::ensure_unlock_time(unlock_time, now)?;
+
+		VoteLocks::<T>::try_mutate(who, |lock| -> DispatchResult {
+			let lock = lock.as_mut().ok_or(Error::<T>::NoLock)?;
+			ensure!(lock.end > now, Error::<T>::LockExpired);
+			ensure!(unlock_time > lock.end, Error::<T>::InvalidUnlockTime);
+
+			lock.end = unlock_time;
+			Ok(())
+		})?;
+		Self::refresh_vote_decay(who, now)?;
+
+		Self::deposit_event(Event::ExtendLock { caller: who.clone(), end: unlock_time });
+		Ok(())
+	}
+
+	pub(crate) fn inner_withdraw_lock(who: &T::AccountId) -> DispatchResult {
+		let now = T::TimeProvider::now().as_secs();
+		let lock = Self::vote_lock(who).ok_or(Error::<T>::NoLock)?;
+		ensure!(lock.end <= now, Error::<T>::LockNotExpired);
+		ensure!(Self::account_total_vote(who).is_zero(), Error::<T>::VotesNotReleased);
+
+		let vote_currency = Self::vote_currency().ok_or(Error::<T>::Uninitialized)?;
+		let pallet_account = T::PalletId::get().into_account_truncating();
+		T::MultiCurrency::transfer(vote_currency, &pallet_account, who, lock.amount)?;
+		VoteLocks::<T>::remove(who);
+
+		Self::deposit_event(Event::WithdrawLock { caller: who.clone(), amount: lock.amount });
+		Ok(())
+	}
+
+	fn ensure_unlock_time(unlock_time: Timestamp, now: Timestamp) -> DispatchResult {
+		let max_unlock_time = now.checked_add(T::MaxLockDuration::get()).ok_or(Error::<T>::Math)?;
+		ensure!(unlock_time > now && unlock_time <= max_unlock_time, Error::<T>::InvalidUnlockTime);
+		Ok(())
+	}
+
+	fn transfer_in_vote_currency(who: &T::AccountId, amount: Balance) -> DispatchResult {
+		let vote_currency = Self::vote_currency().ok_or(Error::<T>::Uninitialized)?;
+		let pallet_account = T::PalletId::get().into_account_truncating();
+		T::MultiCurrency::transfer(vote_currency, who, &pallet_account, amount)
+	}
+}
@@ -47,6 +47,7 @@ use xcm::v3::{Junction, Junctions, MultiLocation};
 
 mod fee;
 mod foreign;
+pub mod migrations;
 mod multiassets;
 mod primitives;
 mod rpc;
@@ -111,9 +112,13 @@ pub mod pallet {
 		type WeightInfo: WeightInfo;
 	}
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
 	#[pallet::generate_store(pub(super) trait Store)]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// Foreign foreign storage
@@ -150,6 +155,12 @@ pub mod pallet {
 	pub type LiquidityPairs<T: Config> =
 		StorageMap<_, Blake2_128Concat, (T::AssetId, T::AssetId), Option<T::AssetId>, ValueQuery>;
 
+	#[pallet::storage]
+	#[pallet::getter(fn lp_asset_pair)]
+	/// lp AssetId -> (T::AssetId, T::AssetId), the reverse of `LiquidityPairs`
+	pub type LpAssetPairs<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, (T::AssetId, T::AssetId)>;
+
 	#[pallet::storage]
 	#[pallet::getter(fn pair_status)]
 	/// (T::AssetId, T::AssetId) -> PairStatus
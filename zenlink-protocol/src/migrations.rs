@@ -0,0 +1,53 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+use frame_support::traits::OnRuntimeUpgrade;
+
+pub mod v1 {
+	use super::*;
+
+	/// Fill `LpAssetPairs` with the lp assets of the pairs created before it was added.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let mut count = 0u64;
+			for (pair, lp_asset_id) in LiquidityPairs::<T>::iter() {
+				count = count.saturating_add(1);
+				if let Some(lp_asset_id) = lp_asset_id {
+					LpAssetPairs::<T>::insert(lp_asset_id, pair);
+				}
+			}
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(count.saturating_add(1), count.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let pairs = LiquidityPairs::<T>::iter_values().filter(Option::is_some).count() as u32;
+			Ok(pairs.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pairs = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version not updated");
+			ensure!(LpAssetPairs::<T>::iter_keys().count() as u32 == pairs, "lp pairs missing");
+			ensure!(
+				LiquidityPairs::<T>::iter().all(|(pair, lp_asset_id)| {
+					lp_asset_id.map_or(true, |lp_asset_id| {
+						Pallet::<T>::lp_asset_pair(lp_asset_id) == Some(pair)
+					})
+				}),
+				"lp pairs missing"
+			);
+			Ok(())
+		}
+	}
+}
@@ -40,10 +40,12 @@ impl<T: Config> Pallet<T> {
 	}
 
 	pub(crate) fn mutate_lp_pairs(asset_0: T::AssetId, asset_1: T::AssetId) -> DispatchResult {
-		Ok(LiquidityPairs::<T>::insert(
-			Self::sort_asset_id(asset_0, asset_1),
-			Some(Self::lp_asset_id(&asset_0, &asset_1).ok_or(Error::<T>::AssetNotExists)?),
-		))
+		let pair = Self::sort_asset_id(asset_0, asset_1);
+		let lp_asset_id =
+			Self::lp_asset_id(&asset_0, &asset_1).ok_or(Error::<T>::AssetNotExists)?;
+		LiquidityPairs::<T>::insert(pair, Some(lp_asset_id));
+		LpAssetPairs::<T>::insert(lp_asset_id, pair);
+		Ok(())
 	}
 
 	pub fn lp_asset_id(asset_0: &T::AssetId, asset_1: &T::AssetId) -> Option<T::AssetId> {
@@ -1082,6 +1084,21 @@ impl<T: Config> ExportZenlink<T::AccountId, T::AssetId> for Pallet<T> {
 		Self::get_amount_out_by_path(amount_in, path)
 	}
 
+	fn get_paired_assets(asset_id: T::AssetId) -> Vec<T::AssetId> {
+		PairStatuses::<T>::iter()
+			.filter(|(_, status)| matches!(status, Trading(_)))
+			.filter_map(|((asset_0, asset_1), _)| {
+				if asset_0 == asset_id {
+					Some(asset_1)
+				} else if asset_1 == asset_id {
+					Some(asset_0)
+				} else {
+					None
+				}
+			})
+			.collect()
+	}
+
 	fn inner_swap_assets_for_exact_assets(
 		who: &T::AccountId,
 		amount_out: AssetBalance,
@@ -1158,6 +1175,10 @@ impl<AccountId, AssetId> ExportZenlink<AccountId, AssetId> for () {
 		unimplemented!()
 	}
 
+	fn get_paired_assets(_asset_id: AssetId) -> Vec<AssetId> {
+		Vec::new()
+	}
+
 	fn inner_swap_assets_for_exact_assets(
 		_who: &AccountId,
 		_amount_out: AssetBalance,
@@ -2,8 +2,11 @@
 // Licensed under Apache 2.0.
 
 use super::{mock::*, AssetId, Error, MultiAssetsHandler};
-use crate::primitives::PairStatus::Trading;
-use frame_support::{assert_noop, assert_ok};
+use crate::{migrations::v1::MigrateToV1, primitives::PairStatus::Trading, LpAssetPairs};
+use frame_support::{
+	assert_noop, assert_ok,
+	traits::{GetStorageVersion, OnRuntimeUpgrade, StorageVersion},
+};
 use frame_system::RawOrigin;
 use sp_core::U256;
 use sp_runtime::{traits::Zero, DispatchError::BadOrigin};
@@ -2015,3 +2018,32 @@ fn bootstrap_zero_reward_claim_should_work() {
 		assert_eq!(<Test as Config>::MultiAssetsHandler::balance_of(KSM_ASSET_ID, &ALICE), 0);
 	})
 }
+
+#[test]
+fn create_pair_should_index_lp_asset() {
+	new_test_ext().execute_with(|| {
+		assert_eq!(DexPallet::lp_asset_pair(DOT_BTC_LP_ID), None);
+		assert_ok!(DexPallet::create_pair(RawOrigin::Root.into(), DOT_ASSET_ID, BTC_ASSET_ID));
+		assert_eq!(DexPallet::lp_asset_pair(DOT_BTC_LP_ID), Some((DOT_ASSET_ID, BTC_ASSET_ID)));
+	})
+}
+
+#[test]
+fn migrate_to_v1_should_index_lp_assets() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(DexPallet::create_pair(RawOrigin::Root.into(), DOT_ASSET_ID, BTC_ASSET_ID));
+
+		// a pair created before the lp assets were indexed
+		StorageVersion::new(0).put::<DexPallet>();
+		LpAssetPairs::<Test>::remove(DOT_BTC_LP_ID);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(DexPallet::on_chain_storage_version(), 1);
+		assert_eq!(DexPallet::lp_asset_pair(DOT_BTC_LP_ID), Some((DOT_ASSET_ID, BTC_ASSET_ID)));
+
+		// running it again changes nothing
+		LpAssetPairs::<Test>::remove(DOT_BTC_LP_ID);
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(DexPallet::lp_asset_pair(DOT_BTC_LP_ID), None);
+	})
+}
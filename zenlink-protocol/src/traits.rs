@@ -142,6 +142,9 @@ pub trait ExportZenlink<AccountId, AssetId> {
 		path: &[AssetId],
 	) -> Result<Vec<AssetBalance>, DispatchError>;
 
+	/// The assets in a trading pair with `asset_id`.
+	fn get_paired_assets(asset_id: AssetId) -> Vec<AssetId>;
+
 	fn inner_swap_assets_for_exact_assets(
 		who: &AccountId,
 		amount_out: AssetBalance,
@@ -10,6 +10,7 @@
 #![allow(clippy::unnecessary_mut_passed)]
 use codec::Codec;
 use sp_std::vec::Vec;
+use zenlink_stable_amm::StablePoolInfo;
 
 sp_api::decl_runtime_apis! {
 	pub trait StableAmmApi<CurrencyId, Balance, AccountId, PoolId> where
@@ -45,5 +46,29 @@ sp_api::decl_runtime_apis! {
 		fn calculate_remove_liquidity(pool_id: PoolId, amount: Balance)->Vec<Balance>;
 
 		fn calculate_remove_liquidity_one_currency(pool_id: PoolId, amount:Balance, index: u32)->Balance;
+
+		fn get_price_oracles(pool_id: PoolId)->Vec<Balance>;
+
+		fn get_last_prices(pool_id: PoolId)->Vec<Balance>;
+
+		fn get_virtual_price_oracle(pool_id: PoolId)->Balance;
+
+		fn get_min_virtual_price(pool_id: PoolId)->Balance;
+
+		fn is_base_virtual_price_stale(pool_id: PoolId)->bool;
+
+		fn get_remaining_tvl_capacity(pool_id: PoolId)->Option<Balance>;
+
+		fn get_remaining_account_lp_capacity(pool_id: PoolId, account: AccountId)->Option<Balance>;
+
+		fn get_pool_info(pool_id: PoolId)->Option<StablePoolInfo<PoolId, CurrencyId, AccountId, Balance>>;
+
+		fn get_all_pools(start: PoolId, limit: u32)->Vec<StablePoolInfo<PoolId, CurrencyId, AccountId, Balance>>;
+
+		fn calculate_swap_pool_from_base(pool_id: PoolId, base_pool_id: PoolId, in_index: u32, out_index: u32, in_amount: Balance)->Balance;
+
+		fn calculate_swap_pool_to_base(pool_id: PoolId, base_pool_id: PoolId, in_index: u32, out_index: u32, in_amount: Balance)->Balance;
+
+		fn calculate_meta_swap_underlying(pool_id: PoolId, in_index: u32, out_index: u32, in_amount: Balance)->Balance;
 	}
 }
@@ -12,14 +12,25 @@ use jsonrpsee::{
 	types::error::{CallError, ErrorObject},
 };
 
+use serde::{Deserialize, Serialize};
 use sp_api::ProvideRuntimeApi;
 use sp_blockchain::HeaderBackend;
 use sp_rpc::number::NumberOrHex;
 use sp_runtime::traits::{Block as BlockT, MaybeDisplay};
 use std::sync::Arc;
 
+use zenlink_stable_amm::StablePoolInfo;
 use zenlink_stable_amm_runtime_api::StableAmmApi as StableAmmRuntimeApi;
 
+/// A quote of a stable pool, telling whether a meta pool priced it with a cached base pool
+/// virtual price which no longer matches the base pool.
+#[derive(Clone, Debug, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+pub struct StableAmmQuote {
+	pub amount: NumberOrHex,
+	pub stale_base_virtual_price: bool,
+}
+
 #[rpc(server)]
 pub trait StableAmmApi<BlockHash, CurrencyId, Balance, AccountId, PoolId> {
 	#[method(name = "zenlinkStableAmm_getVirtualPrice")]
@@ -120,6 +131,124 @@ pub trait StableAmmApi<BlockHash, CurrencyId, Balance, AccountId, PoolId> {
 		index: u32,
 		at: Option<BlockHash>,
 	) -> RpcResult<NumberOrHex>;
+
+	#[method(name = "zenlinkStableAmm_getPriceOracles")]
+	fn get_price_oracles(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<NumberOrHex>>;
+
+	#[method(name = "zenlinkStableAmm_getLastPrices")]
+	fn get_last_prices(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<NumberOrHex>>;
+
+	#[method(name = "zenlinkStableAmm_getVirtualPriceOracle")]
+	fn get_virtual_price_oracle(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<NumberOrHex>;
+
+	#[method(name = "zenlinkStableAmm_getMinVirtualPrice")]
+	fn get_min_virtual_price(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<NumberOrHex>;
+
+	#[method(name = "zenlinkStableAmm_quoteCurrencyAmount")]
+	fn quote_currency_amount(
+		&self,
+		pool_id: PoolId,
+		amounts: Vec<Balance>,
+		deposit: bool,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
+
+	#[method(name = "zenlinkStableAmm_quoteSwap")]
+	fn quote_swap(
+		&self,
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
+
+	#[method(name = "zenlinkStableAmm_quoteRemoveLiquidityOneCurrency")]
+	fn quote_remove_liquidity_one_currency(
+		&self,
+		pool_id: PoolId,
+		amount: Balance,
+		index: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
+
+	#[method(name = "zenlinkStableAmm_getRemainingTvlCapacity")]
+	fn get_remaining_tvl_capacity(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NumberOrHex>>;
+
+	#[method(name = "zenlinkStableAmm_getRemainingAccountLpCapacity")]
+	fn get_remaining_account_lp_capacity(
+		&self,
+		pool_id: PoolId,
+		account: AccountId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<NumberOrHex>>;
+
+	#[method(name = "zenlinkStableAmm_getPoolInfo")]
+	fn get_pool_info(
+		&self,
+		pool_id: PoolId,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<StablePoolInfo<PoolId, CurrencyId, AccountId, NumberOrHex>>>;
+
+	#[method(name = "zenlinkStableAmm_getAllPools")]
+	fn get_all_pools(
+		&self,
+		start: PoolId,
+		limit: u32,
+		at: Option<BlockHash>,
+	) -> RpcResult<Vec<StablePoolInfo<PoolId, CurrencyId, AccountId, NumberOrHex>>>;
+
+	#[method(name = "zenlinkStableAmm_calculateSwapPoolFromBase")]
+	fn calculate_swap_pool_from_base(
+		&self,
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
+
+	#[method(name = "zenlinkStableAmm_calculateSwapPoolToBase")]
+	fn calculate_swap_pool_to_base(
+		&self,
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
+
+	#[method(name = "zenlinkStableAmm_calculateMetaSwapUnderlying")]
+	fn calculate_meta_swap_underlying(
+		&self,
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<StableAmmQuote>;
 }
 
 pub struct StableAmm<C, M> {
@@ -141,7 +270,7 @@ where
 	CurrencyId: Codec + std::cmp::PartialEq,
 	Balance: Codec + TryInto<NumberOrHex> + std::fmt::Debug + MaybeDisplay + Copy,
 	AccountId: Codec,
-	PoolId: Codec,
+	PoolId: Codec + Clone,
 	C: Send + Sync + 'static,
 	C: ProvideRuntimeApi<Block>,
 	C: HeaderBackend<Block>,
@@ -388,6 +517,296 @@ where
 
 		try_into_rpc_balance(amount)
 	}
+
+	fn get_price_oracles(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_price_oracles(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.iter()
+			.map(|b| try_into_rpc_balance(*b))
+			.collect()
+	}
+
+	fn get_last_prices(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_last_prices(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.iter()
+			.map(|b| try_into_rpc_balance(*b))
+			.collect()
+	}
+
+	fn get_virtual_price_oracle(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<NumberOrHex> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let price =
+			api.get_virtual_price_oracle(at, pool_id).map_err(runtime_error_into_rpc_err)?;
+
+		try_into_rpc_balance(price)
+	}
+
+	fn get_min_virtual_price(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<NumberOrHex> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let price = api.get_min_virtual_price(at, pool_id).map_err(runtime_error_into_rpc_err)?;
+
+		try_into_rpc_balance(price)
+	}
+
+	fn quote_currency_amount(
+		&self,
+		pool_id: PoolId,
+		amounts: Vec<Balance>,
+		deposit: bool,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_currency_amount(at, pool_id.clone(), amounts, deposit)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+
+	fn quote_swap(
+		&self,
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_swap(at, pool_id.clone(), in_index, out_index, in_amount)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+
+	fn quote_remove_liquidity_one_currency(
+		&self,
+		pool_id: PoolId,
+		amount: Balance,
+		index: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_remove_liquidity_one_currency(at, pool_id.clone(), amount, index)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+
+	fn get_remaining_tvl_capacity(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_remaining_tvl_capacity(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(try_into_rpc_balance)
+			.transpose()
+	}
+
+	fn get_remaining_account_lp_capacity(
+		&self,
+		pool_id: PoolId,
+		account: AccountId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<NumberOrHex>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_remaining_account_lp_capacity(at, pool_id, account)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(try_into_rpc_balance)
+			.transpose()
+	}
+
+	fn get_pool_info(
+		&self,
+		pool_id: PoolId,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<StablePoolInfo<PoolId, CurrencyId, AccountId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_pool_info(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(try_into_rpc_pool_info)
+			.transpose()
+	}
+
+	fn get_all_pools(
+		&self,
+		start: PoolId,
+		limit: u32,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Vec<StablePoolInfo<PoolId, CurrencyId, AccountId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.get_all_pools(at, start, limit)
+			.map_err(runtime_error_into_rpc_err)?
+			.into_iter()
+			.map(try_into_rpc_pool_info)
+			.collect()
+	}
+
+	fn calculate_swap_pool_from_base(
+		&self,
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_swap_pool_from_base(
+				at,
+				pool_id.clone(),
+				base_pool_id,
+				in_index,
+				out_index,
+				in_amount,
+			)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+
+	fn calculate_swap_pool_to_base(
+		&self,
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_swap_pool_to_base(
+				at,
+				pool_id.clone(),
+				base_pool_id,
+				in_index,
+				out_index,
+				in_amount,
+			)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+
+	fn calculate_meta_swap_underlying(
+		&self,
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		in_amount: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<StableAmmQuote> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		let amount = api
+			.calculate_meta_swap_underlying(at, pool_id.clone(), in_index, out_index, in_amount)
+			.map_err(runtime_error_into_rpc_err)?;
+		let stale_base_virtual_price = api
+			.is_base_virtual_price_stale(at, pool_id)
+			.map_err(runtime_error_into_rpc_err)?;
+
+		Ok(StableAmmQuote { amount: try_into_rpc_balance(amount)?, stale_base_virtual_price })
+	}
+}
+
+fn try_into_rpc_pool_info<
+	PoolId,
+	CurrencyId,
+	AccountId,
+	Balance: Codec + TryInto<NumberOrHex> + MaybeDisplay + Copy + std::fmt::Debug,
+>(
+	info: StablePoolInfo<PoolId, CurrencyId, AccountId, Balance>,
+) -> RpcResult<StablePoolInfo<PoolId, CurrencyId, AccountId, NumberOrHex>> {
+	let into_rpc_balances = |balances: Vec<Balance>| -> RpcResult<Vec<NumberOrHex>> {
+		balances.into_iter().map(try_into_rpc_balance).collect()
+	};
+
+	Ok(StablePoolInfo {
+		pool_id: info.pool_id,
+		base_pool_id: info.base_pool_id,
+		currency_ids: info.currency_ids,
+		lp_currency_id: info.lp_currency_id,
+		balances: into_rpc_balances(info.balances)?,
+		admin_balances: into_rpc_balances(info.admin_balances)?,
+		lp_total_supply: try_into_rpc_balance(info.lp_total_supply)?,
+		initial_a: try_into_rpc_balance(info.initial_a)?,
+		future_a: try_into_rpc_balance(info.future_a)?,
+		initial_a_time: try_into_rpc_balance(info.initial_a_time)?,
+		future_a_time: try_into_rpc_balance(info.future_a_time)?,
+		a_precise: try_into_rpc_balance(info.a_precise)?,
+		fee: try_into_rpc_balance(info.fee)?,
+		admin_fee: try_into_rpc_balance(info.admin_fee)?,
+		offpeg_fee_multiplier: try_into_rpc_balance(info.offpeg_fee_multiplier)?,
+		virtual_price: try_into_rpc_balance(info.virtual_price)?,
+		account: info.account,
+		admin_fee_receiver: info.admin_fee_receiver,
+	})
 }
 
 fn try_into_rpc_balance<
@@ -0,0 +1,165 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	/// Split the admin fee of the pool among the receivers of its route, after swapping it into
+	/// the target currency of the route.
+	///
+	/// The unassigned share is added to the pool balances, raising the virtual price. It stays in
+	/// the pool as admin fee when the pool has no liquidity or the currency is retiring. A share
+	/// too small to keep the account of its receiver alive stays as admin fee until it grows.
+	pub(crate) fn distribute_admin_fee(
+		pool_id: T::PoolId,
+		route: &AdminFeeRoute<T::CurrencyId, T::AccountId>,
+	) -> DispatchResult {
+		if let Some(target_currency) = route.target_currency {
+			Self::convert_admin_fee(pool_id, target_currency, route.max_slippage)?;
+		}
+
+		let pool = Self::pools(pool_id).ok_or(Error::<T>::InvalidPoolId)?.info();
+		let retiring_index = Self::retiring_currency_index(pool_id);
+		let donate = !T::MultiCurrency::total_issuance(pool.lp_currency_id).is_zero();
+
+		let mut donations = vec![Balance::default(); pool.currency_ids.len()];
+		for (i, currency_id) in pool.currency_ids.iter().enumerate() {
+			let admin_balance =
+				Self::get_admin_balance(pool_id, i).ok_or(Error::<T>::Arithmetic)?;
+			if admin_balance.is_zero() {
+				continue
+			}
+
+			let mut remaining = admin_balance;
+			let mut dust = Balance::default();
+			for (receiver, share) in route.receivers.iter() {
+				let amount = U256::from(admin_balance)
+					.checked_mul(U256::from(*share))
+					.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
+					.and_then(|n| TryInto::<Balance>::try_into(n).ok())
+					.ok_or(Error::<T>::Arithmetic)?;
+				if amount.is_zero() {
+					continue
+				}
+				if T::MultiCurrency::total_balance(*currency_id, receiver).saturating_add(amount) <
+					T::MultiCurrency::minimum_balance(*currency_id)
+				{
+					dust = dust.checked_add(amount).ok_or(Error::<T>::Arithmetic)?;
+					continue
+				}
+
+				T::MultiCurrency::transfer(*currency_id, &pool.account, receiver, amount)?;
+				remaining = remaining.checked_sub(amount).ok_or(Error::<T>::Arithmetic)?;
+				Self::deposit_event(Event::DistributeAdminFee {
+					pool_id,
+					currency_id: *currency_id,
+					receiver: receiver.clone(),
+					amount,
+				});
+			}
+
+			let donation = remaining.checked_sub(dust).ok_or(Error::<T>::Arithmetic)?;
+			if donate && retiring_index != Some(i) && !donation.is_zero() {
+				donations[i] = donation;
+				Self::deposit_event(Event::DonateAdminFee {
+					pool_id,
+					currency_id: *currency_id,
+					amount: donation,
+				});
+			}
+		}
+
+		if donations.iter().all(Zero::is_zero) {
+			return Ok(())
+		}
+
+		Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
+			let general_pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+			let pool = match general_pool {
+				Pool::Base(bp) => bp,
+				Pool::Meta(mp) => &mut mp.info,
+			};
+			for (balance, donation) in pool.balances.iter_mut().zip(donations.iter()) {
+				*balance = balance.checked_add(*donation).ok_or(Error::<T>::Arithmetic)?;
+			}
+			Self::update_oracle(pool_id, general_pool);
+			Ok(())
+		})
+	}
+
+	/// Swap the admin fee of every other currency into `target_currency` through the pool itself.
+	///
+	/// The admin fee is moved to a converter account which swaps it back into the pool, the
+	/// output is left in the pool account as admin fee of the target currency.
+	fn convert_admin_fee(
+		pool_id: T::PoolId,
+		target_currency: T::CurrencyId,
+		max_slippage: Number,
+	) -> DispatchResult {
+		let pool = Self::pools(pool_id).ok_or(Error::<T>::InvalidPoolId)?.info();
+		let target_index = pool
+			.currency_ids
+			.iter()
+			.position(|c| *c == target_currency)
+			.ok_or(Error::<T>::MismatchParameter)?;
+		let retiring_index = Self::retiring_currency_index(pool_id);
+		let prices = Self::current_oracle(pool_id)
+			.map(|oracle| oracle.price_emas)
+			.filter(|prices| prices.len() == pool.currency_ids.len())
+			.unwrap_or_default();
+		let has_price = |index: usize| prices.get(index).map_or(false, |price| !price.is_zero());
+		let converter = Self::admin_fee_converter(pool_id);
+
+		for (i, currency_id) in pool.currency_ids.iter().enumerate() {
+			if i == target_index || retiring_index == Some(i) {
+				continue
+			}
+
+			let amount = Self::get_admin_balance(pool_id, i).ok_or(Error::<T>::Arithmetic)?;
+			if amount.is_zero() || amount < T::MultiCurrency::minimum_balance(*currency_id) {
+				continue
+			}
+			// the swap is not bounded without a price for both currencies
+			ensure!(has_price(i) && has_price(target_index), Error::<T>::NoOraclePrice);
+
+			let min_out = Self::admin_fee_conversion_min_out(
+				&pool,
+				&prices,
+				i,
+				target_index,
+				amount,
+				max_slippage,
+			)
+			.ok_or(Error::<T>::Arithmetic)?;
+
+			T::MultiCurrency::transfer(*currency_id, &pool.account, &converter, amount)?;
+			Self::inner_swap(&converter, pool_id, i, target_index, amount, min_out, &pool.account)?;
+		}
+
+		Ok(())
+	}
+
+	/// The least amount of currency `j` that swapping `amount` of currency `i` must return,
+	/// valued with the price oracle.
+	fn admin_fee_conversion_min_out(
+		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
+		prices: &[Balance],
+		i: usize,
+		j: usize,
+		amount: Balance,
+		max_slippage: Number,
+	) -> Option<Balance> {
+		U256::from(amount)
+			.checked_mul(U256::from(pool.token_multipliers[i]))?
+			.checked_mul(U256::from(prices[i]))?
+			.checked_div(U256::from(prices[j]))?
+			.checked_div(U256::from(pool.token_multipliers[j]))?
+			.checked_mul(U256::from(FEE_DENOMINATOR.checked_sub(max_slippage)?))?
+			.checked_div(U256::from(FEE_DENOMINATOR))
+			.and_then(|n| TryInto::<Balance>::try_into(n).ok())
+	}
+
+	fn admin_fee_converter(pool_id: T::PoolId) -> T::AccountId {
+		T::PalletId::get().into_sub_account_truncating((b"fee", pool_id))
+	}
+}
@@ -25,6 +25,7 @@ impl<T: Config> Pallet<T> {
 		);
 
 		ensure!(currency_ids.len() == currency_decimals.len(), Error::<T>::MismatchParameter);
+		ensure!(currency_ids.len() as u32 <= MAX_POOL_CURRENCIES, Error::<T>::TooManyCurrencies);
 		ensure!(a < MAX_A, Error::<T>::ExceedMaxA);
 		ensure!(fee <= MAX_SWAP_FEE, Error::<T>::ExceedMaxFee);
 		ensure!(admin_fee <= MAX_ADMIN_FEE, Error::<T>::ExceedMaxAdminFee);
@@ -64,6 +65,7 @@ impl<T: Config> Pallet<T> {
 				balances: vec![Zero::zero(); currency_ids.len()],
 				fee,
 				admin_fee,
+				offpeg_fee_multiplier: Zero::zero(),
 				initial_a: a_with_precision,
 				future_a: a_with_precision,
 				initial_a_time: Zero::zero(),
@@ -98,7 +100,10 @@ impl<T: Config> Pallet<T> {
 
 		let mut d0 = Balance::default();
 		let amp = Self::get_a_precise(pool).ok_or(Error::<T>::Arithmetic)?;
-		if lp_total_supply > Zero::zero() {
+		// a currency being added by `add_pool_currency` holds no balance until this deposit seeds
+		// it, and the invariant of the pool is not defined until then.
+		let seeding = !lp_total_supply.is_zero() && pool.balances.iter().any(|b| b.is_zero());
+		if lp_total_supply > Zero::zero() && !seeding {
 			d0 = Self::get_d(
 				&Self::xp(&pool.balances, &pool.token_multipliers).ok_or(Error::<T>::Arithmetic)?,
 				amp,
@@ -109,7 +114,7 @@ impl<T: Config> Pallet<T> {
 		let mut new_balances = pool.balances.clone();
 
 		for i in 0..n_currencies {
-			if lp_total_supply == Zero::zero() {
+			if lp_total_supply == Zero::zero() || pool.balances[i].is_zero() {
 				ensure!(!amounts[i].is_zero(), Error::<T>::RequireAllCurrencies);
 			}
 			new_balances[i] = new_balances[i]
@@ -134,6 +139,16 @@ impl<T: Config> Pallet<T> {
 		if lp_total_supply.is_zero() {
 			pool.balances = new_balances;
 			mint_amount = d1;
+		} else if seeding {
+			(mint_amount, fees) = Self::calculate_base_seed_mint_amount(
+				pool,
+				&mut new_balances,
+				&mut d1,
+				fee_per_token,
+				amp,
+				lp_total_supply,
+			)
+			.ok_or(Error::<T>::Arithmetic)?;
 		} else {
 			(mint_amount, fees) = Self::calculate_base_mint_amount(
 				pool,
@@ -182,30 +197,41 @@ impl<T: Config> Pallet<T> {
 
 		let in_amount = Self::do_transfer_in(pool.currency_ids[i], who, &pool.account, in_amount)?;
 
+		let weighted_pool =
+			Self::weighted_base_pool(pool_id, pool).ok_or(Error::<T>::Arithmetic)?;
+		let multipliers = &weighted_pool.token_multipliers;
 		let normalized_balances =
-			Self::xp(&pool.balances, &pool.token_multipliers).ok_or(Error::<T>::Arithmetic)?;
+			Self::xp(&pool.balances, multipliers).ok_or(Error::<T>::Arithmetic)?;
 
 		let x = in_amount
-			.checked_mul(pool.token_multipliers[i])
+			.checked_mul(multipliers[i])
 			.and_then(|n| n.checked_add(normalized_balances[i]))
 			.ok_or(Error::<T>::Arithmetic)?;
 
-		let y = Self::get_y(pool, i, j, x, &normalized_balances).ok_or(Error::<T>::Arithmetic)?;
+		let y = Self::get_y(&weighted_pool, i, j, x, &normalized_balances)
+			.ok_or(Error::<T>::Arithmetic)?;
 
 		let mut dy = normalized_balances[j]
 			.checked_sub(y)
 			.and_then(|n| n.checked_sub(One::one()))
 			.ok_or(Error::<T>::Arithmetic)?;
 
+		let base_fee = Self::base_swap_fee(pool_id, pool, j);
+		let fee = normalized_balances[i]
+			.checked_add(x)
+			.zip(normalized_balances[j].checked_add(y))
+			.and_then(|(xs, ys)| Self::dynamic_fee(xs, ys, base_fee, pool.offpeg_fee_multiplier))
+			.ok_or(Error::<T>::Arithmetic)?;
+
 		let dy_fee = U256::from(dy)
-			.checked_mul(U256::from(pool.fee))
+			.checked_mul(U256::from(fee))
 			.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
 			.and_then(|n| TryInto::<Balance>::try_into(n).ok())
 			.ok_or(Error::<T>::Arithmetic)?;
 
 		dy = dy
 			.checked_sub(dy_fee)
-			.and_then(|n| n.checked_div(pool.token_multipliers[j]))
+			.and_then(|n| n.checked_div(multipliers[j]))
 			.ok_or(Error::<T>::Arithmetic)?;
 
 		ensure!(dy >= out_min_amount, Error::<T>::AmountSlippage);
@@ -213,7 +239,7 @@ impl<T: Config> Pallet<T> {
 		let admin_fee = U256::from(dy_fee)
 			.checked_mul(U256::from(pool.admin_fee))
 			.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
-			.and_then(|n| n.checked_div(U256::from(pool.token_multipliers[j])))
+			.and_then(|n| n.checked_div(U256::from(multipliers[j])))
 			.and_then(|n| TryInto::<Balance>::try_into(n).ok())
 			.ok_or(Error::<T>::Arithmetic)?;
 
@@ -367,7 +393,7 @@ impl<T: Config> Pallet<T> {
 		total_supply: Balance,
 	) -> Option<(Balance, Vec<Balance>, Balance)> {
 		let currencies_len = pool.currency_ids.len();
-		let fee_per_token = U256::from(Self::calculate_fee_per_token(pool)?);
+		let fee_per_token = Self::calculate_fee_per_token(pool)?;
 		let amp = Self::get_a_precise(pool)?;
 
 		let mut new_balances = pool.balances.clone();
@@ -377,17 +403,25 @@ impl<T: Config> Pallet<T> {
 			new_balances[i] = new_balances[i].checked_sub(*x)?;
 		}
 
-		let d1 = U256::from(Self::get_d(&Self::xp(&new_balances, &pool.token_multipliers)?, amp)?);
+		let old_xp = Self::xp(&pool.balances, &pool.token_multipliers)?;
+		let new_xp = Self::xp(&new_balances, &pool.token_multipliers)?;
+		let d1 = U256::from(Self::get_d(&new_xp, amp)?);
+		let ys = d0
+			.checked_add(d1)?
+			.checked_div(U256::from(currencies_len as u64))
+			.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 		let mut fees = vec![Balance::default(); currencies_len];
 		let fee_denominator = U256::from(FEE_DENOMINATOR);
 
 		for (i, balance) in pool.balances.iter_mut().enumerate() {
 			let ideal_balance = d1.checked_mul(U256::from(*balance))?.checked_div(d0)?;
 			let diff = Self::distance(U256::from(new_balances[i]), ideal_balance);
-			fees[i] = fee_per_token
-				.checked_mul(diff)?
-				.checked_div(fee_denominator)
-				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
+			let xs = old_xp[i].checked_add(new_xp[i])?;
+			fees[i] =
+				U256::from(Self::dynamic_fee(xs, ys, fee_per_token, pool.offpeg_fee_multiplier)?)
+					.checked_mul(diff)?
+					.checked_div(fee_denominator)
+					.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 
 			*balance = U256::from(new_balances[i])
 				.checked_sub(
@@ -434,23 +468,31 @@ impl<T: Config> Pallet<T> {
 
 		let new_y = Self::get_yd(pool, amp, index, &xp, d1)?;
 
-		let fee_per_token = U256::from(Self::calculate_fee_per_token(pool)?);
+		let fee_per_token = Self::calculate_fee_per_token(pool)?;
 		let fee_denominator = U256::from(FEE_DENOMINATOR);
+		let ys = d0.checked_add(d1)?.checked_div(xp.len() as Balance)?;
 
 		let mut xp_reduced = vec![Zero::zero(); xp.len()];
 		for (i, x) in xp.iter().enumerate() {
-			let expected_dx = if i as u32 == index {
-				U256::from(*x)
-					.checked_mul(U256::from(d1))?
-					.checked_div(U256::from(d0))?
-					.checked_sub(U256::from(new_y))?
+			let (expected_dx, xs) = if i as u32 == index {
+				(
+					U256::from(*x)
+						.checked_mul(U256::from(d1))?
+						.checked_div(U256::from(d0))?
+						.checked_sub(U256::from(new_y))?,
+					x.checked_add(new_y)?,
+				)
 			} else {
-				U256::from(*x).checked_sub(
-					U256::from(*x).checked_mul(U256::from(d1))?.checked_div(U256::from(d0))?,
-				)?
+				(
+					U256::from(*x).checked_sub(
+						U256::from(*x).checked_mul(U256::from(d1))?.checked_div(U256::from(d0))?,
+					)?,
+					x.checked_mul(2)?,
+				)
 			};
+			let fee = Self::dynamic_fee(xs, ys, fee_per_token, pool.offpeg_fee_multiplier)?;
 			xp_reduced[i] = xp[i].checked_sub(
-				fee_per_token
+				U256::from(fee)
 					.checked_mul(expected_dx)?
 					.checked_div(fee_denominator)
 					.and_then(|n| TryInto::<Balance>::try_into(n).ok())?,
@@ -476,11 +518,65 @@ impl<T: Config> Pallet<T> {
 		Some((dy, fee))
 	}
 
+	/// The fee of a swap out of currency `j` of the pool, before the offpeg multiplier.
+	///
+	/// Swaps taking the retiring currency out of the pool are free of fee.
+	pub(crate) fn base_swap_fee(
+		pool_id: T::PoolId,
+		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
+		j: usize,
+	) -> Number {
+		if Self::retiring_currency_index(pool_id) == Some(j) {
+			return Zero::zero()
+		}
+		pool.fee
+	}
+
+	/// The target weight of a retiring currency, in RETIRING_WEIGHT_PRECISION. It ramps down
+	/// linearly to MIN_RETIRING_WEIGHT over RETIRING_WEIGHT_RAMP_TIME.
+	pub(crate) fn retiring_weight(retiring: &RetiringCurrency<T::CurrencyId>) -> Balance {
+		let elapsed = T::TimeProvider::now()
+			.as_secs()
+			.saturating_sub(retiring.start_time)
+			.min(RETIRING_WEIGHT_RAMP_TIME);
+		RETIRING_WEIGHT_PRECISION.saturating_sub(
+			(RETIRING_WEIGHT_PRECISION - MIN_RETIRING_WEIGHT) * Balance::from(elapsed) /
+				Balance::from(RETIRING_WEIGHT_RAMP_TIME),
+		)
+	}
+
+	/// The pool as swaps price it. The multiplier of the retiring currency is scaled by its
+	/// target weight and the others by RETIRING_WEIGHT_PRECISION, so the retiring currency gets
+	/// cheaper than its peers until arbitrage drains it. The swap math does not change with the
+	/// common scale of the multipliers.
+	pub(crate) fn weighted_base_pool(
+		pool_id: T::PoolId,
+		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
+	) -> Option<BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>> {
+		let mut weighted_pool = pool.clone();
+		if let Some(retiring) = Self::retiring_currencies(pool_id) {
+			let weight = Self::retiring_weight(&retiring);
+			for (currency_id, multiplier) in weighted_pool
+				.currency_ids
+				.iter()
+				.zip(weighted_pool.token_multipliers.iter_mut())
+			{
+				*multiplier = multiplier.checked_mul(if *currency_id == retiring.currency_id {
+					weight
+				} else {
+					RETIRING_WEIGHT_PRECISION
+				})?;
+			}
+		}
+		Some(weighted_pool)
+	}
+
 	pub(crate) fn calculate_base_swap_amount(
 		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
 		i: usize,
 		j: usize,
 		in_balance: Balance,
+		fee: Number,
 	) -> Option<Balance> {
 		let n_currencies = pool.currency_ids.len();
 		if i == j || i >= n_currencies || j >= n_currencies {
@@ -497,8 +593,15 @@ impl<T: Config> Pallet<T> {
 			.checked_sub(One::one())?
 			.checked_div(pool.token_multipliers[j])?;
 
+		let dynamic_fee = Self::dynamic_fee(
+			normalized_balances[i].checked_add(new_in_balance)?,
+			normalized_balances[j].checked_add(out_balance)?,
+			fee,
+			pool.offpeg_fee_multiplier,
+		)?;
+
 		let fee = U256::from(out_amount)
-			.checked_mul(U256::from(pool.fee))?
+			.checked_mul(U256::from(dynamic_fee))?
 			.checked_div(U256::from(FEE_DENOMINATOR))
 			.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 
@@ -524,6 +627,9 @@ impl<T: Config> Pallet<T> {
 		let n_currencies = pool.currency_ids.len();
 		let fee_denominator = U256::from(FEE_DENOMINATOR);
 		let mut fees = vec![Zero::zero(); n_currencies];
+		let old_xp = Self::xp(&pool.balances, &pool.token_multipliers)?;
+		let new_xp = Self::xp(new_balances, &pool.token_multipliers)?;
+		let ys = d0.checked_add(*d1)?.checked_div(n_currencies as Balance)?;
 
 		for i in 0..n_currencies {
 			diff = Self::distance(
@@ -533,7 +639,10 @@ impl<T: Config> Pallet<T> {
 				U256::from(new_balances[i]),
 			);
 
-			fees[i] = U256::from(fee)
+			let xs = old_xp[i].checked_add(new_xp[i])?;
+			let dynamic_fee = Self::dynamic_fee(xs, ys, fee, pool.offpeg_fee_multiplier)?;
+
+			fees[i] = U256::from(dynamic_fee)
 				.checked_mul(diff)
 				.and_then(|n| n.checked_div(fee_denominator))
 				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
@@ -557,6 +666,66 @@ impl<T: Config> Pallet<T> {
 		Some((mint_amount, fees))
 	}
 
+	/// The lp currency minted for a deposit that seeds a currency holding no balance.
+	///
+	/// The invariant of the pool is zero while a currency holds no balance, so the deposit and
+	/// the prior balances are both valued at the spot prices of the pool after the deposit. The
+	/// deposit is charged the fee per token on its whole amount.
+	pub(crate) fn calculate_base_seed_mint_amount(
+		pool: &mut BasePool<
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+		new_balances: &mut [Balance],
+		d1: &mut Balance,
+		fee: Balance,
+		amp: Balance,
+		total_supply: Balance,
+	) -> Option<(Balance, Vec<Balance>)> {
+		let n_currencies = pool.currency_ids.len();
+		let fee_denominator = U256::from(FEE_DENOMINATOR);
+		let mut fees = vec![Zero::zero(); n_currencies];
+		let old_xp = Self::xp(&pool.balances, &pool.token_multipliers)?;
+
+		for i in 0..n_currencies {
+			fees[i] = U256::from(new_balances[i].checked_sub(pool.balances[i])?)
+				.checked_mul(U256::from(fee))
+				.and_then(|n| n.checked_div(fee_denominator))
+				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
+
+			pool.balances[i] = new_balances[i].checked_sub(
+				U256::from(fees[i])
+					.checked_mul(U256::from(pool.admin_fee))
+					.and_then(|n| n.checked_div(fee_denominator))
+					.and_then(|n| TryInto::<Balance>::try_into(n).ok())?,
+			)?;
+
+			new_balances[i] = new_balances[i].checked_sub(fees[i])?;
+		}
+		let new_xp = Self::xp(new_balances, &pool.token_multipliers)?;
+		*d1 = Self::get_d(&new_xp, amp)?;
+
+		let prices = Self::calculate_xp_spot_prices(&new_xp, amp)?;
+		let mut old_value = U256::zero();
+		let mut new_value = U256::zero();
+		for i in 0..n_currencies {
+			old_value = U256::from(old_xp[i])
+				.checked_mul(U256::from(prices[i]))
+				.and_then(|n| n.checked_add(old_value))?;
+			new_value = U256::from(new_xp[i])
+				.checked_mul(U256::from(prices[i]))
+				.and_then(|n| n.checked_add(new_value))?;
+		}
+
+		let mint_amount = U256::from(total_supply)
+			.checked_mul(new_value.checked_sub(old_value)?)?
+			.checked_div(old_value)
+			.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
+
+		Some((mint_amount, fees))
+	}
+
 	pub(crate) fn calculate_base_remove_liquidity(
 		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
 		amount: Balance,
@@ -577,6 +746,46 @@ impl<T: Config> Pallet<T> {
 		Some(amounts)
 	}
 
+	/// The lp amount `base_pool_add_liquidity` mints for `amounts`, charging the imbalance fee.
+	/// None if the pool holds no liquidity yet in any of its currencies.
+	pub(crate) fn calculate_base_add_liquidity(
+		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
+		amounts: &[Balance],
+	) -> Option<Balance> {
+		if pool.currency_ids.len() != amounts.len() || pool.balances.iter().any(Zero::is_zero) {
+			return None
+		}
+		let total_supply = T::MultiCurrency::total_issuance(pool.lp_currency_id);
+		if total_supply.is_zero() {
+			return None
+		}
+
+		let mut pool = pool.clone();
+		let amp = Self::get_a_precise(&pool)?;
+		let fee_per_token = Self::calculate_fee_per_token(&pool)?;
+		let d0 = Self::get_d(&Self::xp(&pool.balances, &pool.token_multipliers)?, amp)?;
+
+		let mut new_balances = pool.balances.clone();
+		for (balance, amount) in new_balances.iter_mut().zip(amounts) {
+			*balance = balance.checked_add(*amount)?;
+		}
+		let mut d1 = Self::get_d(&Self::xp(&new_balances, &pool.token_multipliers)?, amp)?;
+		if d1 <= d0 {
+			return None
+		}
+
+		Self::calculate_base_mint_amount(
+			&mut pool,
+			&mut new_balances,
+			d0,
+			&mut d1,
+			fee_per_token,
+			amp,
+			total_supply,
+		)
+		.map(|(mint_amount, _)| mint_amount)
+	}
+
 	pub(crate) fn calculate_base_currency_amount(
 		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
 		amounts: Vec<Balance>,
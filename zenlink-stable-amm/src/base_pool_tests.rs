@@ -1,7 +1,7 @@
 // Copyright 2021-2022 Zenlink.
 // Licensed under Apache 2.0.
 
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, storage::unhashed, traits::OnRuntimeUpgrade};
 use frame_system::RawOrigin;
 use sp_runtime::DispatchError::BadOrigin;
 
@@ -9,6 +9,10 @@ use super::{
 	mock::{CurrencyId::*, *},
 	*,
 };
+use crate::{
+	migrations::v1::{MigrateToV1, OldBasePool, OldPool},
+	traits::{StableAmmApi, StableAmmOracle},
+};
 
 const INITIAL_A_VALUE: Balance = 50;
 const SWAP_FEE: Balance = 1e7 as Balance;
@@ -241,6 +245,7 @@ fn create_pool_should_work() {
 				balances: vec![Zero::zero(); 4],
 				fee: SWAP_FEE,
 				admin_fee: ADMIN_FEE,
+				offpeg_fee_multiplier: 0,
 				initial_a: INITIAL_A_VALUE * (A_PRECISION as Balance),
 				future_a: INITIAL_A_VALUE * (A_PRECISION as Balance),
 				initial_a_time: 0,
@@ -1114,7 +1119,10 @@ fn swap_with_currency_index_out_of_index_should_not_work() {
 	new_test_ext().execute_with(|| {
 		let (pool_id, _) = setup_test_base_pool();
 		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
-		assert_eq!(StableAmm::calculate_base_swap_amount(&pool, 0, 9, 1e17 as Balance), None);
+		assert_eq!(
+			StableAmm::calculate_base_swap_amount(&pool, 0, 9, 1e17 as Balance, pool.fee),
+			None
+		);
 	})
 }
 
@@ -1145,7 +1153,7 @@ fn swap_with_expected_amounts_should_work() {
 		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
 
 		let calculated_swap_return =
-			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance).unwrap();
+			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance, pool.fee).unwrap();
 		assert_eq!(calculated_swap_return, 99702611562565288);
 
 		let token_from_balance_before =
@@ -1179,7 +1187,7 @@ fn swap_when_min_amount_receive_not_reached_due_to_front_running_should_not_work
 		let (pool_id, _) = setup_test_base_pool();
 		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
 		let calculated_swap_return =
-			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance).unwrap();
+			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance, pool.fee).unwrap();
 		assert_eq!(calculated_swap_return, 99702611562565288);
 
 		assert_ok!(StableAmm::swap(
@@ -1222,7 +1230,7 @@ fn swap_with_lower_min_dy_when_transaction_is_front_ran_should_work() {
 
 		// BOB calculates how much token to receive with 1% slippage
 		let calculated_swap_return =
-			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance).unwrap();
+			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance, pool.fee).unwrap();
 		assert_eq!(calculated_swap_return, 99702611562565288);
 		let calculated_swap_return_with_negative_slippage = calculated_swap_return * 99 / 100;
 
@@ -1521,6 +1529,100 @@ fn set_admin_fee_should_work() {
 	})
 }
 
+#[test]
+fn set_offpeg_fee_multiplier_with_non_owner_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_noop!(
+			StableAmm::set_offpeg_fee_multiplier(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				2 * FEE_DENOMINATOR
+			),
+			BadOrigin
+		);
+	})
+}
+
+#[test]
+fn set_offpeg_fee_multiplier_with_exceed_threshold_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_noop!(
+			StableAmm::set_offpeg_fee_multiplier(
+				RawOrigin::Root.into(),
+				pool_id,
+				11 * FEE_DENOMINATOR
+			),
+			Error::<Test>::ExceedThreshold
+		);
+
+		assert_ok!(StableAmm::set_offpeg_fee_multiplier(
+			RawOrigin::Root.into(),
+			pool_id,
+			10 * FEE_DENOMINATOR
+		));
+		assert_noop!(
+			StableAmm::set_swap_fee(RawOrigin::Root.into(), pool_id, SWAP_FEE + 1),
+			Error::<Test>::ExceedThreshold
+		);
+	})
+}
+
+#[test]
+fn set_offpeg_fee_multiplier_should_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_ok!(StableAmm::set_offpeg_fee_multiplier(
+			RawOrigin::Root.into(),
+			pool_id,
+			2 * FEE_DENOMINATOR
+		));
+
+		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
+		assert_eq!(pool.offpeg_fee_multiplier, 2 * FEE_DENOMINATOR);
+	})
+}
+
+#[test]
+fn swap_with_offpeg_fee_multiplier_should_charge_more_fee() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_ok!(StableAmm::set_offpeg_fee_multiplier(
+			RawOrigin::Root.into(),
+			pool_id,
+			2 * FEE_DENOMINATOR
+		));
+		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
+
+		let calculated_swap_return =
+			StableAmm::calculate_base_swap_amount(&pool, 0, 1, 1e17 as Balance, pool.fee).unwrap();
+		assert!(calculated_swap_return < 99702611562565288);
+
+		let token_to_balance_before =
+			<Test as Config>::MultiCurrency::free_balance(Token(TOKEN2_SYMBOL), &CHARLIE);
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			1,
+			1e17 as Balance,
+			calculated_swap_return,
+			CHARLIE,
+			u64::MAX
+		));
+		let token_to_balance_after =
+			<Test as Config>::MultiCurrency::free_balance(Token(TOKEN2_SYMBOL), &CHARLIE);
+
+		assert!(token_to_balance_after - token_to_balance_before >= calculated_swap_return);
+		assert!(token_to_balance_after - token_to_balance_before < 99702611562565289);
+	})
+}
+
 #[test]
 fn get_admin_balance_with_index_out_of_range_should_not_work() {
 	new_test_ext().execute_with(|| {
@@ -3228,3 +3330,1008 @@ fn check_arithmetic_in_swap_imbalance_with_admin_fee_should_successfully() {
 		assert_eq!(balances[1], 400600099040276221776518758);
 	})
 }
+
+#[test]
+fn oracle_should_follow_spot_price_over_time() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		let oracle = StableAmm::pool_oracles(pool_id).unwrap();
+		assert_eq!(oracle.last_prices, vec![ORACLE_PRICE_PRECISION, ORACLE_PRICE_PRECISION]);
+		assert_eq!(oracle.price_emas, vec![ORACLE_PRICE_PRECISION, ORACLE_PRICE_PRECISION]);
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			1,
+			1e17 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+
+		// currency 1 became scarce, but the average does not move within the same block.
+		let last_price = StableAmm::get_last_prices(pool_id)[1];
+		assert!(last_price > ORACLE_PRICE_PRECISION);
+		assert_eq!(StableAmm::price_oracle(pool_id, 1), Some(ORACLE_PRICE_PRECISION));
+		assert_eq!(StableAmm::price_oracle(pool_id, 0), Some(ORACLE_PRICE_PRECISION));
+
+		mine_block_with_timestamp(OracleEmaPeriod::get());
+		let expected = (last_price + ORACLE_PRICE_PRECISION) / 2;
+		assert_eq!(StableAmm::price_oracle(pool_id, 1), Some(expected));
+		assert_eq!(StableAmm::get_price_oracles(pool_id), vec![ORACLE_PRICE_PRECISION, expected]);
+		assert_eq!(StableAmm::price_oracle(pool_id, 2), None);
+
+		// the stored averages catch up on the next action.
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			1,
+			0,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		let oracle = StableAmm::pool_oracles(pool_id).unwrap();
+		assert_eq!(oracle.price_emas[1], expected);
+		assert_eq!(oracle.last_updated, OracleEmaPeriod::get());
+	})
+}
+
+#[test]
+fn oracle_should_track_virtual_price() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+		let initial_virtual_price = StableAmm::get_virtual_price(pool_id);
+
+		assert_eq!(StableAmm::virtual_price_oracle(pool_id), Some(initial_virtual_price));
+		assert_eq!(StableAmm::min_virtual_price(pool_id), Some(initial_virtual_price));
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			1,
+			1e17 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		let virtual_price = StableAmm::get_virtual_price(pool_id);
+		assert!(virtual_price > initial_virtual_price);
+		assert_eq!(StableAmm::min_virtual_price(pool_id), Some(initial_virtual_price));
+
+		mine_block_with_timestamp(OracleEmaPeriod::get());
+		assert_eq!(
+			StableAmm::get_virtual_price_oracle(pool_id),
+			(initial_virtual_price + virtual_price) / 2
+		);
+
+		// the previous window is still covered by the min tracker.
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			1,
+			0,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		assert_eq!(StableAmm::get_min_virtual_price(pool_id), initial_virtual_price);
+
+		mine_block_with_timestamp(2 * OracleEmaPeriod::get());
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			1,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		assert!(StableAmm::get_min_virtual_price(pool_id) > initial_virtual_price);
+	})
+}
+
+#[test]
+fn oracle_of_unknown_pool_should_be_empty() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_eq!(StableAmm::price_oracle(pool_id + 1, 0), None);
+		assert_eq!(StableAmm::virtual_price_oracle(pool_id + 1), None);
+		assert_eq!(StableAmm::min_virtual_price(pool_id + 1), None);
+		assert_eq!(StableAmm::get_price_oracles(pool_id + 1), Vec::<Balance>::new());
+	})
+}
+
+#[test]
+fn oracle_should_drop_removed_currency() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+		add_and_seed_pool_currency(pool_id);
+		PoolOracles::<Test>::mutate(pool_id, |oracle| {
+			let oracle = oracle.as_mut().unwrap();
+			oracle.last_prices = vec![ORACLE_PRICE_PRECISION, 2e18 as Balance, 4e18 as Balance];
+			oracle.price_emas = vec![ORACLE_PRICE_PRECISION, 3e18 as Balance, 6e18 as Balance];
+		});
+
+		// the prices are rebased on the new first currency
+		StableAmm::remove_oracle_currency(pool_id, 0);
+		let oracle = StableAmm::pool_oracles(pool_id).unwrap();
+		assert_eq!(oracle.last_prices, vec![ORACLE_PRICE_PRECISION, 2e18 as Balance]);
+		assert_eq!(oracle.price_emas, vec![ORACLE_PRICE_PRECISION, 2e18 as Balance]);
+
+		StableAmm::remove_oracle_currency(pool_id, 1);
+		let oracle = StableAmm::pool_oracles(pool_id).unwrap();
+		assert_eq!(oracle.last_prices, vec![ORACLE_PRICE_PRECISION]);
+		assert_eq!(oracle.price_emas, vec![ORACLE_PRICE_PRECISION]);
+	})
+}
+
+fn add_and_seed_pool_currency(pool_id: PoolId) {
+	assert_ok!(StableAmm::add_pool_currency(
+		RawOrigin::Root.into(),
+		pool_id,
+		Token(TOKEN3_SYMBOL),
+		TOKEN3_DECIMAL,
+		BOB,
+		1e6 as Balance,
+		StableAmm::next_pool_id()
+	));
+}
+
+#[test]
+fn add_pool_currency_should_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, lp_currency_id) = setup_test_base_pool();
+
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				Token(TOKEN3_SYMBOL),
+				TOKEN3_DECIMAL,
+				BOB,
+				1e6 as Balance,
+				StableAmm::next_pool_id()
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				pool_id,
+				Token(TOKEN2_SYMBOL),
+				TOKEN2_DECIMAL,
+				BOB,
+				1e18 as Balance,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::CurrencyAlreadyInPool
+		);
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				pool_id,
+				lp_currency_id,
+				STABLE_LP_DECIMAL,
+				BOB,
+				1e18 as Balance,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::InvalidPooledCurrency
+		);
+		// the new currency must be seeded in the same call.
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				pool_id,
+				Token(TOKEN3_SYMBOL),
+				TOKEN3_DECIMAL,
+				BOB,
+				0,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::RequireAllCurrencies
+		);
+
+		let virtual_price_before = StableAmm::get_virtual_price(pool_id);
+		assert_ok!(StableAmm::add_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL),
+			TOKEN3_DECIMAL,
+			BOB,
+			1e6 as Balance,
+			StableAmm::next_pool_id()
+		));
+		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
+		assert_eq!(
+			pool.currency_ids,
+			vec![Token(TOKEN1_SYMBOL), Token(TOKEN2_SYMBOL), Token(TOKEN3_SYMBOL)]
+		);
+		assert_eq!(pool.token_multipliers, vec![1, 1, 1e12 as Balance]);
+		assert_eq!(pool.balances, vec![1e18 as Balance, 1e18 as Balance, 1e6 as Balance]);
+
+		// the seed is worth a third of the pool and pays the fee per token.
+		let seed_lp_amount = <Test as Config>::MultiCurrency::free_balance(lp_currency_id, &BOB);
+		assert!(seed_lp_amount < 1e18 as Balance);
+		assert!(seed_lp_amount > 9996e14 as Balance);
+		assert!(StableAmm::get_virtual_price(pool_id) >= virtual_price_before);
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			2,
+			1e17 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+	})
+}
+
+#[test]
+fn add_pool_currency_should_value_imbalanced_seed_at_spot_price() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, lp_currency_id) = setup_test_base_pool();
+		let virtual_price_before = StableAmm::get_virtual_price(pool_id);
+
+		assert_ok!(StableAmm::add_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL),
+			TOKEN3_DECIMAL,
+			BOB,
+			3e6 as Balance,
+			StableAmm::next_pool_id()
+		));
+
+		// the seed makes its currency abundant, so it is worth less than its amount and the
+		// prior liquidity providers are not diluted.
+		let seed_lp_amount = <Test as Config>::MultiCurrency::free_balance(lp_currency_id, &BOB);
+		assert!(seed_lp_amount < 3e18 as Balance);
+		assert!(seed_lp_amount > 2e18 as Balance);
+		assert!(StableAmm::get_virtual_price(pool_id) >= virtual_price_before);
+	})
+}
+
+#[test]
+fn retire_pool_currency_should_block_inflow() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+		add_and_seed_pool_currency(pool_id);
+
+		assert_noop!(
+			StableAmm::retire_pool_currency(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				Token(TOKEN3_SYMBOL)
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::retire_pool_currency(RawOrigin::Root.into(), pool_id, Token(TOKEN4_SYMBOL)),
+			Error::<Test>::MismatchParameter
+		);
+
+		let charged_out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance).unwrap();
+
+		assert_ok!(StableAmm::retire_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL)
+		));
+		assert_eq!(
+			StableAmm::retiring_currencies(pool_id).map(|retiring| retiring.currency_id),
+			Some(Token(TOKEN3_SYMBOL))
+		);
+		assert_noop!(
+			StableAmm::retire_pool_currency(RawOrigin::Root.into(), pool_id, Token(TOKEN1_SYMBOL)),
+			Error::<Test>::CurrencyRetiring
+		);
+
+		assert_noop!(
+			StableAmm::swap(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				2,
+				0,
+				1e5 as Balance,
+				0,
+				BOB,
+				u64::MAX
+			),
+			Error::<Test>::CurrencyRetiring
+		);
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				vec![1e17 as Balance, 0, 1e5 as Balance],
+				0,
+				BOB,
+				u64::MAX,
+			),
+			Error::<Test>::CurrencyRetiring
+		);
+		assert_eq!(
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 2, 0, 1e5 as Balance),
+			None
+		);
+		assert_eq!(
+			StableAmm::stable_amm_calculate_currency_amount(
+				pool_id,
+				&[1e17 as Balance, 0, 1e5 as Balance],
+				true
+			),
+			Err(Error::<Test>::CurrencyRetiring.into())
+		);
+
+		// the retiring currency can still flow out, and swaps taking it out are free of fee.
+		let out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance).unwrap();
+		assert!(out_amount > charged_out_amount);
+		let balance_before = get_user_balance(Token(TOKEN3_SYMBOL), &BOB);
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			2,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		assert!(get_user_balance(Token(TOKEN3_SYMBOL), &BOB) - balance_before >= out_amount);
+		assert_ok!(StableAmm::add_liquidity(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			vec![1e17 as Balance, 0, 0],
+			0,
+			BOB,
+			u64::MAX,
+		));
+	})
+}
+
+#[test]
+fn retiring_currency_should_ramp_down_its_weight() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+		add_and_seed_pool_currency(pool_id);
+		assert_ok!(StableAmm::retire_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL)
+		));
+		let start_time = Timestamp::now() / 1000;
+
+		// the swaps taking the retiring currency out pay more of it as its weight ramps down.
+		let start_out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance).unwrap();
+		mine_block_with_timestamp(start_time + RETIRING_WEIGHT_RAMP_TIME / 2);
+		let half_out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance).unwrap();
+		assert!(half_out_amount > start_out_amount);
+
+		mine_block_with_timestamp(start_time + RETIRING_WEIGHT_RAMP_TIME);
+		let end_out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance).unwrap();
+		assert!(end_out_amount > half_out_amount);
+		mine_block_with_timestamp(start_time + 2 * RETIRING_WEIGHT_RAMP_TIME);
+		assert_eq!(
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 2, 1e16 as Balance),
+			Some(end_out_amount)
+		);
+
+		let balance_before = get_user_balance(Token(TOKEN3_SYMBOL), &BOB);
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			2,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+		assert!(get_user_balance(Token(TOKEN3_SYMBOL), &BOB) - balance_before >= end_out_amount);
+
+		// the swaps between the other currencies still price them at par.
+		let out_amount =
+			StableAmm::stable_amm_calculate_swap_amount(pool_id, 0, 1, 1e16 as Balance).unwrap();
+		assert!(out_amount > 99e14 as Balance);
+		assert!(out_amount < 1e16 as Balance);
+	})
+}
+
+#[test]
+fn remove_pool_currency_should_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, lp_currency_id) = setup_test_base_pool();
+		add_and_seed_pool_currency(pool_id);
+
+		assert_noop!(
+			StableAmm::remove_pool_currency(
+				RawOrigin::Root.into(),
+				pool_id,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::CurrencyNotRetiring
+		);
+		assert_ok!(StableAmm::retire_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL)
+		));
+		assert_noop!(
+			StableAmm::remove_pool_currency(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				StableAmm::next_pool_id()
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::remove_pool_currency(
+				RawOrigin::Root.into(),
+				pool_id,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::RetiringCurrencyNotDust
+		);
+
+		for who in [ALICE, BOB] {
+			let lp_amount = <Test as Config>::MultiCurrency::free_balance(lp_currency_id, &who);
+			assert_ok!(StableAmm::remove_liquidity(
+				RawOrigin::Signed(who).into(),
+				pool_id,
+				lp_amount,
+				vec![0, 0, 0],
+				who,
+				u64::MAX,
+			));
+		}
+
+		assert_ok!(StableAmm::remove_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			StableAmm::next_pool_id()
+		));
+		let pool = StableAmm::pools(pool_id).unwrap().get_pool_info();
+		assert_eq!(pool.currency_ids, vec![Token(TOKEN1_SYMBOL), Token(TOKEN2_SYMBOL)]);
+		assert_eq!(pool.token_multipliers, vec![1, 1]);
+		assert_eq!(pool.balances, vec![0, 0]);
+		assert_eq!(StableAmm::retiring_currencies(pool_id), None);
+
+		assert_noop!(
+			StableAmm::retire_pool_currency(RawOrigin::Root.into(), pool_id, Token(TOKEN1_SYMBOL)),
+			Error::<Test>::TooFewCurrencies
+		);
+	})
+}
+
+#[test]
+fn empty_pool_with_retiring_currency_should_take_first_deposit() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, lp_currency_id) = setup_test_base_pool();
+		add_and_seed_pool_currency(pool_id);
+		assert_ok!(StableAmm::retire_pool_currency(
+			RawOrigin::Root.into(),
+			pool_id,
+			Token(TOKEN3_SYMBOL)
+		));
+
+		for who in [ALICE, BOB] {
+			let lp_amount = <Test as Config>::MultiCurrency::free_balance(lp_currency_id, &who);
+			assert_ok!(StableAmm::remove_liquidity(
+				RawOrigin::Signed(who).into(),
+				pool_id,
+				lp_amount,
+				vec![0, 0, 0],
+				who,
+				u64::MAX,
+			));
+		}
+
+		// an empty pool needs every currency, the retiring one included.
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				vec![1e18 as Balance, 1e18 as Balance, 0],
+				0,
+				BOB,
+				u64::MAX,
+			),
+			Error::<Test>::RequireAllCurrencies
+		);
+		assert_ok!(StableAmm::add_liquidity(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			vec![1e18 as Balance, 1e18 as Balance, 1e6 as Balance],
+			0,
+			BOB,
+			u64::MAX,
+		));
+		assert_eq!(
+			StableAmm::retiring_currencies(pool_id).map(|retiring| retiring.currency_id),
+			Some(Token(TOKEN3_SYMBOL))
+		);
+	})
+}
+
+#[test]
+fn liquidity_caps_should_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, lp_currency_id) = setup_test_base_pool();
+
+		assert_noop!(
+			StableAmm::set_liquidity_caps(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				Some(3e18 as Balance),
+				None
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::set_liquidity_caps(RawOrigin::Root.into(), 99, Some(3e18 as Balance), None),
+			Error::<Test>::InvalidPoolId
+		);
+		assert_eq!(StableAmm::get_remaining_tvl_capacity(pool_id), None);
+
+		assert_ok!(StableAmm::set_liquidity_caps(
+			RawOrigin::Root.into(),
+			pool_id,
+			Some(3e18 as Balance),
+			None
+		));
+		assert_eq!(StableAmm::get_remaining_tvl_capacity(pool_id), Some(1e18 as Balance));
+		assert_eq!(StableAmm::get_remaining_account_lp_capacity(pool_id, BOB), None);
+
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				vec![1e18 as Balance, 1e18 as Balance],
+				0,
+				BOB,
+				u64::MAX,
+			),
+			Error::<Test>::ExceedPoolTvlCap
+		);
+		assert_ok!(StableAmm::add_liquidity(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			vec![5e17 as Balance, 5e17 as Balance],
+			0,
+			BOB,
+			u64::MAX,
+		));
+		assert_eq!(StableAmm::get_remaining_tvl_capacity(pool_id), Some(0));
+
+		assert_ok!(StableAmm::set_liquidity_caps(
+			RawOrigin::Root.into(),
+			pool_id,
+			None,
+			Some(15e17 as Balance)
+		));
+		let bob_lp = <Test as Config>::MultiCurrency::free_balance(lp_currency_id, &BOB);
+		assert_eq!(
+			StableAmm::get_remaining_account_lp_capacity(pool_id, BOB),
+			Some(15e17 as Balance - bob_lp)
+		);
+		assert_eq!(StableAmm::get_remaining_account_lp_capacity(pool_id, ALICE), Some(0));
+
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(ALICE).into(),
+				pool_id,
+				vec![1e16 as Balance, 1e16 as Balance],
+				0,
+				ALICE,
+				u64::MAX,
+			),
+			Error::<Test>::ExceedAccountLpCap
+		);
+		// the caller's lp balance is capped too, whoever receives the lp currency
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(ALICE).into(),
+				pool_id,
+				vec![1e16 as Balance, 1e16 as Balance],
+				0,
+				CHARLIE,
+				u64::MAX,
+			),
+			Error::<Test>::ExceedAccountLpCap
+		);
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				vec![5e17 as Balance, 5e17 as Balance],
+				0,
+				BOB,
+				u64::MAX,
+			),
+			Error::<Test>::ExceedAccountLpCap
+		);
+		assert_ok!(StableAmm::add_liquidity(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			vec![1e17 as Balance, 1e17 as Balance],
+			0,
+			BOB,
+			u64::MAX,
+		));
+
+		assert_ok!(StableAmm::set_liquidity_caps(RawOrigin::Root.into(), pool_id, None, None));
+		assert_eq!(StableAmm::liquidity_caps(pool_id), None);
+		assert_ok!(StableAmm::add_liquidity(
+			RawOrigin::Signed(ALICE).into(),
+			pool_id,
+			vec![1e18 as Balance, 1e18 as Balance],
+			0,
+			ALICE,
+			u64::MAX,
+		));
+	})
+}
+
+fn setup_test_base_pool_with_admin_fee() -> PoolId {
+	let (pool_id, _) = setup_test_base_pool();
+
+	assert_ok!(StableAmm::set_swap_fee(RawOrigin::Root.into(), pool_id, 1e7 as Balance,));
+	assert_ok!(StableAmm::set_admin_fee(RawOrigin::Root.into(), pool_id, 1e8 as Balance,));
+
+	assert_ok!(StableAmm::swap(
+		RawOrigin::Signed(BOB).into(),
+		pool_id,
+		0,
+		1,
+		1e17 as Balance,
+		0,
+		BOB,
+		u64::MAX
+	));
+	assert_ok!(StableAmm::swap(
+		RawOrigin::Signed(BOB).into(),
+		pool_id,
+		1,
+		0,
+		1e17 as Balance,
+		0,
+		BOB,
+		u64::MAX
+	));
+	pool_id
+}
+
+#[test]
+fn set_admin_fee_route_with_incorrect_parameter_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		assert_noop!(
+			StableAmm::set_admin_fee_route(
+				RawOrigin::Signed(BOB).into(),
+				pool_id,
+				vec![(BOB, FEE_DENOMINATOR)],
+				None,
+				0
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::set_admin_fee_route(
+				RawOrigin::Root.into(),
+				pool_id,
+				vec![(BOB, 1); MAX_ADMIN_FEE_RECEIVERS + 1],
+				None,
+				0
+			),
+			Error::<Test>::TooManyAdminFeeReceivers
+		);
+		assert_noop!(
+			StableAmm::set_admin_fee_route(
+				RawOrigin::Root.into(),
+				pool_id,
+				vec![(BOB, FEE_DENOMINATOR), (CHARLIE, 1)],
+				None,
+				0
+			),
+			Error::<Test>::InvalidAdminFeeRoute
+		);
+		assert_noop!(
+			StableAmm::set_admin_fee_route(
+				RawOrigin::Root.into(),
+				pool_id,
+				vec![(BOB, FEE_DENOMINATOR)],
+				Some(Token(TOKEN3_SYMBOL)),
+				0
+			),
+			Error::<Test>::MismatchParameter
+		);
+		assert_noop!(
+			StableAmm::set_admin_fee_route(
+				RawOrigin::Root.into(),
+				pool_id,
+				vec![(BOB, FEE_DENOMINATOR)],
+				Some(Token(TOKEN1_SYMBOL)),
+				MAX_ADMIN_FEE_SLIPPAGE + 1
+			),
+			Error::<Test>::InvalidAdminFeeRoute
+		);
+		assert_noop!(
+			StableAmm::remove_admin_fee_route(RawOrigin::Root.into(), pool_id),
+			Error::<Test>::InvalidPoolId
+		);
+	})
+}
+
+#[test]
+fn withdraw_admin_fee_with_route_should_split_and_donate() {
+	new_test_ext().execute_with(|| {
+		let pool_id = setup_test_base_pool_with_admin_fee();
+		let balances_before = StableAmm::pools(pool_id).unwrap().get_balances();
+		let virtual_price_before = StableAmm::get_virtual_price(pool_id);
+
+		assert_ok!(StableAmm::set_admin_fee_route(
+			RawOrigin::Root.into(),
+			pool_id,
+			vec![(CHARLIE, FEE_DENOMINATOR / 2)],
+			None,
+			0
+		));
+
+		let first_token_balance_before = get_user_balance(Token(TOKEN1_SYMBOL), &CHARLIE);
+		let second_token_balance_before = get_user_balance(Token(TOKEN2_SYMBOL), &CHARLIE);
+
+		assert_ok!(StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id));
+
+		assert_eq!(
+			get_user_balance(Token(TOKEN1_SYMBOL), &CHARLIE) - first_token_balance_before,
+			1001973776101 / 2
+		);
+		assert_eq!(
+			get_user_balance(Token(TOKEN2_SYMBOL), &CHARLIE) - second_token_balance_before,
+			998024139765 / 2
+		);
+		assert_eq!(
+			StableAmm::pools(pool_id).unwrap().get_balances(),
+			vec![
+				balances_before[0] + 1001973776101 - 1001973776101 / 2,
+				balances_before[1] + 998024139765 - 998024139765 / 2
+			]
+		);
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 0), Some(0));
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 1), Some(0));
+		assert!(StableAmm::get_virtual_price(pool_id) > virtual_price_before);
+
+		assert_ok!(StableAmm::remove_admin_fee_route(RawOrigin::Root.into(), pool_id));
+		assert_eq!(StableAmm::admin_fee_routes(pool_id), None);
+	})
+}
+
+#[test]
+fn withdraw_admin_fee_with_route_should_keep_dust_shares() {
+	new_test_ext().execute_with(|| {
+		let pool_id = setup_test_base_pool_with_admin_fee();
+		let balances_before = StableAmm::pools(pool_id).unwrap().get_balances();
+		let dust_receiver: AccountId = 100;
+
+		assert_ok!(StableAmm::set_admin_fee_route(
+			RawOrigin::Root.into(),
+			pool_id,
+			vec![(dust_receiver, FEE_DENOMINATOR / 2)],
+			None,
+			0
+		));
+
+		// half of the admin fee can not keep the account of the receiver alive
+		TokenExistentialDeposit::set(1001973776101);
+		assert_ok!(StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id));
+		TokenExistentialDeposit::set(0);
+
+		assert_eq!(get_user_balance(Token(TOKEN1_SYMBOL), &dust_receiver), 0);
+		assert_eq!(get_user_balance(Token(TOKEN2_SYMBOL), &dust_receiver), 0);
+		assert_eq!(
+			StableAmm::pools(pool_id).unwrap().get_balances(),
+			vec![
+				balances_before[0] + 1001973776101 - 1001973776101 / 2,
+				balances_before[1] + 998024139765 - 998024139765 / 2
+			]
+		);
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 0), Some(1001973776101 / 2));
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 1), Some(998024139765 / 2));
+	})
+}
+
+#[test]
+fn withdraw_admin_fee_with_route_should_convert_into_target_currency() {
+	new_test_ext().execute_with(|| {
+		let pool_id = setup_test_base_pool_with_admin_fee();
+
+		assert_ok!(StableAmm::set_admin_fee_route(
+			RawOrigin::Root.into(),
+			pool_id,
+			vec![(CHARLIE, FEE_DENOMINATOR)],
+			Some(Token(TOKEN1_SYMBOL)),
+			0
+		));
+		// the conversion pays the swap fee, which is more than no slippage allows
+		assert_noop!(
+			StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id),
+			Error::<Test>::AmountSlippage
+		);
+
+		assert_ok!(StableAmm::set_admin_fee_route(
+			RawOrigin::Root.into(),
+			pool_id,
+			vec![(CHARLIE, FEE_DENOMINATOR)],
+			Some(Token(TOKEN1_SYMBOL)),
+			FEE_DENOMINATOR / 100
+		));
+
+		let first_token_balance_before = get_user_balance(Token(TOKEN1_SYMBOL), &CHARLIE);
+		let second_token_balance_before = get_user_balance(Token(TOKEN2_SYMBOL), &CHARLIE);
+
+		assert_ok!(StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id));
+
+		let received =
+			get_user_balance(Token(TOKEN1_SYMBOL), &CHARLIE) - first_token_balance_before;
+		assert!(received > 1001973776101 + 998024139765 * 99 / 100);
+		assert!(received < 1001973776101 + 998024139765);
+		assert_eq!(get_user_balance(Token(TOKEN2_SYMBOL), &CHARLIE), second_token_balance_before);
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 0), Some(0));
+		assert_eq!(StableAmm::get_admin_balance(pool_id, 1), Some(0));
+	})
+}
+
+#[test]
+fn withdraw_admin_fee_with_route_should_not_convert_without_oracle_price() {
+	new_test_ext().execute_with(|| {
+		let pool_id = setup_test_base_pool_with_admin_fee();
+
+		assert_ok!(StableAmm::set_admin_fee_route(
+			RawOrigin::Root.into(),
+			pool_id,
+			vec![(CHARLIE, FEE_DENOMINATOR)],
+			Some(Token(TOKEN1_SYMBOL)),
+			MAX_ADMIN_FEE_SLIPPAGE
+		));
+		PoolOracles::<Test>::remove(pool_id);
+
+		assert_noop!(
+			StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id),
+			Error::<Test>::NoOraclePrice
+		);
+	})
+}
+
+#[test]
+fn create_pool_with_too_many_currencies_should_not_work() {
+	new_test_ext().execute_with(|| {
+		let currency_ids: Vec<CurrencyId> =
+			(1..=MAX_POOL_CURRENCIES as u8 + 1).map(Token).collect();
+		assert_noop!(
+			StableAmm::create_base_pool(
+				RawOrigin::Root.into(),
+				currency_ids.clone(),
+				vec![TOKEN1_DECIMAL; currency_ids.len()],
+				INITIAL_A_VALUE,
+				SWAP_FEE,
+				ADMIN_FEE,
+				ALICE,
+				Vec::from("stable_pool_lp"),
+			),
+			Error::<Test>::TooManyCurrencies
+		);
+
+		assert_ok!(StableAmm::create_base_pool(
+			RawOrigin::Root.into(),
+			currency_ids[..MAX_POOL_CURRENCIES as usize].to_vec(),
+			vec![TOKEN1_DECIMAL; MAX_POOL_CURRENCIES as usize],
+			INITIAL_A_VALUE,
+			SWAP_FEE,
+			ADMIN_FEE,
+			ALICE,
+			Vec::from("stable_pool_lp"),
+		));
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				0,
+				currency_ids[MAX_POOL_CURRENCIES as usize],
+				TOKEN1_DECIMAL,
+				ALICE,
+				1e18 as Balance,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::TooManyCurrencies
+		);
+	})
+}
+
+#[test]
+fn pool_calls_should_refund_weight_by_currency_count() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+
+		let post_info = StableAmm::swap(
+			RawOrigin::Signed(BOB).into(),
+			pool_id,
+			0,
+			1,
+			1e16 as Balance,
+			0,
+			BOB,
+			u64::MAX,
+		)
+		.unwrap();
+		assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::swap(2, 0)));
+		assert!(
+			<() as WeightInfo>::swap(2, 0).ref_time() <
+				<() as WeightInfo>::swap(MAX_POOL_CURRENCIES, 1).ref_time()
+		);
+
+		let post_info =
+			StableAmm::withdraw_admin_fee(RawOrigin::Signed(BOB).into(), pool_id).unwrap();
+		assert_eq!(post_info.actual_weight, Some(<() as WeightInfo>::withdraw_admin_fee(2, 0)));
+	})
+}
+
+pub fn old_base_pool<BoundString: Clone>(
+	pool: &BasePool<CurrencyId, AccountId, BoundString>,
+) -> OldBasePool<CurrencyId, AccountId, BoundString> {
+	OldBasePool {
+		currency_ids: pool.currency_ids.clone(),
+		lp_currency_id: pool.lp_currency_id,
+		token_multipliers: pool.token_multipliers.clone(),
+		balances: pool.balances.clone(),
+		fee: pool.fee,
+		admin_fee: pool.admin_fee,
+		initial_a: pool.initial_a,
+		future_a: pool.future_a,
+		initial_a_time: pool.initial_a_time,
+		future_a_time: pool.future_a_time,
+		account: pool.account,
+		admin_fee_receiver: pool.admin_fee_receiver,
+		lp_currency_symbol: pool.lp_currency_symbol.clone(),
+		lp_currency_decimal: pool.lp_currency_decimal,
+	}
+}
+
+#[test]
+fn migrate_to_v1_should_translate_base_pools() {
+	new_test_ext().execute_with(|| {
+		let (pool_id, _) = setup_test_base_pool();
+		let pool = match StableAmm::pools(pool_id).unwrap() {
+			Pool::Base(bp) => bp,
+			Pool::Meta(_) => unreachable!(),
+		};
+		let old_pool: OldPool<PoolId, _, _, _> = OldPool::Base(old_base_pool(&pool));
+		let key = Pools::<Test>::hashed_key_for(pool_id);
+		unhashed::put(&key, &old_pool);
+		StorageVersion::new(0).put::<StableAmm>();
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(StableAmm::pools(pool_id), Some(Pool::Base(pool)));
+		assert_eq!(StableAmm::on_chain_storage_version(), 1);
+
+		// running it again changes nothing
+		unhashed::put(&key, &old_pool);
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(unhashed::get_raw(&key), Some(old_pool.encode()));
+	})
+}
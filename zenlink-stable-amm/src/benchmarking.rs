@@ -6,7 +6,7 @@
 use super::*;
 use crate::Pallet as StablePallet;
 
-use frame_benchmarking::{benchmarks, whitelisted_caller};
+use frame_benchmarking::{account, benchmarks, whitelisted_caller};
 use frame_support::assert_ok;
 use frame_system::RawOrigin;
 
@@ -16,6 +16,7 @@ const LP_UNIT: u128 = 1_000_000_000_000_000_000;
 const INITIAL_A_VALUE: Balance = 50;
 const SWAP_FEE: Balance = 10000000;
 const ADMIN_FEE: Balance = 0;
+const WITHDRAW_ADMIN_FEE: Balance = 100000000;
 
 pub fn lookup_of_account<T: Config>(
 	who: T::AccountId,
@@ -23,31 +24,166 @@ pub fn lookup_of_account<T: Config>(
 	<T as frame_system::Config>::Lookup::unlookup(who)
 }
 
-fn token1<CurrencyId: TryFrom<u64> + Default>() -> CurrencyId {
-	CurrencyId::try_from(513u64).unwrap_or_default()
+fn token<CurrencyId: TryFrom<u64> + Default>(index: u32) -> CurrencyId {
+	CurrencyId::try_from(513u64 + index as u64).unwrap_or_default()
 }
 
-fn token2<CurrencyId: TryFrom<u64> + Default>() -> CurrencyId {
-	CurrencyId::try_from(514u64).unwrap_or_default()
+// The amounts of `n` currencies of a pool worth `units` each, the last one being the base pool lp
+// currency of a meta pool.
+fn pool_amounts(n: u32, meta: bool, units: u128) -> Vec<Balance> {
+	let mut amounts = vec![units * UNIT; n as usize];
+	if meta {
+		amounts[n as usize - 1] = units * LP_UNIT;
+	}
+	amounts
 }
 
-fn token3<CurrencyId: TryFrom<u64> + Default>() -> CurrencyId {
-	CurrencyId::try_from(515u64).unwrap_or_default()
+// Create a base pool of `n` currencies, starting at token `first`, with liquidity of `caller`.
+fn create_base_pool_with_liquidity<T: Config>(
+	caller: &T::AccountId,
+	first: u32,
+	n: u32,
+	admin_fee: Balance,
+) -> T::PoolId
+where
+	T::CurrencyId: TryFrom<u64> + Default,
+{
+	let pool_id = StablePallet::<T>::next_pool_id();
+	let currency_ids: Vec<T::CurrencyId> = (first..first + n).map(token).collect();
+
+	assert_ok!(StablePallet::<T>::create_base_pool(
+		(RawOrigin::Root).into(),
+		currency_ids.clone(),
+		vec![12; n as usize],
+		INITIAL_A_VALUE,
+		SWAP_FEE,
+		admin_fee,
+		caller.clone(),
+		Vec::from("stable_pool_lp")
+	));
+
+	for currency_id in currency_ids {
+		assert_ok!(T::MultiCurrency::deposit(currency_id, caller, UNIT * 1000));
+	}
+
+	assert_ok!(StablePallet::<T>::add_liquidity(
+		RawOrigin::Signed(caller.clone()).into(),
+		pool_id,
+		pool_amounts(n, false, 10),
+		0,
+		caller.clone(),
+		1000u32.into()
+	));
+
+	pool_id
+}
+
+// Create a meta pool of `n` currencies on a base pool of `b` currencies, both with liquidity of
+// `caller`. Returns the ids of the meta pool and the base pool.
+fn create_meta_pool_with_liquidity<T: Config>(
+	caller: &T::AccountId,
+	n: u32,
+	b: u32,
+	admin_fee: Balance,
+) -> (T::PoolId, T::PoolId)
+where
+	T::CurrencyId: TryFrom<u64> + Default,
+{
+	let base_pool_id = create_base_pool_with_liquidity::<T>(caller, 0, b, ADMIN_FEE);
+	let base_lp_currency = StablePallet::<T>::get_lp_currency(base_pool_id).unwrap_or_default();
+
+	let pool_id = StablePallet::<T>::next_pool_id();
+	let mut currency_ids: Vec<T::CurrencyId> =
+		(MAX_POOL_CURRENCIES..MAX_POOL_CURRENCIES + n - 1).map(token).collect();
+	for currency_id in currency_ids.iter() {
+		assert_ok!(T::MultiCurrency::deposit(*currency_id, caller, UNIT * 1000));
+	}
+	currency_ids.push(base_lp_currency);
+
+	let mut currency_decimals = vec![12; n as usize - 1];
+	currency_decimals.push(18);
+
+	assert_ok!(StablePallet::<T>::create_meta_pool(
+		(RawOrigin::Root).into(),
+		currency_ids,
+		currency_decimals,
+		INITIAL_A_VALUE,
+		SWAP_FEE,
+		admin_fee,
+		caller.clone(),
+		Vec::from("stable_pool_lp")
+	));
+
+	assert_ok!(StablePallet::<T>::add_liquidity(
+		RawOrigin::Signed(caller.clone()).into(),
+		pool_id,
+		pool_amounts(n, true, 5),
+		0,
+		caller.clone(),
+		1000u32.into()
+	));
+
+	(pool_id, base_pool_id)
 }
 
-fn stable_pool_0<CurrencyId: TryFrom<u64> + Default>() -> CurrencyId {
-	CurrencyId::try_from(1024u64).unwrap_or_default()
+// Create a pool of `n` currencies with liquidity of `caller`, a meta pool on the largest base pool
+// if `m` is not zero.
+fn create_pool_with_liquidity<T: Config>(
+	caller: &T::AccountId,
+	n: u32,
+	m: u32,
+	admin_fee: Balance,
+) -> T::PoolId
+where
+	T::CurrencyId: TryFrom<u64> + Default,
+{
+	if m == 0 {
+		create_base_pool_with_liquidity::<T>(caller, 0, n, admin_fee)
+	} else {
+		create_meta_pool_with_liquidity::<T>(caller, n, MAX_POOL_CURRENCIES, admin_fee).0
+	}
+}
+
+// Create meta pools without liquidity on the base pool until there are `pool_count` pools.
+fn create_meta_pools_up_to<T: Config>(
+	caller: &T::AccountId,
+	base_pool_id: T::PoolId,
+	pool_count: u32,
+) where
+	T::CurrencyId: TryFrom<u64> + Default,
+{
+	let base_lp_currency = StablePallet::<T>::get_lp_currency(base_pool_id).unwrap_or_default();
+	while StablePallet::<T>::next_pool_id() < T::PoolId::from(pool_count) {
+		assert_ok!(StablePallet::<T>::create_meta_pool(
+			(RawOrigin::Root).into(),
+			vec![token(MAX_POOL_CURRENCIES), base_lp_currency],
+			vec![12, 18],
+			INITIAL_A_VALUE,
+			SWAP_FEE,
+			ADMIN_FEE,
+			caller.clone(),
+			Vec::from("stable_pool_lp")
+		));
+	}
+}
+
+// Move the time a day past the creation of the pools, beyond the base cache of the meta pools, so
+// that their next call refreshes the base virtual price.
+fn expire_base_cache<T: pallet_timestamp::Config>() {
+	pallet_timestamp::Pallet::<T>::set_timestamp((DAY * 1000).into());
 }
 
 benchmarks! {
 
-	where_clause { where T::CurrencyId: TryFrom<u64> + Default}
+	where_clause { where T::CurrencyId: TryFrom<u64> + Default, T: pallet_timestamp::Config }
 
 	create_base_pool{
+		let n in 2 .. MAX_POOL_CURRENCIES;
 		let admin_fee_receiver: T::AccountId = whitelisted_caller();
+		let currency_ids: Vec<T::CurrencyId> = (0..n).map(token).collect();
 	}:_(RawOrigin::Root,
-		[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-		[12,12].to_vec(),
+		currency_ids,
+		vec![12; n as usize],
 		INITIAL_A_VALUE,
 		SWAP_FEE,
 		ADMIN_FEE,
@@ -56,35 +192,18 @@ benchmarks! {
 	)
 
 	create_meta_pool{
+		let n in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
+		let base_pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, MAX_POOL_CURRENCIES, ADMIN_FEE);
 
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let mut currency_ids: Vec<T::CurrencyId> = (MAX_POOL_CURRENCIES..MAX_POOL_CURRENCIES + n - 1).map(token).collect();
+		currency_ids.push(StablePallet::<T>::get_lp_currency(base_pool_id).unwrap_or_default());
+		let mut currency_decimals = vec![12; n as usize - 1];
+		currency_decimals.push(18);
 
 	}:_(RawOrigin::Root,
-		[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-		[12,18].to_vec(),
+		currency_ids,
+		currency_decimals,
 		INITIAL_A_VALUE,
 		SWAP_FEE,
 		ADMIN_FEE,
@@ -93,54 +212,26 @@ benchmarks! {
 	)
 
 	add_liquidity{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, ADMIN_FEE);
+		let mut amounts = pool_amounts(n, m == 1, 1);
+		amounts[0] = 2 * UNIT;
 
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-	}:_(RawOrigin::Signed(caller.clone()), 0u32.into(), [10*UNIT, 10*UNIT].to_vec(), 0, caller.clone(),1000u32.into())
+	}:_(RawOrigin::Signed(caller.clone()), pool_id, amounts, 0, caller.clone(), 1000u32.into())
 
 	swap{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, ADMIN_FEE);
+		expire_base_cache::<T>();
 
 	}:_(RawOrigin::Signed(caller.clone()),
-		0u32.into(),
+		pool_id,
 		0u32,
-		1u32,
+		n - 1,
 		1 * UNIT,
 		0,
 		caller.clone(),
@@ -148,277 +239,97 @@ benchmarks! {
 	)
 
 	remove_liquidity{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, ADMIN_FEE);
 
 	}:_(RawOrigin::Signed(caller.clone()),
-		0u32.into(),
-		1 * UNIT,
-		[0,0].to_vec(),
+		pool_id,
+		1 * LP_UNIT,
+		vec![0; n as usize],
 		caller.clone(),
 		1000u32.into()
 	)
 
 	remove_liquidity_one_currency{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, ADMIN_FEE);
 
 	}:_(RawOrigin::Signed(caller.clone()),
-		0u32.into(),
-		1 * UNIT,
-		1,
+		pool_id,
+		1 * LP_UNIT,
+		0,
 		0,
 		caller.clone(),
 		1000u32.into()
 	)
 
 	remove_liquidity_imbalance{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[100*UNIT, 100*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, ADMIN_FEE);
+		let mut amounts = pool_amounts(n, m == 1, 1);
+		amounts[0] = 2 * UNIT;
 
 	}:_(RawOrigin::Signed(caller.clone()),
-		0u32.into(),
-		[10 * UNIT, 1*UNIT].to_vec(),
-		20 * LP_UNIT,
+		pool_id,
+		amounts,
+		2 * n as u128 * LP_UNIT,
 		caller.clone(),
 		1000u32.into()
 	)
 
 	add_pool_and_base_pool_liquidity{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
-
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
+		let (pool_id, base_pool_id) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
+		let mut meta_amounts = pool_amounts(n, true, 1);
+		meta_amounts[n as usize - 1] = 0;
 
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
-		0u32.into(),
-		[9*UNIT, 1* UNIT].to_vec(),
-		[10* UNIT, 10 * UNIT].to_vec(),
+		pool_id,
+		base_pool_id,
+		meta_amounts,
+		pool_amounts(b, false, 1),
 		0,
 		caller.clone(),
 		1000u32.into()
 	)
 
 	remove_pool_and_base_pool_liquidity{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
-
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				1u32.into(),
-				[5*UNIT, 5*LP_UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let (pool_id, base_pool_id) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
 
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
-		0u32.into(),
-		10 * LP_UNIT,
-		[2*UNIT, 2* UNIT].to_vec(),
-		[2* UNIT, 2 * UNIT].to_vec(),
+		pool_id,
+		base_pool_id,
+		1 * LP_UNIT,
+		vec![0; n as usize],
+		vec![0; b as usize],
 		caller.clone(),
 		1000u32.into()
 	)
 
 	remove_pool_and_base_pool_liquidity_one_currency{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
-
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				1u32.into(),
-				[5*UNIT, 5*LP_UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let (pool_id, base_pool_id) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
 
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
-		0u32.into(),
-		10 * LP_UNIT,
+		pool_id,
+		base_pool_id,
+		1 * LP_UNIT,
 		0,
 		0,
 		caller.clone(),
@@ -426,58 +337,16 @@ benchmarks! {
 	)
 
 	swap_pool_from_base{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
+		let (pool_id, base_pool_id) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
+		expire_base_cache::<T>();
 
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				1u32.into(),
-				[5*UNIT, 5*LP_UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
-		0u32.into(),
+		pool_id,
+		base_pool_id,
 		0,
 		0,
 		1*UNIT,
@@ -487,58 +356,16 @@ benchmarks! {
 	)
 
 	swap_pool_to_base{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
-
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
+		let (pool_id, base_pool_id) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
+		expire_base_cache::<T>();
 
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				1u32.into(),
-				[5*UNIT, 5*LP_UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
-		0u32.into(),
+		pool_id,
+		base_pool_id,
 		0,
 		0,
 		1*UNIT,
@@ -548,105 +375,211 @@ benchmarks! {
 	)
 
 	swap_meta_pool_underlying{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let b in 2 .. MAX_POOL_CURRENCIES;
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token3::<T::CurrencyId>(), &caller, UNIT * 1000));
+		let (pool_id, _) = create_meta_pool_with_liquidity::<T>(&caller, n, b, ADMIN_FEE);
+		expire_base_cache::<T>();
 
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
-
-		assert_ok!(StablePallet::<T>::create_meta_pool(
-			(RawOrigin::Root).into(),
-			[token3::<T::CurrencyId>(), stable_pool_0::<T::CurrencyId>()].to_vec(),
-			[12,18].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_1")
-		));
-
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
-				RawOrigin::Signed(caller.clone()).into(),
-				1u32.into(),
-				[5*UNIT, 5*LP_UNIT].to_vec(),
-				0,
-				caller.clone(),
-				1000u32.into()
-			)
-		);
 	}:_(
 		RawOrigin::Signed(caller.clone()),
-		1u32.into(),
+		pool_id,
 		0,
-		2,
+		n - 1,
 		1*UNIT,
 		0,
 		caller.clone(),
 		1000u32.into()
 	)
 
-	withdraw_admin_fee{
+	update_fee_receiver{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, lookup_of_account::<T>(caller))
+
+	set_swap_fee{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, SWAP_FEE / 2)
+
+	set_admin_fee{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, WITHDRAW_ADMIN_FEE)
+
+	ramp_a{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+		let now = 2 * DAY as u128;
+		pallet_timestamp::Pallet::<T>::set_timestamp((2 * DAY * 1000).into());
+
+	}:_(RawOrigin::Root, pool_id, INITIAL_A_VALUE * 2, now + MIN_RAMP_TIME as u128)
+
+	stop_ramp_a{
 		let caller: T::AccountId = whitelisted_caller();
-		assert_ok!(StablePallet::<T>::create_base_pool(
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+		let now = 2 * DAY as u128;
+		pallet_timestamp::Pallet::<T>::set_timestamp((2 * DAY * 1000).into());
+		assert_ok!(StablePallet::<T>::ramp_a(
 			(RawOrigin::Root).into(),
-			[token1::<T::CurrencyId>(), token2::<T::CurrencyId>()].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			100000000,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
+			pool_id,
+			INITIAL_A_VALUE * 2,
+			now + MIN_RAMP_TIME as u128
 		));
 
-		assert_ok!(T::MultiCurrency::deposit(token1::<T::CurrencyId>(), &caller, UNIT * 1000));
-		assert_ok!(T::MultiCurrency::deposit(token2::<T::CurrencyId>(), &caller, UNIT * 1000));
+	}:_(RawOrigin::Root, pool_id)
 
-		assert_ok!(
-			StablePallet::<T>::add_liquidity(
+	withdraw_admin_fee{
+		let n in 2 .. MAX_POOL_CURRENCIES;
+		let m in 0 .. 1;
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_pool_with_liquidity::<T>(&caller, n, m, WITHDRAW_ADMIN_FEE);
+		let amounts = pool_amounts(n, m == 1, 1);
+
+		// Every currency but the last one collects admin fee and gets converted into it, then
+		// split among the most receivers the route allows.
+		for i in 0..n - 1 {
+			assert_ok!(StablePallet::<T>::swap(
 				RawOrigin::Signed(caller.clone()).into(),
-				0u32.into(),
-				[10*UNIT, 10*UNIT].to_vec(),
+				pool_id,
+				n - 1,
+				i,
+				amounts[n as usize - 1],
 				0,
 				caller.clone(),
 				1000u32.into()
-			)
-		);
+			));
+			assert_ok!(StablePallet::<T>::swap(
+				RawOrigin::Signed(caller.clone()).into(),
+				pool_id,
+				i,
+				n - 1,
+				amounts[i as usize],
+				0,
+				caller.clone(),
+				1000u32.into()
+			));
+		}
+
+		let target_currency = StablePallet::<T>::pools(pool_id)
+			.and_then(|pool| pool.get_currency_ids().last().copied());
+		let share = FEE_DENOMINATOR / (MAX_ADMIN_FEE_RECEIVERS as Number + 1);
+		let receivers = (0..MAX_ADMIN_FEE_RECEIVERS as u32)
+			.map(|i| (account("receiver", i, 0), share))
+			.collect();
+		assert_ok!(StablePallet::<T>::set_admin_fee_route(
+			(RawOrigin::Root).into(),
+			pool_id,
+			receivers,
+			target_currency,
+			MAX_ADMIN_FEE_SLIPPAGE
+		));
+		expire_base_cache::<T>();
+
+	}:_(
+		RawOrigin::Signed(caller.clone()),
+		pool_id
+	)
+	verify {
+		for i in 0..n as usize - 1 {
+			assert_eq!(StablePallet::<T>::get_admin_balance(pool_id, i), Some(0));
+		}
+	}
+
+	set_offpeg_fee_multiplier{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, 2 * FEE_DENOMINATOR)
+
+	set_base_cache_policy{
+		let caller: T::AccountId = whitelisted_caller();
+		let (pool_id, _) = create_meta_pool_with_liquidity::<T>(&caller, 2, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, BaseCachePolicy::Blocks(10))
+
+	refresh_base_virtual_price{
+		let b in 2 .. MAX_POOL_CURRENCIES;
+		let caller: T::AccountId = whitelisted_caller();
+		let (pool_id, _) = create_meta_pool_with_liquidity::<T>(&caller, 2, b, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id)
+
+	add_pool_currency{
+		let p in 1 .. 100;
+		let caller: T::AccountId = whitelisted_caller();
+		let base_pool_id =
+			create_base_pool_with_liquidity::<T>(&caller, 0, MAX_POOL_CURRENCIES - 1, ADMIN_FEE);
+		create_meta_pools_up_to::<T>(&caller, base_pool_id, p);
+		let currency_id = token(MAX_POOL_CURRENCIES - 1);
+		assert_ok!(T::MultiCurrency::deposit(currency_id, &caller, UNIT * 1000));
+
+	}:_(RawOrigin::Root, base_pool_id, currency_id, 12, caller.clone(), 10 * UNIT, p)
+
+	retire_pool_currency{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 3, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, token(2))
+
+	remove_pool_currency{
+		let n in 3 .. MAX_POOL_CURRENCIES;
+		let p in 1 .. 100;
+		let caller: T::AccountId = whitelisted_caller();
+		let base_pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, n, ADMIN_FEE);
+		create_meta_pools_up_to::<T>(&caller, base_pool_id, p);
 
-		assert_ok!(StablePallet::<T>::swap(
+		// The retiring currency can only leave the pool, so withdraw all the liquidity to make
+		// its balance dust.
+		assert_ok!(StablePallet::<T>::retire_pool_currency(
+			(RawOrigin::Root).into(),
+			base_pool_id,
+			token(n - 1)
+		));
+		let lp_currency = StablePallet::<T>::get_lp_currency(base_pool_id).unwrap_or_default();
+		assert_ok!(StablePallet::<T>::remove_liquidity(
 			RawOrigin::Signed(caller.clone()).into(),
-			0u32.into(),
-			0u32,
-			1u32,
-			1 * UNIT,
-			0,
+			base_pool_id,
+			T::MultiCurrency::free_balance(lp_currency, &caller),
+			vec![0; n as usize],
 			caller.clone(),
 			1000u32.into()
 		));
 
-	}:_(
-		RawOrigin::Signed(caller.clone()),
-		0u32.into()
-	)
+	}:_(RawOrigin::Root, base_pool_id, p)
+
+	set_liquidity_caps{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+	}:_(RawOrigin::Root, pool_id, Some(1000 * UNIT), Some(100 * LP_UNIT))
+
+	set_admin_fee_route{
+		let r in 0 .. MAX_ADMIN_FEE_RECEIVERS as u32;
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+		let share = FEE_DENOMINATOR / (MAX_ADMIN_FEE_RECEIVERS as Number + 1);
+		let receivers = (0..r).map(|i| (account("receiver", i, 0), share)).collect();
+
+	}:_(RawOrigin::Root, pool_id, receivers, Some(token(0)), MAX_ADMIN_FEE_SLIPPAGE)
+
+	remove_admin_fee_route{
+		let caller: T::AccountId = whitelisted_caller();
+		let pool_id = create_base_pool_with_liquidity::<T>(&caller, 0, 2, ADMIN_FEE);
+
+		assert_ok!(StablePallet::<T>::set_admin_fee_route(
+			(RawOrigin::Root).into(),
+			pool_id,
+			vec![(caller.clone(), FEE_DENOMINATOR)],
+			None,
+			MAX_ADMIN_FEE_SLIPPAGE
+		));
+
+	}:_(RawOrigin::Root, pool_id)
 }
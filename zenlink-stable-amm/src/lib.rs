@@ -31,6 +31,7 @@
 #![allow(clippy::too_many_arguments)]
 #![allow(clippy::type_complexity)]
 
+pub mod migrations;
 pub mod rpc;
 pub mod traits;
 
@@ -43,8 +44,10 @@ mod meta_pool_tests;
 #[cfg(test)]
 mod mock;
 
+mod admin_fee;
 mod base_pool;
 mod meta_pool;
+mod oracle;
 mod primitives;
 mod utils;
 mod weights;
@@ -58,11 +61,13 @@ use frame_support::{
 use orml_traits::MultiCurrency;
 use sp_arithmetic::traits::{checked_pow, AtLeast32BitUnsigned, CheckedAdd, One, Zero};
 use sp_core::U256;
-use sp_runtime::traits::{AccountIdConversion, StaticLookup};
+use sp_runtime::traits::{AccountIdConversion, SaturatedConversion, StaticLookup};
 use sp_std::{ops::Sub, vec, vec::Vec};
 
 pub use pallet::*;
+pub use primitives::MAX_POOL_CURRENCIES;
 use primitives::*;
+pub use rpc::StablePoolInfo;
 use traits::{StablePoolLpCurrencyIdGenerate, ValidateCurrency};
 pub use weights::WeightInfo;
 
@@ -108,6 +113,10 @@ pub mod pallet {
 		#[pallet::constant]
 		type PoolCurrencySymbolLimit: Get<u32>;
 
+		/// The time constant of the pool oracles' moving averages, in seconds.
+		#[pallet::constant]
+		type OracleEmaPeriod: Get<u64>;
+
 		/// This pallet ID.
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
@@ -116,8 +125,12 @@ pub mod pallet {
 		type WeightInfo: WeightInfo;
 	}
 
+	/// The current storage version.
+	const STORAGE_VERSION: StorageVersion = StorageVersion::new(1);
+
 	#[pallet::pallet]
 	#[pallet::without_storage_info]
+	#[pallet::storage_version(STORAGE_VERSION)]
 	pub struct Pallet<T>(_);
 
 	/// The id of next pool
@@ -140,6 +153,29 @@ pub mod pallet {
 	#[pallet::getter(fn lp_currencies)]
 	pub type LpCurrencies<T: Config> = StorageMap<_, Blake2_128Concat, T::CurrencyId, T::PoolId>;
 
+	/// The currency of a base pool which is being retired.
+	#[pallet::storage]
+	#[pallet::getter(fn retiring_currencies)]
+	pub type RetiringCurrencies<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::PoolId, RetiringCurrency<T::CurrencyId>>;
+
+	/// Price and virtual price oracle of a pool.
+	#[pallet::storage]
+	#[pallet::getter(fn pool_oracles)]
+	pub type PoolOracles<T: Config> = StorageMap<_, Blake2_128Concat, T::PoolId, PoolOracle>;
+
+	/// Deposit caps of a pool, unlimited when absent.
+	#[pallet::storage]
+	#[pallet::getter(fn liquidity_caps)]
+	pub type PoolLiquidityCaps<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::PoolId, LiquidityCaps>;
+
+	/// How the admin fee of a pool is distributed, all to the admin fee receiver when absent.
+	#[pallet::storage]
+	#[pallet::getter(fn admin_fee_routes)]
+	pub type AdminFeeRoutes<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::PoolId, AdminFeeRoute<T::CurrencyId, T::AccountId>>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -206,6 +242,39 @@ pub mod pallet {
 		NewSwapFee { pool_id: T::PoolId, new_swap_fee: Number },
 		/// A pool's admin fee parameters was updated
 		NewAdminFee { pool_id: T::PoolId, new_admin_fee: Number },
+		/// A pool's offpeg fee multiplier was updated
+		NewOffpegFeeMultiplier { pool_id: T::PoolId, offpeg_fee_multiplier: Number },
+		/// A meta pool's base virtual price cache policy was updated
+		NewBaseCachePolicy { pool_id: T::PoolId, policy: BaseCachePolicy },
+		/// A meta pool's cached base virtual price was updated
+		UpdateBaseVirtualPrice { pool_id: T::PoolId, base_virtual_price: Balance },
+		/// A currency was added to a base pool.
+		AddPoolCurrency { pool_id: T::PoolId, currency_id: T::CurrencyId },
+		/// A currency of a base pool started retiring.
+		RetirePoolCurrency { pool_id: T::PoolId, currency_id: T::CurrencyId },
+		/// A retired currency was removed from a base pool, and the dust was sent to the admin
+		/// fee receiver. The currencies after `index` moved down one index.
+		RemovePoolCurrency {
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			index: u32,
+			dust: Balance,
+		},
+		/// A pool's deposit caps were updated.
+		NewLiquidityCaps { pool_id: T::PoolId, caps: LiquidityCaps },
+		/// A pool's admin fee route was updated.
+		NewAdminFeeRoute { pool_id: T::PoolId, route: AdminFeeRoute<T::CurrencyId, T::AccountId> },
+		/// A pool's admin fee route was removed.
+		RemoveAdminFeeRoute { pool_id: T::PoolId },
+		/// A share of a pool's admin fee was sent to a receiver of its route.
+		DistributeAdminFee {
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			receiver: T::AccountId,
+			amount: Balance,
+		},
+		/// The unassigned share of a pool's admin fee was donated to the liquidity providers.
+		DonateAdminFee { pool_id: T::PoolId, currency_id: T::CurrencyId, amount: Balance },
 		/// A pool's 'A' was ramped.
 		RampA {
 			pool_id: T::PoolId,
@@ -290,6 +359,31 @@ pub mod pallet {
 		InvalidBasePoolLpCurrency,
 		/// The token index out of range.
 		TokenIndexOutOfRange,
+		/// The currency is already in the pool.
+		CurrencyAlreadyInPool,
+		/// The currency is retiring and can't flow into the pool.
+		CurrencyRetiring,
+		/// The currency is not retiring.
+		CurrencyNotRetiring,
+		/// The balance of the retiring currency is above MAX_RETIRED_CURRENCY_DUST.
+		RetiringCurrencyNotDust,
+		/// A pool requires at least two currencies.
+		TooFewCurrencies,
+		/// A pool can't have more than MAX_POOL_CURRENCIES currencies.
+		TooManyCurrencies,
+		/// The deposit would push the pool over its tvl cap.
+		ExceedPoolTvlCap,
+		/// The deposit would push the account over its lp cap.
+		ExceedAccountLpCap,
+		/// The admin fee route has too many receivers.
+		TooManyAdminFeeReceivers,
+		/// The shares of the admin fee route exceed FEE_DENOMINATOR or its max slippage exceeds
+		/// MAX_ADMIN_FEE_SLIPPAGE.
+		InvalidAdminFeeRoute,
+		/// The pool count witness is below the number of pools.
+		InvalidPoolCountWitness,
+		/// The price oracle has no price to bound the admin fee conversion with.
+		NoOraclePrice,
 	}
 
 	#[pallet::call]
@@ -310,7 +404,7 @@ pub mod pallet {
 		/// - `lp_currency_symbol`: The symbol of created pool lp currency.
 		/// - `lp_currency_decimal`: The decimal of created pool lp currency.
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::create_base_pool())]
+		#[pallet::weight(T::WeightInfo::create_base_pool(currency_ids.len() as u32))]
 		#[transactional]
 		pub fn create_base_pool(
 			origin: OriginFor<T>,
@@ -377,7 +471,7 @@ pub mod pallet {
 		/// - `lp_currency_symbol`: The symbol of created pool lp currency.
 		/// - `lp_currency_decimal`: The decimal of created pool lp currency.
 		#[pallet::call_index(1)]
-		#[pallet::weight(T::WeightInfo::create_meta_pool())]
+		#[pallet::weight(T::WeightInfo::create_meta_pool(currency_ids.len() as u32))]
 		#[transactional]
 		pub fn create_meta_pool(
 			origin: OriginFor<T>,
@@ -414,6 +508,9 @@ pub mod pallet {
 				base_pool_id,
 				base_virtual_price: base_pool_virtual_price,
 				base_cache_last_updated: T::TimeProvider::now().as_secs(),
+				base_cache_last_updated_block: frame_system::Pallet::<T>::block_number()
+					.saturated_into::<u64>(),
+				base_cache_policy: Default::default(),
 				base_currencies: base_pool.get_currency_ids(),
 				info: meta_pool_info,
 			};
@@ -453,7 +550,7 @@ pub mod pallet {
 		/// - `min_mint_amount`: The min amount of lp currency get.
 		/// - `deadline`: Height of the cutoff block of this transaction
 		#[pallet::call_index(2)]
-		#[pallet::weight(T::WeightInfo::add_liquidity())]
+		#[pallet::weight(T::WeightInfo::add_liquidity(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
 		pub fn add_liquidity(
 			origin: OriginFor<T>,
@@ -462,7 +559,7 @@ pub mod pallet {
 			min_mint_amount: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
@@ -470,7 +567,8 @@ pub mod pallet {
 
 			Self::inner_add_liquidity(&who, pool_id, &amounts, min_mint_amount, &to)?;
 
-			Ok(())
+			let (n, m) = Self::pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::add_liquidity(n, m)).into())
 		}
 
 		/// Swap a amounts of currencies to get other.
@@ -484,7 +582,7 @@ pub mod pallet {
 		/// - `min_mint_amount`: The min amount of receive currency.
 		/// - `deadline`: Height of the cutoff block of this transaction
 		#[pallet::call_index(3)]
-		#[pallet::weight(T::WeightInfo::swap())]
+		#[pallet::weight(T::WeightInfo::swap(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
 		pub fn swap(
 			origin: OriginFor<T>,
@@ -495,7 +593,7 @@ pub mod pallet {
 			min_out_amount: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
@@ -511,7 +609,8 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, m) = Self::pool_weight_components(poo_id);
+			Ok(Some(T::WeightInfo::swap(n, m)).into())
 		}
 
 		/// Remove liquidity from a pool.
@@ -523,7 +622,7 @@ pub mod pallet {
 		/// - `min_amounts`: The min amounts of pool's currencies to get.
 		/// - `deadline`: Height of the cutoff block of this transaction
 		#[pallet::call_index(4)]
-		#[pallet::weight(T::WeightInfo::remove_liquidity())]
+		#[pallet::weight(T::WeightInfo::remove_liquidity(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
 		pub fn remove_liquidity(
 			origin: OriginFor<T>,
@@ -532,7 +631,7 @@ pub mod pallet {
 			min_amounts: Vec<Balance>,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
@@ -540,7 +639,8 @@ pub mod pallet {
 
 			Self::inner_remove_liquidity(poo_id, &who, lp_amount, &min_amounts, &to)?;
 
-			Ok(())
+			let (n, m) = Self::pool_weight_components(poo_id);
+			Ok(Some(T::WeightInfo::remove_liquidity(n, m)).into())
 		}
 
 		/// Remove liquidity from a pool to get one currency.
@@ -553,7 +653,7 @@ pub mod pallet {
 		/// - `min_amount`: The min amounts of received currency;
 		/// - `deadline`: Height of the cutoff block of this transaction
 		#[pallet::call_index(5)]
-		#[pallet::weight(T::WeightInfo::remove_liquidity_one_currency())]
+		#[pallet::weight(T::WeightInfo::remove_liquidity_one_currency(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
 		pub fn remove_liquidity_one_currency(
 			origin: OriginFor<T>,
@@ -563,7 +663,7 @@ pub mod pallet {
 			min_amount: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
@@ -573,7 +673,8 @@ pub mod pallet {
 				poo_id, &who, lp_amount, index, min_amount, &to,
 			)?;
 
-			Ok(())
+			let (n, m) = Self::pool_weight_components(poo_id);
+			Ok(Some(T::WeightInfo::remove_liquidity_one_currency(n, m)).into())
 		}
 
 		/// Remove liquidity from a pool to the specify amounts of currencies.
@@ -585,7 +686,7 @@ pub mod pallet {
 		/// - `max_burn_amount`: The max amount of burned lp currency.
 		/// - `deadline`: Height of the cutoff block of this transaction
 		#[pallet::call_index(6)]
-		#[pallet::weight(T::WeightInfo::remove_liquidity_imbalance())]
+		#[pallet::weight(T::WeightInfo::remove_liquidity_imbalance(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
 		pub fn remove_liquidity_imbalance(
 			origin: OriginFor<T>,
@@ -594,7 +695,7 @@ pub mod pallet {
 			max_burn_amount: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 
 			let now = frame_system::Pallet::<T>::block_number();
@@ -602,12 +703,16 @@ pub mod pallet {
 
 			Self::inner_remove_liquidity_imbalance(&who, pool_id, &amounts, max_burn_amount, &to)?;
 
-			Ok(())
+			let (n, m) = Self::pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::remove_liquidity_imbalance(n, m)).into())
 		}
 
 		/// Supply amounts of currencies to the pool which contains the lp currency of the base
 		/// pool.
 		///
+		/// The base pool lp currency minted on the way goes into the pool, so it does not count
+		/// towards the account cap of the base pool.
+		///
 		/// # Argument
 		///
 		/// - `pool_id`: The id of pool.
@@ -617,7 +722,10 @@ pub mod pallet {
 		/// - `min_to_mint`: The min amount of pool lp currency get.
 		/// - `deadline`: Height of the cutoff block of this transaction.
 		#[pallet::call_index(7)]
-		#[pallet::weight(T::WeightInfo::add_pool_and_base_pool_liquidity())]
+		#[pallet::weight(T::WeightInfo::add_pool_and_base_pool_liquidity(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn add_pool_and_base_pool_liquidity(
 			origin: OriginFor<T>,
@@ -628,7 +736,7 @@ pub mod pallet {
 			min_to_mint: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
@@ -643,7 +751,8 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::add_pool_and_base_pool_liquidity(n, b)).into())
 		}
 
 		/// Remove liquidity from a pool which contains the lp currency of the base pool.
@@ -657,7 +766,10 @@ pub mod pallet {
 		/// - `min_amounts_base`: The min amounts of basic pool's currencies to get.
 		/// - `deadline`: Height of the cutoff block of this transaction.
 		#[pallet::call_index(8)]
-		#[pallet::weight(T::WeightInfo::remove_pool_and_base_pool_liquidity())]
+		#[pallet::weight(T::WeightInfo::remove_pool_and_base_pool_liquidity(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn remove_pool_and_base_pool_liquidity(
 			origin: OriginFor<T>,
@@ -668,7 +780,7 @@ pub mod pallet {
 			min_amounts_base: Vec<Balance>,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
@@ -683,7 +795,8 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::remove_pool_and_base_pool_liquidity(n, b)).into())
 		}
 
 		/// Remove liquidity from a pool which contains the lp currency of the base pool
@@ -698,7 +811,10 @@ pub mod pallet {
 		/// - `min_amount`: The min amounts of received currency.
 		/// - `deadline`: Height of the cutoff block of this transaction.
 		#[pallet::call_index(9)]
-		#[pallet::weight(T::WeightInfo::remove_pool_and_base_pool_liquidity_one_currency())]
+		#[pallet::weight(T::WeightInfo::remove_pool_and_base_pool_liquidity_one_currency(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn remove_pool_and_base_pool_liquidity_one_currency(
 			origin: OriginFor<T>,
@@ -709,7 +825,7 @@ pub mod pallet {
 			min_amount: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
@@ -724,7 +840,8 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::remove_pool_and_base_pool_liquidity_one_currency(n, b)).into())
 		}
 
 		/// Swap the currency from basic pool to get amounts of other currency in pool.
@@ -740,7 +857,10 @@ pub mod pallet {
 		/// - `min_dy`: The min amounts of target currency.
 		/// - `deadline`: Height of the cutoff block of this transaction.
 		#[pallet::call_index(10)]
-		#[pallet::weight(T::WeightInfo::swap_pool_from_base())]
+		#[pallet::weight(T::WeightInfo::swap_pool_from_base(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn swap_pool_from_base(
 			origin: OriginFor<T>,
@@ -752,7 +872,7 @@ pub mod pallet {
 			min_dy: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
@@ -768,7 +888,8 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::swap_pool_from_base(n, b)).into())
 		}
 
 		/// Swap the currency from pool to get amounts of other currency in basic pool.
@@ -784,7 +905,10 @@ pub mod pallet {
 		/// - `min_dy`: The min amounts of target currency.
 		/// - `deadline`: Height of the cutoff block of this transaction.
 		#[pallet::call_index(11)]
-		#[pallet::weight(T::WeightInfo::swap_pool_to_base())]
+		#[pallet::weight(T::WeightInfo::swap_pool_to_base(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn swap_pool_to_base(
 			origin: OriginFor<T>,
@@ -796,7 +920,7 @@ pub mod pallet {
 			min_dy: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
@@ -812,11 +936,15 @@ pub mod pallet {
 				&to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::swap_pool_to_base(n, b)).into())
 		}
 
 		#[pallet::call_index(12)]
-		#[pallet::weight(T::WeightInfo::swap_meta_pool_underlying())]
+		#[pallet::weight(T::WeightInfo::swap_meta_pool_underlying(
+			MAX_POOL_CURRENCIES,
+			MAX_POOL_CURRENCIES
+		))]
 		#[transactional]
 		pub fn swap_meta_pool_underlying(
 			origin: OriginFor<T>,
@@ -827,32 +955,17 @@ pub mod pallet {
 			min_dy: Balance,
 			to: T::AccountId,
 			deadline: T::BlockNumber,
-		) -> DispatchResult {
+		) -> DispatchResultWithPostInfo {
 			let who = ensure_signed(origin)?;
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
 
-			Pools::<T>::try_mutate_exists(
-				pool_id,
-				|optioned_pool| -> Result<Balance, DispatchError> {
-					let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
-					match pool {
-						Pool::Meta(mp) => Self::meta_pool_swap_underlying(
-							mp,
-							pool_id,
-							&who,
-							&to,
-							dx,
-							min_dy,
-							in_index as usize,
-							out_index as usize,
-						),
-						_ => Err(Error::<T>::InvalidPoolId.into()),
-					}
-				},
+			Self::inner_swap_meta_pool_underlying(
+				&who, pool_id, in_index, out_index, dx, min_dy, &to,
 			)?;
 
-			Ok(())
+			let (n, b) = Self::meta_pool_weight_components(pool_id);
+			Ok(Some(T::WeightInfo::swap_meta_pool_underlying(n, b)).into())
 		}
 
 		/// Update admin fee receiver of the pool.
@@ -864,7 +977,7 @@ pub mod pallet {
 		/// - `pool_id`: The id of pool.
 		/// - `fee_receiver`: The new admin fee receiver of this pool.
 		#[pallet::call_index(13)]
-		#[pallet::weight(1_000_000)]
+		#[pallet::weight(T::WeightInfo::update_fee_receiver())]
 		#[transactional]
 		pub fn update_fee_receiver(
 			origin: OriginFor<T>,
@@ -891,7 +1004,7 @@ pub mod pallet {
 		/// - `pool_id`: The id of pool.
 		/// - `new_swap_fee`: The new swap fee of this pool.
 		#[pallet::call_index(14)]
-		#[pallet::weight(1_000_000)]
+		#[pallet::weight(T::WeightInfo::set_swap_fee())]
 		#[transactional]
 		pub fn set_swap_fee(
 			origin: OriginFor<T>,
@@ -902,6 +1015,12 @@ pub mod pallet {
 			Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
 				let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
 				ensure!(new_swap_fee <= MAX_SWAP_FEE, Error::<T>::ExceedThreshold);
+				ensure!(
+					Self::max_dynamic_fee(new_swap_fee, pool.get_offpeg_fee_multiplier())
+						.ok_or(Error::<T>::Arithmetic)? <=
+						MAX_SWAP_FEE,
+					Error::<T>::ExceedThreshold
+				);
 
 				pool.set_fee(new_swap_fee);
 
@@ -919,7 +1038,7 @@ pub mod pallet {
 		/// - `pool_id`: The id of pool.
 		/// - `new_admin_fee`: The new admin fee of this pool.
 		#[pallet::call_index(15)]
-		#[pallet::weight(1_000_000)]
+		#[pallet::weight(T::WeightInfo::set_admin_fee())]
 		#[transactional]
 		pub fn set_admin_fee(
 			origin: OriginFor<T>,
@@ -950,7 +1069,7 @@ pub mod pallet {
 		/// - `future_a`: The new A to ramp towards.
 		/// - `future_a_time`: Timestamp when the new A should be reached
 		#[pallet::call_index(16)]
-		#[pallet::weight(1_000_000)]
+		#[pallet::weight(T::WeightInfo::ramp_a())]
 		#[transactional]
 		pub fn ramp_a(
 			origin: OriginFor<T>,
@@ -1033,7 +1152,7 @@ pub mod pallet {
 		///
 		/// - `pool_id`: The id of pool.
 		#[pallet::call_index(17)]
-		#[pallet::weight(1_000_000)]
+		#[pallet::weight(T::WeightInfo::stop_ramp_a())]
 		#[transactional]
 		pub fn stop_ramp_a(origin: OriginFor<T>, pool_id: T::PoolId) -> DispatchResult {
 			ensure_root(origin)?;
@@ -1059,7 +1178,8 @@ pub mod pallet {
 			})
 		}
 
-		/// Withdraw the admin fee from pool to admin fee receiver.
+		/// Withdraw the admin fee from pool to admin fee receiver, or distribute it by the admin
+		/// fee route of the pool if it has one.
 		///
 		/// Can called by anyone.
 		///
@@ -1067,10 +1187,19 @@ pub mod pallet {
 		///
 		/// - `pool_id`: The id of pool.
 		#[pallet::call_index(18)]
-		#[pallet::weight(T::WeightInfo::withdraw_admin_fee())]
+		#[pallet::weight(T::WeightInfo::withdraw_admin_fee(MAX_POOL_CURRENCIES, 1))]
 		#[transactional]
-		pub fn withdraw_admin_fee(origin: OriginFor<T>, pool_id: T::PoolId) -> DispatchResult {
+		pub fn withdraw_admin_fee(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+		) -> DispatchResultWithPostInfo {
 			ensure_signed(origin)?;
+			let (n, m) = Self::pool_weight_components(pool_id);
+
+			if let Some(route) = Self::admin_fee_routes(pool_id) {
+				Self::distribute_admin_fee(pool_id, &route)?;
+				return Ok(Some(T::WeightInfo::withdraw_admin_fee(n, m)).into())
+			}
 
 			Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
 				let general_pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
@@ -1100,8 +1229,426 @@ pub mod pallet {
 					});
 				}
 				Ok(())
+			})?;
+
+			Ok(Some(T::WeightInfo::withdraw_admin_fee(n, m)).into())
+		}
+
+		/// Update offpeg fee multiplier of the pool.
+		///
+		/// Only called by admin.
+		/// The fee of the pool rises towards `fee * offpeg_fee_multiplier / FEE_DENOMINATOR` as
+		/// the balances move away from the peg, which must not exceed MAX_SWAP_FEE. A value not
+		/// bigger than FEE_DENOMINATOR disables the dynamic fee.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of pool.
+		/// - `offpeg_fee_multiplier`: The new offpeg fee multiplier of this pool.
+		#[pallet::call_index(19)]
+		#[pallet::weight(T::WeightInfo::set_offpeg_fee_multiplier())]
+		#[transactional]
+		pub fn set_offpeg_fee_multiplier(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			offpeg_fee_multiplier: Number,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
+				let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+				ensure!(
+					Self::max_dynamic_fee(pool.get_fee(), offpeg_fee_multiplier)
+						.ok_or(Error::<T>::Arithmetic)? <=
+						MAX_SWAP_FEE,
+					Error::<T>::ExceedThreshold
+				);
+
+				pool.set_offpeg_fee_multiplier(offpeg_fee_multiplier);
+
+				Self::deposit_event(Event::NewOffpegFeeMultiplier {
+					pool_id,
+					offpeg_fee_multiplier,
+				});
+				Ok(())
+			})
+		}
+
+		/// Update when the cached base virtual price of the meta pool expires.
+		///
+		/// Only called by admin.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of meta pool.
+		/// - `policy`: Expire the cache after a number of seconds or a number of blocks.
+		#[pallet::call_index(20)]
+		#[pallet::weight(T::WeightInfo::set_base_cache_policy())]
+		#[transactional]
+		pub fn set_base_cache_policy(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			policy: BaseCachePolicy,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
+				let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+				match pool {
+					Pool::Meta(mp) => mp.base_cache_policy = policy,
+					_ => return Err(Error::<T>::InvalidPoolId.into()),
+				}
+
+				Self::deposit_event(Event::NewBaseCachePolicy { pool_id, policy });
+				Ok(())
 			})
 		}
+
+		/// Refresh the cached base virtual price of the meta pool regardless of its policy.
+		///
+		/// Only called by admin.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of meta pool.
+		#[pallet::call_index(21)]
+		#[pallet::weight(T::WeightInfo::refresh_base_virtual_price(MAX_POOL_CURRENCIES))]
+		#[transactional]
+		pub fn refresh_base_virtual_price(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> DispatchResult {
+				let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+				match pool {
+					Pool::Meta(mp) => Self::meta_pool_refresh_virtual_price(pool_id, mp)
+						.ok_or(Error::<T>::Arithmetic)?,
+					_ => return Err(Error::<T>::InvalidPoolId.into()),
+				};
+
+				Self::update_oracle(pool_id, pool);
+				Ok(())
+			})
+		}
+
+		/// Add a currency to a base pool, seeding it in the same call.
+		///
+		/// Only called by admin.
+		/// The seed is supplied as liquidity, valued with the prior balances at the spot prices of
+		/// the pool after it and charged the fee per token, so the invariant of the pool never
+		/// sees the new currency without balance. An empty pool takes no seed, as its first
+		/// deposit must supply every currency anyway.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of base pool.
+		/// - `currency_id`: The currency to join the pool.
+		/// - `currency_decimal`: The decimal of the currency.
+		/// - `seed_from`: The account supplying the seed, which receives the minted lp currency.
+		/// - `seed_amount`: The amount of the new currency to seed the pool with.
+		/// - `pool_count`: Witness of the number of pools, which the call walks through to update
+		///   the meta pools of the base pool.
+		#[pallet::call_index(22)]
+		#[pallet::weight(T::WeightInfo::add_pool_currency(*pool_count))]
+		#[transactional]
+		pub fn add_pool_currency(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+			currency_decimal: u32,
+			seed_from: T::AccountId,
+			seed_amount: Balance,
+			pool_count: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::ensure_pool_count_witness(pool_count)?;
+			ensure!(
+				T::EnsurePoolAsset::validate_pooled_currency(&[currency_id]) &&
+					Self::lp_currencies(currency_id).is_none(),
+				Error::<T>::InvalidPooledCurrency
+			);
+			ensure!(
+				currency_decimal <= POOL_TOKEN_COMMON_DECIMALS,
+				Error::<T>::InvalidCurrencyDecimal
+			);
+			let multiplier = checked_pow(
+				Balance::from(10u32),
+				(POOL_TOKEN_COMMON_DECIMALS - currency_decimal) as usize,
+			)
+			.ok_or(Error::<T>::Arithmetic)?;
+
+			let currency_ids = Pools::<T>::try_mutate_exists(
+				pool_id,
+				|optioned_pool| -> Result<_, DispatchError> {
+					let general_pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+					let pool = match general_pool {
+						Pool::Base(bp) => bp,
+						_ => return Err(Error::<T>::InvalidPoolId.into()),
+					};
+					ensure!(
+						!pool.currency_ids.contains(&currency_id),
+						Error::<T>::CurrencyAlreadyInPool
+					);
+					ensure!(
+						(pool.currency_ids.len() as u32) < MAX_POOL_CURRENCIES,
+						Error::<T>::TooManyCurrencies
+					);
+
+					pool.currency_ids.push(currency_id);
+					pool.token_multipliers.push(multiplier);
+					pool.balances.push(Zero::zero());
+
+					if !T::MultiCurrency::total_issuance(pool.lp_currency_id).is_zero() {
+						let mut amounts = vec![Balance::default(); pool.currency_ids.len() - 1];
+						amounts.push(seed_amount);
+						Self::base_pool_add_liquidity(
+							&seed_from,
+							pool_id,
+							pool,
+							&amounts,
+							Zero::zero(),
+							&seed_from,
+						)?;
+					}
+					let currency_ids = pool.currency_ids.clone();
+
+					Self::update_oracle(pool_id, general_pool);
+					Ok(currency_ids)
+				},
+			)?;
+
+			Self::update_meta_pools_base_currencies(pool_id, &currency_ids);
+			Self::deposit_event(Event::AddPoolCurrency { pool_id, currency_id });
+			Ok(())
+		}
+
+		/// Start retiring a currency of a base pool.
+		///
+		/// Only called by admin.
+		/// From now on the currency can only flow out of the pool, until its balance is dust and
+		/// it can be removed by `remove_pool_currency`. Swaps taking it out are free of fee and
+		/// price it at its target weight, which ramps down to MIN_RETIRING_WEIGHT over
+		/// RETIRING_WEIGHT_RAMP_TIME, so that arbitrage drains it from the pool.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of base pool.
+		/// - `currency_id`: The currency to retire.
+		#[pallet::call_index(23)]
+		#[pallet::weight(T::WeightInfo::retire_pool_currency())]
+		#[transactional]
+		pub fn retire_pool_currency(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			currency_id: T::CurrencyId,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let pool = match Self::pools(pool_id).ok_or(Error::<T>::InvalidPoolId)? {
+				Pool::Base(bp) => bp,
+				_ => return Err(Error::<T>::InvalidPoolId.into()),
+			};
+			ensure!(pool.currency_ids.contains(&currency_id), Error::<T>::MismatchParameter);
+			ensure!(pool.currency_ids.len() > 2, Error::<T>::TooFewCurrencies);
+			ensure!(Self::retiring_currencies(pool_id).is_none(), Error::<T>::CurrencyRetiring);
+
+			RetiringCurrencies::<T>::insert(
+				pool_id,
+				RetiringCurrency { currency_id, start_time: T::TimeProvider::now().as_secs() },
+			);
+			Self::deposit_event(Event::RetirePoolCurrency { pool_id, currency_id });
+			Ok(())
+		}
+
+		/// Remove the retiring currency from a base pool once its balance is dust.
+		///
+		/// Only called by admin.
+		/// All of the currency left in the pool account, including admin fees, goes to the admin
+		/// fee receiver.
+		/// The currencies after the removed one move down one index, in the pool, in its oracle
+		/// and among the underlying currencies of its meta pools. Callers holding currency
+		/// indices of the pool must remap them, as the `RemovePoolCurrency` event tells.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of base pool.
+		/// - `pool_count`: Witness of the number of pools, which the call walks through to update
+		///   the meta pools of the base pool.
+		#[pallet::call_index(24)]
+		#[pallet::weight(T::WeightInfo::remove_pool_currency(MAX_POOL_CURRENCIES, *pool_count))]
+		#[transactional]
+		pub fn remove_pool_currency(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			pool_count: u32,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			Self::ensure_pool_count_witness(pool_count)?;
+			let currency_id = Self::retiring_currencies(pool_id)
+				.ok_or(Error::<T>::CurrencyNotRetiring)?
+				.currency_id;
+
+			let (currency_ids, index, dust) = Pools::<T>::try_mutate_exists(
+				pool_id,
+				|optioned_pool| -> Result<_, DispatchError> {
+					let general_pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+					let pool = match general_pool {
+						Pool::Base(bp) => bp,
+						_ => return Err(Error::<T>::InvalidPoolId.into()),
+					};
+					let index = pool
+						.currency_ids
+						.iter()
+						.position(|c| *c == currency_id)
+						.ok_or(Error::<T>::MismatchParameter)?;
+					ensure!(pool.currency_ids.len() > 2, Error::<T>::TooFewCurrencies);
+					ensure!(
+						pool.balances[index]
+							.checked_mul(pool.token_multipliers[index])
+							.ok_or(Error::<T>::Arithmetic)? <=
+							MAX_RETIRED_CURRENCY_DUST,
+						Error::<T>::RetiringCurrencyNotDust
+					);
+
+					let dust = T::MultiCurrency::free_balance(currency_id, &pool.account);
+					T::MultiCurrency::transfer(
+						currency_id,
+						&pool.account,
+						&pool.admin_fee_receiver,
+						dust,
+					)?;
+
+					pool.currency_ids.remove(index);
+					pool.token_multipliers.remove(index);
+					pool.balances.remove(index);
+					let currency_ids = pool.currency_ids.clone();
+
+					Self::remove_oracle_currency(pool_id, index);
+					Self::update_oracle(pool_id, general_pool);
+					Ok((currency_ids, index, dust))
+				},
+			)?;
+
+			RetiringCurrencies::<T>::remove(pool_id);
+			AdminFeeRoutes::<T>::mutate(pool_id, |optioned_route| {
+				if let Some(route) = optioned_route {
+					if route.target_currency == Some(currency_id) {
+						route.target_currency = None;
+					}
+				}
+			});
+			Self::update_meta_pools_base_currencies(pool_id, &currency_ids);
+			Self::deposit_event(Event::RemovePoolCurrency {
+				pool_id,
+				currency_id,
+				index: index as u32,
+				dust,
+			});
+			Ok(())
+		}
+
+		/// Set the deposit caps of a pool. `None` removes the corresponding cap.
+		///
+		/// Only called by admin.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of pool.
+		/// - `max_tvl`: The max sum of the normalized balances of the pool, the base lp of a meta
+		///   pool valued at the base virtual price.
+		/// - `max_account_lp`: The max lp currency an account may hold after supplying liquidity.
+		#[pallet::call_index(25)]
+		#[pallet::weight(T::WeightInfo::set_liquidity_caps())]
+		#[transactional]
+		pub fn set_liquidity_caps(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			max_tvl: Option<Balance>,
+			max_account_lp: Option<Balance>,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::pools(pool_id).is_some(), Error::<T>::InvalidPoolId);
+
+			let caps = LiquidityCaps { max_tvl, max_account_lp };
+			if caps == LiquidityCaps::default() {
+				PoolLiquidityCaps::<T>::remove(pool_id);
+			} else {
+				PoolLiquidityCaps::<T>::insert(pool_id, caps.clone());
+			}
+
+			Self::deposit_event(Event::NewLiquidityCaps { pool_id, caps });
+			Ok(())
+		}
+
+		/// Set how the admin fee of a pool is distributed by `withdraw_admin_fee`.
+		///
+		/// Only called by admin.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of pool.
+		/// - `receivers`: Receivers of the admin fee and their shares in FEE_DENOMINATOR. The
+		///   unassigned share is donated to the liquidity providers.
+		/// - `target_currency`: The currency the admin fee is swapped into through the pool before
+		///   distribution.
+		/// - `max_slippage`: The max deviation of the swaps from the price oracle, in
+		///   FEE_DENOMINATOR. At most MAX_ADMIN_FEE_SLIPPAGE.
+		#[pallet::call_index(26)]
+		#[pallet::weight(T::WeightInfo::set_admin_fee_route(receivers.len() as u32))]
+		#[transactional]
+		pub fn set_admin_fee_route(
+			origin: OriginFor<T>,
+			pool_id: T::PoolId,
+			receivers: Vec<(T::AccountId, Number)>,
+			target_currency: Option<T::CurrencyId>,
+			max_slippage: Number,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let pool = Self::pools(pool_id).ok_or(Error::<T>::InvalidPoolId)?;
+
+			ensure!(
+				receivers.len() <= MAX_ADMIN_FEE_RECEIVERS,
+				Error::<T>::TooManyAdminFeeReceivers
+			);
+			let total_share = receivers
+				.iter()
+				.try_fold(Number::default(), |acc, (_, share)| acc.checked_add(*share))
+				.ok_or(Error::<T>::Arithmetic)?;
+			ensure!(
+				total_share <= FEE_DENOMINATOR && max_slippage <= MAX_ADMIN_FEE_SLIPPAGE,
+				Error::<T>::InvalidAdminFeeRoute
+			);
+			if let Some(currency_id) = target_currency {
+				ensure!(
+					pool.get_currency_ids().contains(&currency_id),
+					Error::<T>::MismatchParameter
+				);
+			}
+
+			let route = AdminFeeRoute { receivers, target_currency, max_slippage };
+			AdminFeeRoutes::<T>::insert(pool_id, route.clone());
+
+			Self::deposit_event(Event::NewAdminFeeRoute { pool_id, route });
+			Ok(())
+		}
+
+		/// Remove the admin fee route of a pool, so its admin fee goes to the admin fee receiver
+		/// again.
+		///
+		/// Only called by admin.
+		///
+		/// # Argument
+		///
+		/// - `pool_id`: The id of pool.
+		#[pallet::call_index(27)]
+		#[pallet::weight(T::WeightInfo::remove_admin_fee_route())]
+		#[transactional]
+		pub fn remove_admin_fee_route(origin: OriginFor<T>, pool_id: T::PoolId) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(Self::admin_fee_routes(pool_id).is_some(), Error::<T>::InvalidPoolId);
+
+			AdminFeeRoutes::<T>::remove(pool_id);
+			Self::deposit_event(Event::RemoveAdminFeeRoute { pool_id });
+			Ok(())
+		}
 	}
 }
 
@@ -1113,14 +1660,32 @@ impl<T: Config> Pallet<T> {
 		min_mint_amount: Balance,
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
+		Self::inner_add_liquidity_capping(who, pool_id, amounts, min_mint_amount, to, &[who, to])
+	}
+
+	/// Supply liquidity to the pool, checking the account cap of the pool only on the lp balance
+	/// of `capped_accounts`.
+	fn inner_add_liquidity_capping(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		amounts: &[Balance],
+		min_mint_amount: Balance,
+		to: &T::AccountId,
+		capped_accounts: &[&T::AccountId],
+	) -> Result<Balance, DispatchError> {
+		Self::ensure_not_supplying_retiring(pool_id, amounts)?;
+
 		Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> Result<Balance, DispatchError> {
 			let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
-			match pool {
+			let mint_amount = match pool {
 				Pool::Base(bp) =>
 					Self::base_pool_add_liquidity(who, pool_id, bp, amounts, min_mint_amount, to),
 				Pool::Meta(mp) =>
 					Self::meta_pool_add_liquidity(who, pool_id, mp, amounts, min_mint_amount, to),
-			}
+			}?;
+			Self::ensure_liquidity_caps(pool_id, pool, capped_accounts)?;
+			Self::update_oracle(pool_id, pool);
+			Ok(mint_amount)
 		})
 	}
 
@@ -1134,15 +1699,18 @@ impl<T: Config> Pallet<T> {
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
 		ensure!(i != j, Error::<T>::SwapSameCurrency);
+		ensure!(Self::retiring_currency_index(pool_id) != Some(i), Error::<T>::CurrencyRetiring);
 
 		Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> Result<Balance, DispatchError> {
 			let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
-			match pool {
+			let out_amount = match pool {
 				Pool::Base(bp) =>
 					Self::base_pool_swap(who, pool_id, bp, i, j, in_amount, out_min_amount, to),
 				Pool::Meta(mp) =>
 					Self::meta_pool_swap(who, pool_id, mp, i, j, in_amount, out_min_amount, to),
-			}
+			}?;
+			Self::update_oracle(pool_id, pool);
+			Ok(out_amount)
 		})
 	}
 
@@ -1189,6 +1757,7 @@ impl<T: Config> Pallet<T> {
 				fees,
 				new_total_supply: lp_total_supply - lp_amount,
 			});
+			Self::update_oracle(pool_id, global_pool);
 			Ok(())
 		})
 	}
@@ -1204,14 +1773,16 @@ impl<T: Config> Pallet<T> {
 		Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> Result<Balance, DispatchError> {
 			ensure!(!lp_amount.is_zero(), Error::<T>::InvalidTransaction);
 			let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
-			match pool {
+			let out_amount = match pool {
 				Pool::Base(bp) => Self::base_pool_remove_liquidity_one_currency(
 					pool_id, bp, who, lp_amount, index, min_amount, to,
 				),
 				Pool::Meta(mp) => Self::meta_pool_remove_liquidity_one_currency(
 					pool_id, mp, who, lp_amount, index, min_amount, to,
 				),
-			}
+			}?;
+			Self::update_oracle(pool_id, pool);
+			Ok(out_amount)
 		})
 	}
 
@@ -1241,7 +1812,9 @@ impl<T: Config> Pallet<T> {
 					max_burn_amount,
 					to,
 				),
-			}
+			}?;
+			Self::update_oracle(pool_id, pool);
+			Ok(())
 		})
 	}
 
@@ -1277,7 +1850,9 @@ impl<T: Config> Pallet<T> {
 		}
 		let mut base_lp_received: Balance = Balance::default();
 		if deposit_base {
-			base_lp_received = Self::inner_add_liquidity(who, base_pool_id, base_amounts, 0, who)?;
+			// the base pool lp currency only passes through the caller into the pool
+			base_lp_received =
+				Self::inner_add_liquidity_capping(who, base_pool_id, base_amounts, 0, who, &[])?;
 		}
 		let base_lp_prior = <T as Config>::MultiCurrency::free_balance(base_pool_lp_currency, who);
 
@@ -1433,7 +2008,9 @@ impl<T: Config> Pallet<T> {
 		let mut base_amounts = vec![Balance::default(); base_pool_len];
 		base_amounts[in_index as usize] = dx;
 
-		let base_lp_amount = Self::inner_add_liquidity(who, base_pool_id, &base_amounts, 0, who)?;
+		// the base pool lp currency only passes through the caller into the meta pool
+		let base_lp_amount =
+			Self::inner_add_liquidity_capping(who, base_pool_id, &base_amounts, 0, who, &[])?;
 
 		let mut out_amount: Balance = 0;
 		if base_pool_lp_currency_in_meta_index != (out_index as usize) {
@@ -1490,11 +2067,180 @@ impl<T: Config> Pallet<T> {
 		Ok(out_amount)
 	}
 
+	fn inner_swap_meta_pool_underlying(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+		min_dy: Balance,
+		to: &T::AccountId,
+	) -> Result<Balance, DispatchError> {
+		Pools::<T>::try_mutate_exists(pool_id, |optioned_pool| -> Result<Balance, DispatchError> {
+			let pool = optioned_pool.as_mut().ok_or(Error::<T>::InvalidPoolId)?;
+			let out_amount = match pool {
+				Pool::Meta(mp) => Self::meta_pool_swap_underlying(
+					mp,
+					pool_id,
+					who,
+					to,
+					dx,
+					min_dy,
+					in_index as usize,
+					out_index as usize,
+				),
+				_ => Err(Error::<T>::InvalidPoolId.into()),
+			}?;
+			Self::update_oracle(pool_id, pool);
+			Ok(out_amount)
+		})
+	}
+
+	/// The index of the base pool lp currency in a meta pool of `base_pool_id`.
+	fn meta_pool_base_lp_index(pool_id: T::PoolId, base_pool_id: T::PoolId) -> Option<u32> {
+		match Self::pools(pool_id)? {
+			Pool::Meta(mp) if mp.base_pool_id == base_pool_id => {
+				let base_lp_currency = Self::get_lp_currency(base_pool_id)?;
+				Self::get_currency_index(pool_id, base_lp_currency)
+			},
+			_ => None,
+		}
+	}
+
+	/// The output of a swap in a meta pool, priced with its cached base virtual price as the
+	/// swap itself would be.
+	fn calculate_meta_pool_swap_amount(
+		pool_id: T::PoolId,
+		i: usize,
+		j: usize,
+		dx: Balance,
+	) -> Option<Balance> {
+		match Self::pools(pool_id)? {
+			Pool::Meta(mp) => {
+				let base_virtual_price = Self::meta_pool_base_virtual_price(&mp)?;
+				Self::calculate_meta_swap_amount(&mp, i, j, dx, base_virtual_price)
+					.map(|(dy, _)| dy)
+			},
+			_ => None,
+		}
+	}
+
+	/// The currency count of a pool and whether it is a meta pool, as the components of its
+	/// weights.
+	fn pool_weight_components(pool_id: T::PoolId) -> (u32, u32) {
+		match Self::pools(pool_id) {
+			Some(Pool::Base(bp)) => (bp.currency_ids.len() as u32, 0),
+			Some(Pool::Meta(mp)) => (mp.info.currency_ids.len() as u32, 1),
+			None => (MAX_POOL_CURRENCIES, 1),
+		}
+	}
+
+	/// The currency counts of a meta pool and of its base pool, as the components of its
+	/// weights.
+	fn meta_pool_weight_components(pool_id: T::PoolId) -> (u32, u32) {
+		match Self::pools(pool_id) {
+			Some(Pool::Meta(mp)) =>
+				(mp.info.currency_ids.len() as u32, mp.base_currencies.len() as u32),
+			_ => (MAX_POOL_CURRENCIES, MAX_POOL_CURRENCIES),
+		}
+	}
+
+	fn retiring_currency_index(pool_id: T::PoolId) -> Option<usize> {
+		let retiring = Self::retiring_currencies(pool_id)?;
+		Self::get_currency_index(pool_id, retiring.currency_id).map(|i| i as usize)
+	}
+
+	/// Deny supplying the retiring currency of a pool. An empty pool is exempt, as its first
+	/// deposit must supply every currency.
+	fn ensure_not_supplying_retiring(pool_id: T::PoolId, amounts: &[Balance]) -> DispatchResult {
+		if let Some(index) = Self::retiring_currency_index(pool_id) {
+			let empty = Self::get_lp_currency(pool_id).map_or(true, |lp_currency_id| {
+				T::MultiCurrency::total_issuance(lp_currency_id).is_zero()
+			});
+			ensure!(
+				empty || amounts.get(index).map_or(true, Zero::is_zero),
+				Error::<T>::CurrencyRetiring
+			);
+		}
+		Ok(())
+	}
+
+	/// Check the deposit caps of a pool after liquidity was supplied, the account cap on the lp
+	/// balance of each of `accounts`.
+	///
+	/// Pool accounts are exempt from the account cap, as meta pools hold the lp currency of
+	/// their base pool.
+	fn ensure_liquidity_caps(
+		pool_id: T::PoolId,
+		global_pool: &Pool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+		accounts: &[&T::AccountId],
+	) -> DispatchResult {
+		let caps = match Self::liquidity_caps(pool_id) {
+			Some(caps) => caps,
+			None => return Ok(()),
+		};
+
+		if let Some(max_tvl) = caps.max_tvl {
+			let tvl = Self::get_pool_tvl(global_pool).ok_or(Error::<T>::Arithmetic)?;
+			ensure!(tvl <= max_tvl, Error::<T>::ExceedPoolTvlCap);
+		}
+
+		if let Some(max_account_lp) = caps.max_account_lp {
+			let lp_currency_id = global_pool.get_lp_currency();
+			for account in accounts.iter().filter(|account| !Self::is_pool_account(account)) {
+				ensure!(
+					T::MultiCurrency::free_balance(lp_currency_id, account) <= max_account_lp,
+					Error::<T>::ExceedAccountLpCap
+				);
+			}
+		}
+
+		Ok(())
+	}
+
+	fn is_pool_account(who: &T::AccountId) -> bool {
+		PalletId::try_from_sub_account::<T::PoolId>(who)
+			.map_or(false, |(pallet_id, _)| pallet_id == T::PalletId::get())
+	}
+
+	fn ensure_pool_count_witness(pool_count: u32) -> DispatchResult {
+		ensure!(
+			Self::next_pool_id() <= T::PoolId::from(pool_count),
+			Error::<T>::InvalidPoolCountWitness
+		);
+		Ok(())
+	}
+
+	fn update_meta_pools_base_currencies(base_pool_id: T::PoolId, currency_ids: &[T::CurrencyId]) {
+		let meta_pool_ids: Vec<T::PoolId> = Pools::<T>::iter()
+			.filter_map(|(pool_id, pool)| match pool {
+				Pool::Meta(mp) if mp.base_pool_id == base_pool_id => Some(pool_id),
+				_ => None,
+			})
+			.collect();
+
+		for pool_id in meta_pool_ids {
+			Pools::<T>::mutate(pool_id, |optioned_pool| {
+				if let Some(Pool::Meta(mp)) = optioned_pool {
+					mp.base_currencies = currency_ids.to_vec();
+				}
+			});
+		}
+	}
+
 	pub(crate) fn calculate_currency_amount(
 		pool_id: T::PoolId,
 		amounts: Vec<Balance>,
 		deposit: bool,
 	) -> Result<Balance, DispatchError> {
+		if deposit {
+			Self::ensure_not_supplying_retiring(pool_id, &amounts)?;
+		}
 		if let Some(pool) = Self::pools(pool_id) {
 			match pool {
 				Pool::Base(bp) => Self::calculate_base_currency_amount(&bp, amounts, deposit),
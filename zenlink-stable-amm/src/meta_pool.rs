@@ -24,8 +24,8 @@ impl<T: Config> Pallet<T> {
 		let mut d0 = Balance::default();
 		let lp_total_supply = T::MultiCurrency::total_issuance(meta_pool.info.lp_currency_id);
 		let amp = Self::get_a_precise(&meta_pool.info).ok_or(Error::<T>::Arithmetic)?;
-		let base_virtual_price =
-			Self::meta_pool_update_virtual_price(meta_pool).ok_or(Error::<T>::Arithmetic)?;
+		let base_virtual_price = Self::meta_pool_update_virtual_price(pool_id, meta_pool)
+			.ok_or(Error::<T>::Arithmetic)?;
 
 		if !lp_total_supply.is_zero() {
 			let normalized_balances = Self::meta_pool_xp(
@@ -73,13 +73,36 @@ impl<T: Config> Pallet<T> {
 		if !lp_total_supply.is_zero() {
 			let fee_per_token =
 				Self::calculate_fee_per_token(&meta_pool.info).ok_or(Error::<T>::Arithmetic)?;
+			let old_xp = Self::meta_pool_xp(
+				&meta_pool.info.balances,
+				&meta_pool.info.token_multipliers,
+				base_virtual_price,
+			)
+			.ok_or(Error::<T>::Arithmetic)?;
+			let ys = d0
+				.checked_add(d1)
+				.and_then(|n| n.checked_div(n_currencies as Balance))
+				.ok_or(Error::<T>::Arithmetic)?;
+
 			for i in 0..meta_pool.info.currency_ids.len() {
 				let ideal_balance = U256::from(d1)
 					.checked_mul(U256::from(meta_pool.info.balances[i]))
 					.and_then(|n| n.checked_div(U256::from(d0)))
 					.ok_or(Error::<T>::Arithmetic)?;
 
-				fees[i] = U256::from(fee_per_token)
+				let dynamic_fee = old_xp[i]
+					.checked_add(normalized_balances[i])
+					.and_then(|xs| {
+						Self::dynamic_fee(
+							xs,
+							ys,
+							fee_per_token,
+							meta_pool.info.offpeg_fee_multiplier,
+						)
+					})
+					.ok_or(Error::<T>::Arithmetic)?;
+
+				fees[i] = U256::from(dynamic_fee)
 					.checked_mul(Self::distance(ideal_balance, U256::from(new_balances[i])))
 					.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
 					.and_then(|n| TryInto::<Balance>::try_into(n).ok())
@@ -159,8 +182,8 @@ impl<T: Config> Pallet<T> {
 			in_amount,
 		)?;
 
-		let virtual_price =
-			Self::meta_pool_update_virtual_price(meta_pool).ok_or(Error::<T>::Arithmetic)?;
+		let virtual_price = Self::meta_pool_update_virtual_price(pool_id, meta_pool)
+			.ok_or(Error::<T>::Arithmetic)?;
 		let (dy, dy_fee) =
 			Self::calculate_meta_swap_amount(meta_pool, i, j, in_amount, virtual_price)
 				.ok_or(Error::<T>::Arithmetic)?;
@@ -223,7 +246,7 @@ impl<T: Config> Pallet<T> {
 			Error::<T>::CurrencyIndexOutRange
 		);
 
-		Self::meta_pool_update_virtual_price(meta_pool).ok_or(Error::<T>::Arithmetic)?;
+		Self::meta_pool_update_virtual_price(pool_id, meta_pool).ok_or(Error::<T>::Arithmetic)?;
 
 		let (dy, dy_fee) = Self::calculate_meta_remove_liquidity_one_currency(
 			meta_pool,
@@ -281,8 +304,8 @@ impl<T: Config> Pallet<T> {
 		ensure!(total_supply > Zero::zero(), Error::<T>::InsufficientLpReserve);
 		ensure!(amounts.len() == meta_pool.info.currency_ids.len(), Error::<T>::MismatchParameter);
 
-		let base_virtual_price =
-			Self::meta_pool_update_virtual_price(meta_pool).ok_or(Error::<T>::Arithmetic)?;
+		let base_virtual_price = Self::meta_pool_update_virtual_price(pool_id, meta_pool)
+			.ok_or(Error::<T>::Arithmetic)?;
 
 		let (mut burn_amount, fees, d1) = Self::calculate_meta_remove_liquidity_imbalance(
 			meta_pool,
@@ -345,8 +368,8 @@ impl<T: Config> Pallet<T> {
 			.checked_sub(One::one())
 			.ok_or(Error::<T>::Arithmetic)?;
 
-		let base_virtual_price =
-			Self::meta_pool_update_virtual_price(meta_pool).ok_or(Error::<T>::Arithmetic)?;
+		let base_virtual_price = Self::meta_pool_update_virtual_price(pool_id, meta_pool)
+			.ok_or(Error::<T>::Arithmetic)?;
 
 		let max_range = base_lp_currency_index + meta_pool.base_currencies.len();
 		ensure!(
@@ -400,12 +423,15 @@ impl<T: Config> Pallet<T> {
 			} else {
 				let mut base_amounts = vec![Balance::default(); meta_pool.base_currencies.len()];
 				base_amounts[currency_index_from - base_lp_currency_index] = dx;
-				dx = Self::inner_add_liquidity(
+				// the base pool lp currency is minted to the pool account, which is exempt from
+				// the account cap of the base pool
+				dx = Self::inner_add_liquidity_capping(
 					&meta_pool.info.account,
 					meta_pool.base_pool_id,
 					&base_amounts,
 					0,
 					&meta_pool.info.account,
+					&[],
 				)?;
 
 				x = U256::from(dx)
@@ -431,8 +457,20 @@ impl<T: Config> Pallet<T> {
 					.and_then(|n| TryInto::<Balance>::try_into(n).ok())
 					.ok_or(Error::<T>::Arithmetic)?;
 			}
+			let fee = xp[meta_index_from]
+				.checked_add(x)
+				.zip(xp[meta_index_to].checked_add(y))
+				.and_then(|(xs, ys)| {
+					Self::dynamic_fee(
+						xs,
+						ys,
+						meta_pool.info.fee,
+						meta_pool.info.offpeg_fee_multiplier,
+					)
+				})
+				.ok_or(Error::<T>::Arithmetic)?;
 			let dy_fee = U256::from(dy)
-				.checked_mul(U256::from(meta_pool.info.fee))
+				.checked_mul(U256::from(fee))
 				.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
 				.and_then(|n| TryInto::<Balance>::try_into(n).ok())
 				.ok_or(Error::<T>::Arithmetic)?;
@@ -508,6 +546,7 @@ impl<T: Config> Pallet<T> {
 	}
 
 	pub(crate) fn meta_pool_update_virtual_price(
+		pool_id: T::PoolId,
 		meta_pool: &mut MetaPool<
 			T::PoolId,
 			T::CurrencyId,
@@ -515,20 +554,52 @@ impl<T: Config> Pallet<T> {
 			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
 		>,
 	) -> Option<Balance> {
-		let now = T::TimeProvider::now().as_secs();
-		if now > (meta_pool.base_cache_last_updated + BASE_CACHE_EXPIRE_TIME) {
-			let base_pool = Self::pools(meta_pool.base_pool_id)?;
-			let base_virtual_price = Self::get_pool_virtual_price(&base_pool)?;
-
-			meta_pool.base_virtual_price = base_virtual_price;
-			meta_pool.base_cache_last_updated = now;
-
-			Some(base_virtual_price)
+		if Self::meta_pool_base_cache_expired(meta_pool) {
+			Self::meta_pool_refresh_virtual_price(pool_id, meta_pool)
 		} else {
 			Some(meta_pool.base_virtual_price)
 		}
 	}
 
+	pub(crate) fn meta_pool_refresh_virtual_price(
+		pool_id: T::PoolId,
+		meta_pool: &mut MetaPool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+	) -> Option<Balance> {
+		let base_pool = Self::pools(meta_pool.base_pool_id)?;
+		let base_virtual_price = Self::get_pool_virtual_price(&base_pool)?;
+
+		meta_pool.base_virtual_price = base_virtual_price;
+		meta_pool.base_cache_last_updated = T::TimeProvider::now().as_secs();
+		meta_pool.base_cache_last_updated_block =
+			frame_system::Pallet::<T>::block_number().saturated_into::<u64>();
+
+		Self::deposit_event(Event::UpdateBaseVirtualPrice { pool_id, base_virtual_price });
+		Some(base_virtual_price)
+	}
+
+	pub(crate) fn meta_pool_base_cache_expired(
+		meta_pool: &MetaPool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+	) -> bool {
+		match meta_pool.base_cache_policy {
+			BaseCachePolicy::Time(expire_time) =>
+				T::TimeProvider::now().as_secs() >
+					meta_pool.base_cache_last_updated.saturating_add(expire_time),
+			BaseCachePolicy::Blocks(expire_blocks) =>
+				frame_system::Pallet::<T>::block_number().saturated_into::<u64>() >
+					meta_pool.base_cache_last_updated_block.saturating_add(expire_blocks),
+		}
+	}
+
 	pub(crate) fn calculate_meta_virtual_price(
 		meta_pool: &MetaPool<
 			T::PoolId,
@@ -555,7 +626,7 @@ impl<T: Config> Pallet<T> {
 		None
 	}
 
-	fn meta_pool_xp(
+	pub(crate) fn meta_pool_xp(
 		balances: &[Balance],
 		rates: &[Balance],
 		base_virtual_price: Balance,
@@ -570,7 +641,7 @@ impl<T: Config> Pallet<T> {
 		Some(xp)
 	}
 
-	fn meta_pool_base_virtual_price(
+	pub(crate) fn meta_pool_base_virtual_price(
 		meta_pool: &MetaPool<
 			T::PoolId,
 			T::CurrencyId,
@@ -578,8 +649,7 @@ impl<T: Config> Pallet<T> {
 			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
 		>,
 	) -> Option<Balance> {
-		let now = T::TimeProvider::now().as_secs();
-		if now > (meta_pool.base_cache_last_updated + BASE_CACHE_EXPIRE_TIME) {
+		if Self::meta_pool_base_cache_expired(meta_pool) {
 			let pool = Self::pools(meta_pool.base_pool_id)?;
 			return match pool {
 				Pool::Base(bp) => Self::calculate_base_virtual_price(&bp),
@@ -625,23 +695,32 @@ impl<T: Config> Pallet<T> {
 
 		let mut new_y = Self::get_yd(&meta_pool.info, a_precise, index as u32, &xp, d1)?;
 		let mut xp_reduced = vec![Zero::zero(); xp.len()];
+		let ys = d0.checked_add(d1)?.checked_div(xp.len() as Balance)?;
 
 		for (i, xpi) in xp.iter().enumerate() {
 			let u256_xpi = U256::from(*xpi);
-			let dx_expected: U256 = if i == index {
-				u256_xpi
-					.checked_mul(U256::from(d1))?
-					.checked_div(U256::from(d0))?
-					.checked_sub(U256::from(new_y))?
+			let (dx_expected, xs) = if i == index {
+				(
+					u256_xpi
+						.checked_mul(U256::from(d1))?
+						.checked_div(U256::from(d0))?
+						.checked_sub(U256::from(new_y))?,
+					xpi.checked_add(new_y)?,
+				)
 			} else {
-				u256_xpi
-					.checked_mul(U256::from(d1))?
-					.checked_div(U256::from(d0))
-					.and_then(|n| u256_xpi.checked_sub(n))?
+				(
+					u256_xpi
+						.checked_mul(U256::from(d1))?
+						.checked_div(U256::from(d0))
+						.and_then(|n| u256_xpi.checked_sub(n))?,
+					xpi.checked_mul(2)?,
+				)
 			};
+			let fee =
+				Self::dynamic_fee(xs, ys, fee_per_token, meta_pool.info.offpeg_fee_multiplier)?;
 
 			xp_reduced[i] = dx_expected
-				.checked_mul(U256::from(fee_per_token))?
+				.checked_mul(U256::from(fee))?
 				.checked_div(U256::from(FEE_DENOMINATOR))
 				.and_then(|n| u256_xpi.checked_sub(n))
 				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
@@ -751,7 +830,7 @@ impl<T: Config> Pallet<T> {
 		base_virtual_price: Balance,
 	) -> Option<(Balance, Vec<Balance>, Balance)> {
 		let currencies_len = meta_pool.info.currency_ids.len();
-		let fee_per_token = U256::from(Self::calculate_fee_per_token(&meta_pool.info)?);
+		let fee_per_token = Self::calculate_fee_per_token(&meta_pool.info)?;
 		let amp = Self::get_a_precise(&meta_pool.info)?;
 		let mut fees = vec![Balance::default(); currencies_len];
 		let mut new_balances = meta_pool.info.balances.clone();
@@ -774,11 +853,18 @@ impl<T: Config> Pallet<T> {
 			base_virtual_price,
 		)?;
 		let d1 = U256::from(Self::get_d(&new_xp, amp)?);
+		let ys = d0
+			.checked_add(d1)?
+			.checked_div(U256::from(currencies_len as u64))
+			.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 
 		for (i, balance) in meta_pool.info.balances.iter_mut().enumerate() {
 			let ideal_balance = d1.checked_mul(U256::from(*balance))?.checked_div(d0)?;
 			let diff = Self::distance(U256::from(new_balances[i]), ideal_balance);
-			fees[i] = fee_per_token
+			let xs = xp[i].checked_add(new_xp[i])?;
+			let fee =
+				Self::dynamic_fee(xs, ys, fee_per_token, meta_pool.info.offpeg_fee_multiplier)?;
+			fees[i] = U256::from(fee)
 				.checked_mul(diff)?
 				.checked_div(fee_denominator)
 				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
@@ -856,8 +942,15 @@ impl<T: Config> Pallet<T> {
 				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 		}
 
+		let dynamic_fee = Self::dynamic_fee(
+			xp[i].checked_add(x)?,
+			xp[j].checked_add(y)?,
+			meta_pool.info.fee,
+			meta_pool.info.offpeg_fee_multiplier,
+		)?;
+
 		let fee = U256::from(dy)
-			.checked_mul(U256::from(meta_pool.info.fee))?
+			.checked_mul(U256::from(dynamic_fee))?
 			.checked_div(U256::from(FEE_DENOMINATOR))
 			.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
 
@@ -904,6 +997,12 @@ impl<T: Config> Pallet<T> {
 				currency_index_to < max_range,
 			Error::<T>::MismatchParameter
 		);
+		if let Some(base_index_from) = currency_index_from.checked_sub(base_lp_currency_index) {
+			ensure!(
+				Self::retiring_currency_index(meta_pool.base_pool_id) != Some(base_index_from),
+				Error::<T>::CurrencyRetiring
+			);
+		}
 
 		let xp = Self::meta_pool_xp(
 			&meta_pool.info.balances,
@@ -974,8 +1073,16 @@ impl<T: Config> Pallet<T> {
 			.and_then(|n| n.checked_sub(One::one()))
 			.ok_or(Error::<T>::Arithmetic)?;
 
+		let fee = xp[currency_index_from]
+			.checked_add(x)
+			.zip(xp[meta_index_to].checked_add(y))
+			.and_then(|(xs, ys)| {
+				Self::dynamic_fee(xs, ys, meta_pool.info.fee, meta_pool.info.offpeg_fee_multiplier)
+			})
+			.ok_or(Error::<T>::Arithmetic)?;
+
 		let dy_fee = U256::from(dy)
-			.checked_mul(U256::from(meta_pool.info.fee))
+			.checked_mul(U256::from(fee))
 			.and_then(|n| n.checked_div(U256::from(FEE_DENOMINATOR)))
 			.and_then(|n| TryInto::<Balance>::try_into(n).ok())
 			.ok_or(Error::<T>::Arithmetic)?;
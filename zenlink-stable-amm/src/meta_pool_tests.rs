@@ -1,7 +1,7 @@
 // Copyright 2021-2022 Zenlink.
 // Licensed under Apache 2.0.
 
-use frame_support::{assert_noop, assert_ok};
+use frame_support::{assert_noop, assert_ok, storage::unhashed, traits::OnRuntimeUpgrade};
 use frame_system::RawOrigin;
 use sp_runtime::DispatchError::BadOrigin;
 
@@ -9,7 +9,11 @@ use super::{
 	mock::{CurrencyId::*, *},
 	*,
 };
-use crate::{base_pool_tests::setup_test_base_pool, traits::StableAmmApi};
+use crate::{
+	base_pool_tests::{old_base_pool, setup_test_base_pool},
+	migrations::v1::{MigrateToV1, OldMetaPool, OldPool},
+	traits::StableAmmApi,
+};
 
 const INITIAL_A_VALUE: Number = 50;
 const SWAP_FEE: Number = 1e7 as Number;
@@ -313,6 +317,8 @@ fn create_meta_pool_should_work() {
 				base_pool_id,
 				base_virtual_price,
 				base_cache_last_updated: now,
+				base_cache_last_updated_block: System::block_number(),
+				base_cache_policy: BaseCachePolicy::Time(BASE_CACHE_EXPIRE_TIME),
 				base_currencies: vec![Token(TOKEN1_SYMBOL), Token(TOKEN2_SYMBOL),],
 				info: BasePool {
 					currency_ids: vec![
@@ -335,6 +341,7 @@ fn create_meta_pool_should_work() {
 					balances: vec![Zero::zero(); 4],
 					fee: SWAP_FEE,
 					admin_fee: ADMIN_FEE,
+					offpeg_fee_multiplier: 0,
 					initial_a: INITIAL_A_VALUE * (A_PRECISION as Balance),
 					future_a: INITIAL_A_VALUE * (A_PRECISION as Balance),
 					initial_a_time: 0,
@@ -4409,3 +4416,453 @@ fn meta_pool_swap_underlying_impact_on_base_pool_price_should_work() {
 		assert_eq!(target_currency_amount_after - target_currency_amount_before, 9993224247822464);
 	})
 }
+
+#[test]
+fn set_base_cache_policy_should_work() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+
+		assert_noop!(
+			StableAmm::set_base_cache_policy(
+				RawOrigin::Signed(BOB).into(),
+				meta_pool_id,
+				BaseCachePolicy::Blocks(2)
+			),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::set_base_cache_policy(
+				RawOrigin::Root.into(),
+				base_pool_id,
+				BaseCachePolicy::Blocks(2)
+			),
+			Error::<Test>::InvalidPoolId
+		);
+
+		assert_ok!(StableAmm::set_base_cache_policy(
+			RawOrigin::Root.into(),
+			meta_pool_id,
+			BaseCachePolicy::Blocks(2)
+		));
+		match StableAmm::pools(meta_pool_id) {
+			Some(Pool::Meta(mp)) => assert_eq!(mp.base_cache_policy, BaseCachePolicy::Blocks(2)),
+			_ => unreachable!(),
+		}
+	})
+}
+
+#[test]
+fn base_virtual_price_cache_should_expire_by_time() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+		let created_at = Timestamp::now() / 1000;
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(CHARLIE).into(),
+			base_pool_id,
+			0,
+			1,
+			1e18 as Balance,
+			0,
+			CHARLIE,
+			u64::MAX
+		));
+		assert!(StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		set_block_timestamp(created_at + BASE_CACHE_EXPIRE_TIME);
+		assert!(StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		set_block_timestamp(created_at + BASE_CACHE_EXPIRE_TIME + 1);
+		assert!(!StableAmm::is_base_virtual_price_stale(meta_pool_id));
+	})
+}
+
+#[test]
+fn base_virtual_price_cache_should_expire_by_blocks() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+		let created_at = System::block_number();
+
+		assert_ok!(StableAmm::set_base_cache_policy(
+			RawOrigin::Root.into(),
+			meta_pool_id,
+			BaseCachePolicy::Blocks(2)
+		));
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(CHARLIE).into(),
+			base_pool_id,
+			0,
+			1,
+			1e18 as Balance,
+			0,
+			CHARLIE,
+			u64::MAX
+		));
+		assert!(StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		System::set_block_number(created_at + 2);
+		assert!(StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		System::set_block_number(created_at + 3);
+		assert!(!StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(CHARLIE).into(),
+			meta_pool_id,
+			0,
+			1,
+			1e17 as Balance,
+			0,
+			CHARLIE,
+			u64::MAX
+		));
+		match StableAmm::pools(meta_pool_id) {
+			Some(Pool::Meta(mp)) => {
+				assert_eq!(mp.base_virtual_price, StableAmm::get_virtual_price(base_pool_id));
+				assert_eq!(mp.base_cache_last_updated_block, created_at + 3);
+			},
+			_ => unreachable!(),
+		}
+	})
+}
+
+#[test]
+fn refresh_base_virtual_price_should_work() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(CHARLIE).into(),
+			base_pool_id,
+			0,
+			1,
+			1e18 as Balance,
+			0,
+			CHARLIE,
+			u64::MAX
+		));
+		assert!(StableAmm::is_base_virtual_price_stale(meta_pool_id));
+
+		assert_noop!(
+			StableAmm::refresh_base_virtual_price(RawOrigin::Signed(BOB).into(), meta_pool_id),
+			BadOrigin
+		);
+		assert_noop!(
+			StableAmm::refresh_base_virtual_price(RawOrigin::Root.into(), base_pool_id),
+			Error::<Test>::InvalidPoolId
+		);
+
+		assert_ok!(StableAmm::refresh_base_virtual_price(RawOrigin::Root.into(), meta_pool_id));
+		assert!(!StableAmm::is_base_virtual_price_stale(meta_pool_id));
+		match StableAmm::pools(meta_pool_id) {
+			Some(Pool::Meta(mp)) =>
+				assert_eq!(mp.base_virtual_price, StableAmm::get_virtual_price(base_pool_id)),
+			_ => unreachable!(),
+		}
+	})
+}
+
+#[test]
+fn add_pool_currency_should_update_meta_pool_base_currencies() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+		assert_ok!(<Test as Config>::MultiCurrency::deposit(
+			Token(TOKEN4_SYMBOL + 1),
+			&ALICE,
+			3e8 as Balance
+		));
+
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				base_pool_id,
+				Token(TOKEN4_SYMBOL + 1),
+				TOKEN4_DECIMAL,
+				ALICE,
+				3e8 as Balance,
+				StableAmm::next_pool_id() - 1
+			),
+			Error::<Test>::InvalidPoolCountWitness
+		);
+		assert_noop!(
+			StableAmm::add_pool_currency(
+				RawOrigin::Root.into(),
+				meta_pool_id,
+				Token(TOKEN4_SYMBOL + 1),
+				TOKEN4_DECIMAL,
+				ALICE,
+				3e8 as Balance,
+				StableAmm::next_pool_id()
+			),
+			Error::<Test>::InvalidPoolId
+		);
+
+		assert_ok!(StableAmm::add_pool_currency(
+			RawOrigin::Root.into(),
+			base_pool_id,
+			Token(TOKEN4_SYMBOL + 1),
+			TOKEN4_DECIMAL,
+			ALICE,
+			3e8 as Balance,
+			StableAmm::next_pool_id()
+		));
+		match StableAmm::pools(meta_pool_id) {
+			Some(Pool::Meta(mp)) => assert_eq!(
+				mp.base_currencies,
+				vec![
+					Token(TOKEN1_SYMBOL),
+					Token(TOKEN3_SYMBOL),
+					Token(TOKEN4_SYMBOL),
+					Token(TOKEN4_SYMBOL + 1)
+				]
+			),
+			_ => unreachable!(),
+		}
+	})
+}
+
+#[test]
+fn liquidity_caps_should_apply_to_meta_pool() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+
+		// the meta pool account holds base pool lp currency beyond the account cap
+		assert_ok!(StableAmm::set_liquidity_caps(
+			RawOrigin::Root.into(),
+			base_pool_id,
+			None,
+			Some(1)
+		));
+		assert_ok!(StableAmm::swap_meta_pool_underlying(
+			RawOrigin::Signed(BOB).into(),
+			meta_pool_id,
+			1,
+			0,
+			1e17 as Balance,
+			0,
+			BOB,
+			u64::MAX,
+		));
+		// the base pool lp currency minted on the way only passes through the caller
+		assert!(get_user_balance(StableAmm::get_lp_currency(base_pool_id).unwrap(), &BOB) > 1);
+		assert_ok!(StableAmm::add_pool_and_base_pool_liquidity(
+			RawOrigin::Signed(BOB).into(),
+			meta_pool_id,
+			base_pool_id,
+			vec![0, 0],
+			vec![1e17 as Balance, 0, 0],
+			0,
+			BOB,
+			u64::MAX
+		));
+		assert_ok!(StableAmm::swap_pool_from_base(
+			RawOrigin::Signed(BOB).into(),
+			meta_pool_id,
+			base_pool_id,
+			0,
+			0,
+			1e17 as Balance,
+			0,
+			BOB,
+			u64::MAX
+		));
+
+		// the base pool lp currency of the meta pool counts at the cached base virtual price
+		Pools::<Test>::mutate(meta_pool_id, |pool| {
+			if let Some(Pool::Meta(mp)) = pool {
+				mp.base_virtual_price = 2 * BASE_VIRTUAL_PRICE_PRECISION;
+			}
+		});
+		let meta_pool = StableAmm::pools(meta_pool_id).unwrap().get_pool_info();
+		let tvl = meta_pool.balances[0] * meta_pool.token_multipliers[0] +
+			meta_pool.balances[1] * meta_pool.token_multipliers[1] * 2;
+		assert_ok!(StableAmm::set_liquidity_caps(
+			RawOrigin::Root.into(),
+			meta_pool_id,
+			Some(tvl),
+			None
+		));
+		assert_eq!(StableAmm::get_remaining_tvl_capacity(meta_pool_id), Some(0));
+
+		assert_noop!(
+			StableAmm::add_liquidity(
+				RawOrigin::Signed(BOB).into(),
+				meta_pool_id,
+				vec![1e17 as Balance, 0],
+				0,
+				BOB,
+				u64::MAX,
+			),
+			Error::<Test>::ExceedPoolTvlCap
+		);
+		assert_noop!(
+			StableAmm::add_pool_and_base_pool_liquidity(
+				RawOrigin::Signed(CHARLIE).into(),
+				meta_pool_id,
+				base_pool_id,
+				vec![1e17 as Balance, 0],
+				vec![0, 0, 0],
+				0,
+				CHARLIE,
+				u64::MAX
+			),
+			Error::<Test>::ExceedPoolTvlCap
+		);
+	})
+}
+
+#[test]
+fn stable_amm_api_meta_underlying_should_work() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+
+		let quote = <StableAmm as StableAmmApi<_, _, _, _>>::calculate_meta_swap_underlying(
+			meta_pool_id,
+			0,
+			1,
+			1e17 as Balance,
+		);
+		assert_eq!(quote, Some(99682616104034773));
+		assert_eq!(
+			<StableAmm as StableAmmApi<_, _, _, _>>::swap_meta_underlying(
+				&BOB,
+				meta_pool_id,
+				0,
+				1,
+				1e17 as Balance,
+				99682616104034773,
+				&BOB,
+			),
+			Ok(99682616104034773)
+		);
+
+		let quote = <StableAmm as StableAmmApi<_, _, _, _>>::calculate_swap_pool_to_base(
+			meta_pool_id,
+			base_pool_id,
+			0,
+			0,
+			1e17 as Balance,
+		)
+		.unwrap();
+		assert_eq!(
+			<StableAmm as StableAmmApi<_, _, _, _>>::swap_pool_to_base(
+				&BOB,
+				meta_pool_id,
+				base_pool_id,
+				0,
+				0,
+				1e17 as Balance,
+				quote,
+				&BOB,
+			),
+			Ok(quote)
+		);
+
+		let quote = <StableAmm as StableAmmApi<_, _, _, _>>::calculate_swap_pool_from_base(
+			meta_pool_id,
+			base_pool_id,
+			0,
+			0,
+			1e17 as Balance,
+		)
+		.unwrap();
+		let out_amount = <StableAmm as StableAmmApi<_, _, _, _>>::swap_pool_from_base(
+			&BOB,
+			meta_pool_id,
+			base_pool_id,
+			0,
+			0,
+			1e17 as Balance,
+			0,
+			&BOB,
+		)
+		.unwrap();
+		// the deposit fee left in the base pool may only raise its virtual price
+		assert!(out_amount >= quote && out_amount - quote <= quote / 1_000_000);
+
+		// the base pool lp currency is not an underlying currency of the meta pool
+		assert_eq!(
+			<StableAmm as StableAmmApi<_, _, _, _>>::calculate_swap_pool_to_base(
+				meta_pool_id,
+				base_pool_id,
+				1,
+				0,
+				1e17 as Balance,
+			),
+			None
+		);
+		assert_eq!(
+			<StableAmm as StableAmmApi<_, _, _, _>>::calculate_swap_pool_from_base(
+				meta_pool_id,
+				meta_pool_id,
+				0,
+				0,
+				1e17 as Balance,
+			),
+			None
+		);
+	})
+}
+
+#[test]
+fn get_pool_info_should_work() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+
+		let info = StableAmm::get_pool_info(meta_pool_id).unwrap();
+		let meta_pool = StableAmm::pools(meta_pool_id).unwrap().get_pool_info();
+		assert_eq!(info.pool_id, meta_pool_id);
+		assert_eq!(info.base_pool_id, Some(base_pool_id));
+		assert_eq!(info.currency_ids, meta_pool.currency_ids);
+		assert_eq!(info.balances, meta_pool.balances);
+		assert_eq!(info.admin_balances, StableAmm::get_admin_balances(meta_pool_id));
+		assert_eq!(info.lp_total_supply, get_user_balance(meta_pool.lp_currency_id, &ALICE));
+		assert_eq!(info.a_precise, INITIAL_A_VALUE * A_PRECISION);
+		assert_eq!(info.virtual_price, StableAmm::get_virtual_price(meta_pool_id));
+		assert_eq!(StableAmm::get_pool_info(base_pool_id).unwrap().base_pool_id, None);
+		assert_eq!(StableAmm::get_pool_info(meta_pool_id + 1), None);
+
+		let pools = StableAmm::get_all_pools(0, 10);
+		assert_eq!(pools.iter().map(|p| p.pool_id).collect::<Vec<_>>(), vec![0, 1]);
+		assert_eq!(StableAmm::get_all_pools(0, 1).len(), 1);
+		assert_eq!(StableAmm::get_all_pools(1, 10), vec![info]);
+		assert_eq!(StableAmm::get_all_pools(2, 10), vec![]);
+	})
+}
+
+#[test]
+fn migrate_to_v1_should_translate_meta_pools() {
+	new_test_ext().execute_with(|| {
+		let (base_pool_id, meta_pool_id) = setup_test_meta_pool();
+		let base_pool = StableAmm::pools(base_pool_id).unwrap();
+		let pool = match StableAmm::pools(meta_pool_id).unwrap() {
+			Pool::Meta(mp) => mp,
+			Pool::Base(_) => unreachable!(),
+		};
+		let old_base: OldPool<PoolId, _, _, _> =
+			OldPool::Base(old_base_pool(&base_pool.clone().info()));
+		let old_meta = OldPool::Meta(OldMetaPool {
+			base_pool_id: pool.base_pool_id,
+			base_virtual_price: pool.base_virtual_price,
+			base_cache_last_updated: pool.base_cache_last_updated,
+			base_currencies: pool.base_currencies.clone(),
+			info: old_base_pool(&pool.info),
+		});
+		unhashed::put(&Pools::<Test>::hashed_key_for(base_pool_id), &old_base);
+		unhashed::put(&Pools::<Test>::hashed_key_for(meta_pool_id), &old_meta);
+		StorageVersion::new(0).put::<StableAmm>();
+		System::set_block_number(100);
+
+		MigrateToV1::<Test>::on_runtime_upgrade();
+		assert_eq!(StableAmm::pools(base_pool_id), Some(base_pool));
+		assert_eq!(
+			StableAmm::pools(meta_pool_id),
+			Some(Pool::Meta(MetaPool {
+				base_cache_last_updated_block: 100,
+				base_cache_policy: BaseCachePolicy::Time(BASE_CACHE_EXPIRE_TIME),
+				..pool
+			}))
+		);
+		assert_eq!(StableAmm::on_chain_storage_version(), 1);
+	})
+}
@@ -0,0 +1,132 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+use frame_support::traits::OnRuntimeUpgrade;
+use sp_std::marker::PhantomData;
+
+pub mod v1 {
+	use super::*;
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub struct OldBasePool<CurrencyId, AccountId, BoundString> {
+		pub currency_ids: Vec<CurrencyId>,
+		pub lp_currency_id: CurrencyId,
+		pub token_multipliers: Vec<Balance>,
+		pub balances: Vec<Balance>,
+		pub fee: Number,
+		pub admin_fee: Number,
+		pub initial_a: Number,
+		pub future_a: Number,
+		pub initial_a_time: Number,
+		pub future_a_time: Number,
+		pub account: AccountId,
+		pub admin_fee_receiver: AccountId,
+		pub lp_currency_symbol: BoundString,
+		pub lp_currency_decimal: u8,
+	}
+
+	impl<CurrencyId, AccountId, BoundString> OldBasePool<CurrencyId, AccountId, BoundString> {
+		fn upgrade(self) -> BasePool<CurrencyId, AccountId, BoundString> {
+			BasePool {
+				currency_ids: self.currency_ids,
+				lp_currency_id: self.lp_currency_id,
+				token_multipliers: self.token_multipliers,
+				balances: self.balances,
+				fee: self.fee,
+				admin_fee: self.admin_fee,
+				offpeg_fee_multiplier: Zero::zero(),
+				initial_a: self.initial_a,
+				future_a: self.future_a,
+				initial_a_time: self.initial_a_time,
+				future_a_time: self.future_a_time,
+				account: self.account,
+				admin_fee_receiver: self.admin_fee_receiver,
+				lp_currency_symbol: self.lp_currency_symbol,
+				lp_currency_decimal: self.lp_currency_decimal,
+			}
+		}
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub struct OldMetaPool<PoolId, CurrencyId, AccountId, BoundString> {
+		pub base_pool_id: PoolId,
+		pub base_virtual_price: Balance,
+		pub base_cache_last_updated: u64,
+		pub base_currencies: Vec<CurrencyId>,
+
+		pub info: OldBasePool<CurrencyId, AccountId, BoundString>,
+	}
+
+	#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug)]
+	pub enum OldPool<PoolId, CurrencyId, AccountId, BoundString> {
+		Base(OldBasePool<CurrencyId, AccountId, BoundString>),
+		Meta(OldMetaPool<PoolId, CurrencyId, AccountId, BoundString>),
+	}
+
+	/// Translate the pools stored before the offpeg fee multiplier and the base cache policy
+	/// were added. The dynamic fee starts disabled and meta pools keep the time based cache.
+	pub struct MigrateToV1<T>(PhantomData<T>);
+
+	impl<T: Config> OnRuntimeUpgrade for MigrateToV1<T> {
+		fn on_runtime_upgrade() -> Weight {
+			if Pallet::<T>::on_chain_storage_version() != 0 {
+				return T::DbWeight::get().reads(1)
+			}
+
+			let now: u64 = frame_system::Pallet::<T>::block_number().saturated_into();
+			let mut count = 0u64;
+			Pools::<T>::translate::<
+				OldPool<
+					T::PoolId,
+					T::CurrencyId,
+					T::AccountId,
+					BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+				>,
+				_,
+			>(|_, old_pool| {
+				count = count.saturating_add(1);
+				Some(match old_pool {
+					OldPool::Base(bp) => Pool::Base(bp.upgrade()),
+					OldPool::Meta(mp) => Pool::Meta(MetaPool {
+						base_pool_id: mp.base_pool_id,
+						base_virtual_price: mp.base_virtual_price,
+						base_cache_last_updated: mp.base_cache_last_updated,
+						base_cache_last_updated_block: now,
+						base_cache_policy: BaseCachePolicy::default(),
+						base_currencies: mp.base_currencies,
+						info: mp.info.upgrade(),
+					}),
+				})
+			});
+			StorageVersion::new(1).put::<Pallet<T>>();
+
+			T::DbWeight::get().reads_writes(count.saturating_add(2), count.saturating_add(1))
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn pre_upgrade() -> Result<Vec<u8>, &'static str> {
+			let pools = Pools::<T>::iter_keys().count() as u32;
+			Ok(pools.encode())
+		}
+
+		#[cfg(feature = "try-runtime")]
+		fn post_upgrade(state: Vec<u8>) -> Result<(), &'static str> {
+			let pools = u32::decode(&mut &state[..]).map_err(|_| "invalid pre upgrade state")?;
+			ensure!(Pallet::<T>::on_chain_storage_version() == 1, "storage version not updated");
+			ensure!(Pools::<T>::iter_values().count() as u32 == pools, "pools not translated");
+			ensure!(
+				Pools::<T>::iter_values().all(|pool| pool.get_offpeg_fee_multiplier().is_zero()),
+				"offpeg fee enabled"
+			);
+			ensure!(
+				Pools::<T>::iter_values().all(|pool| match pool {
+					Pool::Base(_) => true,
+					Pool::Meta(mp) => mp.base_cache_policy == BaseCachePolicy::default(),
+				}),
+				"base cache policy not set"
+			);
+			Ok(())
+		}
+	}
+}
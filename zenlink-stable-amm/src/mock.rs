@@ -40,11 +40,13 @@ parameter_types! {
 	pub const MaxLocks:u32 = 50;
 	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
 	pub const PoolCurrencySymbolLimit: u32 = 50;
+	pub const OracleEmaPeriod: u64 = 600;
+	pub static TokenExistentialDeposit: u128 = 0;
 }
 
 parameter_type_with_key! {
 	pub ExistentialDeposits: |_currency_id: CurrencyId| -> u128 {
-		0
+		TokenExistentialDeposit::get()
 	};
 }
 
@@ -198,6 +200,7 @@ impl Config for Test {
 	type LpGenerate = PoolLpGenerate;
 	type TimeProvider = Timestamp;
 	type PoolCurrencySymbolLimit = PoolCurrencySymbolLimit;
+	type OracleEmaPeriod = OracleEmaPeriod;
 	type PalletId = StableAmmPalletId;
 	type WeightInfo = ();
 }
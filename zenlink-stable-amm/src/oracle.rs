@@ -0,0 +1,195 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+
+impl<T: Config> Pallet<T> {
+	/// Fold the last observation of the pool into its moving averages, then record the current
+	/// spot prices and virtual price as the new observation.
+	///
+	/// The averages only move when time passes, so a price pushed within a single block has no
+	/// effect on them until it has held for a while.
+	pub(crate) fn update_oracle(
+		pool_id: T::PoolId,
+		pool: &Pool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+	) {
+		let now = T::TimeProvider::now().as_secs();
+		let prices = Self::calculate_spot_prices(pool);
+		let virtual_price = Self::get_pool_virtual_price(pool);
+
+		PoolOracles::<T>::mutate(pool_id, |optioned_oracle| {
+			let oracle = optioned_oracle.get_or_insert_with(Default::default);
+			if let Some(averaged) = Self::oracle_at(oracle, now) {
+				*oracle = averaged;
+			}
+
+			if let Some(prices) = prices {
+				if oracle.price_emas.len() != prices.len() {
+					oracle.price_emas = prices.clone();
+				}
+				oracle.last_prices = prices;
+			}
+
+			if let Some(virtual_price) = virtual_price {
+				if oracle.virtual_price_ema.is_zero() {
+					oracle.virtual_price_ema = virtual_price;
+				}
+				oracle.last_virtual_price = virtual_price;
+
+				if now >= oracle.window_started.saturating_add(T::OracleEmaPeriod::get()) {
+					oracle.prev_min_virtual_price = oracle.min_virtual_price;
+					oracle.min_virtual_price = virtual_price;
+					oracle.window_started = now;
+				} else if oracle.min_virtual_price.is_zero() ||
+					virtual_price < oracle.min_virtual_price
+				{
+					oracle.min_virtual_price = virtual_price;
+				}
+			}
+
+			oracle.last_updated = now;
+		});
+	}
+
+	/// Drop currency `index` of the pool from its oracle, rebasing the prices on the new first
+	/// currency when the first one is dropped.
+	pub(crate) fn remove_oracle_currency(pool_id: T::PoolId, index: usize) {
+		PoolOracles::<T>::mutate(pool_id, |optioned_oracle| {
+			if let Some(oracle) = optioned_oracle {
+				for prices in [&mut oracle.last_prices, &mut oracle.price_emas] {
+					if index >= prices.len() {
+						continue
+					}
+					prices.remove(index);
+					if index == 0 {
+						let first_price = prices.first().copied().unwrap_or_default();
+						for price in prices.iter_mut() {
+							*price = U256::from(*price)
+								.checked_mul(U256::from(ORACLE_PRICE_PRECISION))
+								.and_then(|n| n.checked_div(U256::from(first_price)))
+								.and_then(|n| TryInto::<Balance>::try_into(n).ok())
+								.unwrap_or_default();
+						}
+					}
+				}
+			}
+		});
+	}
+
+	/// The oracle of the pool with its moving averages brought up to the current time.
+	pub(crate) fn current_oracle(pool_id: T::PoolId) -> Option<PoolOracle> {
+		let oracle = Self::pool_oracles(pool_id)?;
+		Self::oracle_at(&oracle, T::TimeProvider::now().as_secs())
+	}
+
+	/// The lowest virtual price observed over at least the last `OracleEmaPeriod`.
+	pub(crate) fn oracle_min_virtual_price(oracle: &PoolOracle) -> Balance {
+		if oracle.prev_min_virtual_price.is_zero() {
+			return oracle.min_virtual_price
+		}
+		oracle.min_virtual_price.min(oracle.prev_min_virtual_price)
+	}
+
+	pub(crate) fn oracle_at(oracle: &PoolOracle, now: u64) -> Option<PoolOracle> {
+		let elapsed = now.saturating_sub(oracle.last_updated);
+		let mut res = oracle.clone();
+		if elapsed.is_zero() {
+			return Some(res)
+		}
+
+		for (ema, last_price) in res.price_emas.iter_mut().zip(oracle.last_prices.iter()) {
+			*ema = Self::moving_average(*ema, *last_price, elapsed)?;
+		}
+		res.virtual_price_ema =
+			Self::moving_average(oracle.virtual_price_ema, oracle.last_virtual_price, elapsed)?;
+		res.last_updated = now;
+
+		Some(res)
+	}
+
+	fn moving_average(ema: Balance, last: Balance, elapsed: u64) -> Option<Balance> {
+		let period = T::OracleEmaPeriod::get();
+		if period.is_zero() {
+			return Some(last)
+		}
+
+		let period = U256::from(period);
+		let elapsed = U256::from(elapsed);
+		U256::from(ema)
+			.checked_mul(period)?
+			.checked_add(U256::from(last).checked_mul(elapsed)?)?
+			.checked_div(period.checked_add(elapsed)?)
+			.and_then(|n| TryInto::<Balance>::try_into(n).ok())
+	}
+
+	/// Marginal price of each currency in terms of currency 0, with ORACLE_PRICE_PRECISION.
+	///
+	/// The base pool lp currency of a meta pool is priced per lp token, not per unit of
+	/// virtual value.
+	pub(crate) fn calculate_spot_prices(
+		pool: &Pool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+	) -> Option<Vec<Balance>> {
+		let (info, xp, base_virtual_price) = match pool {
+			Pool::Base(bp) => (bp, Self::xp(&bp.balances, &bp.token_multipliers)?, None),
+			Pool::Meta(mp) => {
+				let base_virtual_price = Self::meta_pool_base_virtual_price(mp)?;
+				let xp = Self::meta_pool_xp(
+					&mp.info.balances,
+					&mp.info.token_multipliers,
+					base_virtual_price,
+				)?;
+				(&mp.info, xp, Some(base_virtual_price))
+			},
+		};
+
+		let mut prices = Self::calculate_xp_spot_prices(&xp, Self::get_a_precise(info)?)?;
+		if let (Some(base_virtual_price), Some(lp_price)) = (base_virtual_price, prices.last_mut())
+		{
+			*lp_price = U256::from(*lp_price)
+				.checked_mul(U256::from(base_virtual_price))?
+				.checked_div(U256::from(BASE_VIRTUAL_PRICE_PRECISION))
+				.and_then(|n| TryInto::<Balance>::try_into(n).ok())?;
+		}
+
+		Some(prices)
+	}
+
+	/// The spot prices of the normalized balances `xp` in the first of them, scaled by
+	/// ORACLE_PRICE_PRECISION.
+	pub(crate) fn calculate_xp_spot_prices(xp: &[Balance], amp: Balance) -> Option<Vec<Balance>> {
+		let d = U256::from(Self::get_d(xp, amp)?);
+		let n_currencies = U256::from(xp.len() as u64);
+
+		let mut dr = d.checked_div(n_currencies.checked_pow(n_currencies)?)?;
+		for x in xp.iter() {
+			dr = dr.checked_mul(d)?.checked_div(U256::from(*x))?;
+		}
+
+		let xp0 = U256::from(*xp.first()?);
+		let xp0_a = U256::from(amp)
+			.checked_mul(n_currencies)?
+			.checked_mul(xp0)?
+			.checked_div(U256::from(A_PRECISION))?;
+		let denominator = xp0_a.checked_add(dr)?;
+
+		let mut prices = vec![ORACLE_PRICE_PRECISION];
+		for x in xp.iter().skip(1) {
+			let price = U256::from(ORACLE_PRICE_PRECISION)
+				.checked_mul(xp0_a.checked_add(dr.checked_mul(xp0)?.checked_div(U256::from(*x))?)?)?
+				.checked_div(denominator)?;
+			prices.push(TryInto::<Balance>::try_into(price).ok()?);
+		}
+
+		Some(prices)
+	}
+}
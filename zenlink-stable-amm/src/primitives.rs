@@ -12,6 +12,7 @@ pub const FEE_DENOMINATOR: Number = 10_000_000_000;
 pub const POOL_LP_CURRENCY_ID_DECIMAL: u8 = 18;
 
 pub const BASE_VIRTUAL_PRICE_PRECISION: Balance = 1_000_000_000_000_000_000;
+pub const ORACLE_PRICE_PRECISION: Balance = 1_000_000_000_000_000_000;
 
 // protect from division loss when run approximation loop
 pub const A_PRECISION: Number = 100;
@@ -23,7 +24,7 @@ pub const POOL_TOKEN_COMMON_DECIMALS: u32 = 18;
 pub const DAY: u32 = 86400;
 pub const MIN_RAMP_TIME: u32 = DAY;
 
-pub const MINUTE: u64 = 3600;
+pub const MINUTE: u64 = 60;
 pub const BASE_CACHE_EXPIRE_TIME: u64 = 10 * MINUTE;
 
 // max_a with precision
@@ -32,6 +33,24 @@ pub const MAX_A_CHANGE: u32 = 10;
 pub const MAX_ADMIN_FEE: Number = 10_000_000_000; // 100%
 pub const MAX_SWAP_FEE: Number = 100_000_000; // 1%
 
+// the max number of currencies of a pool, which bounds the weight of the pool calls
+pub const MAX_POOL_CURRENCIES: u32 = 8;
+
+// the max number of receivers an admin fee route splits into
+pub const MAX_ADMIN_FEE_RECEIVERS: usize = 8;
+
+// the max slippage an admin fee route may allow its conversion swaps
+pub const MAX_ADMIN_FEE_SLIPPAGE: Number = 500_000_000; // 5%
+
+// the max balance of a retiring currency to be removed, in POOL_TOKEN_COMMON_DECIMALS
+pub const MAX_RETIRED_CURRENCY_DUST: Balance = 1_000_000_000_000;
+
+// the target weight of a retiring currency ramps down from RETIRING_WEIGHT_PRECISION to
+// MIN_RETIRING_WEIGHT over RETIRING_WEIGHT_RAMP_TIME seconds
+pub const RETIRING_WEIGHT_PRECISION: Balance = 10_000;
+pub const MIN_RETIRING_WEIGHT: Balance = 100; // 1%
+pub const RETIRING_WEIGHT_RAMP_TIME: u64 = 7 * DAY as u64;
+
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
 pub struct BasePool<CurrencyId, AccountId, BoundString> {
 	pub currency_ids: Vec<CurrencyId>,
@@ -45,6 +64,9 @@ pub struct BasePool<CurrencyId, AccountId, BoundString> {
 	pub fee: Number,
 	// admin fee in ratio of swap fee.
 	pub admin_fee: Number,
+	// multiplier of the fee when the balances move away from the peg, in FEE_DENOMINATOR units.
+	// A value not bigger than FEE_DENOMINATOR disables the dynamic fee.
+	pub offpeg_fee_multiplier: Number,
 	// observation of A, multiplied with A_PRECISION
 	pub initial_a: Number,
 	pub future_a: Number,
@@ -57,16 +79,77 @@ pub struct BasePool<CurrencyId, AccountId, BoundString> {
 	pub lp_currency_decimal: u8,
 }
 
+#[derive(Encode, Decode, Clone, Copy, PartialEq, Eq, Debug, TypeInfo)]
+pub enum BaseCachePolicy {
+	// expire after the number of seconds
+	Time(u64),
+	// expire after the number of blocks
+	Blocks(u64),
+}
+
+impl Default for BaseCachePolicy {
+	fn default() -> Self {
+		BaseCachePolicy::Time(BASE_CACHE_EXPIRE_TIME)
+	}
+}
+
 #[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
 pub struct MetaPool<PoolId, CurrencyId, AccountId, BoundString> {
 	pub base_pool_id: PoolId,
 	pub base_virtual_price: Balance,
 	pub base_cache_last_updated: u64,
+	pub base_cache_last_updated_block: u64,
+	// when the cached base_virtual_price expires
+	pub base_cache_policy: BaseCachePolicy,
 	pub base_currencies: Vec<CurrencyId>,
 
 	pub info: BasePool<CurrencyId, AccountId, BoundString>,
 }
 
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct PoolOracle {
+	// spot price of each currency in terms of currency 0 at the last update, with
+	// ORACLE_PRICE_PRECISION. Both currencies are normalized to POOL_TOKEN_COMMON_DECIMALS.
+	pub last_prices: Vec<Balance>,
+	// moving average of last_prices
+	pub price_emas: Vec<Balance>,
+	pub last_virtual_price: Balance,
+	// moving average of last_virtual_price
+	pub virtual_price_ema: Balance,
+	// the lowest virtual price observed in the current and the previous window
+	pub min_virtual_price: Balance,
+	pub prev_min_virtual_price: Balance,
+	pub window_started: u64,
+	// timestamp in seconds
+	pub last_updated: u64,
+}
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct LiquidityCaps {
+	// max sum of the normalized balances of the pool, in POOL_TOKEN_COMMON_DECIMALS
+	pub max_tvl: Option<Balance>,
+	// max lp currency an account may hold after supplying liquidity
+	pub max_account_lp: Option<Balance>,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct RetiringCurrency<CurrencyId> {
+	pub currency_id: CurrencyId,
+	// when the currency started retiring, in seconds
+	pub start_time: u64,
+}
+
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct AdminFeeRoute<CurrencyId, AccountId> {
+	// receivers of the admin fee and their shares in FEE_DENOMINATOR. The unassigned share is
+	// donated to the liquidity providers.
+	pub receivers: Vec<(AccountId, Number)>,
+	// the currency the admin fee is swapped into through the pool before distribution
+	pub target_currency: Option<CurrencyId>,
+	// max deviation of a conversion from the price oracle, in FEE_DENOMINATOR
+	pub max_slippage: Number,
+}
+
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
 pub enum Pool<PoolId, CurrencyId, AccountId, BoundString> {
 	Base(BasePool<CurrencyId, AccountId, BoundString>),
@@ -125,6 +208,13 @@ impl<PoolId, CurrencyId: Copy, AccountId: Clone, BoundString>
 		}
 	}
 
+	pub fn get_offpeg_fee_multiplier(&self) -> Number {
+		match self {
+			Pool::Base(bp) => bp.offpeg_fee_multiplier,
+			Pool::Meta(mp) => mp.info.offpeg_fee_multiplier,
+		}
+	}
+
 	pub fn get_account(&self) -> AccountId {
 		match self {
 			Pool::Base(bp) => bp.account.clone(),
@@ -146,6 +236,13 @@ impl<PoolId, CurrencyId: Copy, AccountId: Clone, BoundString>
 		}
 	}
 
+	pub fn set_offpeg_fee_multiplier(&mut self, offpeg_fee_multiplier: Number) {
+		match self {
+			Pool::Base(bp) => bp.offpeg_fee_multiplier = offpeg_fee_multiplier,
+			Pool::Meta(mp) => mp.info.offpeg_fee_multiplier = offpeg_fee_multiplier,
+		}
+	}
+
 	pub fn set_admin_fee_receiver(&mut self, receiver: AccountId) {
 		match self {
 			Pool::Base(bp) => bp.admin_fee_receiver = receiver,
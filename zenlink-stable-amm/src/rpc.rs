@@ -3,8 +3,38 @@
 
 #![allow(clippy::type_complexity)]
 
+use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
+
 use super::*;
 
+/// The full state of a stable pool.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct StablePoolInfo<PoolId, CurrencyId, AccountId, Balance> {
+	pub pool_id: PoolId,
+	// the base pool of a meta pool, None for a base pool
+	pub base_pool_id: Option<PoolId>,
+	pub currency_ids: Vec<CurrencyId>,
+	pub lp_currency_id: CurrencyId,
+	pub balances: Vec<Balance>,
+	pub admin_balances: Vec<Balance>,
+	pub lp_total_supply: Balance,
+	pub initial_a: Balance,
+	pub future_a: Balance,
+	pub initial_a_time: Balance,
+	pub future_a_time: Balance,
+	pub a_precise: Balance,
+	pub fee: Balance,
+	pub admin_fee: Balance,
+	pub offpeg_fee_multiplier: Balance,
+	pub virtual_price: Balance,
+	pub account: AccountId,
+	pub admin_fee_receiver: AccountId,
+}
+
 impl<T: Config> Pallet<T> {
 	pub fn get_virtual_price(pool_id: T::PoolId) -> Balance {
 		if let Some(pool) = Self::pools(pool_id) {
@@ -100,4 +130,110 @@ impl<T: Config> Pallet<T> {
 		};
 		balances
 	}
+
+	pub fn get_price_oracles(pool_id: T::PoolId) -> Vec<Balance> {
+		if let Some(oracle) = Self::current_oracle(pool_id) {
+			return oracle.price_emas
+		};
+		Vec::new()
+	}
+
+	pub fn get_last_prices(pool_id: T::PoolId) -> Vec<Balance> {
+		if let Some(oracle) = Self::pool_oracles(pool_id) {
+			return oracle.last_prices
+		};
+		Vec::new()
+	}
+
+	pub fn get_virtual_price_oracle(pool_id: T::PoolId) -> Balance {
+		if let Some(oracle) = Self::current_oracle(pool_id) {
+			return oracle.virtual_price_ema
+		};
+		Balance::default()
+	}
+
+	pub fn get_min_virtual_price(pool_id: T::PoolId) -> Balance {
+		if let Some(oracle) = Self::pool_oracles(pool_id) {
+			return Self::oracle_min_virtual_price(&oracle)
+		};
+		Balance::default()
+	}
+
+	pub fn is_base_virtual_price_stale(pool_id: T::PoolId) -> bool {
+		if let Some(Pool::Meta(mp)) = Self::pools(pool_id) {
+			if Self::meta_pool_base_cache_expired(&mp) {
+				return false
+			}
+			if let Some(base_pool) = Self::pools(mp.base_pool_id) {
+				return Self::get_pool_virtual_price(&base_pool) != Some(mp.base_virtual_price)
+			}
+		};
+		false
+	}
+
+	/// The remaining tvl capacity of a pool, `None` if the pool has no tvl cap.
+	pub fn get_remaining_tvl_capacity(pool_id: T::PoolId) -> Option<Balance> {
+		let max_tvl = Self::liquidity_caps(pool_id)?.max_tvl?;
+		let tvl = Self::get_pool_tvl(&Self::pools(pool_id)?)?;
+		Some(max_tvl.saturating_sub(tvl))
+	}
+
+	/// The remaining lp capacity of an account in a pool, `None` if the pool has no account cap.
+	pub fn get_remaining_account_lp_capacity(
+		pool_id: T::PoolId,
+		account: T::AccountId,
+	) -> Option<Balance> {
+		let max_account_lp = Self::liquidity_caps(pool_id)?.max_account_lp?;
+		let lp_currency_id = Self::pools(pool_id)?.get_lp_currency();
+		let lp_balance = T::MultiCurrency::free_balance(lp_currency_id, &account);
+		Some(max_account_lp.saturating_sub(lp_balance))
+	}
+
+	pub fn get_pool_info(
+		pool_id: T::PoolId,
+	) -> Option<StablePoolInfo<T::PoolId, T::CurrencyId, T::AccountId, Balance>> {
+		let pool = Self::pools(pool_id)?;
+		let virtual_price = Self::get_pool_virtual_price(&pool).unwrap_or_default();
+		let (base_pool_id, info) = match pool {
+			Pool::Base(bp) => (None, bp),
+			Pool::Meta(mp) => (Some(mp.base_pool_id), mp.info),
+		};
+
+		Some(StablePoolInfo {
+			pool_id,
+			base_pool_id,
+			lp_total_supply: T::MultiCurrency::total_issuance(info.lp_currency_id),
+			a_precise: Self::get_a_precise(&info).unwrap_or_default(),
+			admin_balances: Self::get_admin_balances(pool_id),
+			virtual_price,
+			currency_ids: info.currency_ids,
+			lp_currency_id: info.lp_currency_id,
+			balances: info.balances,
+			initial_a: info.initial_a,
+			future_a: info.future_a,
+			initial_a_time: info.initial_a_time,
+			future_a_time: info.future_a_time,
+			fee: info.fee,
+			admin_fee: info.admin_fee,
+			offpeg_fee_multiplier: info.offpeg_fee_multiplier,
+			account: info.account,
+			admin_fee_receiver: info.admin_fee_receiver,
+		})
+	}
+
+	/// Info of at most `limit` pools, starting from `start` in ascending pool id.
+	pub fn get_all_pools(
+		start: T::PoolId,
+		limit: u32,
+	) -> Vec<StablePoolInfo<T::PoolId, T::CurrencyId, T::AccountId, Balance>> {
+		let mut pools = Vec::new();
+		let mut pool_id = start;
+		while pool_id < Self::next_pool_id() && pools.len() < limit as usize {
+			if let Some(info) = Self::get_pool_info(pool_id) {
+				pools.push(info);
+			}
+			pool_id += One::one();
+		}
+		pools
+	}
 }
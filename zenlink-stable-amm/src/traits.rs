@@ -103,6 +103,56 @@ pub trait StableAmmApi<PoolId, CurrencyId, AccountId, Balance> {
 		min_dy: Balance,
 		to: &AccountId,
 	) -> Result<Balance, DispatchError>;
+
+	/// Swap between the currencies of a meta pool and the currencies of its base pool, in the
+	/// index space of `calculate_meta_swap_underlying`.
+	fn swap_meta_underlying(
+		who: &AccountId,
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+		min_dy: Balance,
+		to: &AccountId,
+	) -> Result<Balance, DispatchError>;
+
+	/// Quote of `swap_pool_from_base`, including the imbalance fee of the deposit into the base
+	/// pool.
+	fn calculate_swap_pool_from_base(
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance>;
+
+	/// Quote of `swap_pool_to_base`.
+	fn calculate_swap_pool_to_base(
+		pool_id: PoolId,
+		base_pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance>;
+
+	/// Quote of `swap_meta_underlying`.
+	fn calculate_meta_swap_underlying(
+		pool_id: PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance>;
+
+	/// The pools holding `currency` directly, not through the lp currency of a base pool.
+	fn pools_of_currency(currency: CurrencyId) -> Vec<PoolId>;
+
+	/// The base pool of a meta pool.
+	fn base_pool_id(pool_id: PoolId) -> Option<PoolId>;
+
+	/// The currencies of the pool, in pool index order.
+	fn pool_currencies(pool_id: PoolId) -> Vec<CurrencyId>;
+
+	fn lp_currency(pool_id: PoolId) -> Option<CurrencyId>;
 }
 
 impl<T: Config> StableAmmApi<T::PoolId, T::CurrencyId, T::AccountId, Balance> for Pallet<T> {
@@ -133,9 +183,16 @@ impl<T: Config> StableAmmApi<T::PoolId, T::CurrencyId, T::AccountId, Balance> fo
 		j: usize,
 		in_balance: Balance,
 	) -> Option<Balance> {
+		if Self::retiring_currency_index(pool_id) == Some(i) {
+			return None
+		}
 		if let Some(pool) = Self::pools(pool_id) {
 			return match pool {
-				Pool::Base(bp) => Self::calculate_base_swap_amount(&bp, i, j, in_balance),
+				Pool::Base(bp) => {
+					let fee = Self::base_swap_fee(pool_id, &bp, j);
+					let weighted_pool = Self::weighted_base_pool(pool_id, &bp)?;
+					Self::calculate_base_swap_amount(&weighted_pool, i, j, in_balance, fee)
+				},
 				Pool::Meta(mp) => {
 					let virtual_price = Self::calculate_meta_virtual_price(&mp)?;
 					let res =
@@ -176,6 +233,28 @@ impl<T: Config> StableAmmApi<T::PoolId, T::CurrencyId, T::AccountId, Balance> fo
 		Self::get_currency_index(pool_id, currency)
 	}
 
+	fn pools_of_currency(currency: T::CurrencyId) -> Vec<T::PoolId> {
+		Pools::<T>::iter()
+			.filter(|(_, pool)| pool.clone().get_currency_ids().contains(&currency))
+			.map(|(pool_id, _)| pool_id)
+			.collect()
+	}
+
+	fn base_pool_id(pool_id: T::PoolId) -> Option<T::PoolId> {
+		match Self::pools(pool_id)? {
+			Pool::Meta(mp) => Some(mp.base_pool_id),
+			_ => None,
+		}
+	}
+
+	fn pool_currencies(pool_id: T::PoolId) -> Vec<T::CurrencyId> {
+		Self::get_currencies(pool_id)
+	}
+
+	fn lp_currency(pool_id: T::PoolId) -> Option<T::CurrencyId> {
+		Self::get_lp_currency(pool_id)
+	}
+
 	fn add_liquidity(
 		who: &T::AccountId,
 		pool_id: T::PoolId,
@@ -280,4 +359,121 @@ impl<T: Config> StableAmmApi<T::PoolId, T::CurrencyId, T::AccountId, Balance> fo
 			to,
 		)
 	}
+
+	fn swap_meta_underlying(
+		who: &T::AccountId,
+		pool_id: T::PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+		min_dy: Balance,
+		to: &T::AccountId,
+	) -> Result<Balance, DispatchError> {
+		Self::inner_swap_meta_pool_underlying(who, pool_id, in_index, out_index, dx, min_dy, to)
+	}
+
+	fn calculate_swap_pool_from_base(
+		pool_id: T::PoolId,
+		base_pool_id: T::PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance> {
+		let base_lp_index = Self::meta_pool_base_lp_index(pool_id, base_pool_id)?;
+		if base_lp_index == out_index {
+			return Some(Zero::zero())
+		}
+		if Self::retiring_currency_index(base_pool_id) == Some(in_index as usize) {
+			return None
+		}
+
+		let base_pool = match Self::pools(base_pool_id)? {
+			Pool::Base(bp) => bp,
+			Pool::Meta(_) => return None,
+		};
+		let mut base_amounts = vec![Balance::default(); base_pool.currency_ids.len()];
+		*base_amounts.get_mut(in_index as usize)? = dx;
+		let base_lp_amount = Self::calculate_base_add_liquidity(&base_pool, &base_amounts)?;
+
+		Self::calculate_meta_pool_swap_amount(
+			pool_id,
+			base_lp_index as usize,
+			out_index as usize,
+			base_lp_amount,
+		)
+	}
+
+	fn calculate_swap_pool_to_base(
+		pool_id: T::PoolId,
+		base_pool_id: T::PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance> {
+		let base_lp_index = Self::meta_pool_base_lp_index(pool_id, base_pool_id)?;
+		if base_lp_index == in_index {
+			return None
+		}
+
+		let base_lp_amount = Self::calculate_meta_pool_swap_amount(
+			pool_id,
+			in_index as usize,
+			base_lp_index as usize,
+			dx,
+		)?;
+		Self::stable_amm_calculate_remove_liquidity_one_currency(
+			base_pool_id,
+			base_lp_amount,
+			out_index,
+		)
+	}
+
+	fn calculate_meta_swap_underlying(
+		pool_id: T::PoolId,
+		in_index: u32,
+		out_index: u32,
+		dx: Balance,
+	) -> Option<Balance> {
+		Pallet::<T>::calculate_meta_swap_underlying(
+			pool_id,
+			dx,
+			in_index as usize,
+			out_index as usize,
+		)
+		.ok()
+	}
+}
+
+/// Manipulation resistant prices of stable pools, all with 1e18 precision.
+pub trait StableAmmOracle<PoolId, Balance> {
+	/// Moving average price of the currency at `index` in terms of the pool's first currency.
+	fn price_oracle(pool_id: PoolId, index: u32) -> Option<Balance>;
+
+	/// Moving average of the pool's virtual price.
+	fn virtual_price_oracle(pool_id: PoolId) -> Option<Balance>;
+
+	/// The lowest virtual price observed over at least the last `OracleEmaPeriod`.
+	fn min_virtual_price(pool_id: PoolId) -> Option<Balance>;
+}
+
+impl<T: Config> StableAmmOracle<T::PoolId, Balance> for Pallet<T> {
+	fn price_oracle(pool_id: T::PoolId, index: u32) -> Option<Balance> {
+		Self::current_oracle(pool_id)?.price_emas.get(index as usize).copied()
+	}
+
+	fn virtual_price_oracle(pool_id: T::PoolId) -> Option<Balance> {
+		let oracle = Self::current_oracle(pool_id)?;
+		if oracle.virtual_price_ema.is_zero() {
+			return None
+		}
+		Some(oracle.virtual_price_ema)
+	}
+
+	fn min_virtual_price(pool_id: T::PoolId) -> Option<Balance> {
+		let min_virtual_price = Self::oracle_min_virtual_price(&Self::pool_oracles(pool_id)?);
+		if min_virtual_price.is_zero() {
+			return None
+		}
+		Some(min_virtual_price)
+	}
 }
@@ -231,6 +231,27 @@ impl<T: Config> Pallet<T> {
 		}
 	}
 
+	/// The sum of the normalized balances of the pool, the base lp of a meta pool valued at the
+	/// base virtual price.
+	pub(crate) fn get_pool_tvl(
+		pool: &Pool<
+			T::PoolId,
+			T::CurrencyId,
+			T::AccountId,
+			BoundedVec<u8, T::PoolCurrencySymbolLimit>,
+		>,
+	) -> Option<Balance> {
+		let xp = match pool {
+			Pool::Base(bp) => Self::xp(&bp.balances, &bp.token_multipliers)?,
+			Pool::Meta(mp) => Self::meta_pool_xp(
+				&mp.info.balances,
+				&mp.info.token_multipliers,
+				Self::meta_pool_base_virtual_price(mp)?,
+			)?,
+		};
+		Self::sum_of(&xp)
+	}
+
 	pub(crate) fn calculate_fee_per_token(
 		pool: &BasePool<T::CurrencyId, T::AccountId, BoundedVec<u8, T::PoolCurrencySymbolLimit>>,
 	) -> Option<Balance> {
@@ -241,6 +262,49 @@ impl<T: Config> Pallet<T> {
 		)
 	}
 
+	/// Scale `fee` up by the offpeg fee multiplier as `xpi` and `xpj` move apart.
+	///
+	/// Only the ratio of `xpi` to `xpj` matters, so callers may pass sums instead of averages.
+	pub(crate) fn dynamic_fee(
+		xpi: Balance,
+		xpj: Balance,
+		fee: Number,
+		fee_multiplier: Number,
+	) -> Option<Number> {
+		if fee_multiplier <= FEE_DENOMINATOR {
+			return Some(fee)
+		}
+
+		let xps2 = U256::from(xpi).checked_add(U256::from(xpj))?.checked_pow(U256::from(2u32))?;
+		if xps2.is_zero() {
+			return Some(fee)
+		}
+
+		let fee_denominator = U256::from(FEE_DENOMINATOR);
+		let denominator = U256::from(fee_multiplier - FEE_DENOMINATOR)
+			.checked_mul(U256::from(4u32))?
+			.checked_mul(U256::from(xpi))?
+			.checked_mul(U256::from(xpj))?
+			.checked_div(xps2)?
+			.checked_add(fee_denominator)?;
+
+		U256::from(fee_multiplier)
+			.checked_mul(U256::from(fee))?
+			.checked_div(denominator)
+			.and_then(|n| TryInto::<Number>::try_into(n).ok())
+	}
+
+	/// The maximal fee the pool charges under the given offpeg fee multiplier.
+	pub(crate) fn max_dynamic_fee(fee: Number, offpeg_fee_multiplier: Number) -> Option<Number> {
+		if offpeg_fee_multiplier <= FEE_DENOMINATOR {
+			return Some(fee)
+		}
+		U256::from(fee)
+			.checked_mul(U256::from(offpeg_fee_multiplier))?
+			.checked_div(U256::from(FEE_DENOMINATOR))
+			.and_then(|n| TryInto::<Number>::try_into(n).ok())
+	}
+
 	pub(crate) fn distance<Number: PartialOrd + Sub<Output = Number>>(
 		x: Number,
 		y: Number,
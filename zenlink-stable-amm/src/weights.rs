@@ -15,14 +15,18 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
-//! Autogenerated weights for zenlink_stable_amm
+//! Weights for zenlink_stable_amm
 //!
-//! THIS FILE WAS AUTO-GENERATED USING THE SUBSTRATE BENCHMARK CLI VERSION 4.0.0-dev
-//! DATE: 2022-11-23, STEPS: `50`, REPEAT: 20, LOW RANGE: `[]`, HIGH RANGE: `[]`
-//! HOSTNAME: `manjaro`, CPU: `Intel(R) Core(TM) i7-10875H CPU @ 2.30GHz`
-//! EXECUTION: Some(Wasm), WASM-EXECUTION: Compiled, CHAIN: Some("dev"), DB CACHE: 1024
+//! The weights of the calls up to `withdraw_admin_fee` come from a benchmark run on
+//! 2022-11-23 (STEPS: `50`, REPEAT: 20, CPU: `Intel(R) Core(TM) i7-10875H CPU @ 2.30GHz`), with
+//! the per-currency slopes and storage accesses adjusted by hand as the calls changed since: the
+//! per-currency slope of `withdraw_admin_fee` includes a routed conversion swap on a pool of the
+//! largest size, and the meta pool swaps include a refresh of the base virtual price. The
+//! weights of the admin calls added after it are estimated by hand and marked as such. None of them
+//! is a measurement of the current code: regenerate this file with the benchmark CLI before
+//! relying on it.
 
-// Executed Command:
+// Command to regenerate:
 // ../target/release/zenlink-template-node
 // benchmark
 // pallet
@@ -51,20 +55,34 @@ use sp_std::marker::PhantomData;
 
 /// Weight functions needed for zenlink_stable_amm.
 pub trait WeightInfo {
-	fn create_base_pool() -> Weight;
-	fn create_meta_pool() -> Weight;
-	fn add_liquidity() -> Weight;
-	fn swap() -> Weight;
-	fn remove_liquidity() -> Weight;
-	fn remove_liquidity_one_currency() -> Weight;
-	fn remove_liquidity_imbalance() -> Weight;
-	fn add_pool_and_base_pool_liquidity() -> Weight;
-	fn remove_pool_and_base_pool_liquidity() -> Weight;
-	fn remove_pool_and_base_pool_liquidity_one_currency() -> Weight;
-	fn swap_pool_from_base() -> Weight;
-	fn swap_pool_to_base() -> Weight;
-	fn swap_meta_pool_underlying() -> Weight;
-	fn withdraw_admin_fee() -> Weight;
+	fn create_base_pool(n: u32, ) -> Weight;
+	fn create_meta_pool(n: u32, ) -> Weight;
+	fn add_liquidity(n: u32, m: u32, ) -> Weight;
+	fn swap(n: u32, m: u32, ) -> Weight;
+	fn remove_liquidity(n: u32, m: u32, ) -> Weight;
+	fn remove_liquidity_one_currency(n: u32, m: u32, ) -> Weight;
+	fn remove_liquidity_imbalance(n: u32, m: u32, ) -> Weight;
+	fn add_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight;
+	fn remove_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight;
+	fn remove_pool_and_base_pool_liquidity_one_currency(n: u32, b: u32, ) -> Weight;
+	fn swap_pool_from_base(n: u32, b: u32, ) -> Weight;
+	fn swap_pool_to_base(n: u32, b: u32, ) -> Weight;
+	fn swap_meta_pool_underlying(n: u32, b: u32, ) -> Weight;
+	fn update_fee_receiver() -> Weight;
+	fn set_swap_fee() -> Weight;
+	fn set_admin_fee() -> Weight;
+	fn ramp_a() -> Weight;
+	fn stop_ramp_a() -> Weight;
+	fn withdraw_admin_fee(n: u32, m: u32, ) -> Weight;
+	fn set_offpeg_fee_multiplier() -> Weight;
+	fn set_base_cache_policy() -> Weight;
+	fn refresh_base_virtual_price(b: u32, ) -> Weight;
+	fn add_pool_currency(p: u32, ) -> Weight;
+	fn retire_pool_currency() -> Weight;
+	fn remove_pool_currency(n: u32, p: u32, ) -> Weight;
+	fn set_liquidity_caps() -> Weight;
+	fn set_admin_fee_route(r: u32, ) -> Weight;
+	fn remove_admin_fee_route() -> Weight;
 }
 
 /// Weights for zenlink_stable_amm using the Substrate node and recommended hardware.
@@ -74,9 +92,10 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: ZenlinkStableAmm LpCurrencies (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
 	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	fn create_base_pool() -> Weight {
-		// Minimum execution time: 46_736 nanoseconds.
-		Weight::from_ref_time(47_481_000 as u64)
+	/// The range of component `n` is `[2, 8]`.
+	fn create_base_pool(n: u32, ) -> Weight {
+		Weight::from_ref_time(46_281_000 as u64)
+			.saturating_add(Weight::from_ref_time(600_000 as u64).saturating_mul(n as u64))
 			.saturating_add(T::DbWeight::get().reads(4 as u64))
 			.saturating_add(T::DbWeight::get().writes(4 as u64))
 	}
@@ -86,138 +105,349 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens TotalIssuance (r:1 w:0)
-	fn create_meta_pool() -> Weight {
-		// Minimum execution time: 81_917 nanoseconds.
-		Weight::from_ref_time(83_417_000 as u64)
+	/// The range of component `n` is `[2, 8]`.
+	fn create_meta_pool(n: u32, ) -> Weight {
+		Weight::from_ref_time(80_017_000 as u64)
+			.saturating_add(Weight::from_ref_time(1_700_000 as u64).saturating_mul(n as u64))
 			.saturating_add(T::DbWeight::get().reads(8 as u64))
 			.saturating_add(T::DbWeight::get().writes(4 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: Tokens Accounts (r:17 w:17)
 	// Storage: System Account (r:1 w:1)
-	fn add_liquidity() -> Weight {
-		// Minimum execution time: 142_017 nanoseconds.
-		Weight::from_ref_time(143_700_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(9 as u64))
-			.saturating_add(T::DbWeight::get().writes(8 as u64))
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn add_liquidity(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(99_700_000 as u64)
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(T::DbWeight::get().writes(5 as u64))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(m as u64)))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
 	// Storage: Tokens Accounts (r:4 w:4)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn swap() -> Weight {
-		// Minimum execution time: 111_324 nanoseconds.
-		Weight::from_ref_time(117_940_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(7 as u64))
-			.saturating_add(T::DbWeight::get().writes(5 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn swap(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(85_940_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(141_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(T::DbWeight::get().writes(6 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
+	// Storage: Tokens Accounts (r:17 w:17)
+	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity() -> Weight {
-		// Minimum execution time: 144_402 nanoseconds.
-		Weight::from_ref_time(146_147_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(8 as u64))
-			.saturating_add(T::DbWeight::get().writes(7 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(140_147_000 as u64)
+			.saturating_add(Weight::from_ref_time(19_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Tokens Accounts (r:3 w:3)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity_one_currency() -> Weight {
-		// Minimum execution time: 111_716 nanoseconds.
-		Weight::from_ref_time(117_518_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(7 as u64))
-			.saturating_add(T::DbWeight::get().writes(5 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity_one_currency(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(81_518_000 as u64)
+			.saturating_add(Weight::from_ref_time(34_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(45_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(8 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(T::DbWeight::get().writes(6 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: Tokens Accounts (r:17 w:17)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity_imbalance() -> Weight {
-		// Minimum execution time: 132_703 nanoseconds.
-		Weight::from_ref_time(133_706_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(9 as u64))
-			.saturating_add(T::DbWeight::get().writes(7 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity_imbalance(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(85_706_000 as u64)
+			.saturating_add(Weight::from_ref_time(40_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(45_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(T::DbWeight::get().writes(4 as u64))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:9 w:9)
+	// Storage: Tokens Accounts (r:33 w:33)
 	// Storage: System Account (r:1 w:1)
-	fn add_pool_and_base_pool_liquidity() -> Weight {
-		// Minimum execution time: 248_369 nanoseconds.
-		Weight::from_ref_time(249_976_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(15 as u64))
-			.saturating_add(T::DbWeight::get().writes(14 as u64))
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:2 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn add_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(157_976_000 as u64)
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(40_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(b as u64)))
+			.saturating_add(T::DbWeight::get().writes(8 as u64))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(b as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
-	// Storage: Tokens Accounts (r:9 w:9)
+	// Storage: Tokens Accounts (r:33 w:33)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
+	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
-	fn remove_pool_and_base_pool_liquidity() -> Weight {
-		// Minimum execution time: 193_866 nanoseconds.
-		Weight::from_ref_time(195_741_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(15 as u64))
-			.saturating_add(T::DbWeight::get().writes(13 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn remove_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(175_741_000 as u64)
+			.saturating_add(Weight::from_ref_time(20_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(22_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(b as u64)))
+			.saturating_add(T::DbWeight::get().writes(7 as u64))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().writes((2 as u64).saturating_mul(b as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens Accounts (r:5 w:5)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
-	fn remove_pool_and_base_pool_liquidity_one_currency() -> Weight {
-		// Minimum execution time: 185_493 nanoseconds.
-		Weight::from_ref_time(186_668_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(12 as u64))
-			.saturating_add(T::DbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn remove_pool_and_base_pool_liquidity_one_currency(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(122_668_000 as u64)
+			.saturating_add(Weight::from_ref_time(30_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(34_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens Accounts (r:7 w:6)
 	// Storage: System Account (r:1 w:0)
-	fn swap_pool_from_base() -> Weight {
-		// Minimum execution time: 195_027 nanoseconds.
-		Weight::from_ref_time(196_708_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(12 as u64))
-			.saturating_add(T::DbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_pool_from_base(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(116_708_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(52_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens Accounts (r:6 w:6)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
-	fn swap_pool_to_base() -> Weight {
-		// Minimum execution time: 192_067 nanoseconds.
-		Weight::from_ref_time(193_276_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(12 as u64))
-			.saturating_add(T::DbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_pool_to_base(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(125_276_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(46_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens Accounts (r:6 w:6)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: System Account (r:2 w:1)
-	fn swap_meta_pool_underlying() -> Weight {
-		// Minimum execution time: 197_939 nanoseconds.
-		Weight::from_ref_time(199_533_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(12 as u64))
-			.saturating_add(T::DbWeight::get().writes(10 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_meta_pool_underlying(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(127_533_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(14 as u64))
+			.saturating_add(T::DbWeight::get().writes(12 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens Accounts (r:3 w:2)
-	// Storage: System Account (r:1 w:0)
-	fn withdraw_admin_fee() -> Weight {
-		// Minimum execution time: 77_701 nanoseconds.
-		Weight::from_ref_time(79_068_000 as u64)
-			.saturating_add(T::DbWeight::get().reads(5 as u64))
+	fn update_fee_receiver() -> Weight {
+		Weight::from_ref_time(25_012_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_swap_fee() -> Weight {
+		Weight::from_ref_time(24_143_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_admin_fee() -> Weight {
+		Weight::from_ref_time(23_518_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn ramp_a() -> Weight {
+		Weight::from_ref_time(30_286_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn stop_ramp_a() -> Weight {
+		Weight::from_ref_time(28_401_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens Accounts (r:96 w:96)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System Account (r:2 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn withdraw_admin_fee(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(40_012_000 as u64)
+			.saturating_add(Weight::from_ref_time(437_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(141_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(T::DbWeight::get().reads(6 as u64))
+			.saturating_add(T::DbWeight::get().reads((12 as u64).saturating_mul(n as u64)))
+			.saturating_add(T::DbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
 			.saturating_add(T::DbWeight::get().writes(3 as u64))
+			.saturating_add(T::DbWeight::get().writes((12 as u64).saturating_mul(n as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_offpeg_fee_multiplier() -> Weight {
+		Weight::from_ref_time(24_500_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_base_cache_policy() -> Weight {
+		Weight::from_ref_time(23_500_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `b` is `[2, 8]`.
+	fn refresh_base_virtual_price(b: u32, ) -> Weight {
+		Weight::from_ref_time(42_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(14_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().writes(2 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm LpCurrencies (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Tokens Accounts (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:p w:p)
+	/// The range of component `p` is `[1, 100]`.
+	fn add_pool_currency(p: u32, ) -> Weight {
+		Weight::from_ref_time(420_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(7_000_000 as u64).saturating_mul(p as u64))
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(p as u64)))
+			.saturating_add(T::DbWeight::get().writes(7 as u64))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(p as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:1)
+	fn retire_pool_currency() -> Weight {
+		Weight::from_ref_time(26_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(2 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Tokens Accounts (r:2 w:2)
+	// Storage: System Account (r:1 w:0)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:p w:p)
+	/// The range of component `n` is `[3, 8]`.
+	/// The range of component `p` is `[1, 100]`.
+	fn remove_pool_currency(n: u32, p: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(12_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(7_000_000 as u64).saturating_mul(p as u64))
+			.saturating_add(T::DbWeight::get().reads(9 as u64))
+			.saturating_add(T::DbWeight::get().reads((1 as u64).saturating_mul(p as u64)))
+			.saturating_add(T::DbWeight::get().writes(6 as u64))
+			.saturating_add(T::DbWeight::get().writes((1 as u64).saturating_mul(p as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:0 w:1)
+	fn set_liquidity_caps() -> Weight {
+		Weight::from_ref_time(22_500_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:0 w:1)
+	/// The range of component `r` is `[0, 8]`.
+	fn set_admin_fee_route(r: u32, ) -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(600_000 as u64).saturating_mul(r as u64))
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:1)
+	fn remove_admin_fee_route() -> Weight {
+		Weight::from_ref_time(21_500_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(1 as u64))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
 	}
 }
 
@@ -227,9 +457,10 @@ impl WeightInfo for () {
 	// Storage: ZenlinkStableAmm LpCurrencies (r:1 w:1)
 	// Storage: System Account (r:1 w:1)
 	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	fn create_base_pool() -> Weight {
-		// Minimum execution time: 46_736 nanoseconds.
-		Weight::from_ref_time(47_481_000 as u64)
+	/// The range of component `n` is `[2, 8]`.
+	fn create_base_pool(n: u32, ) -> Weight {
+		Weight::from_ref_time(46_281_000 as u64)
+			.saturating_add(Weight::from_ref_time(600_000 as u64).saturating_mul(n as u64))
 			.saturating_add(RocksDbWeight::get().reads(4 as u64))
 			.saturating_add(RocksDbWeight::get().writes(4 as u64))
 	}
@@ -239,137 +470,348 @@ impl WeightInfo for () {
 	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens TotalIssuance (r:1 w:0)
-	fn create_meta_pool() -> Weight {
-		// Minimum execution time: 81_917 nanoseconds.
-		Weight::from_ref_time(83_417_000 as u64)
+	/// The range of component `n` is `[2, 8]`.
+	fn create_meta_pool(n: u32, ) -> Weight {
+		Weight::from_ref_time(80_017_000 as u64)
+			.saturating_add(Weight::from_ref_time(1_700_000 as u64).saturating_mul(n as u64))
 			.saturating_add(RocksDbWeight::get().reads(8 as u64))
 			.saturating_add(RocksDbWeight::get().writes(4 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: Tokens Accounts (r:17 w:17)
 	// Storage: System Account (r:1 w:1)
-	fn add_liquidity() -> Weight {
-		// Minimum execution time: 142_017 nanoseconds.
-		Weight::from_ref_time(143_700_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(9 as u64))
-			.saturating_add(RocksDbWeight::get().writes(8 as u64))
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn add_liquidity(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(99_700_000 as u64)
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(m as u64)))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
 	// Storage: Tokens Accounts (r:4 w:4)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn swap() -> Weight {
-		// Minimum execution time: 111_324 nanoseconds.
-		Weight::from_ref_time(117_940_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(7 as u64))
-			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn swap(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(85_940_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(141_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(RocksDbWeight::get().writes(6 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
+	// Storage: Tokens Accounts (r:17 w:17)
+	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity() -> Weight {
-		// Minimum execution time: 144_402 nanoseconds.
-		Weight::from_ref_time(146_147_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(8 as u64))
-			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(140_147_000 as u64)
+			.saturating_add(Weight::from_ref_time(19_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Tokens Accounts (r:3 w:3)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity_one_currency() -> Weight {
-		// Minimum execution time: 111_716 nanoseconds.
-		Weight::from_ref_time(117_518_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(7 as u64))
-			.saturating_add(RocksDbWeight::get().writes(5 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity_one_currency(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(81_518_000 as u64)
+			.saturating_add(Weight::from_ref_time(34_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(45_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(8 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(RocksDbWeight::get().writes(6 as u64))
 	}
-	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:2 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:5 w:5)
+	// Storage: Tokens Accounts (r:17 w:17)
 	// Storage: System Account (r:1 w:0)
-	fn remove_liquidity_imbalance() -> Weight {
-		// Minimum execution time: 132_703 nanoseconds.
-		Weight::from_ref_time(133_706_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(9 as u64))
-			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn remove_liquidity_imbalance(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(85_706_000 as u64)
+			.saturating_add(Weight::from_ref_time(40_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(45_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
+			.saturating_add(RocksDbWeight::get().writes(4 as u64))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
-	// Storage: Tokens Accounts (r:9 w:9)
+	// Storage: Tokens Accounts (r:33 w:33)
 	// Storage: System Account (r:1 w:1)
-	fn add_pool_and_base_pool_liquidity() -> Weight {
-		// Minimum execution time: 248_369 nanoseconds.
-		Weight::from_ref_time(249_976_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(15 as u64))
-			.saturating_add(RocksDbWeight::get().writes(14 as u64))
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:2 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn add_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(157_976_000 as u64)
+			.saturating_add(Weight::from_ref_time(38_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(40_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(b as u64)))
+			.saturating_add(RocksDbWeight::get().writes(8 as u64))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(b as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
-	// Storage: Tokens Accounts (r:9 w:9)
+	// Storage: Tokens Accounts (r:33 w:33)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
+	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
-	fn remove_pool_and_base_pool_liquidity() -> Weight {
-		// Minimum execution time: 193_866 nanoseconds.
-		Weight::from_ref_time(195_741_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(15 as u64))
-			.saturating_add(RocksDbWeight::get().writes(13 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn remove_pool_and_base_pool_liquidity(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(175_741_000 as u64)
+			.saturating_add(Weight::from_ref_time(20_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(22_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(b as u64)))
+			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().writes((2 as u64).saturating_mul(b as u64)))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens Accounts (r:5 w:5)
 	// Storage: Tokens TotalIssuance (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
-	fn remove_pool_and_base_pool_liquidity_one_currency() -> Weight {
-		// Minimum execution time: 185_493 nanoseconds.
-		Weight::from_ref_time(186_668_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(12 as u64))
-			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn remove_pool_and_base_pool_liquidity_one_currency(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(122_668_000 as u64)
+			.saturating_add(Weight::from_ref_time(30_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(34_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens Accounts (r:7 w:6)
 	// Storage: System Account (r:1 w:0)
-	fn swap_pool_from_base() -> Weight {
-		// Minimum execution time: 195_027 nanoseconds.
-		Weight::from_ref_time(196_708_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(12 as u64))
-			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_pool_from_base(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(116_708_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(52_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Tokens Accounts (r:6 w:6)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: System Account (r:2 w:0)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
-	fn swap_pool_to_base() -> Weight {
-		// Minimum execution time: 192_067 nanoseconds.
-		Weight::from_ref_time(193_276_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(12 as u64))
-			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_pool_to_base(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(125_276_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(46_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(11 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:2 w:2)
 	// Storage: Timestamp Now (r:1 w:0)
 	// Storage: Tokens Accounts (r:6 w:6)
 	// Storage: Tokens TotalIssuance (r:1 w:1)
 	// Storage: System Account (r:2 w:1)
-	fn swap_meta_pool_underlying() -> Weight {
-		// Minimum execution time: 197_939 nanoseconds.
-		Weight::from_ref_time(199_533_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(12 as u64))
-			.saturating_add(RocksDbWeight::get().writes(10 as u64))
+	// Storage: ZenlinkStableAmm PoolOracles (r:2 w:2)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `b` is `[2, 8]`.
+	fn swap_meta_pool_underlying(n: u32, b: u32, ) -> Weight {
+		Weight::from_ref_time(127_533_000 as u64)
+			.saturating_add(Weight::from_ref_time(32_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(48_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(14 as u64))
+			.saturating_add(RocksDbWeight::get().writes(12 as u64))
 	}
 	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
-	// Storage: Tokens Accounts (r:3 w:2)
-	// Storage: System Account (r:1 w:0)
-	fn withdraw_admin_fee() -> Weight {
-		// Minimum execution time: 77_701 nanoseconds.
-		Weight::from_ref_time(79_068_000 as u64)
-			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+	fn update_fee_receiver() -> Weight {
+		Weight::from_ref_time(25_012_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_swap_fee() -> Weight {
+		Weight::from_ref_time(24_143_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_admin_fee() -> Weight {
+		Weight::from_ref_time(23_518_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn ramp_a() -> Weight {
+		Weight::from_ref_time(30_286_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	fn stop_ramp_a() -> Weight {
+		Weight::from_ref_time(28_401_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens Accounts (r:96 w:96)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: System Account (r:2 w:1)
+	/// The range of component `n` is `[2, 8]`.
+	/// The range of component `m` is `[0, 1]`.
+	fn withdraw_admin_fee(n: u32, m: u32, ) -> Weight {
+		Weight::from_ref_time(40_012_000 as u64)
+			.saturating_add(Weight::from_ref_time(437_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(141_000_000 as u64).saturating_mul(m as u64))
+			.saturating_add(RocksDbWeight::get().reads(6 as u64))
+			.saturating_add(RocksDbWeight::get().reads((12 as u64).saturating_mul(n as u64)))
+			.saturating_add(RocksDbWeight::get().reads((2 as u64).saturating_mul(m as u64)))
 			.saturating_add(RocksDbWeight::get().writes(3 as u64))
+			.saturating_add(RocksDbWeight::get().writes((12 as u64).saturating_mul(n as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_offpeg_fee_multiplier() -> Weight {
+		Weight::from_ref_time(24_500_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	fn set_base_cache_policy() -> Weight {
+		Weight::from_ref_time(23_500_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:2 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	/// The range of component `b` is `[2, 8]`.
+	fn refresh_base_virtual_price(b: u32, ) -> Weight {
+		Weight::from_ref_time(42_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(14_000_000 as u64).saturating_mul(b as u64))
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().writes(2 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm LpCurrencies (r:1 w:0)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Tokens TotalIssuance (r:1 w:1)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: Tokens Accounts (r:3 w:3)
+	// Storage: System Account (r:1 w:1)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:p w:p)
+	/// The range of component `p` is `[1, 100]`.
+	fn add_pool_currency(p: u32, ) -> Weight {
+		Weight::from_ref_time(420_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(7_000_000 as u64).saturating_mul(p as u64))
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(p as u64)))
+			.saturating_add(RocksDbWeight::get().writes(7 as u64))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(p as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:1)
+	fn retire_pool_currency() -> Weight {
+		Weight::from_ref_time(26_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(2 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm RetiringCurrencies (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:1 w:1)
+	// Storage: Tokens Accounts (r:2 w:2)
+	// Storage: System Account (r:1 w:0)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	// Storage: Timestamp Now (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolOracles (r:1 w:1)
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:1)
+	// Storage: ZenlinkStableAmm Pools (r:p w:p)
+	/// The range of component `n` is `[3, 8]`.
+	/// The range of component `p` is `[1, 100]`.
+	fn remove_pool_currency(n: u32, p: u32, ) -> Weight {
+		Weight::from_ref_time(70_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(12_000_000 as u64).saturating_mul(n as u64))
+			.saturating_add(Weight::from_ref_time(7_000_000 as u64).saturating_mul(p as u64))
+			.saturating_add(RocksDbWeight::get().reads(9 as u64))
+			.saturating_add(RocksDbWeight::get().reads((1 as u64).saturating_mul(p as u64)))
+			.saturating_add(RocksDbWeight::get().writes(6 as u64))
+			.saturating_add(RocksDbWeight::get().writes((1 as u64).saturating_mul(p as u64)))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm PoolLiquidityCaps (r:0 w:1)
+	fn set_liquidity_caps() -> Weight {
+		Weight::from_ref_time(22_500_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm Pools (r:1 w:0)
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:0 w:1)
+	/// The range of component `r` is `[0, 8]`.
+	fn set_admin_fee_route(r: u32, ) -> Weight {
+		Weight::from_ref_time(25_000_000 as u64)
+			.saturating_add(Weight::from_ref_time(600_000 as u64).saturating_mul(r as u64))
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+	}
+	// Not benchmarked, estimated by hand.
+	// Storage: ZenlinkStableAmm AdminFeeRoutes (r:1 w:1)
+	fn remove_admin_fee_route() -> Weight {
+		Weight::from_ref_time(21_500_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
 	}
 }
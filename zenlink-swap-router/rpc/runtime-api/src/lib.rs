@@ -0,0 +1,51 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+//! Runtime API definition for swap router.
+
+#![cfg_attr(not(feature = "std"), no_std)]
+// The `too_many_arguments` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::too_many_arguments)]
+// The `unnecessary_mut_passed` warning originates from `decl_runtime_apis` macro.
+#![allow(clippy::unnecessary_mut_passed)]
+use codec::Codec;
+use sp_std::vec::Vec;
+use zenlink_swap_router::{Route, RouteQuote, SplitQuote, Venue};
+
+sp_api::decl_runtime_apis! {
+	pub trait SwapRouterApi<PoolId, StableCurrencyId, NormalCurrencyId, Balance> where
+		PoolId: Codec,
+		StableCurrencyId: Codec,
+		NormalCurrencyId: Codec,
+		Balance: Codec,
+	{
+		fn pools_of_asset(asset_id: NormalCurrencyId) -> Vec<Venue<PoolId, NormalCurrencyId>>;
+
+		fn best_venue(
+			from: NormalCurrencyId,
+			to: NormalCurrencyId,
+			amount_in: Balance,
+		) -> Option<(Route<PoolId, StableCurrencyId, NormalCurrencyId>, Balance)>;
+
+		fn to_stable_currency(asset_id: NormalCurrencyId) -> Option<StableCurrencyId>;
+
+		fn to_normal_currency(currency_id: StableCurrencyId) -> Option<NormalCurrencyId>;
+
+		fn find_best_route(
+			from: NormalCurrencyId,
+			to: NormalCurrencyId,
+			amount_in: Balance,
+		) -> Option<RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance>>;
+
+		fn find_optimal_split(
+			from: NormalCurrencyId,
+			to: NormalCurrencyId,
+			amount_in: Balance,
+		) -> Option<SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance>>;
+
+		fn quote_routes(
+			amount_in: Balance,
+			routes: Vec<Route<PoolId, StableCurrencyId, NormalCurrencyId>>,
+		) -> Option<Vec<Balance>>;
+	}
+}
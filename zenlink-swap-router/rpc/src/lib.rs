@@ -0,0 +1,213 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+//! RPC interface for the swap router pallet.
+#![allow(clippy::type_complexity)]
+
+use codec::Codec;
+use jsonrpsee::{
+	core::{Error as JsonRpseeError, RpcResult},
+	proc_macros::rpc,
+	types::error::{CallError, ErrorObject},
+};
+
+use sp_api::ProvideRuntimeApi;
+use sp_blockchain::HeaderBackend;
+use sp_rpc::number::NumberOrHex;
+use sp_runtime::traits::{Block as BlockT, MaybeDisplay};
+use std::sync::Arc;
+
+use zenlink_swap_router::{Route, RouteQuote, SplitQuote, SplitRoutes, SplitShare};
+use zenlink_swap_router_runtime_api::SwapRouterApi as SwapRouterRuntimeApi;
+
+#[rpc(server)]
+pub trait SwapRouterApi<BlockHash, PoolId, StableCurrencyId, NormalCurrencyId, Balance> {
+	#[method(name = "zenlinkSwapRouter_findBestRoute")]
+	fn find_best_route(
+		&self,
+		from: NormalCurrencyId,
+		to: NormalCurrencyId,
+		amount_in: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>>>;
+
+	#[method(name = "zenlinkSwapRouter_findOptimalSplit")]
+	fn find_optimal_split(
+		&self,
+		from: NormalCurrencyId,
+		to: NormalCurrencyId,
+		amount_in: Balance,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>>>;
+
+	#[method(name = "zenlinkSwapRouter_quoteRoutes")]
+	fn quote_routes(
+		&self,
+		amount_in: Balance,
+		routes: Vec<Route<PoolId, StableCurrencyId, NormalCurrencyId>>,
+		at: Option<BlockHash>,
+	) -> RpcResult<Option<Vec<NumberOrHex>>>;
+}
+
+pub struct SwapRouter<C, M> {
+	client: Arc<C>,
+	_marker: std::marker::PhantomData<M>,
+}
+
+impl<C, M> SwapRouter<C, M> {
+	pub fn new(client: Arc<C>) -> Self {
+		Self { client, _marker: Default::default() }
+	}
+}
+
+impl<C, Block, PoolId, StableCurrencyId, NormalCurrencyId, Balance>
+	SwapRouterApiServer<
+		<Block as BlockT>::Hash,
+		PoolId,
+		StableCurrencyId,
+		NormalCurrencyId,
+		Balance,
+	> for SwapRouter<C, Block>
+where
+	Block: BlockT,
+	PoolId: Codec,
+	StableCurrencyId: Codec,
+	NormalCurrencyId: Codec,
+	Balance: Codec + TryInto<NumberOrHex> + std::fmt::Debug + MaybeDisplay + Copy,
+	C: Send + Sync + 'static,
+	C: ProvideRuntimeApi<Block>,
+	C: HeaderBackend<Block>,
+	C::Api: SwapRouterRuntimeApi<Block, PoolId, StableCurrencyId, NormalCurrencyId, Balance>,
+{
+	fn find_best_route(
+		&self,
+		from: NormalCurrencyId,
+		to: NormalCurrencyId,
+		amount_in: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.find_best_route(at, from, to, amount_in)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(try_into_rpc_route_quote)
+			.transpose()
+	}
+
+	fn find_optimal_split(
+		&self,
+		from: NormalCurrencyId,
+		to: NormalCurrencyId,
+		amount_in: Balance,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.find_optimal_split(at, from, to, amount_in)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(try_into_rpc_split_quote)
+			.transpose()
+	}
+
+	fn quote_routes(
+		&self,
+		amount_in: Balance,
+		routes: Vec<Route<PoolId, StableCurrencyId, NormalCurrencyId>>,
+		at: Option<<Block as BlockT>::Hash>,
+	) -> RpcResult<Option<Vec<NumberOrHex>>> {
+		let api = self.client.runtime_api();
+		let at = at.unwrap_or_else(|| self.client.info().best_hash);
+
+		api.quote_routes(at, amount_in, routes)
+			.map_err(runtime_error_into_rpc_err)?
+			.map(|amounts| amounts.into_iter().map(try_into_rpc_balance).collect())
+			.transpose()
+	}
+}
+
+fn try_into_rpc_route_quote<
+	PoolId,
+	StableCurrencyId,
+	NormalCurrencyId,
+	Balance: Codec + TryInto<NumberOrHex> + MaybeDisplay + Copy + std::fmt::Debug,
+>(
+	quote: RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance>,
+) -> RpcResult<RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>> {
+	Ok(RouteQuote {
+		routes: quote.routes,
+		amounts: quote.amounts.into_iter().map(try_into_rpc_balance).collect::<RpcResult<_>>()?,
+		amount_out: try_into_rpc_balance(quote.amount_out)?,
+		price_impact: quote.price_impact,
+	})
+}
+
+fn try_into_rpc_split_quote<
+	PoolId,
+	StableCurrencyId,
+	NormalCurrencyId,
+	Balance: Codec + TryInto<NumberOrHex> + MaybeDisplay + Copy + std::fmt::Debug,
+>(
+	quote: SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance>,
+) -> RpcResult<SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, NumberOrHex>> {
+	let splits = quote
+		.splits
+		.into_iter()
+		.map(|split| {
+			let share = match split.share {
+				SplitShare::Percent(percent) => SplitShare::Percent(percent),
+				SplitShare::Amount(amount) => SplitShare::Amount(try_into_rpc_balance(amount)?),
+			};
+			Ok(SplitRoutes { share, routes: split.routes })
+		})
+		.collect::<RpcResult<_>>()?;
+
+	Ok(SplitQuote {
+		splits,
+		amounts_out: quote
+			.amounts_out
+			.into_iter()
+			.map(try_into_rpc_balance)
+			.collect::<RpcResult<_>>()?,
+		amount_out: try_into_rpc_balance(quote.amount_out)?,
+	})
+}
+
+fn try_into_rpc_balance<
+	Balance: Codec + TryInto<NumberOrHex> + MaybeDisplay + Copy + std::fmt::Debug,
+>(
+	value: Balance,
+) -> RpcResult<NumberOrHex> {
+	value.try_into().map_err(|_| {
+		CallError::Custom(ErrorObject::owned(
+			Error::RuntimeError.into(),
+			"error in swap router pallet",
+			Some("transfer into rpc balance".to_string()),
+		))
+		.into()
+	})
+}
+
+/// Error type of this RPC api.
+pub enum Error {
+	/// The call to runtime failed.
+	RuntimeError,
+}
+
+impl From<Error> for i32 {
+	fn from(e: Error) -> i32 {
+		match e {
+			Error::RuntimeError => 1,
+		}
+	}
+}
+
+fn runtime_error_into_rpc_err(err: impl std::fmt::Display) -> JsonRpseeError {
+	CallError::Custom(ErrorObject::owned(
+		Error::RuntimeError.into(),
+		"error in swap router pallet",
+		Some(err.to_string()),
+	))
+	.into()
+}
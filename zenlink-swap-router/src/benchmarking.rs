@@ -32,6 +32,75 @@ fn token2<CurrencyId: TryFrom<u64> + Default>() -> CurrencyId {
 	CurrencyId::try_from(514u64).unwrap_or_default()
 }
 
+fn setup_pools<T>(caller: &T::AccountId)
+where
+	T: Config + zenlink_protocol::Config + zenlink_stable_amm::Config,
+	<T as zenlink_stable_amm::Config>::CurrencyId: TryFrom<u64> + Default,
+	<T as zenlink_protocol::Config>::AssetId: From<AssetId>,
+{
+	assert_ok!(<T as zenlink_protocol::Config>::MultiAssetsHandler::deposit(
+		ASSET_0.into(),
+		caller,
+		1000 * UNIT
+	));
+	assert_ok!(<T as zenlink_protocol::Config>::MultiAssetsHandler::deposit(
+		ASSET_1.into(),
+		caller,
+		1000 * UNIT
+	));
+
+	let stable_token1 = token1::<<T as zenlink_stable_amm::Config>::CurrencyId>();
+	let stable_token2 = token2::<<T as zenlink_stable_amm::Config>::CurrencyId>();
+
+	assert_ok!(<T as zenlink_stable_amm::Config>::MultiCurrency::deposit(
+		stable_token1,
+		caller,
+		1000 * UNIT
+	));
+	assert_ok!(<T as zenlink_stable_amm::Config>::MultiCurrency::deposit(
+		stable_token2,
+		caller,
+		1000 * UNIT
+	));
+
+	assert_ok!(StableAmmPallet::<T>::create_base_pool(
+		(RawOrigin::Root).into(),
+		[stable_token1, stable_token2].to_vec(),
+		[12, 12].to_vec(),
+		INITIAL_A_VALUE,
+		SWAP_FEE,
+		ADMIN_FEE,
+		caller.clone(),
+		Vec::from("stable_pool_lp_0")
+	));
+
+	assert_ok!(NormalAmmPallet::<T>::create_pair(
+		(RawOrigin::Root).into(),
+		ASSET_0.into(),
+		ASSET_1.into(),
+	));
+
+	assert_ok!(NormalAmmPallet::<T>::add_liquidity(
+		RawOrigin::Signed(caller.clone()).into(),
+		ASSET_0.into(),
+		ASSET_1.into(),
+		100 * UNIT,
+		100 * UNIT,
+		0,
+		0,
+		100u32.into()
+	));
+
+	assert_ok!(StableAmmPallet::<T>::add_liquidity(
+		RawOrigin::Signed(caller.clone()).into(),
+		0u32.into(),
+		[10 * UNIT, 10 * UNIT].to_vec(),
+		0,
+		caller.clone(),
+		1000u32.into()
+	));
+}
+
 benchmarks! {
 	where_clause { where T: Config + zenlink_protocol::Config + zenlink_stable_amm::Config,
 						<T as zenlink_stable_amm::Config>::CurrencyId: TryFrom<u64> + Default,
@@ -42,53 +111,7 @@ benchmarks! {
 
 	swap_exact_token_for_tokens_through_stable_pool{
 		let caller: T::AccountId = whitelisted_caller();
-
-		assert_ok!(<T as zenlink_protocol::Config>::MultiAssetsHandler::deposit(ASSET_0.into(), &caller, 1000 * UNIT));
-		assert_ok!(<T as zenlink_protocol::Config>::MultiAssetsHandler::deposit(ASSET_1.into(), &caller, 1000 * UNIT));
-
-		let stable_token1 = token1::<<T as zenlink_stable_amm::Config>::CurrencyId>();
-		let stable_token2 = token2::<<T as zenlink_stable_amm::Config>::CurrencyId>();
-
-		assert_ok!(<T as zenlink_stable_amm::Config>::MultiCurrency::deposit(stable_token1, &caller, 1000 * UNIT));
-		assert_ok!(<T as zenlink_stable_amm::Config>::MultiCurrency::deposit(stable_token2, &caller, 1000 * UNIT));
-
-		assert_ok!(StableAmmPallet::<T>::create_base_pool(
-			(RawOrigin::Root).into(),
-			[stable_token1, stable_token2].to_vec(),
-			[12,12].to_vec(),
-			INITIAL_A_VALUE,
-			SWAP_FEE,
-			ADMIN_FEE,
-			caller.clone(),
-			Vec::from("stable_pool_lp_0")
-		));
-
-		assert_ok!(NormalAmmPallet::<T>::create_pair(
-			(RawOrigin::Root).into(),
-			ASSET_0.into(),
-			ASSET_1.into(),
-		));
-
-
-		assert_ok!(NormalAmmPallet::<T>::add_liquidity(
-			RawOrigin::Signed(caller.clone()).into(),
-			ASSET_0.into(),
-			ASSET_1.into(),
-			100 * UNIT,
-			100 * UNIT,
-			0,
-			0,
-			100u32.into()
-		));
-
-		assert_ok!(StableAmmPallet::<T>::add_liquidity(
-			RawOrigin::Signed(caller.clone()).into(),
-			0u32.into(),
-			[10*UNIT, 10*UNIT].to_vec(),
-			0,
-			caller.clone(),
-			1000u32.into()
-		));
+		setup_pools::<T>(&caller);
 
 		let router_stable_token1 = token1::<<T as Config>::StableCurrencyId>();
 		let router_stable_token2 = token2::<<T as Config>::StableCurrencyId>();
@@ -98,16 +121,109 @@ benchmarks! {
 		(100u32).into(),
 		0u32.into(),
 		vec![
-			Route::Normal([ASSET_1.into(), ASSET_0.into()].to_vec()),
+			Route::Normal([ASSET_1.into(), ASSET_0.into()].to_vec(), None),
 			Route::Stable(StablePath::<T::StablePoolId, <T as Config>::StableCurrencyId> {
 				pool_id: 0u32.into(),
 				base_pool_id: 0u32.into(),
 				mode: Single,
 				from_currency: router_stable_token2,
 				to_currency: router_stable_token1,
+				min_amount_out: None,
 			}),
 		],
 		caller.clone(),
 		1000u32.into()
 	)
+
+	swap_tokens_for_exact_tokens_through_stable_pool{
+		let h in 1 .. 4;
+
+		let caller: T::AccountId = whitelisted_caller();
+		setup_pools::<T>(&caller);
+
+		let router_stable_token1 = token1::<<T as Config>::StableCurrencyId>();
+		let router_stable_token2 = token2::<<T as Config>::StableCurrencyId>();
+
+		// swap back and forth through the stable pool
+		let mut routes = vec![Route::Normal([ASSET_1.into(), ASSET_0.into()].to_vec(), None)];
+		for i in 0..h {
+			let (from_currency, to_currency) = if i % 2 == 0 {
+				(router_stable_token2, router_stable_token1)
+			} else {
+				(router_stable_token1, router_stable_token2)
+			};
+			routes.push(Route::Stable(StablePath::<T::StablePoolId, <T as Config>::StableCurrencyId> {
+				pool_id: 0u32.into(),
+				base_pool_id: 0u32.into(),
+				mode: Single,
+				from_currency,
+				to_currency,
+				min_amount_out: None,
+			}));
+		}
+
+	 }:_(
+		RawOrigin::Signed(caller.clone()),
+		(100u32).into(),
+		(1000u32).into(),
+		routes,
+		caller.clone(),
+		1000u32.into()
+	)
+
+	stable_amount_in{
+		let caller: T::AccountId = whitelisted_caller();
+		setup_pools::<T>(&caller);
+
+		let path = StablePath::<T::StablePoolId, <T as Config>::StableCurrencyId> {
+			pool_id: 0u32.into(),
+			base_pool_id: 0u32.into(),
+			mode: Single,
+			from_currency: token2::<<T as Config>::StableCurrencyId>(),
+			to_currency: token1::<<T as Config>::StableCurrencyId>(),
+			min_amount_out: None,
+		};
+		let amount_in = <T as Config>::Balance::from(UNIT);
+
+	 }: {
+		// as many quotes as the search of the input of a stable path may take
+		for _ in 0..MAX_AMOUNT_IN_QUOTES {
+			assert!(Pallet::<T>::quote_stable_path(&path, amount_in).is_some());
+		}
+	}
+
+	swap_exact_token_for_tokens_through_split_routes{
+		let s in 1 .. T::MaxSplits::get();
+		// the hops of all the splits, those beyond two per split taken by the first one
+		let h in (2 * T::MaxSplits::get()) .. (4 * T::MaxSplits::get());
+
+		let caller: T::AccountId = whitelisted_caller();
+		setup_pools::<T>(&caller);
+
+		let router_stable_token1 = token1::<<T as Config>::StableCurrencyId>();
+		let router_stable_token2 = token2::<<T as Config>::StableCurrencyId>();
+		let stable_route = Route::Stable(StablePath::<T::StablePoolId, <T as Config>::StableCurrencyId> {
+			pool_id: 0u32.into(),
+			base_pool_id: 0u32.into(),
+			mode: Single,
+			from_currency: router_stable_token2,
+			to_currency: router_stable_token1,
+			min_amount_out: None,
+		});
+
+		let splits = (0..s).map(|i| {
+			let mut routes = vec![Route::Normal([ASSET_1.into(), ASSET_0.into()].to_vec(), None)];
+			let stable_hops = if i == 0 { h - 2 * s + 1 } else { 1 };
+			routes.extend((0..stable_hops).map(|_| stable_route.clone()));
+			SplitRoutes { share: SplitShare::Percent(Perbill::from_rational(1, s)), routes }
+		}).collect::<Vec<_>>();
+
+	 }:_(
+		RawOrigin::Signed(caller.clone()),
+		(100u32 * s).into(),
+		0u32.into(),
+		splits,
+		caller.clone(),
+		1000u32.into()
+	)
 }
@@ -11,14 +11,26 @@ mod mock;
 
 #[cfg(any(feature = "runtime-benchmarks", test))]
 pub mod benchmarking;
+mod path_finder;
+mod registry;
+mod split;
 #[cfg(test)]
 mod test;
 pub mod weights;
+pub use path_finder::RouteQuote;
+pub use registry::{CurrencyConverter, PoolRegistry, Venue};
+pub use split::{SplitQuote, SplitRoutes, SplitShare};
 pub use weights::WeightInfo;
 
 use codec::{Decode, Encode};
+#[cfg(feature = "std")]
+use serde::{Deserialize, Serialize};
 
-use sp_runtime::traits::{AtLeast32BitUnsigned, One, Zero};
+use sp_runtime::{
+	helpers_128bit::multiply_by_rational_with_rounding,
+	traits::{AtLeast32BitUnsigned, One, Zero},
+	Perbill, Rounding,
+};
 use sp_std::{fmt::Debug, vec::Vec};
 
 use frame_support::{
@@ -30,16 +42,28 @@ use frame_support::{
 use zenlink_protocol::{AssetBalance, ExportZenlink};
 use zenlink_stable_amm::traits::StableAmmApi;
 
+/// The most quotes the search of the input of a stable path in an exact-output swap may take.
+pub const MAX_AMOUNT_IN_QUOTES: u32 = 128;
+
+/// The search of the input of a stable path stops once it is known within a
+/// `AMOUNT_IN_PRECISION`th.
+pub const AMOUNT_IN_PRECISION: AssetBalance = 1_000_000_000_000;
+
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
 pub struct StablePath<PoolId, CurrencyId> {
 	pub pool_id: PoolId,
 	pub base_pool_id: PoolId,
 	pub mode: StableSwapMode,
 	pub from_currency: CurrencyId,
 	pub to_currency: CurrencyId,
+	// the least output of the path, none for no minimum
+	pub min_amount_out: Option<AssetBalance>,
 }
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum StableSwapMode {
 	Single,
 	FromBase,
@@ -47,9 +71,37 @@ pub enum StableSwapMode {
 }
 
 #[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum VaultDirection {
+	/// Deposit the underlying currency for vault shares.
+	Deposit,
+	/// Redeem vault shares for the underlying currency.
+	Redeem,
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
 pub enum Route<PoolId, StableCurrencyId, NormalCurrencyId> {
 	Stable(StablePath<PoolId, StableCurrencyId>),
-	Normal(Vec<NormalCurrencyId>),
+	/// A path of zenlink-protocol pairs and the least output of it, none for no minimum.
+	Normal(Vec<NormalCurrencyId>, Option<AssetBalance>),
+	/// A deposit into or a redemption from the vault of `underlying` in zenlink-vault.
+	Vault {
+		underlying: StableCurrencyId,
+		direction: VaultDirection,
+	},
+}
+
+impl<PoolId, StableCurrencyId, NormalCurrencyId> Route<PoolId, StableCurrencyId, NormalCurrencyId> {
+	/// The least output of the route, zero if it has no minimum.
+	pub fn min_amount_out(&self) -> AssetBalance {
+		match self {
+			Route::Stable(stable_path) => stable_path.min_amount_out,
+			Route::Normal(_, min_amount_out) => *min_amount_out,
+			Route::Vault { .. } => None,
+		}
+		.unwrap_or_default()
+	}
 }
 
 pub use pallet::*;
@@ -108,6 +160,17 @@ pub mod pallet {
 
 		type NormalAmm: ExportZenlink<AccountIdOf<Self>, Self::NormalCurrencyId>;
 
+		/// Converts between the currency ids of the standard amm and the stable amm.
+		type CurrencyConverter: CurrencyConverter<Self::NormalCurrencyId, Self::StableCurrencyId>;
+
+		/// The most routes a discovered swap may go through.
+		#[pallet::constant]
+		type MaxHops: Get<u32>;
+
+		/// The most lists of routes a split swap may go through.
+		#[pallet::constant]
+		type MaxSplits: Get<u32>;
+
 		type StableAMM: StableAmmApi<
 			Self::StablePoolId,
 			Self::StableCurrencyId,
@@ -115,6 +178,12 @@ pub mod pallet {
 			Self::Balance,
 		>;
 
+		/// The runtime of zenlink-vault, whose vaults hold stable amm currencies.
+		type VaultRuntime: zenlink_vault::Config<AssetId = Self::StableCurrencyId>
+			+ frame_system::Config<AccountId = AccountIdOf<Self>>;
+
+		type Vault: zenlink_vault::Vault<Self::VaultRuntime>;
+
 		/// Weight information for extrinsics in this pallet.
 		type WeightInfo: WeightInfo;
 	}
@@ -124,7 +193,17 @@ pub mod pallet {
 	pub struct Pallet<T>(_);
 
 	#[pallet::event]
-	pub enum Event<T: Config> {}
+	#[pallet::generate_deposit(pub(super) fn deposit_event)]
+	pub enum Event<T: Config> {
+		/// Swapped `amount_in` through `routes` into `amount_out` for `to`.
+		RouterSwap {
+			who: T::AccountId,
+			routes: Vec<Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>>,
+			amount_in: T::Balance,
+			amount_out: T::Balance,
+			to: T::AccountId,
+		},
+	}
 
 	#[pallet::error]
 	pub enum Error<T> {
@@ -134,12 +213,15 @@ pub mod pallet {
 		ConvertCurrencyFailed,
 		AmountSlippage,
 		InvalidPath,
+		ExcessiveSoldAmount,
+		InvalidSplits,
 	}
 
 	#[pallet::call]
 	impl<T: Config> Pallet<T> {
 		#[pallet::call_index(0)]
-		#[pallet::weight(T::WeightInfo::swap_exact_token_for_tokens_through_stable_pool())]
+		#[pallet::weight(T::WeightInfo::swap_exact_token_for_tokens_through_stable_pool()
+			.saturating_add(Pallet::<T>::vault_routes_weight(routes)))]
 		#[transactional]
 		pub fn swap_exact_token_for_tokens_through_stable_pool(
 			origin: OriginFor<T>,
@@ -154,27 +236,130 @@ pub mod pallet {
 			let now = frame_system::Pallet::<T>::block_number();
 			ensure!(deadline > now, Error::<T>::Deadline);
 
-			let mut amount_out = amount_in;
-			let mut receiver = who.clone();
+			let amount_out = Self::swap_through_routes(&who, amount_in, &routes, &to)?;
+			ensure!(amount_out >= amount_out_min, Error::<T>::AmountSlippage);
+
+			Ok(())
+		}
+
+		/// Swap as little as needed to receive `amount_out` through `routes`, spending at most
+		/// `amount_in_max`.
+		///
+		/// The input of every route is computed backwards from `amount_out`, so only the input of
+		/// the first route is taken from the caller. As the search of the input of a stable route
+		/// may overshoot, `to` can receive slightly more than `amount_out`. Vault routes are not
+		/// supported, as redeeming charges a withdraw penalty that depends on the reserve of the
+		/// vault.
+		#[pallet::call_index(1)]
+		#[pallet::weight({
+			let stable_hops =
+				routes.iter().filter(|route| matches!(route, Route::Stable(_))).count() as u32;
+			T::WeightInfo::swap_tokens_for_exact_tokens_through_stable_pool(stable_hops)
+				.saturating_add(T::WeightInfo::stable_amount_in().saturating_mul(stable_hops as u64))
+		})]
+		#[transactional]
+		pub fn swap_tokens_for_exact_tokens_through_stable_pool(
+			origin: OriginFor<T>,
+			amount_out: T::Balance,
+			amount_in_max: T::Balance,
+			routes: Vec<Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>>,
+			to: T::AccountId,
+			deadline: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(deadline > now, Error::<T>::Deadline);
+			ensure!(!routes.is_empty(), Error::<T>::InvalidRoutes);
+
+			let amounts = Self::get_amount_in_by_routes(&routes, amount_out)?;
+			ensure!(amounts[0] <= amount_in_max, Error::<T>::ExcessiveSoldAmount);
 
+			// every route spends all the previous one returned, at least its computed input, so
+			// what a stable route returns beyond its searched output reaches `to` instead of
+			// being left with the caller
+			let mut amount_received = amounts[0];
 			for (i, route) in routes.iter().enumerate() {
-				if i == routes.len() - 1 {
-					receiver = to.clone();
-				}
+				ensure!(
+					amounts[i + 1] >= T::Balance::from(route.min_amount_out()),
+					Error::<T>::AmountSlippage
+				);
+				let receiver = if i == routes.len() - 1 { &to } else { &who };
 				match route {
 					Route::Stable(stable_path) => {
-						(amount_out) = Self::stable_swap(&who, stable_path, amount_out, &receiver)?;
+						amount_received = Self::stable_swap(
+							&who,
+							stable_path,
+							amount_received,
+							amounts[i + 1],
+							receiver,
+						)?;
 					},
-					Route::Normal(path) => {
-						let amounts =
-							T::NormalAmm::get_amount_out_by_path(amount_out.into(), path)?;
-						Self::swap(&who, amount_out, path, &receiver)?;
-						amount_out =
-							T::Balance::from(*amounts.last().ok_or(Error::<T>::InvalidPath)?);
+					Route::Normal(path, _) => {
+						let amounts_out =
+							T::NormalAmm::get_amount_out_by_path(amount_received.into(), path)?;
+						Self::swap(&who, amount_received, amounts[i + 1], path, receiver)?;
+						amount_received =
+							T::Balance::from(*amounts_out.last().ok_or(Error::<T>::InvalidPath)?);
 					},
+					Route::Vault { .. } => return Err(Error::<T>::InvalidRoutes.into()),
 				}
 			}
 
+			Self::deposit_event(Event::RouterSwap {
+				who,
+				routes,
+				amount_in: amounts[0],
+				amount_out: amount_received,
+				to,
+			});
+			Ok(())
+		}
+
+		/// Swap `amount_in` through several lists of routes at once, each taking its share of the
+		/// input.
+		///
+		/// The rounding left by the percent shares goes to the last of them. All the lists must
+		/// start from and end in the same currency, so `amount_out_min` applies to their combined
+		/// output. The call is weighed by the routes of all the lists, vault ones included.
+		#[pallet::call_index(2)]
+		#[pallet::weight(T::WeightInfo::swap_exact_token_for_tokens_through_split_routes(
+			splits.len() as u32,
+			splits.iter().map(|split| split.routes.len() as u32).sum::<u32>()
+		)
+		.saturating_add(splits.iter().fold(Weight::zero(), |weight, split| {
+			weight.saturating_add(Pallet::<T>::vault_routes_weight(&split.routes))
+		})))]
+		#[transactional]
+		pub fn swap_exact_token_for_tokens_through_split_routes(
+			origin: OriginFor<T>,
+			amount_in: T::Balance,
+			amount_out_min: T::Balance,
+			splits: Vec<
+				SplitRoutes<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId, T::Balance>,
+			>,
+			to: T::AccountId,
+			deadline: T::BlockNumber,
+		) -> DispatchResult {
+			let who = ensure_signed(origin)?;
+
+			let now = frame_system::Pallet::<T>::block_number();
+			ensure!(deadline > now, Error::<T>::Deadline);
+			ensure!(
+				!splits.is_empty() && splits.len() as u32 <= T::MaxSplits::get(),
+				Error::<T>::InvalidSplits
+			);
+			Self::validate_splits(&splits)?;
+
+			let amounts = Self::split_amounts(amount_in, &splits)?;
+			let mut amount_out = T::Balance::zero();
+			for (split, amount) in splits.iter().zip(amounts) {
+				if amount.is_zero() {
+					continue
+				}
+				let split_out = Self::swap_through_routes(&who, amount, &split.routes, &to)?;
+				amount_out = amount_out.saturating_add(split_out);
+			}
 			ensure!(amount_out >= amount_out_min, Error::<T>::AmountSlippage);
 
 			Ok(())
@@ -183,10 +368,158 @@ pub mod pallet {
 }
 
 impl<T: Config> Pallet<T> {
+	/// The weight of the vault routes in `routes`, which the benchmarked weights do not cover.
+	pub(crate) fn vault_routes_weight(
+		routes: &[Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>],
+	) -> Weight {
+		routes.iter().fold(Weight::zero(), |weight, route| match route {
+			Route::Vault { direction: VaultDirection::Deposit, .. } =>
+				weight.saturating_add(zenlink_vault::Pallet::<T::VaultRuntime>::deposit_weight()),
+			Route::Vault { direction: VaultDirection::Redeem, .. } =>
+				weight.saturating_add(zenlink_vault::Pallet::<T::VaultRuntime>::redeem_weight()),
+			_ => weight,
+		})
+	}
+
+	/// Swap `amount_in` through `routes` in order, the output of the last one going to `to`.
+	///
+	/// Every route must return at least its own minimum.
+	fn swap_through_routes(
+		who: &T::AccountId,
+		amount_in: T::Balance,
+		routes: &[Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>],
+		to: &T::AccountId,
+	) -> Result<T::Balance, DispatchError> {
+		ensure!(!routes.is_empty(), Error::<T>::InvalidRoutes);
+
+		let mut amount_out = amount_in;
+		let mut receiver = who;
+
+		for (i, route) in routes.iter().enumerate() {
+			if i == routes.len() - 1 {
+				receiver = to;
+			}
+			let min_amount_out = T::Balance::from(route.min_amount_out());
+			match route {
+				Route::Stable(stable_path) => {
+					(amount_out) =
+						Self::stable_swap(who, stable_path, amount_out, min_amount_out, receiver)?;
+				},
+				Route::Normal(path, _) => {
+					let amounts = T::NormalAmm::get_amount_out_by_path(amount_out.into(), path)?;
+					Self::swap(who, amount_out, min_amount_out, path, receiver)?;
+					amount_out = T::Balance::from(*amounts.last().ok_or(Error::<T>::InvalidPath)?);
+				},
+				Route::Vault { underlying, direction } => {
+					amount_out =
+						Self::vault_swap(who, *underlying, direction, amount_out, receiver)?;
+				},
+			}
+		}
+
+		Self::deposit_event(Event::RouterSwap {
+			who: who.clone(),
+			routes: routes.to_vec(),
+			amount_in,
+			amount_out,
+			to: to.clone(),
+		});
+		Ok(amount_out)
+	}
+
+	/// The input of every route needed to receive `amount_out` through `routes`, followed by
+	/// `amount_out`.
+	pub fn get_amount_in_by_routes(
+		routes: &[Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>],
+		amount_out: T::Balance,
+	) -> Result<Vec<T::Balance>, DispatchError> {
+		let mut amounts = Vec::from([amount_out]);
+		let mut amount = amount_out;
+		for route in routes.iter().rev() {
+			amount = match route {
+				Route::Stable(stable_path) =>
+					Self::stable_amount_in(stable_path, amount).ok_or(Error::<T>::InvalidRoutes)?,
+				Route::Normal(path, _) => {
+					let amounts_in = T::NormalAmm::get_amount_in_by_path(amount.into(), path)?;
+					T::Balance::from(*amounts_in.first().ok_or(Error::<T>::InvalidPath)?)
+				},
+				Route::Vault { .. } => return Err(Error::<T>::InvalidRoutes.into()),
+			};
+			amounts.push(amount);
+		}
+		amounts.reverse();
+		Ok(amounts)
+	}
+
+	/// The input of a stable path returning `amount_out`. The stable amm only quotes outputs, so
+	/// the input is searched over at most `MAX_AMOUNT_IN_QUOTES` of them. The input found exceeds
+	/// the least one by at most a `AMOUNT_IN_PRECISION`th of it.
+	fn stable_amount_in(
+		path: &StablePath<T::StablePoolId, T::StableCurrencyId>,
+		amount_out: T::Balance,
+	) -> Option<T::Balance> {
+		let mut quotes = 0u32;
+		let mut returns = |amount_in: T::Balance| -> Option<bool> {
+			quotes += 1;
+			if quotes > MAX_AMOUNT_IN_QUOTES {
+				return None
+			}
+			Some(Self::quote_stable_path(path, amount_in).map_or(false, |out| out >= amount_out))
+		};
+
+		// start from the input the rate of the path at `amount_out` gives
+		let rate_out: AssetBalance =
+			Self::quote_stable_path(path, amount_out).unwrap_or_default().into();
+		let guess = if rate_out.is_zero() {
+			amount_out
+		} else {
+			T::Balance::from(multiply_by_rational_with_rounding(
+				amount_out.into(),
+				amount_out.into(),
+				rate_out,
+				Rounding::Up,
+			)?)
+		}
+		.max(One::one());
+
+		let two = T::Balance::from(2u32);
+		let (mut low, mut high) = if returns(guess)? {
+			let mut high = guess;
+			loop {
+				let low = high / two;
+				if low.is_zero() || !returns(low)? {
+					break (low, high)
+				}
+				high = low;
+			}
+		} else {
+			let mut low = guess;
+			loop {
+				let high = low.checked_mul(&two)?;
+				if returns(high)? {
+					break (low, high)
+				}
+				low = high;
+			}
+		};
+
+		let precision = T::Balance::from(AMOUNT_IN_PRECISION);
+		while high - low > (high / precision).max(One::one()) {
+			let mid = low + (high - low) / two;
+			if returns(mid)? {
+				high = mid;
+			} else {
+				low = mid;
+			}
+		}
+		Some(high)
+	}
+
 	fn stable_swap(
 		who: &T::AccountId,
 		path: &StablePath<T::StablePoolId, T::StableCurrencyId>,
 		amount_in: T::Balance,
+		amount_out_min: T::Balance,
 		to: &T::AccountId,
 	) -> Result<T::Balance, DispatchError> {
 		let out_amount = match path.mode {
@@ -201,7 +534,7 @@ impl<T: Config> Pallet<T> {
 					from_index,
 					to_index,
 					amount_in,
-					Zero::zero(),
+					amount_out_min,
 					to,
 				)?
 			},
@@ -218,7 +551,7 @@ impl<T: Config> Pallet<T> {
 					from_index,
 					to_index,
 					amount_in,
-					Zero::zero(),
+					amount_out_min,
 					to,
 				)?
 			},
@@ -234,7 +567,7 @@ impl<T: Config> Pallet<T> {
 					from_index,
 					to_index,
 					amount_in,
-					Zero::zero(),
+					amount_out_min,
 					to,
 				)?
 			},
@@ -242,16 +575,31 @@ impl<T: Config> Pallet<T> {
 		Ok(out_amount)
 	}
 
+	fn vault_swap(
+		who: &T::AccountId,
+		underlying: T::StableCurrencyId,
+		direction: &VaultDirection,
+		amount_in: T::Balance,
+		to: &T::AccountId,
+	) -> Result<T::Balance, DispatchError> {
+		let amount_out = match direction {
+			VaultDirection::Deposit => T::Vault::deposit(who, underlying, amount_in.into(), to)?,
+			VaultDirection::Redeem => T::Vault::redeem(who, underlying, amount_in.into(), to)?,
+		};
+		Ok(T::Balance::from(amount_out))
+	}
+
 	fn swap(
 		who: &T::AccountId,
 		amount_in: T::Balance,
+		amount_out_min: T::Balance,
 		path: &[T::NormalCurrencyId],
 		to: &T::AccountId,
 	) -> DispatchResult {
 		T::NormalAmm::inner_swap_exact_assets_for_assets(
 			who,
 			amount_in.into(),
-			Zero::zero(),
+			amount_out_min.into(),
 			path,
 			to,
 		)
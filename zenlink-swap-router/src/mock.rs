@@ -23,13 +23,13 @@ use sp_runtime::{
 };
 
 use crate as router;
-use crate::{Config, Pallet};
+use crate::{Config, CurrencyConverter, Pallet};
 use orml_traits::{parameter_type_with_key, MultiCurrency};
 use zenlink_protocol::{
-	AssetBalance, AssetId, LocalAssetHandler, PairLpGenerate, ZenlinkMultiAssets,
-	LOCAL,
+	AssetBalance, AssetId, LocalAssetHandler, PairLpGenerate, ZenlinkMultiAssets, LOCAL,
 };
 use zenlink_stable_amm::traits::{StablePoolLpCurrencyIdGenerate, ValidateCurrency};
+use zenlink_vault::VaultAssetGenerate;
 
 type UncheckedExtrinsic = frame_system::mocking::MockUncheckedExtrinsic<Test>;
 type Block = frame_system::mocking::MockBlock<Test>;
@@ -44,7 +44,10 @@ parameter_types! {
 	pub const MaxLocks:u32 = 50;
 	pub const MinimumPeriod: Moment = SLOT_DURATION / 2;
 	pub const PoolCurrencySymbolLimit: u32 = 50;
+	pub const OracleEmaPeriod: u64 = 600;
 	pub SelfParaId: u32 = CHAIN_ID;
+	pub const VaultPalletId: PalletId = PalletId(*b"/zlkVaut");
+	pub const VaultBlocksPerYear: u64 = 2_628_000;
 }
 
 parameter_type_with_key! {
@@ -84,6 +87,7 @@ pub enum CurrencyId {
 	StableLP(PoolType),
 	StableLPV2(PoolId),
 	ZenlinkLp(TokenSymbol, TokenSymbol),
+	VaultToken(TokenSymbol),
 }
 
 #[derive(
@@ -206,6 +210,7 @@ impl zenlink_stable_amm::Config for Test {
 	type LpGenerate = PoolLpGenerate;
 	type TimeProvider = Timestamp;
 	type PoolCurrencySymbolLimit = PoolCurrencySymbolLimit;
+	type OracleEmaPeriod = OracleEmaPeriod;
 	type PalletId = StableAmmPalletId;
 	type WeightInfo = ();
 }
@@ -229,7 +234,34 @@ impl Config for Test {
 	type StableCurrencyId = CurrencyId;
 	type NormalCurrencyId = AssetId;
 	type NormalAmm = Zenlink;
+	type CurrencyConverter = AssetConverter;
+	type MaxHops = frame_support::traits::ConstU32<3>;
+	type MaxSplits = frame_support::traits::ConstU32<4>;
 	type StableAMM = StableAMM;
+	type VaultRuntime = Test;
+	type Vault = Vault;
+	type WeightInfo = ();
+}
+
+pub struct VaultAssetGenerator;
+
+impl VaultAssetGenerate<CurrencyId> for VaultAssetGenerator {
+	fn generate(asset: CurrencyId) -> Option<CurrencyId> {
+		match asset {
+			CurrencyId::Token(symbol) => Some(CurrencyId::VaultToken(symbol)),
+			_ => None,
+		}
+	}
+}
+
+impl zenlink_vault::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type AssetId = CurrencyId;
+	type MultiAsset = Tokens;
+	type VaultAssetGenerate = VaultAssetGenerator;
+	type Strategy = ();
+	type BlocksPerYear = VaultBlocksPerYear;
+	type PalletId = VaultPalletId;
 	type WeightInfo = ();
 }
 
@@ -281,6 +313,22 @@ pub fn asset_id_to_currency_id(asset_id: &AssetId) -> Result<CurrencyId, ()> {
 	}
 }
 
+pub struct AssetConverter;
+
+impl CurrencyConverter<AssetId, CurrencyId> for AssetConverter {
+	fn to_stable(asset_id: AssetId) -> Option<CurrencyId> {
+		asset_id_to_currency_id(&asset_id).ok()
+	}
+
+	fn to_normal(currency_id: CurrencyId) -> Option<AssetId> {
+		match currency_id {
+			CurrencyId::Token(symbol) =>
+				Some(AssetId { chain_id: CHAIN_ID, asset_type: LOCAL, asset_index: symbol as u64 }),
+			_ => None,
+		}
+	}
+}
+
 pub struct LocalAssetAdaptor<Local>(PhantomData<Local>);
 
 impl<Local> LocalAssetHandler<AccountId> for LocalAssetAdaptor<Local>
@@ -349,6 +397,7 @@ frame_support::construct_runtime!(
 		Tokens: orml_tokens::{Pallet, Storage, Event<T>, Config<T>} = 11,
 		Zenlink: zenlink_protocol::{Pallet, Call, Storage, Event<T>} = 12,
 		Router: router::{Pallet, Call, Event<T>} = 13,
+		Vault: zenlink_vault::{Pallet, Call, Storage, Event<T>} = 14,
 	}
 );
 
@@ -378,6 +427,9 @@ pub const TOKEN1_ASSET_ID: AssetId =
 pub const TOKEN2_ASSET_ID: AssetId =
 	AssetId { chain_id: CHAIN_ID, asset_type: LOCAL, asset_index: 2 };
 
+pub const TOKEN4_ASSET_ID: AssetId =
+	AssetId { chain_id: CHAIN_ID, asset_type: LOCAL, asset_index: 4 };
+
 pub fn new_test_ext() -> sp_io::TestExternalities {
 	let mut t = frame_system::GenesisConfig::default().build_storage::<Test>().unwrap().into();
 	pallet_balances::GenesisConfig::<Test> { balances: vec![(USER1, u128::MAX)] }
@@ -0,0 +1,206 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+
+/// The price impact is measured against swapping this fraction of the input through the same
+/// routes.
+const PRICE_IMPACT_PROBE: AssetBalance = 1_000;
+
+/// The routes found for a swap and what they are expected to return.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct RouteQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance> {
+	pub routes: Vec<Route<PoolId, StableCurrencyId, NormalCurrencyId>>,
+	// the input amount followed by the output of every route
+	pub amounts: Vec<Balance>,
+	pub amount_out: Balance,
+	// the share of the output lost to the depth of the pools, fees excluded
+	pub price_impact: Perbill,
+}
+
+type Routes<T> = Vec<
+	Route<
+		<T as Config>::StablePoolId,
+		<T as Config>::StableCurrencyId,
+		<T as Config>::NormalCurrencyId,
+	>,
+>;
+
+impl<T: Config> Pallet<T> {
+	/// Search the routes of at most `MaxHops` hops through the pairs and stable pools, each used
+	/// once, that swap `amount_in` of `from` into the most of `to`.
+	pub fn find_best_route(
+		from: T::NormalCurrencyId,
+		to: T::NormalCurrencyId,
+		amount_in: T::Balance,
+	) -> Option<RouteQuote<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId, T::Balance>> {
+		if from == to || amount_in.is_zero() {
+			return None
+		}
+
+		let mut best = None;
+		Self::search_routes(
+			to,
+			T::MaxHops::get(),
+			&mut Vec::from([from]),
+			&mut Vec::new(),
+			&mut Vec::from([amount_in]),
+			&mut best,
+		);
+
+		let (routes, amounts) = best?;
+		let amount_out = *amounts.last()?;
+		let price_impact = Self::price_impact(&routes, amount_in, amount_out);
+		Some(RouteQuote { routes, amounts, amount_out, price_impact })
+	}
+
+	/// Depth first search from the last visited asset, keeping the routes with the largest
+	/// output in `best`.
+	fn search_routes(
+		to: T::NormalCurrencyId,
+		hops_left: u32,
+		visited: &mut Vec<T::NormalCurrencyId>,
+		routes: &mut Routes<T>,
+		amounts: &mut Vec<T::Balance>,
+		best: &mut Option<(Routes<T>, Vec<T::Balance>)>,
+	) {
+		if hops_left.is_zero() {
+			return
+		}
+		let (asset, amount) = match (visited.last(), amounts.last()) {
+			(Some(asset), Some(amount)) => (*asset, *amount),
+			_ => return,
+		};
+
+		for (route, next) in Self::hops_from(asset) {
+			// a pair is used at most once as its assets are visited, a stable pool is quoted
+			// without the swaps of the earlier routes through it
+			if visited.contains(&next) || routes.iter().any(|used| Self::share_pool(used, &route)) {
+				continue
+			}
+			let out = match Self::quote_route(&route, amount) {
+				Some(out) if !out.is_zero() => out,
+				_ => continue,
+			};
+
+			routes.push(route);
+			amounts.push(out);
+			if next == to {
+				let better = best
+					.as_ref()
+					.and_then(|(_, best_amounts)| best_amounts.last())
+					.map_or(true, |best_out| out > *best_out);
+				if better {
+					*best = Some((routes.clone(), amounts.clone()));
+				}
+			} else {
+				visited.push(next);
+				Self::search_routes(to, hops_left - 1, visited, routes, amounts, best);
+				visited.pop();
+			}
+			routes.pop();
+			amounts.pop();
+		}
+	}
+
+	/// Whether two routes swap through the same stable pool, directly or as the base pool of a
+	/// meta pool.
+	fn share_pool(
+		a: &Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>,
+		b: &Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>,
+	) -> bool {
+		match (a, b) {
+			(Route::Stable(a), Route::Stable(b)) => [a.pool_id, a.base_pool_id]
+				.iter()
+				.any(|pool_id| *pool_id == b.pool_id || *pool_id == b.base_pool_id),
+			_ => false,
+		}
+	}
+
+	/// The single routes out of `asset`, with the asset each of them swaps into.
+	fn hops_from(
+		asset: T::NormalCurrencyId,
+	) -> Vec<(Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>, T::NormalCurrencyId)>
+	{
+		let mut hops: Vec<_> = T::NormalAmm::get_paired_assets(asset)
+			.into_iter()
+			.map(|next| (Route::Normal(Vec::from([asset, next]), None), next))
+			.collect();
+
+		let from_currency = match T::CurrencyConverter::to_stable(asset) {
+			Some(currency_id) => currency_id,
+			None => return hops,
+		};
+		let mut push = |pool_id, base_pool_id, mode, to_currency| {
+			if to_currency == from_currency {
+				return
+			}
+			if let Some(next) = T::CurrencyConverter::to_normal(to_currency) {
+				hops.push((
+					Route::Stable(StablePath {
+						pool_id,
+						base_pool_id,
+						mode,
+						from_currency,
+						to_currency,
+						min_amount_out: None,
+					}),
+					next,
+				));
+			}
+		};
+
+		for pool_id in T::StableAMM::pools_of_currency(from_currency) {
+			for currency_id in T::StableAMM::pool_currencies(pool_id) {
+				push(pool_id, pool_id, StableSwapMode::Single, currency_id);
+			}
+
+			if let Some(base_pool_id) = T::StableAMM::base_pool_id(pool_id) {
+				// the lp currency of the base pool is swapped within the meta pool itself
+				if T::StableAMM::lp_currency(base_pool_id) != Some(from_currency) {
+					for currency_id in T::StableAMM::pool_currencies(base_pool_id) {
+						push(pool_id, base_pool_id, StableSwapMode::ToBase, currency_id);
+					}
+				}
+			}
+
+			let lp_currency_id = match T::StableAMM::lp_currency(pool_id) {
+				Some(currency_id) => currency_id,
+				None => continue,
+			};
+			for meta_pool_id in T::StableAMM::pools_of_currency(lp_currency_id) {
+				if T::StableAMM::base_pool_id(meta_pool_id) != Some(pool_id) {
+					continue
+				}
+				for currency_id in T::StableAMM::pool_currencies(meta_pool_id) {
+					if currency_id != lp_currency_id {
+						push(meta_pool_id, pool_id, StableSwapMode::FromBase, currency_id);
+					}
+				}
+			}
+		}
+		hops
+	}
+
+	/// Compare the output with swapping a small fraction of the input through the same routes,
+	/// which follows the marginal price of the pools.
+	fn price_impact(routes: &Routes<T>, amount_in: T::Balance, amount_out: T::Balance) -> Perbill {
+		let probe = amount_in / T::Balance::from(PRICE_IMPACT_PROBE);
+		if probe.is_zero() {
+			return Perbill::zero()
+		}
+
+		let probe_out: AssetBalance =
+			match routes.iter().try_fold(probe, |amount, route| Self::quote_route(route, amount)) {
+				Some(out) => out.into(),
+				None => return Perbill::zero(),
+			};
+		let expected = probe_out.saturating_mul(PRICE_IMPACT_PROBE);
+		if expected.is_zero() {
+			return Perbill::zero()
+		}
+		Perbill::from_rational(expected.saturating_sub(amount_out.into()), expected)
+	}
+}
@@ -0,0 +1,222 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+
+/// Conversion between the asset ids of zenlink-protocol and the currency ids of the stable amm.
+pub trait CurrencyConverter<NormalCurrencyId, StableCurrencyId> {
+	fn to_stable(asset_id: NormalCurrencyId) -> Option<StableCurrencyId>;
+
+	fn to_normal(currency_id: StableCurrencyId) -> Option<NormalCurrencyId>;
+}
+
+impl<NormalCurrencyId, StableCurrencyId> CurrencyConverter<NormalCurrencyId, StableCurrencyId>
+	for ()
+{
+	fn to_stable(_asset_id: NormalCurrencyId) -> Option<StableCurrencyId> {
+		None
+	}
+
+	fn to_normal(_currency_id: StableCurrencyId) -> Option<NormalCurrencyId> {
+		None
+	}
+}
+
+/// A pool of zenlink-protocol or of the stable amm.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub enum Venue<PoolId, NormalCurrencyId> {
+	/// A trading pair of zenlink-protocol.
+	Pair(NormalCurrencyId, NormalCurrencyId),
+	/// A pool of the stable amm.
+	Stable(PoolId),
+}
+
+/// Registry of the pools of both amms, addressed by the asset ids of zenlink-protocol.
+pub trait PoolRegistry<PoolId, StableCurrencyId, NormalCurrencyId, Balance> {
+	/// The pairs and stable pools holding `asset_id`.
+	fn pools_of_asset(asset_id: NormalCurrencyId) -> Vec<Venue<PoolId, NormalCurrencyId>>;
+
+	/// The single hop route that swaps `amount_in` of `from` into the most of `to`, with its
+	/// output.
+	fn best_venue(
+		from: NormalCurrencyId,
+		to: NormalCurrencyId,
+		amount_in: Balance,
+	) -> Option<(Route<PoolId, StableCurrencyId, NormalCurrencyId>, Balance)>;
+
+	fn to_stable_currency(asset_id: NormalCurrencyId) -> Option<StableCurrencyId>;
+
+	fn to_normal_currency(currency_id: StableCurrencyId) -> Option<NormalCurrencyId>;
+}
+
+impl<T: Config> PoolRegistry<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId, T::Balance>
+	for Pallet<T>
+{
+	fn pools_of_asset(
+		asset_id: T::NormalCurrencyId,
+	) -> Vec<Venue<T::StablePoolId, T::NormalCurrencyId>> {
+		let mut venues: Vec<Venue<T::StablePoolId, T::NormalCurrencyId>> =
+			T::NormalAmm::get_paired_assets(asset_id)
+				.into_iter()
+				.map(|other| Venue::Pair(asset_id, other))
+				.collect();
+
+		if let Some(currency_id) = T::CurrencyConverter::to_stable(asset_id) {
+			venues.extend(
+				T::StableAMM::pools_of_currency(currency_id).into_iter().map(Venue::Stable),
+			);
+		}
+		venues
+	}
+
+	fn best_venue(
+		from: T::NormalCurrencyId,
+		to: T::NormalCurrencyId,
+		amount_in: T::Balance,
+	) -> Option<(Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>, T::Balance)> {
+		let mut routes = Self::stable_routes(from, to);
+		if T::NormalAmm::get_paired_assets(from).contains(&to) {
+			routes.push(Route::Normal(Vec::from([from, to]), None));
+		}
+
+		routes
+			.into_iter()
+			.filter_map(|route| Self::quote_route(&route, amount_in).map(|out| (route, out)))
+			.max_by_key(|(_, out)| *out)
+	}
+
+	fn to_stable_currency(asset_id: T::NormalCurrencyId) -> Option<T::StableCurrencyId> {
+		T::CurrencyConverter::to_stable(asset_id)
+	}
+
+	fn to_normal_currency(currency_id: T::StableCurrencyId) -> Option<T::NormalCurrencyId> {
+		T::CurrencyConverter::to_normal(currency_id)
+	}
+}
+
+impl<T: Config> Pallet<T> {
+	/// The stable routes from `from` to `to` through a single pool, or between a meta pool and
+	/// its base pool.
+	pub(crate) fn stable_routes(
+		from: T::NormalCurrencyId,
+		to: T::NormalCurrencyId,
+	) -> Vec<Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>> {
+		let (from_currency, to_currency) =
+			match (T::CurrencyConverter::to_stable(from), T::CurrencyConverter::to_stable(to)) {
+				(Some(from_currency), Some(to_currency)) => (from_currency, to_currency),
+				_ => return Vec::new(),
+			};
+
+		let holds =
+			|pool_id, currency_id| T::StableAMM::currency_index(pool_id, currency_id).is_some();
+		let path = |pool_id, base_pool_id, mode| {
+			Route::Stable(StablePath {
+				pool_id,
+				base_pool_id,
+				mode,
+				from_currency,
+				to_currency,
+				min_amount_out: None,
+			})
+		};
+
+		let mut routes = Vec::new();
+		for pool_id in T::StableAMM::pools_of_currency(from_currency) {
+			if holds(pool_id, to_currency) {
+				routes.push(path(pool_id, pool_id, StableSwapMode::Single));
+			}
+			if let Some(base_pool_id) = T::StableAMM::base_pool_id(pool_id) {
+				if holds(base_pool_id, to_currency) {
+					routes.push(path(pool_id, base_pool_id, StableSwapMode::ToBase));
+				}
+			}
+		}
+		for pool_id in T::StableAMM::pools_of_currency(to_currency) {
+			if let Some(base_pool_id) = T::StableAMM::base_pool_id(pool_id) {
+				if holds(base_pool_id, from_currency) {
+					routes.push(path(pool_id, base_pool_id, StableSwapMode::FromBase));
+				}
+			}
+		}
+		routes
+	}
+
+	/// The output of every route when swapping `amount_in` through `routes` in order, without
+	/// executing them.
+	pub fn quote_routes(
+		amount_in: T::Balance,
+		routes: &[Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>],
+	) -> Option<Vec<T::Balance>> {
+		let mut amount = amount_in;
+		routes
+			.iter()
+			.map(|route| {
+				amount = Self::quote_route(route, amount)?;
+				Some(amount)
+			})
+			.collect()
+	}
+
+	/// The output of swapping `amount_in` through `route`. Redeeming from a vault is quoted net
+	/// of its withdraw penalty.
+	pub(crate) fn quote_route(
+		route: &Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>,
+		amount_in: T::Balance,
+	) -> Option<T::Balance> {
+		match route {
+			Route::Stable(stable_path) => Self::quote_stable_path(stable_path, amount_in),
+			Route::Normal(path, _) => T::NormalAmm::get_amount_out_by_path(amount_in.into(), path)
+				.ok()?
+				.last()
+				.map(|amount| T::Balance::from(*amount)),
+			Route::Vault { underlying, direction } => match direction {
+				VaultDirection::Deposit => T::Vault::preview_deposit(*underlying, amount_in.into()),
+				VaultDirection::Redeem =>
+					T::Vault::preview_redeem_after_penalty(*underlying, amount_in.into()),
+			}
+			.ok()
+			.map(T::Balance::from),
+		}
+	}
+
+	pub(crate) fn quote_stable_path(
+		path: &StablePath<T::StablePoolId, T::StableCurrencyId>,
+		amount_in: T::Balance,
+	) -> Option<T::Balance> {
+		match path.mode {
+			StableSwapMode::Single => {
+				let from_index = T::StableAMM::currency_index(path.pool_id, path.from_currency)?;
+				let to_index = T::StableAMM::currency_index(path.pool_id, path.to_currency)?;
+				T::StableAMM::stable_amm_calculate_swap_amount(
+					path.pool_id,
+					from_index as usize,
+					to_index as usize,
+					amount_in,
+				)
+			},
+			StableSwapMode::FromBase => {
+				let from_index =
+					T::StableAMM::currency_index(path.base_pool_id, path.from_currency)?;
+				let to_index = T::StableAMM::currency_index(path.pool_id, path.to_currency)?;
+				T::StableAMM::calculate_swap_pool_from_base(
+					path.pool_id,
+					path.base_pool_id,
+					from_index,
+					to_index,
+					amount_in,
+				)
+			},
+			StableSwapMode::ToBase => {
+				let from_index = T::StableAMM::currency_index(path.pool_id, path.from_currency)?;
+				let to_index = T::StableAMM::currency_index(path.base_pool_id, path.to_currency)?;
+				T::StableAMM::calculate_swap_pool_to_base(
+					path.pool_id,
+					path.base_pool_id,
+					from_index,
+					to_index,
+					amount_in,
+				)
+			},
+		}
+	}
+}
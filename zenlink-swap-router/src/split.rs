@@ -0,0 +1,190 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+
+/// The input is handed out to the venues in this many steps when searching the optimal split.
+const SPLIT_STEPS: u32 = 20;
+
+/// The share of the input of a split swap taken by one list of routes.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+pub enum SplitShare<Balance> {
+	/// A share of the whole input.
+	Percent(Perbill),
+	/// A fixed amount of the input.
+	Amount(Balance),
+}
+
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct SplitRoutes<PoolId, StableCurrencyId, NormalCurrencyId, Balance> {
+	pub share: SplitShare<Balance>,
+	pub routes: Vec<Route<PoolId, StableCurrencyId, NormalCurrencyId>>,
+}
+
+/// The optimal split of a swap and what it is expected to return.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+#[cfg_attr(feature = "std", derive(Serialize, Deserialize))]
+#[cfg_attr(feature = "std", serde(rename_all = "camelCase"))]
+pub struct SplitQuote<PoolId, StableCurrencyId, NormalCurrencyId, Balance> {
+	pub splits: Vec<SplitRoutes<PoolId, StableCurrencyId, NormalCurrencyId, Balance>>,
+	// the output of every split
+	pub amounts_out: Vec<Balance>,
+	pub amount_out: Balance,
+}
+
+/// A currency of either amm, the standard amm ones converted to stable amm ones when they can be.
+#[derive(PartialEq, Eq)]
+enum RouteCurrency<StableCurrencyId, NormalCurrencyId> {
+	Stable(StableCurrencyId),
+	Normal(NormalCurrencyId),
+}
+
+impl<T: Config> Pallet<T> {
+	/// Ensure every split goes through some routes, and all of them from and to the same currency.
+	pub(crate) fn validate_splits(
+		splits: &[SplitRoutes<
+			T::StablePoolId,
+			T::StableCurrencyId,
+			T::NormalCurrencyId,
+			T::Balance,
+		>],
+	) -> DispatchResult {
+		let mut ends = splits.iter().map(|split| Self::route_ends(&split.routes));
+		let first = ends.next().flatten().ok_or(Error::<T>::InvalidSplits)?;
+		for split_ends in ends {
+			ensure!(split_ends.as_ref() == Some(&first), Error::<T>::InvalidSplits);
+		}
+		Ok(())
+	}
+
+	/// The input currency of the first route and the output currency of the last one.
+	#[allow(clippy::type_complexity)]
+	fn route_ends(
+		routes: &[Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>],
+	) -> Option<(
+		RouteCurrency<T::StableCurrencyId, T::NormalCurrencyId>,
+		RouteCurrency<T::StableCurrencyId, T::NormalCurrencyId>,
+	)> {
+		let normal =
+			|currency_id: T::NormalCurrencyId| match T::CurrencyConverter::to_stable(currency_id) {
+				Some(currency_id) => RouteCurrency::Stable(currency_id),
+				None => RouteCurrency::Normal(currency_id),
+			};
+		let ends = |route: &Route<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId>| {
+			Some(match route {
+				Route::Stable(stable_path) => (
+					RouteCurrency::Stable(stable_path.from_currency),
+					RouteCurrency::Stable(stable_path.to_currency),
+				),
+				Route::Normal(path, _) => (normal(*path.first()?), normal(*path.last()?)),
+				Route::Vault { underlying, direction } => {
+					let vault_asset = T::Vault::asset(*underlying).ok()?;
+					match direction {
+						VaultDirection::Deposit =>
+							(RouteCurrency::Stable(*underlying), RouteCurrency::Stable(vault_asset)),
+						VaultDirection::Redeem =>
+							(RouteCurrency::Stable(vault_asset), RouteCurrency::Stable(*underlying)),
+					}
+				},
+			})
+		};
+
+		let (from, _) = ends(routes.first()?)?;
+		let (_, to) = ends(routes.last()?)?;
+		Some((from, to))
+	}
+
+	/// The input of every split of `amount_in`. The rounding left by the percent shares goes to
+	/// the last of them.
+	pub(crate) fn split_amounts(
+		amount_in: T::Balance,
+		splits: &[SplitRoutes<
+			T::StablePoolId,
+			T::StableCurrencyId,
+			T::NormalCurrencyId,
+			T::Balance,
+		>],
+	) -> Result<Vec<T::Balance>, DispatchError> {
+		let mut amounts: Vec<T::Balance> = splits
+			.iter()
+			.map(|split| match split.share {
+				SplitShare::Percent(percent) => percent.mul_floor(amount_in),
+				SplitShare::Amount(amount) => amount,
+			})
+			.collect();
+
+		let total = amounts
+			.iter()
+			.try_fold(T::Balance::zero(), |total, amount| total.checked_add(amount))
+			.ok_or(Error::<T>::InvalidSplits)?;
+		ensure!(total <= amount_in, Error::<T>::InvalidSplits);
+
+		let rest = amount_in - total;
+		if !rest.is_zero() {
+			let last_percent = splits
+				.iter()
+				.rposition(|split| matches!(split.share, SplitShare::Percent(_)))
+				.ok_or(Error::<T>::InvalidSplits)?;
+			amounts[last_percent] += rest;
+		}
+		Ok(amounts)
+	}
+
+	/// Split `amount_in` of `from` across the venues swapping it directly into `to`. Every step
+	/// of the input goes to the venue returning the most for it, which leaves the marginal prices
+	/// of the venues used equal.
+	pub fn find_optimal_split(
+		from: T::NormalCurrencyId,
+		to: T::NormalCurrencyId,
+		amount_in: T::Balance,
+	) -> Option<SplitQuote<T::StablePoolId, T::StableCurrencyId, T::NormalCurrencyId, T::Balance>> {
+		let mut routes = Self::stable_routes(from, to);
+		if T::NormalAmm::get_paired_assets(from).contains(&to) {
+			routes.push(Route::Normal(Vec::from([from, to]), None));
+		}
+		if routes.is_empty() || amount_in.is_zero() {
+			return None
+		}
+
+		let step = (amount_in / T::Balance::from(SPLIT_STEPS)).max(One::one());
+		let mut amounts_in = Vec::from_iter(routes.iter().map(|_| T::Balance::zero()));
+		let mut amounts_out = amounts_in.clone();
+		let mut remaining = amount_in;
+		while !remaining.is_zero() {
+			let chunk = step.min(remaining);
+			let (index, out) = routes
+				.iter()
+				.enumerate()
+				.filter_map(|(i, route)| {
+					Self::quote_route(route, amounts_in[i] + chunk).map(|out| (i, out))
+				})
+				.max_by_key(|(i, out)| out.saturating_sub(amounts_out[*i]))?;
+
+			amounts_in[index] += chunk;
+			amounts_out[index] = out;
+			remaining -= chunk;
+		}
+
+		let (splits, amounts_out): (Vec<_>, Vec<_>) = routes
+			.into_iter()
+			.zip(amounts_in)
+			.zip(amounts_out)
+			.filter(|((_, amount_in), _)| !amount_in.is_zero())
+			.map(|((route, amount_in), amount_out)| {
+				let split = SplitRoutes {
+					share: SplitShare::Amount(amount_in),
+					routes: Vec::from([route]),
+				};
+				(split, amount_out)
+			})
+			.unzip();
+		let amount_out = amounts_out
+			.iter()
+			.fold(T::Balance::zero(), |total, out| total.saturating_add(*out));
+
+		Some(SplitQuote { splits, amounts_out, amount_out })
+	}
+}
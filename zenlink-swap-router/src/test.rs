@@ -79,13 +79,14 @@ fn swap_exact_token_for_tokens_through_stable_pool_with_amount_slippage_should_f
 		setup_pools();
 
 		let routes = vec![
-			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID]),
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
 			Route::Stable(StablePath::<PoolId, CurrencyId> {
 				pool_id: 1,
 				base_pool_id: 0,
 				mode: FromBase,
 				from_currency: Token(TOKEN1_SYMBOL),
 				to_currency: Token(TOKEN4_SYMBOL),
+				min_amount_out: None,
 			}),
 		];
 
@@ -110,13 +111,14 @@ fn swap_exact_token_for_tokens_through_stable_pool_should_work() {
 		setup_pools();
 
 		let routes = vec![
-			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID]),
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
 			Route::Stable(StablePath::<PoolId, CurrencyId> {
 				pool_id: 1,
 				base_pool_id: 0,
 				mode: FromBase,
 				from_currency: Token(TOKEN1_SYMBOL),
 				to_currency: Token(TOKEN4_SYMBOL),
+				min_amount_out: None,
 			}),
 		];
 		let token1_balance_before = Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free;
@@ -145,3 +147,780 @@ fn swap_exact_token_for_tokens_through_stable_pool_should_work() {
 		);
 	})
 }
+
+#[test]
+fn pools_of_asset_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		assert_eq!(
+			RouterPallet::pools_of_asset(TOKEN1_ASSET_ID),
+			vec![Venue::Pair(TOKEN1_ASSET_ID, TOKEN2_ASSET_ID), Venue::Stable(0)]
+		);
+		assert_eq!(RouterPallet::pools_of_asset(TOKEN4_ASSET_ID), vec![Venue::Stable(1)]);
+
+		assert_eq!(RouterPallet::to_stable_currency(TOKEN4_ASSET_ID), Some(Token(TOKEN4_SYMBOL)));
+		assert_eq!(RouterPallet::to_normal_currency(Token(TOKEN4_SYMBOL)), Some(TOKEN4_ASSET_ID));
+	})
+}
+
+#[test]
+fn best_venue_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let amount_in = 1e16 as Balance;
+		let stable_out = StableAMM::stable_amm_calculate_swap_amount(0, 0, 1, amount_in).unwrap();
+		let normal_out =
+			Zenlink::get_amount_out_by_path(amount_in, &[TOKEN1_ASSET_ID, TOKEN2_ASSET_ID])
+				.unwrap()[1];
+		assert!(stable_out > normal_out);
+		assert_eq!(
+			RouterPallet::best_venue(TOKEN1_ASSET_ID, TOKEN2_ASSET_ID, amount_in),
+			Some((
+				Route::Stable(StablePath::<PoolId, CurrencyId> {
+					pool_id: 0,
+					base_pool_id: 0,
+					mode: StableSwapMode::Single,
+					from_currency: Token(TOKEN1_SYMBOL),
+					to_currency: Token(TOKEN2_SYMBOL),
+					min_amount_out: None,
+				}),
+				stable_out
+			))
+		);
+
+		let meta_out = StableAMM::calculate_swap_pool_from_base(1, 0, 0, 0, amount_in).unwrap();
+		assert_eq!(
+			RouterPallet::best_venue(TOKEN1_ASSET_ID, TOKEN4_ASSET_ID, amount_in),
+			Some((
+				Route::Stable(StablePath::<PoolId, CurrencyId> {
+					pool_id: 1,
+					base_pool_id: 0,
+					mode: FromBase,
+					from_currency: Token(TOKEN1_SYMBOL),
+					to_currency: Token(TOKEN4_SYMBOL),
+					min_amount_out: None,
+				}),
+				meta_out
+			))
+		);
+	})
+}
+
+#[test]
+fn find_best_route_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let amount_in = 1e16 as Balance;
+		let quote = RouterPallet::find_best_route(TOKEN2_ASSET_ID, TOKEN4_ASSET_ID, amount_in)
+			.expect("route exists");
+		assert!(quote.routes.len() <= 3);
+		assert_eq!(quote.amounts.len(), quote.routes.len() + 1);
+		assert_eq!(quote.amounts[0], amount_in);
+		assert_eq!(quote.amounts.last(), Some(&quote.amount_out));
+		assert!(quote.price_impact < Perbill::from_percent(1));
+
+		let (_, venue_out) =
+			RouterPallet::best_venue(TOKEN2_ASSET_ID, TOKEN4_ASSET_ID, amount_in).unwrap();
+		assert!(quote.amount_out >= venue_out);
+
+		let token4_balance_before = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free;
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			quote.amount_out,
+			quote.routes,
+			USER2,
+			u64::MAX,
+		));
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free,
+			token4_balance_before + quote.amount_out
+		);
+
+		assert_eq!(
+			RouterPallet::find_best_route(TOKEN1_ASSET_ID, TOKEN1_ASSET_ID, amount_in),
+			None
+		);
+	})
+}
+
+#[test]
+fn find_best_route_should_not_reuse_pools() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let assets = [TOKEN1_ASSET_ID, TOKEN2_ASSET_ID, TOKEN4_ASSET_ID];
+		for from in assets {
+			for to in assets.into_iter().filter(|to| *to != from) {
+				let quote =
+					RouterPallet::find_best_route(from, to, 1e16 as Balance).expect("route exists");
+				let mut pools = Vec::new();
+				for route in quote.routes.iter() {
+					if let Route::Stable(stable_path) = route {
+						assert!(!pools.contains(&stable_path.pool_id));
+						assert!(!pools.contains(&stable_path.base_pool_id));
+						pools.push(stable_path.pool_id);
+						pools.push(stable_path.base_pool_id);
+					}
+				}
+			}
+		}
+	})
+}
+
+#[test]
+fn swap_tokens_for_exact_tokens_through_stable_pool_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let routes = vec![
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
+			Route::Stable(StablePath::<PoolId, CurrencyId> {
+				pool_id: 1,
+				base_pool_id: 0,
+				mode: FromBase,
+				from_currency: Token(TOKEN1_SYMBOL),
+				to_currency: Token(TOKEN4_SYMBOL),
+				min_amount_out: None,
+			}),
+		];
+		let amount_out = 5000;
+		let amounts = RouterPallet::get_amount_in_by_routes(&routes, amount_out).unwrap();
+		assert_eq!(amounts.len(), 3);
+		assert_eq!(amounts[2], amount_out);
+
+		let token1_balance_before = Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free;
+		let token2_balance_before = Tokens::accounts(USER1, Token(TOKEN2_SYMBOL)).free;
+		let token4_balance_before = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free;
+
+		assert_noop!(
+			RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				amount_out,
+				amounts[0] - 1,
+				routes.clone(),
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::ExcessiveSoldAmount
+		);
+
+		assert_ok!(RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_out,
+			1e16 as Balance,
+			routes,
+			USER2,
+			u64::MAX,
+		));
+
+		assert_eq!(Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free, token1_balance_before);
+		assert_eq!(
+			Tokens::accounts(USER1, Token(TOKEN2_SYMBOL)).free,
+			token2_balance_before - amounts[0]
+		);
+		let received = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free - token4_balance_before;
+		assert!(received >= amount_out && received - amount_out <= amount_out / 1_000);
+	})
+}
+
+#[test]
+fn swap_tokens_for_exact_tokens_should_forward_stable_surplus() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+
+		let routes = vec![
+			Route::Stable(StablePath::<PoolId, CurrencyId> {
+				pool_id: 0,
+				base_pool_id: 0,
+				mode: StableSwapMode::Single,
+				from_currency: Token(TOKEN1_SYMBOL),
+				to_currency: Token(TOKEN2_SYMBOL),
+				min_amount_out: None,
+			}),
+			Route::Stable(StablePath::<PoolId, CurrencyId> {
+				pool_id: 0,
+				base_pool_id: 0,
+				mode: StableSwapMode::Single,
+				from_currency: Token(TOKEN2_SYMBOL),
+				to_currency: Token(TOKEN3_SYMBOL),
+				min_amount_out: None,
+			}),
+		];
+		let amount_out = 1e4 as Balance;
+		let amounts = RouterPallet::get_amount_in_by_routes(&routes, amount_out).unwrap();
+
+		let token1_balance_before = Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free;
+		let token2_balance_before = Tokens::accounts(USER1, Token(TOKEN2_SYMBOL)).free;
+		let token3_balance_before = Tokens::accounts(USER2, Token(TOKEN3_SYMBOL)).free;
+		assert_ok!(RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_out,
+			amounts[0],
+			routes,
+			USER2,
+			u64::MAX,
+		));
+
+		// what the first route returns beyond the input of the second does not stay with USER1
+		assert_eq!(
+			Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free,
+			token1_balance_before - amounts[0]
+		);
+		assert_eq!(Tokens::accounts(USER1, Token(TOKEN2_SYMBOL)).free, token2_balance_before);
+		assert!(
+			Tokens::accounts(USER2, Token(TOKEN3_SYMBOL)).free >=
+				token3_balance_before + amount_out
+		);
+	})
+}
+
+#[test]
+fn swap_tokens_for_exact_tokens_from_base_pool_should_cover_deposit_fee() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+
+		// about 1e16 of TOKEN1 in, a percent of the meta pool
+		let amount_out = 10_000;
+		let from_base = Route::Stable(StablePath::<PoolId, CurrencyId> {
+			pool_id: 1,
+			base_pool_id: 0,
+			mode: FromBase,
+			from_currency: Token(TOKEN1_SYMBOL),
+			to_currency: Token(TOKEN4_SYMBOL),
+			min_amount_out: None,
+		});
+		let amounts =
+			RouterPallet::get_amount_in_by_routes(&[from_base.clone()], amount_out).unwrap();
+		assert!(amounts[0] > 1e16 as Balance && amounts[0] < 2e16 as Balance);
+
+		let token4_balance_before = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free;
+		assert_ok!(RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_out,
+			amounts[0],
+			vec![from_base],
+			USER2,
+			u64::MAX,
+		));
+		assert!(
+			Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free >=
+				token4_balance_before + amount_out
+		);
+
+		let amount_out = 1e16 as Balance;
+		let single = Route::Stable(StablePath::<PoolId, CurrencyId> {
+			pool_id: 0,
+			base_pool_id: 0,
+			mode: StableSwapMode::Single,
+			from_currency: Token(TOKEN1_SYMBOL),
+			to_currency: Token(TOKEN2_SYMBOL),
+			min_amount_out: None,
+		});
+		let amounts = RouterPallet::get_amount_in_by_routes(&[single.clone()], amount_out).unwrap();
+
+		let token2_balance_before = Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free;
+		assert_ok!(RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_out,
+			amounts[0],
+			vec![single],
+			USER2,
+			u64::MAX,
+		));
+		let received = Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free - token2_balance_before;
+		assert!(received >= amount_out && received - amount_out <= amount_out / 1_000_000);
+	})
+}
+
+#[test]
+fn swap_exact_token_for_tokens_through_split_routes_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let amount_in = 1e16 as Balance + 1;
+		let splits = vec![
+			SplitRoutes {
+				share: SplitShare::Percent(Perbill::from_percent(50)),
+				routes: vec![Route::Normal(vec![TOKEN1_ASSET_ID, TOKEN2_ASSET_ID], None)],
+			},
+			SplitRoutes {
+				share: SplitShare::Percent(Perbill::from_percent(50)),
+				routes: vec![Route::Stable(StablePath::<PoolId, CurrencyId> {
+					pool_id: 0,
+					base_pool_id: 0,
+					mode: StableSwapMode::Single,
+					from_currency: Token(TOKEN1_SYMBOL),
+					to_currency: Token(TOKEN2_SYMBOL),
+					min_amount_out: None,
+				})],
+			},
+		];
+		let normal_out =
+			Zenlink::get_amount_out_by_path(5e15 as Balance, &[TOKEN1_ASSET_ID, TOKEN2_ASSET_ID])
+				.unwrap()[1];
+		let stable_out =
+			StableAMM::stable_amm_calculate_swap_amount(0, 0, 1, 5e15 as Balance + 1).unwrap();
+
+		let token1_balance_before = Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free;
+		let token2_balance_before = Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free;
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				amount_in,
+				normal_out + stable_out + 1,
+				splits.clone(),
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::AmountSlippage
+		);
+
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			normal_out + stable_out,
+			splits,
+			USER2,
+			u64::MAX,
+		));
+
+		assert_eq!(
+			Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free,
+			token1_balance_before - amount_in
+		);
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free,
+			token2_balance_before + normal_out + stable_out
+		);
+	})
+}
+
+#[test]
+fn swap_through_invalid_split_routes_should_not_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let split = SplitRoutes {
+			share: SplitShare::Amount(1e16 as Balance),
+			routes: vec![Route::Normal(vec![TOKEN1_ASSET_ID, TOKEN2_ASSET_ID], None)],
+		};
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				1e16 as Balance - 1,
+				0,
+				vec![split.clone()],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidSplits
+		);
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				1e16 as Balance + 1,
+				0,
+				vec![split.clone()],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidSplits
+		);
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				5e16 as Balance,
+				0,
+				vec![split.clone(); 5],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidSplits
+		);
+
+		// the splits must swap the same currencies
+		let other_split = SplitRoutes {
+			share: SplitShare::Amount(1e16 as Balance),
+			routes: vec![Route::Stable(StablePath {
+				pool_id: 0,
+				base_pool_id: 0,
+				mode: StableSwapMode::Single,
+				from_currency: Token(TOKEN1_SYMBOL),
+				to_currency: Token(TOKEN3_SYMBOL),
+				min_amount_out: None,
+			})],
+		};
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				2e16 as Balance,
+				0,
+				vec![split.clone(), other_split],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidSplits
+		);
+
+		let empty_split = SplitRoutes { share: SplitShare::Amount(0), routes: vec![] };
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+				RawOrigin::Signed(USER1).into(),
+				1e16 as Balance,
+				0,
+				vec![split, empty_split],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidSplits
+		);
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				1e16 as Balance,
+				0,
+				vec![],
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidRoutes
+		);
+	})
+}
+
+#[test]
+fn find_optimal_split_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let amount_in = 1e17 as Balance;
+		let quote =
+			RouterPallet::find_optimal_split(TOKEN1_ASSET_ID, TOKEN2_ASSET_ID, amount_in).unwrap();
+
+		let split_amounts: Vec<Balance> = quote
+			.splits
+			.iter()
+			.map(|split| match split.share {
+				SplitShare::Amount(amount) => amount,
+				SplitShare::Percent(_) => unreachable!(),
+			})
+			.collect();
+		assert_eq!(split_amounts.iter().sum::<Balance>(), amount_in);
+		assert_eq!(quote.amounts_out.iter().sum::<Balance>(), quote.amount_out);
+
+		let (_, venue_out) =
+			RouterPallet::best_venue(TOKEN1_ASSET_ID, TOKEN2_ASSET_ID, amount_in).unwrap();
+		assert!(quote.amount_out >= venue_out);
+
+		let token2_balance_before = Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free;
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_split_routes(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			quote.amount_out,
+			quote.splits,
+			USER2,
+			u64::MAX,
+		));
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN2_SYMBOL)).free,
+			token2_balance_before + quote.amount_out
+		);
+	})
+}
+
+#[test]
+fn router_swap_should_emit_event_and_match_quote() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_stable_pools();
+		setup_pools();
+
+		let routes = vec![
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
+			Route::Stable(StablePath::<PoolId, CurrencyId> {
+				pool_id: 1,
+				base_pool_id: 0,
+				mode: FromBase,
+				from_currency: Token(TOKEN1_SYMBOL),
+				to_currency: Token(TOKEN4_SYMBOL),
+				min_amount_out: None,
+			}),
+		];
+		let amount_in = 1e16 as Balance;
+		let amounts = RouterPallet::quote_routes(amount_in, &routes).unwrap();
+		assert_eq!(amounts.len(), 2);
+		let token4_balance_before = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free;
+
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			0,
+			routes.clone(),
+			USER2,
+			u64::MAX,
+		));
+
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free,
+			token4_balance_before + amounts[1]
+		);
+		System::assert_last_event(RuntimeEvent::Router(crate::Event::RouterSwap {
+			who: USER1,
+			routes,
+			amount_in,
+			amount_out: amounts[1],
+			to: USER2,
+		}));
+	})
+}
+
+#[test]
+fn swap_with_per_hop_minimum_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+
+		let amount_in = 1e16 as Balance;
+		let stable_path = StablePath::<PoolId, CurrencyId> {
+			pool_id: 1,
+			base_pool_id: 0,
+			mode: FromBase,
+			from_currency: Token(TOKEN1_SYMBOL),
+			to_currency: Token(TOKEN4_SYMBOL),
+			min_amount_out: None,
+		};
+		let routes = vec![
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
+			Route::Stable(stable_path.clone()),
+		];
+		let amounts = RouterPallet::quote_routes(amount_in, &routes).unwrap();
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				amount_in,
+				0,
+				vec![
+					Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], Some(amounts[0] + 1)),
+					Route::Stable(stable_path.clone()),
+				],
+				USER2,
+				u64::MAX,
+			),
+			zenlink_protocol::Error::<Test>::InsufficientTargetAmount
+		);
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				amount_in,
+				0,
+				vec![
+					Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
+					Route::Stable(StablePath {
+						min_amount_out: Some(amounts[1] + 1),
+						..stable_path.clone()
+					}),
+				],
+				USER2,
+				u64::MAX,
+			),
+			zenlink_stable_amm::Error::<Test>::AmountSlippage
+		);
+
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			0,
+			vec![
+				Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], Some(amounts[0])),
+				Route::Stable(StablePath { min_amount_out: Some(amounts[1]), ..stable_path }),
+			],
+			USER2,
+			u64::MAX,
+		));
+	})
+}
+
+fn setup_vault() {
+	assert_ok!(Vault::create_vault_asset(
+		RawOrigin::Root.into(),
+		Token(TOKEN1_SYMBOL),
+		TOKEN1_DECIMAL as u8,
+		TOKEN1_DECIMAL as u8,
+		0,
+		0
+	));
+	assert_ok!(Vault::deposit(
+		RawOrigin::Signed(USER1).into(),
+		Token(TOKEN1_SYMBOL),
+		TOKEN1_UNIT * 1_000,
+		USER1
+	));
+}
+
+#[test]
+fn swap_through_vault_routes_should_work() {
+	new_test_ext().execute_with(|| {
+		setup_stable_pools();
+		setup_pools();
+		setup_vault();
+
+		let amount_in = TOKEN1_UNIT;
+		let routes = vec![
+			Route::Vault { underlying: Token(TOKEN1_SYMBOL), direction: VaultDirection::Redeem },
+			Route::Stable(StablePath {
+				pool_id: 1,
+				base_pool_id: 0,
+				mode: FromBase,
+				from_currency: Token(TOKEN1_SYMBOL),
+				to_currency: Token(TOKEN4_SYMBOL),
+				min_amount_out: None,
+			}),
+		];
+		let amounts = RouterPallet::quote_routes(amount_in, &routes).unwrap();
+
+		let share_balance_before =
+			Tokens::accounts(USER1, CurrencyId::VaultToken(TOKEN1_SYMBOL)).free;
+		let token1_balance_before = Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free;
+		let token4_balance_before = Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free;
+
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				amount_in,
+				amounts[1] + 1,
+				routes.clone(),
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::AmountSlippage
+		);
+
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			amounts[1],
+			routes,
+			USER2,
+			u64::MAX,
+		));
+
+		assert_eq!(
+			Tokens::accounts(USER1, CurrencyId::VaultToken(TOKEN1_SYMBOL)).free,
+			share_balance_before - amount_in
+		);
+		assert_eq!(Tokens::accounts(USER1, Token(TOKEN1_SYMBOL)).free, token1_balance_before);
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN4_SYMBOL)).free,
+			token4_balance_before + amounts[1]
+		);
+
+		let routes = vec![
+			Route::Normal(vec![TOKEN2_ASSET_ID, TOKEN1_ASSET_ID], None),
+			Route::Vault { underlying: Token(TOKEN1_SYMBOL), direction: VaultDirection::Deposit },
+		];
+		let amounts = RouterPallet::quote_routes(1e16 as Balance, &routes).unwrap();
+		let share_balance_before =
+			Tokens::accounts(USER2, CurrencyId::VaultToken(TOKEN1_SYMBOL)).free;
+
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			1e16 as Balance,
+			amounts[1],
+			routes.clone(),
+			USER2,
+			u64::MAX,
+		));
+		assert_eq!(
+			Tokens::accounts(USER2, CurrencyId::VaultToken(TOKEN1_SYMBOL)).free,
+			share_balance_before + amounts[1]
+		);
+
+		assert_noop!(
+			RouterPallet::swap_tokens_for_exact_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				TOKEN1_UNIT,
+				u128::MAX,
+				routes,
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::InvalidRoutes
+		);
+	})
+}
+
+#[test]
+fn quote_vault_redeem_route_should_charge_withdraw_penalty() {
+	new_test_ext().execute_with(|| {
+		// a flat withdraw penalty of 1%
+		assert_ok!(Vault::create_vault_asset(
+			RawOrigin::Root.into(),
+			Token(TOKEN1_SYMBOL),
+			TOKEN1_DECIMAL as u8,
+			TOKEN1_DECIMAL as u8,
+			1e16 as Balance,
+			1e16 as Balance
+		));
+		assert_ok!(Vault::deposit(
+			RawOrigin::Signed(USER1).into(),
+			Token(TOKEN1_SYMBOL),
+			TOKEN1_UNIT * 1_000,
+			USER1
+		));
+
+		let amount_in = TOKEN1_UNIT;
+		let routes = vec![Route::Vault {
+			underlying: Token(TOKEN1_SYMBOL),
+			direction: VaultDirection::Redeem,
+		}];
+		let amounts = RouterPallet::quote_routes(amount_in, &routes).unwrap();
+		let preview =
+			<Vault as zenlink_vault::Vault<Test>>::preview_redeem(Token(TOKEN1_SYMBOL), amount_in)
+				.unwrap();
+		assert_eq!(amounts[0], preview - preview / 100);
+
+		let token1_balance_before = Tokens::accounts(USER2, Token(TOKEN1_SYMBOL)).free;
+		assert_noop!(
+			RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+				RawOrigin::Signed(USER1).into(),
+				amount_in,
+				amounts[0] + 1,
+				routes.clone(),
+				USER2,
+				u64::MAX,
+			),
+			Error::<Test>::AmountSlippage
+		);
+		assert_ok!(RouterPallet::swap_exact_token_for_tokens_through_stable_pool(
+			RawOrigin::Signed(USER1).into(),
+			amount_in,
+			amounts[0],
+			routes,
+			USER2,
+			u64::MAX,
+		));
+		assert_eq!(
+			Tokens::accounts(USER2, Token(TOKEN1_SYMBOL)).free,
+			token1_balance_before + amounts[0]
+		);
+	})
+}
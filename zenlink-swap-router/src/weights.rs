@@ -52,6 +52,9 @@ use sp_std::marker::PhantomData;
 /// Weight functions needed for zenlink_swap_router.
 pub trait WeightInfo {
 	fn swap_exact_token_for_tokens_through_stable_pool() -> Weight;
+	fn swap_tokens_for_exact_tokens_through_stable_pool(h: u32, ) -> Weight;
+	fn stable_amount_in() -> Weight;
+	fn swap_exact_token_for_tokens_through_split_routes(s: u32, h: u32, ) -> Weight;
 }
 
 /// Weights for zenlink_swap_router using the Substrate node and recommended hardware.
@@ -70,6 +73,39 @@ impl<T: frame_system::Config> WeightInfo for SubstrateWeight<T> {
 			.saturating_add(T::DbWeight::get().reads(13 as u64))
 			.saturating_add(T::DbWeight::get().writes(9 as u64))
 	}
+	// Not benchmarked: a hand-written estimate of the exact-input swap plus one more stable
+	// swap per stable hop. The search of the input of the hops is charged apart, by
+	// `stable_amount_in`.
+	/// The range of component `h` is `[1, 4]`.
+	fn swap_tokens_for_exact_tokens_through_stable_pool(h: u32, ) -> Weight {
+		Weight::from_ref_time(183_419_000 as u64)
+			.saturating_add(Weight::from_ref_time(176_852_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(13 as u64))
+			.saturating_add(T::DbWeight::get().reads((6 as u64).saturating_mul(h as u64)))
+			.saturating_add(T::DbWeight::get().writes(9 as u64))
+			.saturating_add(T::DbWeight::get().writes((6 as u64).saturating_mul(h as u64)))
+	}
+	// Not benchmarked: a hand-written estimate of `MAX_AMOUNT_IN_QUOTES` quotes of a stable
+	// path of about 25 microseconds each, the most the search of its input may take.
+	// Storage: ZenlinkStableAmm Pools (r:2 w:0)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	fn stable_amount_in() -> Weight {
+		Weight::from_ref_time(3_200_000_000 as u64)
+			.saturating_add(T::DbWeight::get().reads(3 as u64))
+	}
+	// Not benchmarked: a hand-written estimate of the event of each split and one swap through a
+	// stable pool per hop of all the splits. The vaults charge their own hops on top.
+	/// The range of component `s` is `[1, 4]`.
+	/// The range of component `h` is `[8, 16]`.
+	fn swap_exact_token_for_tokens_through_split_routes(s: u32, h: u32, ) -> Weight {
+		Weight::from_ref_time(12_604_000 as u64)
+			.saturating_add(Weight::from_ref_time(10_000_000 as u64).saturating_mul(s as u64))
+			.saturating_add(Weight::from_ref_time(176_852_000 as u64).saturating_mul(h as u64))
+			.saturating_add(T::DbWeight::get().reads(5 as u64))
+			.saturating_add(T::DbWeight::get().reads((8 as u64).saturating_mul(h as u64)))
+			.saturating_add(T::DbWeight::get().writes(1 as u64))
+			.saturating_add(T::DbWeight::get().writes((8 as u64).saturating_mul(h as u64)))
+	}
 }
 
 // For backwards compatibility and tests
@@ -87,4 +123,37 @@ impl WeightInfo for () {
 			.saturating_add(RocksDbWeight::get().reads(13 as u64))
 			.saturating_add(RocksDbWeight::get().writes(9 as u64))
 	}
+	// Not benchmarked: a hand-written estimate of the exact-input swap plus one more stable
+	// swap per stable hop. The search of the input of the hops is charged apart, by
+	// `stable_amount_in`.
+	/// The range of component `h` is `[1, 4]`.
+	fn swap_tokens_for_exact_tokens_through_stable_pool(h: u32, ) -> Weight {
+		Weight::from_ref_time(183_419_000 as u64)
+			.saturating_add(Weight::from_ref_time(176_852_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(13 as u64))
+			.saturating_add(RocksDbWeight::get().reads((6 as u64).saturating_mul(h as u64)))
+			.saturating_add(RocksDbWeight::get().writes(9 as u64))
+			.saturating_add(RocksDbWeight::get().writes((6 as u64).saturating_mul(h as u64)))
+	}
+	// Not benchmarked: a hand-written estimate of `MAX_AMOUNT_IN_QUOTES` quotes of a stable
+	// path of about 25 microseconds each, the most the search of its input may take.
+	// Storage: ZenlinkStableAmm Pools (r:2 w:0)
+	// Storage: Tokens TotalIssuance (r:1 w:0)
+	fn stable_amount_in() -> Weight {
+		Weight::from_ref_time(3_200_000_000 as u64)
+			.saturating_add(RocksDbWeight::get().reads(3 as u64))
+	}
+	// Not benchmarked: a hand-written estimate of the event of each split and one swap through a
+	// stable pool per hop of all the splits. The vaults charge their own hops on top.
+	/// The range of component `s` is `[1, 4]`.
+	/// The range of component `h` is `[8, 16]`.
+	fn swap_exact_token_for_tokens_through_split_routes(s: u32, h: u32, ) -> Weight {
+		Weight::from_ref_time(12_604_000 as u64)
+			.saturating_add(Weight::from_ref_time(10_000_000 as u64).saturating_mul(s as u64))
+			.saturating_add(Weight::from_ref_time(176_852_000 as u64).saturating_mul(h as u64))
+			.saturating_add(RocksDbWeight::get().reads(5 as u64))
+			.saturating_add(RocksDbWeight::get().reads((8 as u64).saturating_mul(h as u64)))
+			.saturating_add(RocksDbWeight::get().writes(1 as u64))
+			.saturating_add(RocksDbWeight::get().writes((8 as u64).saturating_mul(h as u64)))
+	}
 }
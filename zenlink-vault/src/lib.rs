@@ -11,18 +11,20 @@ mod mock;
 mod test;
 
 pub mod primitives;
+mod strategy;
 mod vault;
 pub mod weights;
 
 pub use pallet::*;
 pub use primitives::*;
-use vault::*;
+pub use strategy::{StableAmmStrategy, VaultStrategy, ZenlinkPairStrategy};
+pub use vault::Vault;
 
 use sp_arithmetic::{
 	traits::{checked_pow, Zero},
 	Rounding,
 };
-use sp_runtime::traits::{AccountIdConversion, StaticLookup};
+use sp_runtime::traits::{AccountIdConversion, Saturating, StaticLookup, UniqueSaturatedInto};
 use sp_std::{collections::btree_set::BTreeSet, vec::Vec};
 
 use frame_support::{dispatch::DispatchResult, pallet_prelude::*, PalletId};
@@ -63,6 +65,13 @@ pub mod pallet {
 		/// The Trait generate vault asset for specific asset.
 		type VaultAssetGenerate: VaultAssetGenerate<Self::AssetId>;
 
+		/// The strategy deploying the idle underlying assets of the vaults.
+		type Strategy: VaultStrategy<Self::AccountId, Self::AssetId>;
+
+		/// The number of blocks in a year, which the management fee accrues over.
+		#[pallet::constant]
+		type BlocksPerYear: Get<Self::BlockNumber>;
+
 		/// This pallet id.
 		#[pallet::constant]
 		type PalletId: Get<PalletId>;
@@ -97,6 +106,30 @@ pub mod pallet {
 	pub type AssetLockedAccounts<T: Config> =
 		StorageMap<_, Blake2_128Concat, T::AssetId, BTreeSet<T::AccountId>, ValueQuery>;
 
+	/// The amount of underlying asset allocated to the strategy.
+	#[pallet::storage]
+	#[pallet::getter(fn strategy_debt)]
+	pub type StrategyDebt<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, Balance, ValueQuery>;
+
+	/// The max amount of underlying asset can be allocated to the strategy.
+	#[pallet::storage]
+	#[pallet::getter(fn debt_limit)]
+	pub type DebtLimit<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, Balance, ValueQuery>;
+
+	/// The fees charged by a vault. The key is the underlying asset of the vault asset.
+	#[pallet::storage]
+	#[pallet::getter(fn vault_fees)]
+	pub type VaultFees<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, FeeConfig<T::AccountId>>;
+
+	/// The accrual state of the fees of a vault.
+	#[pallet::storage]
+	#[pallet::getter(fn fee_state)]
+	pub type VaultFeeState<T: Config> =
+		StorageMap<_, Blake2_128Concat, T::AssetId, FeeState<T::BlockNumber>, ValueQuery>;
+
 	#[pallet::event]
 	#[pallet::generate_deposit(pub(super) fn deposit_event)]
 	pub enum Event<T: Config> {
@@ -138,6 +171,34 @@ pub mod pallet {
 			amounts: Balance,
 			shares: Balance,
 		},
+		UpdateDebtLimit {
+			underlying_asset: T::AssetId,
+			limit: Balance,
+		},
+		Allocate {
+			underlying_asset: T::AssetId,
+			amounts: Balance,
+		},
+		Deallocate {
+			underlying_asset: T::AssetId,
+			amounts: Balance,
+		},
+		Harvest {
+			underlying_asset: T::AssetId,
+			amounts: Balance,
+		},
+		UpdateVaultFees {
+			underlying_asset: T::AssetId,
+			management_fee: Balance,
+			performance_fee: Balance,
+			receiver: T::AccountId,
+		},
+		CollectFees {
+			underlying_asset: T::AssetId,
+			receiver: T::AccountId,
+			management_fee_shares: Balance,
+			performance_fee_shares: Balance,
+		},
 	}
 
 	#[pallet::error]
@@ -162,6 +223,18 @@ pub mod pallet {
 		ExceedMaxWithdraw,
 		/// Exceed the max redeem amount.
 		ExceedMaxRedeem,
+		/// Exceed the max amount allocated to the strategy.
+		ExceedDebtLimit,
+		/// The fee ratio exceeds the whole.
+		InvalidFee,
+		/// The strategy freed less than a withdrawal lacks.
+		StrategyShortfall,
+		/// The strategy has no pool for the underlying asset.
+		InvalidStrategyPool,
+		/// The strategy has no price to bound its slippage with.
+		NoStrategyPrice,
+		/// The strategy realized less than its slippage bound.
+		StrategySlippage,
 	}
 
 	#[pallet::call]
@@ -299,7 +372,9 @@ pub mod pallet {
 			)
 		}
 
-		#[pallet::weight(T::WeightInfo::deposit())]
+		#[pallet::weight(
+			T::WeightInfo::deposit().saturating_add(Pallet::<T>::accrue_fees_weight())
+		)]
 		pub fn deposit(
 			origin: OriginFor<T>,
 			asset_id: T::AssetId,
@@ -312,7 +387,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(T::WeightInfo::mint())]
+		#[pallet::weight(
+			T::WeightInfo::mint().saturating_add(Pallet::<T>::accrue_fees_weight())
+		)]
 		pub fn mint(
 			origin: OriginFor<T>,
 			asset_id: T::AssetId,
@@ -325,7 +402,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(T::WeightInfo::withdraw())]
+		#[pallet::weight(T::WeightInfo::withdraw()
+			.saturating_add(Pallet::<T>::accrue_fees_weight())
+			.saturating_add(Pallet::<T>::free_from_strategy_weight()))]
 		pub fn withdraw(
 			origin: OriginFor<T>,
 			asset_id: T::AssetId,
@@ -338,7 +417,9 @@ pub mod pallet {
 			Ok(())
 		}
 
-		#[pallet::weight(T::WeightInfo::redeem())]
+		#[pallet::weight(T::WeightInfo::redeem()
+			.saturating_add(Pallet::<T>::accrue_fees_weight())
+			.saturating_add(Pallet::<T>::free_from_strategy_weight()))]
 		pub fn redeem(
 			origin: OriginFor<T>,
 			asset_id: T::AssetId,
@@ -350,6 +431,138 @@ pub mod pallet {
 			<Self as Vault<T>>::redeem(&who, asset_id, shares, &to)?;
 			Ok(())
 		}
+
+		#[pallet::weight(1_000_000)]
+		pub fn set_debt_limit(
+			origin: OriginFor<T>,
+			underlying_asset: T::AssetId,
+			limit: Balance,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(
+				Vaults::<T>::contains_key(underlying_asset),
+				Error::<T>::UnknownUnderlyingAsset
+			);
+
+			DebtLimit::<T>::insert(underlying_asset, limit);
+
+			Self::deposit_event(Event::UpdateDebtLimit { underlying_asset, limit });
+			Ok(())
+		}
+
+		/// Deploy the idle underlying asset of a vault to the strategy.
+		#[pallet::weight(1_000_000)]
+		#[transactional]
+		pub fn allocate(
+			origin: OriginFor<T>,
+			underlying_asset: T::AssetId,
+			amounts: Balance,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(
+				Vaults::<T>::contains_key(underlying_asset),
+				Error::<T>::UnknownUnderlyingAsset
+			);
+
+			StrategyDebt::<T>::try_mutate(underlying_asset, |debt| -> DispatchResult {
+				*debt = debt.checked_add(amounts).ok_or(Error::<T>::Math)?;
+				ensure!(*debt <= Self::debt_limit(underlying_asset), Error::<T>::ExceedDebtLimit);
+				Ok(())
+			})?;
+			let pallet_account = T::PalletId::get().into_account_truncating();
+			T::Strategy::deploy(&pallet_account, underlying_asset, amounts)?;
+
+			Self::deposit_event(Event::Allocate { underlying_asset, amounts });
+			Ok(())
+		}
+
+		/// Take the underlying asset of a vault back from the strategy.
+		#[pallet::weight(1_000_000)]
+		#[transactional]
+		pub fn deallocate(
+			origin: OriginFor<T>,
+			underlying_asset: T::AssetId,
+			amounts: Balance,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			ensure!(
+				Vaults::<T>::contains_key(underlying_asset),
+				Error::<T>::UnknownUnderlyingAsset
+			);
+
+			let amounts = Self::free_from_strategy(underlying_asset, amounts)?;
+
+			Self::deposit_event(Event::Deallocate { underlying_asset, amounts });
+			Ok(())
+		}
+
+		/// Collect the yield of the strategy into the vault, which raises the price of the
+		/// shares.
+		///
+		/// `StableAmmStrategy` and `ZenlinkPairStrategy` keep their yield in the position and
+		/// always harvest 0, so with them this call only accrues the fees.
+		#[pallet::weight(1_000_000)]
+		#[transactional]
+		pub fn harvest(origin: OriginFor<T>, underlying_asset: T::AssetId) -> DispatchResult {
+			ensure_signed(origin)?;
+			ensure!(
+				Vaults::<T>::contains_key(underlying_asset),
+				Error::<T>::UnknownUnderlyingAsset
+			);
+
+			Self::accrue_fees(underlying_asset)?;
+			let pallet_account = T::PalletId::get().into_account_truncating();
+			let amounts = T::Strategy::harvest(&pallet_account, underlying_asset)?;
+
+			Self::deposit_event(Event::Harvest { underlying_asset, amounts });
+			Ok(())
+		}
+
+		/// Set the fees of a vault, both collected by minting vault asset to the receiver.
+		#[pallet::weight(1_000_000)]
+		#[transactional]
+		pub fn set_vault_fees(
+			origin: OriginFor<T>,
+			underlying_asset: T::AssetId,
+			management_fee: Balance,
+			performance_fee: Balance,
+			receiver: <T::Lookup as StaticLookup>::Source,
+		) -> DispatchResult {
+			ensure_root(origin)?;
+			let receiver = T::Lookup::lookup(receiver)?;
+			ensure!(
+				Vaults::<T>::contains_key(underlying_asset),
+				Error::<T>::UnknownUnderlyingAsset
+			);
+			ensure!(
+				management_fee <= MAX_MANAGEMENT_FEE && performance_fee <= MAX_PERFORMANCE_FEE,
+				Error::<T>::InvalidFee
+			);
+
+			// the fees charged so far follow the old config
+			Self::accrue_fees(underlying_asset)?;
+			if VaultFees::<T>::get(underlying_asset).is_none() {
+				VaultFeeState::<T>::insert(
+					underlying_asset,
+					FeeState {
+						last_accrued: frame_system::Pallet::<T>::block_number(),
+						high_water_mark: Self::share_price(underlying_asset)?,
+					},
+				);
+			}
+			VaultFees::<T>::insert(
+				underlying_asset,
+				FeeConfig { management_fee, performance_fee, receiver: receiver.clone() },
+			);
+
+			Self::deposit_event(Event::UpdateVaultFees {
+				underlying_asset,
+				management_fee,
+				performance_fee,
+				receiver,
+			});
+			Ok(())
+		}
 	}
 }
 
@@ -397,13 +610,59 @@ impl<T: Config> Pallet<T> {
 		T::MultiAsset::withdraw(vault_asset_id, owner, shares)?;
 		let pallet_account = T::PalletId::get().into_account_truncating();
 
+		// take the lacking underlying asset back from the strategy
+		let idle = T::MultiAsset::free_balance(underlying_asset_id, &pallet_account);
+		if idle < amounts {
+			let lacking = amounts - idle;
+			let freed = Self::free_from_strategy(underlying_asset_id, lacking)?;
+			ensure!(freed >= lacking, Error::<T>::StrategyShortfall);
+		}
+
 		T::MultiAsset::transfer(underlying_asset_id, &pallet_account, receiver, amounts)
 	}
 
+	/// The weight of accruing the fees of a vault, not covered by the benchmarked weights: the
+	/// fee config and state, minting the fee shares and valuing the strategy twice, once for the
+	/// fees and once for the conversion of the call.
+	pub(crate) fn accrue_fees_weight() -> Weight {
+		T::DbWeight::get()
+			.reads_writes(4, 3)
+			.saturating_add(T::Strategy::total_value_weight().saturating_mul(2))
+	}
+
+	/// The weight of taking the lacking underlying asset back from the strategy on withdrawal.
+	pub(crate) fn free_from_strategy_weight() -> Weight {
+		T::DbWeight::get().reads_writes(1, 1).saturating_add(T::Strategy::free_weight())
+	}
+
+	/// The weight of a deposit made by another pallet through `Vault`.
+	pub fn deposit_weight() -> Weight {
+		T::WeightInfo::deposit().saturating_add(Self::accrue_fees_weight())
+	}
+
+	/// The weight of a redeem made by another pallet through `Vault`.
+	pub fn redeem_weight() -> Weight {
+		T::WeightInfo::redeem()
+			.saturating_add(Self::accrue_fees_weight())
+			.saturating_add(Self::free_from_strategy_weight())
+	}
+
+	fn free_from_strategy(
+		underlying_asset_id: T::AssetId,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		let pallet_account = T::PalletId::get().into_account_truncating();
+		let freed = T::Strategy::free(&pallet_account, underlying_asset_id, amounts)?;
+		StrategyDebt::<T>::mutate(underlying_asset_id, |debt| *debt = debt.saturating_sub(freed));
+		Ok(freed)
+	}
+
+	/// The penalty ratio of a withdrawal, lower the larger share of the circulation the vault
+	/// holds. The assets deployed to the strategy count in the holdings, since a withdrawal takes
+	/// them back when the idle reserve falls short.
 	fn withdraw_fee_ratio(asset_id: T::AssetId) -> Option<Balance> {
 		let asset_circulation = Self::asset_circulation(asset_id)?;
-		let pallet_account = T::PalletId::get().into_account_truncating();
-		let reserve = T::MultiAsset::free_balance(asset_id, &pallet_account);
+		let reserve = Self::total_assets(asset_id).ok()?;
 
 		let share = balance_mul_div(reserve, 1e18 as Balance, asset_circulation, Rounding::Down)?;
 		let asset_meta = Self::asset_ratio(asset_id)?;
@@ -451,10 +710,141 @@ impl<T: Config> Pallet<T> {
 		Some(total_supply)
 	}
 
+	/// The total supply of the vault asset, including the fee shares not minted yet.
 	fn vault_asset_total_supply(underlying_asset_id: T::AssetId) -> Result<Balance, DispatchError> {
-		let underlying_asset = Self::asset(underlying_asset_id)?;
-		let vault_asset_total_supply = T::MultiAsset::total_issuance(underlying_asset);
-		Ok(vault_asset_total_supply)
+		let vault_asset = Self::asset(underlying_asset_id)?;
+		let pending_fees = Self::pending_fees(underlying_asset_id)?;
+		T::MultiAsset::total_issuance(vault_asset)
+			.checked_add(pending_fees.management_fee_shares)
+			.and_then(|supply| supply.checked_add(pending_fees.performance_fee_shares))
+			.ok_or_else(|| Error::<T>::Math.into())
+	}
+
+	/// The price of a share counted in the underlying asset, scaled by 1e18. Zero if no share
+	/// exists.
+	fn share_price(underlying_asset_id: T::AssetId) -> Result<Balance, DispatchError> {
+		let vault_asset = Self::asset(underlying_asset_id)?;
+		let supply = T::MultiAsset::total_issuance(vault_asset);
+		if supply.is_zero() {
+			return Ok(Zero::zero())
+		}
+		balance_mul_div(
+			Self::total_assets(underlying_asset_id)?,
+			FEE_DENOMINATOR,
+			supply,
+			Rounding::Down,
+		)
+		.ok_or_else(|| Error::<T>::Math.into())
+	}
+
+	/// Calculate the fee shares accrued since the last accrual. The management fee accrues per
+	/// block on the total assets, the performance fee on the profit above the high water mark.
+	pub fn pending_fees(underlying_asset_id: T::AssetId) -> Result<PendingFees, DispatchError> {
+		let state = Self::fee_state(underlying_asset_id);
+		let fees = match Self::vault_fees(underlying_asset_id) {
+			Some(fees) => fees,
+			None => return Ok(PendingFees::default()),
+		};
+		let vault_asset = Self::asset(underlying_asset_id)?;
+		let supply = T::MultiAsset::total_issuance(vault_asset);
+		let total_assets = Self::total_assets(underlying_asset_id)?;
+		if supply.is_zero() || total_assets.is_zero() {
+			return Ok(PendingFees { high_water_mark: state.high_water_mark, ..Default::default() })
+		}
+
+		// charge at most a year at once, so the fee assets stay below the total assets
+		let blocks_per_year: Balance = T::BlocksPerYear::get().unique_saturated_into();
+		let elapsed: Balance = frame_system::Pallet::<T>::block_number()
+			.saturating_sub(state.last_accrued)
+			.unique_saturated_into();
+		let elapsed = elapsed.min(blocks_per_year);
+		let management_fee_assets =
+			balance_mul_div(total_assets, fees.management_fee, FEE_DENOMINATOR, Rounding::Down)
+				.and_then(|yearly| {
+					balance_mul_div(yearly, elapsed, blocks_per_year, Rounding::Down)
+				})
+				.ok_or(Error::<T>::Math)?;
+
+		let price = balance_mul_div(total_assets, FEE_DENOMINATOR, supply, Rounding::Down)
+			.ok_or(Error::<T>::Math)?;
+		let performance_fee_assets = if state.high_water_mark.is_zero() ||
+			price <= state.high_water_mark
+		{
+			Zero::zero()
+		} else {
+			balance_mul_div(price - state.high_water_mark, supply, FEE_DENOMINATOR, Rounding::Down)
+				.and_then(|profit| {
+					balance_mul_div(profit, fees.performance_fee, FEE_DENOMINATOR, Rounding::Down)
+				})
+				.ok_or(Error::<T>::Math)?
+		};
+
+		// mint the shares which take the fee assets from the other holders
+		let fee_assets = management_fee_assets
+			.checked_add(performance_fee_assets)
+			.ok_or(Error::<T>::Math)?;
+		let fee_shares = balance_mul_div(
+			fee_assets,
+			supply,
+			total_assets.checked_sub(fee_assets).ok_or(Error::<T>::Math)?,
+			Rounding::Down,
+		)
+		.ok_or(Error::<T>::Math)?;
+		let management_fee_shares = if fee_assets.is_zero() {
+			Zero::zero()
+		} else {
+			balance_mul_div(fee_shares, management_fee_assets, fee_assets, Rounding::Down)
+				.ok_or(Error::<T>::Math)?
+		};
+		let performance_fee_shares = fee_shares - management_fee_shares;
+
+		let new_price = balance_mul_div(
+			total_assets,
+			FEE_DENOMINATOR,
+			supply.checked_add(fee_shares).ok_or(Error::<T>::Math)?,
+			Rounding::Down,
+		)
+		.ok_or(Error::<T>::Math)?;
+
+		Ok(PendingFees {
+			management_fee_shares,
+			performance_fee_shares,
+			high_water_mark: state.high_water_mark.max(new_price),
+		})
+	}
+
+	/// Mint the accrued fee shares to the fee receiver.
+	fn accrue_fees(underlying_asset_id: T::AssetId) -> DispatchResult {
+		let fees = match Self::vault_fees(underlying_asset_id) {
+			Some(fees) => fees,
+			None => return Ok(()),
+		};
+		let pending_fees = Self::pending_fees(underlying_asset_id)?;
+		VaultFeeState::<T>::insert(
+			underlying_asset_id,
+			FeeState {
+				last_accrued: frame_system::Pallet::<T>::block_number(),
+				high_water_mark: pending_fees.high_water_mark,
+			},
+		);
+
+		let fee_shares = pending_fees
+			.management_fee_shares
+			.checked_add(pending_fees.performance_fee_shares)
+			.ok_or(Error::<T>::Math)?;
+		if fee_shares.is_zero() {
+			return Ok(())
+		}
+		let vault_asset = Self::asset(underlying_asset_id)?;
+		T::MultiAsset::deposit(vault_asset, &fees.receiver, fee_shares)?;
+
+		Self::deposit_event(Event::CollectFees {
+			underlying_asset: underlying_asset_id,
+			receiver: fees.receiver,
+			management_fee_shares: pending_fees.management_fee_shares,
+			performance_fee_shares: pending_fees.performance_fee_shares,
+		});
+		Ok(())
 	}
 
 	fn convert_to_shares_impl(
@@ -495,7 +885,7 @@ impl<T: Config> Pallet<T> {
 		rounding: Rounding,
 	) -> Result<Balance, DispatchError> {
 		let vault_asset = Self::asset(underlying_asset_id)?;
-		let vault_asset_total_supply = T::MultiAsset::total_issuance(vault_asset);
+		let vault_asset_total_supply = Self::vault_asset_total_supply(underlying_asset_id)?;
 		if vault_asset_total_supply.is_zero() {
 			let vault_asset_decimal = Self::asset_decimal(vault_asset)?;
 			let underlying_asset_decimal = Self::asset_decimal(underlying_asset_id)?;
@@ -529,7 +919,9 @@ impl<T: Config> Vault<T> for Pallet<T> {
 
 	fn total_assets(underlying_asset_id: T::AssetId) -> Result<Balance, DispatchError> {
 		let pallet_account = T::PalletId::get().into_account_truncating();
-		Ok(T::MultiAsset::free_balance(underlying_asset_id, &pallet_account))
+		T::MultiAsset::free_balance(underlying_asset_id, &pallet_account)
+			.checked_add(T::Strategy::total_value(underlying_asset_id))
+			.ok_or_else(|| Error::<T>::Math.into())
 	}
 
 	fn convert_to_shares(
@@ -613,12 +1005,23 @@ impl<T: Config> Vault<T> for Pallet<T> {
 		Self::convert_to_assets_impl(underlying_asset_id, shares, Rounding::Down)
 	}
 
+	fn preview_redeem_after_penalty(
+		underlying_asset_id: T::AssetId,
+		shares: Balance,
+	) -> Result<Balance, DispatchError> {
+		let amounts = Self::preview_redeem(underlying_asset_id, shares)?;
+		Self::calculate_withdraw_amounts(underlying_asset_id, amounts)
+			.map(|(amounts, _)| amounts)
+			.ok_or_else(|| Error::<T>::Math.into())
+	}
+
 	fn deposit(
 		who: &T::AccountId,
 		underlying_asset_id: T::AssetId,
 		amounts: Balance,
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
+		Self::accrue_fees(underlying_asset_id)?;
 		ensure!(
 			amounts < Self::max_deposit(underlying_asset_id, to)?,
 			Error::<T>::ExceedMaxDeposit
@@ -636,6 +1039,7 @@ impl<T: Config> Vault<T> for Pallet<T> {
 		shares: Balance,
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
+		Self::accrue_fees(underlying_asset_id)?;
 		ensure!(shares < Self::max_mint(underlying_asset_id, to)?, Error::<T>::ExceedMaxMint);
 
 		let assets = Self::preview_mint(underlying_asset_id, shares)?;
@@ -650,6 +1054,7 @@ impl<T: Config> Vault<T> for Pallet<T> {
 		amounts: Balance,
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
+		Self::accrue_fees(underlying_asset_id)?;
 		ensure!(
 			amounts <= Self::max_withdraw(underlying_asset_id, who)?,
 			Error::<T>::ExceedMaxWithdraw
@@ -678,6 +1083,7 @@ impl<T: Config> Vault<T> for Pallet<T> {
 		shares: Balance,
 		to: &T::AccountId,
 	) -> Result<Balance, DispatchError> {
+		Self::accrue_fees(underlying_asset_id)?;
 		ensure!(shares <= Self::max_redeem(underlying_asset_id, who)?, Error::<T>::ExceedMaxRedeem);
 		let amounts = Self::preview_redeem(underlying_asset_id, shares)?;
 		let (amounts, fee) = Self::calculate_withdraw_amounts(underlying_asset_id, amounts)
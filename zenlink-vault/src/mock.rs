@@ -13,12 +13,16 @@ use frame_support::{
 use sp_core::H256;
 use sp_runtime::{
 	testing::Header,
-	traits::{BlakeTwo256, IdentityLookup},
+	traits::{BlakeTwo256, Convert, IdentityLookup},
 	RuntimeDebug,
 };
 
 use crate::mock::CurrencyId::VaultToken;
 use orml_traits::parameter_type_with_key;
+use zenlink_protocol::{
+	AssetBalance, AssetId, LocalAssetHandler, PairLpGenerate, ZenlinkMultiAssets, LOCAL,
+};
+use zenlink_stable_amm::traits::{StablePoolLpCurrencyIdGenerate, ValidateCurrency};
 
 use crate as vault;
 use crate::primitives::VaultAssetGenerate;
@@ -33,6 +37,17 @@ parameter_types! {
 	pub const VaultAssetPalletId: PalletId = PalletId(*b"/zlkvtat");
 	pub const MaxReserves: u32 = 50;
 	pub const MaxLocks:u32 = 50;
+	pub static HarvestYield: Balance = 0;
+	pub static FreeLimit: Option<Balance> = None;
+	pub static PairPrice: Option<Balance> = Some(FEE_DENOMINATOR);
+	pub const BlocksPerYear: u64 = 2_628_000;
+	pub const MinimumPeriod: u64 = 6000;
+	pub const StableAmmPalletId: PalletId = PalletId(*b"/zlkSAmm");
+	pub const PoolCurrencySymbolLimit: u32 = 50;
+	pub const OracleEmaPeriod: u64 = 600;
+	pub const ZenlinkPalletId: PalletId = PalletId(*b"/zenlink");
+	pub const StrategyPalletId: PalletId = PalletId(*b"/zlkVStr");
+	pub SelfParaId: u32 = CHAIN_ID;
 }
 
 parameter_type_with_key! {
@@ -43,6 +58,7 @@ parameter_type_with_key! {
 
 pub type AccountId = u128;
 pub type TokenSymbol = u8;
+pub type PoolId = u32;
 
 pub struct MockDustRemovalWhitelist;
 impl Contains<AccountId> for MockDustRemovalWhitelist {
@@ -68,6 +84,8 @@ impl Contains<AccountId> for MockDustRemovalWhitelist {
 pub enum CurrencyId {
 	Token(TokenSymbol),
 	VaultToken(TokenSymbol),
+	StableLp(PoolId),
+	ZenlinkLp(TokenSymbol, TokenSymbol),
 }
 
 impl frame_system::Config for Test {
@@ -133,9 +151,249 @@ impl VaultAssetGenerate<CurrencyId> for VaultAssetGenerator {
 	fn generate(asset: CurrencyId) -> Option<CurrencyId> {
 		match asset {
 			CurrencyId::Token(sym) => Some(VaultToken(sym)),
-			VaultToken(_) => None,
+			_ => None,
+		}
+	}
+}
+
+pub struct StablePoolOf;
+
+impl Convert<CurrencyId, Option<PoolId>> for StablePoolOf {
+	fn convert(currency_id: CurrencyId) -> Option<PoolId> {
+		match currency_id {
+			CurrencyId::Token(TOKEN3_SYMBOL) => Some(0),
+			_ => None,
+		}
+	}
+}
+
+pub struct ZenlinkPairOf;
+
+impl Convert<CurrencyId, Option<(AssetId, AssetId)>> for ZenlinkPairOf {
+	fn convert(currency_id: CurrencyId) -> Option<(AssetId, AssetId)> {
+		match currency_id {
+			CurrencyId::Token(TOKEN4_SYMBOL) => Some((TOKEN4_ASSET_ID, TOKEN5_ASSET_ID)),
+			_ => None,
+		}
+	}
+}
+
+/// Prices `TOKEN5_ASSET_ID` in `TOKEN4_ASSET_ID` at `PairPrice`.
+pub struct MockPairPrice;
+
+impl StrategyPrice<AssetId> for MockPairPrice {
+	fn price(asset: AssetId, quote: AssetId) -> Option<Balance> {
+		if asset == TOKEN5_ASSET_ID && quote == TOKEN4_ASSET_ID {
+			PairPrice::get()
+		} else {
+			None
+		}
+	}
+}
+
+pub type StableStrategy = StableAmmStrategy<Test, StrategyPalletId, StablePoolOf>;
+pub type PairStrategy = ZenlinkPairStrategy<Test, StrategyPalletId, ZenlinkPairOf, MockPairPrice>;
+
+/// Deploys `STABLE_ASSET` with `StableStrategy` and `PAIR_ASSET` with `PairStrategy`. Keeps the
+/// other deployed assets in the strategy account, harvesting `HarvestYield` each time and
+/// freeing at most `FreeLimit` at once.
+pub struct MockStrategy;
+
+impl VaultStrategy<AccountId, CurrencyId> for MockStrategy {
+	fn total_value(underlying_asset_id: CurrencyId) -> Balance {
+		match underlying_asset_id {
+			STABLE_ASSET => StableStrategy::total_value(underlying_asset_id),
+			PAIR_ASSET => PairStrategy::total_value(underlying_asset_id),
+			_ => get_user_balance(underlying_asset_id, &STRATEGY),
+		}
+	}
+
+	fn deploy(
+		vault_account: &AccountId,
+		underlying_asset_id: CurrencyId,
+		amounts: Balance,
+	) -> DispatchResult {
+		match underlying_asset_id {
+			STABLE_ASSET => StableStrategy::deploy(vault_account, underlying_asset_id, amounts),
+			PAIR_ASSET => PairStrategy::deploy(vault_account, underlying_asset_id, amounts),
+			_ => <Tokens as MultiCurrency<AccountId>>::transfer(
+				underlying_asset_id,
+				vault_account,
+				&STRATEGY,
+				amounts,
+			),
 		}
 	}
+
+	fn free(
+		vault_account: &AccountId,
+		underlying_asset_id: CurrencyId,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		match underlying_asset_id {
+			STABLE_ASSET => StableStrategy::free(vault_account, underlying_asset_id, amounts),
+			PAIR_ASSET => PairStrategy::free(vault_account, underlying_asset_id, amounts),
+			_ => {
+				let amounts = amounts
+					.min(Self::total_value(underlying_asset_id))
+					.min(FreeLimit::get().unwrap_or(Balance::MAX));
+				<Tokens as MultiCurrency<AccountId>>::transfer(
+					underlying_asset_id,
+					&STRATEGY,
+					vault_account,
+					amounts,
+				)?;
+				Ok(amounts)
+			},
+		}
+	}
+
+	fn harvest(
+		vault_account: &AccountId,
+		underlying_asset_id: CurrencyId,
+	) -> Result<Balance, DispatchError> {
+		match underlying_asset_id {
+			STABLE_ASSET => StableStrategy::harvest(vault_account, underlying_asset_id),
+			PAIR_ASSET => PairStrategy::harvest(vault_account, underlying_asset_id),
+			_ => {
+				let amounts = HarvestYield::get();
+				<Tokens as MultiCurrency<AccountId>>::deposit(
+					underlying_asset_id,
+					vault_account,
+					amounts,
+				)?;
+				Ok(amounts)
+			},
+		}
+	}
+
+	fn total_value_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn free_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+impl pallet_timestamp::Config for Test {
+	type MinimumPeriod = MinimumPeriod;
+	type Moment = u64;
+	type OnTimestampSet = ();
+	type WeightInfo = ();
+}
+
+pub struct PoolLpGenerate;
+
+impl StablePoolLpCurrencyIdGenerate<CurrencyId, PoolId> for PoolLpGenerate {
+	fn generate_by_pool_id(pool_id: PoolId) -> CurrencyId {
+		CurrencyId::StableLp(pool_id)
+	}
+}
+
+pub struct EnsurePoolAsset;
+
+impl ValidateCurrency<CurrencyId> for EnsurePoolAsset {
+	fn validate_pooled_currency(currencies: &[CurrencyId]) -> bool {
+		currencies.iter().all(|currency_id| matches!(currency_id, CurrencyId::Token(_)))
+	}
+
+	fn validate_pool_lp_currency(currency_id: CurrencyId) -> bool {
+		matches!(currency_id, CurrencyId::StableLp(_)) &&
+			<Tokens as MultiCurrency<AccountId>>::total_issuance(currency_id) == 0
+	}
+}
+
+impl zenlink_stable_amm::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type CurrencyId = CurrencyId;
+	type MultiCurrency = Tokens;
+	type PoolId = PoolId;
+	type EnsurePoolAsset = EnsurePoolAsset;
+	type LpGenerate = PoolLpGenerate;
+	type TimeProvider = Timestamp;
+	type PoolCurrencySymbolLimit = PoolCurrencySymbolLimit;
+	type OracleEmaPeriod = OracleEmaPeriod;
+	type PalletId = StableAmmPalletId;
+	type WeightInfo = ();
+}
+
+pub fn asset_id_to_currency_id(asset_id: &AssetId) -> CurrencyId {
+	if (asset_id.asset_index & 0x0000_0000_0000_ff00) >> 8 == 6 {
+		let token0 = ((asset_id.asset_index & 0x0000_0000_ffff_0000) >> 16) as TokenSymbol;
+		let token1 = ((asset_id.asset_index & 0x0000_ffff_0000_0000) >> 32) as TokenSymbol;
+		CurrencyId::ZenlinkLp(token0, token1)
+	} else {
+		CurrencyId::Token(asset_id.asset_index as TokenSymbol)
+	}
+}
+
+pub struct LocalAssetAdaptor;
+
+impl LocalAssetHandler<AccountId> for LocalAssetAdaptor {
+	fn local_balance_of(asset_id: AssetId, who: &AccountId) -> AssetBalance {
+		<Tokens as MultiCurrency<AccountId>>::free_balance(asset_id_to_currency_id(&asset_id), who)
+	}
+
+	fn local_total_supply(asset_id: AssetId) -> AssetBalance {
+		<Tokens as MultiCurrency<AccountId>>::total_issuance(asset_id_to_currency_id(&asset_id))
+	}
+
+	fn local_is_exists(asset_id: AssetId) -> bool {
+		<Tokens as MultiCurrency<AccountId>>::total_issuance(asset_id_to_currency_id(&asset_id)) > 0
+	}
+
+	fn local_transfer(
+		asset_id: AssetId,
+		origin: &AccountId,
+		target: &AccountId,
+		amount: AssetBalance,
+	) -> DispatchResult {
+		<Tokens as MultiCurrency<AccountId>>::transfer(
+			asset_id_to_currency_id(&asset_id),
+			origin,
+			target,
+			amount,
+		)
+	}
+
+	fn local_deposit(
+		asset_id: AssetId,
+		origin: &AccountId,
+		amount: AssetBalance,
+	) -> Result<AssetBalance, DispatchError> {
+		<Tokens as MultiCurrency<AccountId>>::deposit(
+			asset_id_to_currency_id(&asset_id),
+			origin,
+			amount,
+		)
+		.map(|_| amount)
+	}
+
+	fn local_withdraw(
+		asset_id: AssetId,
+		origin: &AccountId,
+		amount: AssetBalance,
+	) -> Result<AssetBalance, DispatchError> {
+		<Tokens as MultiCurrency<AccountId>>::withdraw(
+			asset_id_to_currency_id(&asset_id),
+			origin,
+			amount,
+		)
+		.map(|_| amount)
+	}
+}
+
+impl zenlink_protocol::Config for Test {
+	type RuntimeEvent = RuntimeEvent;
+	type MultiAssetsHandler = ZenlinkMultiAssets<Zenlink, Balances, LocalAssetAdaptor>;
+	type PalletId = ZenlinkPalletId;
+	type AssetId = AssetId;
+	type LpGenerate = PairLpGenerate<Self>;
+	type TargetChains = ();
+	type SelfParaId = SelfParaId;
+	type WeightInfo = ();
+	type ControlOrigin = frame_system::EnsureRoot<AccountId>;
 }
 
 impl Config for Test {
@@ -143,6 +401,8 @@ impl Config for Test {
 	type AssetId = CurrencyId;
 	type MultiAsset = Tokens;
 	type VaultAssetGenerate = VaultAssetGenerator;
+	type Strategy = MockStrategy;
+	type BlocksPerYear = BlocksPerYear;
 	type PalletId = VaultAssetPalletId;
 	type WeightInfo = ();
 }
@@ -154,9 +414,12 @@ frame_support::construct_runtime!(
 		UncheckedExtrinsic = UncheckedExtrinsic,
 	{
 		System: frame_system::{Pallet, Call, Config, Storage, Event<T>} = 0,
+		Timestamp: pallet_timestamp::{Pallet, Call, Storage, Inherent} = 1,
 
 		Balances: pallet_balances::{Pallet, Call, Storage, Config<T>, Event<T>} = 8,
+		StableAmm: zenlink_stable_amm::{Pallet, Call, Storage, Event<T>} = 9,
 		Tokens: orml_tokens::{Pallet, Storage, Event<T>, Config<T>} = 11,
+		Zenlink: zenlink_protocol::{Pallet, Call, Storage, Event<T>} = 12,
 		Vaults: vault::{Pallet, Call, Storage, Event<T>}
 	}
 );
@@ -166,9 +429,24 @@ pub type VaultPallet = Pallet<Test>;
 pub const ALICE: u128 = 1;
 pub const BOB: u128 = 2;
 pub const CHARLIE: u128 = 3;
+pub const STRATEGY: u128 = 100;
 
 pub const TOKEN1_SYMBOL: u8 = 1;
 pub const TOKEN2_SYMBOL: u8 = 2;
+pub const TOKEN3_SYMBOL: u8 = 3;
+pub const TOKEN4_SYMBOL: u8 = 4;
+pub const TOKEN5_SYMBOL: u8 = 5;
+
+/// The underlying asset deployed to a stable pool of it and `TOKEN5_SYMBOL`.
+pub const STABLE_ASSET: CurrencyId = CurrencyId::Token(TOKEN3_SYMBOL);
+/// The underlying asset deployed to a zenlink pair of it and `TOKEN5_SYMBOL`.
+pub const PAIR_ASSET: CurrencyId = CurrencyId::Token(TOKEN4_SYMBOL);
+
+pub const CHAIN_ID: u32 = 200;
+pub const TOKEN4_ASSET_ID: AssetId =
+	AssetId { chain_id: CHAIN_ID, asset_type: LOCAL, asset_index: TOKEN4_SYMBOL as u64 };
+pub const TOKEN5_ASSET_ID: AssetId =
+	AssetId { chain_id: CHAIN_ID, asset_type: LOCAL, asset_index: TOKEN5_SYMBOL as u64 };
 
 pub const TOKEN1_DECIMAL: u8 = 18;
 pub const TOKEN2_DECIMAL: u8 = 12;
@@ -190,6 +468,9 @@ pub fn new_test_ext() -> sp_io::TestExternalities {
 			(BOB, CurrencyId::Token(TOKEN2_SYMBOL), TOKEN2_UNIT * 30),
 			(CHARLIE, CurrencyId::Token(TOKEN1_SYMBOL), TOKEN1_UNIT * 20),
 			(CHARLIE, CurrencyId::Token(TOKEN2_SYMBOL), TOKEN2_UNIT * 20),
+			(ALICE, STABLE_ASSET, TOKEN1_UNIT * 1000),
+			(ALICE, PAIR_ASSET, TOKEN1_UNIT * 1000),
+			(ALICE, CurrencyId::Token(TOKEN5_SYMBOL), TOKEN1_UNIT * 1000),
 		],
 	}
 	.assimilate_storage(&mut t)
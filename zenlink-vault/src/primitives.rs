@@ -6,6 +6,15 @@ use sp_arithmetic::{helpers_128bit::*, Rounding};
 
 pub type Balance = u128;
 
+/// The scale of the fee ratios and the share price.
+pub const FEE_DENOMINATOR: Balance = 1_000_000_000_000_000_000;
+/// The highest management fee a vault may charge per year, 10%.
+pub const MAX_MANAGEMENT_FEE: Balance = FEE_DENOMINATOR / 10;
+/// The highest performance fee a vault may charge, 50% of the profit.
+pub const MAX_PERFORMANCE_FEE: Balance = FEE_DENOMINATOR / 2;
+/// The most the amounts a strategy realizes may fall below the value of its position, 1%.
+pub const MAX_STRATEGY_SLIPPAGE: Balance = FEE_DENOMINATOR / 100;
+
 pub fn balance_mul_div(x: Balance, y: Balance, z: Balance, rounding: Rounding) -> Option<Balance> {
 	multiply_by_rational_with_rounding(x, y, z, rounding)
 }
@@ -27,3 +36,44 @@ pub struct Ratio {
 pub trait VaultAssetGenerate<CurrencyId> {
 	fn generate(asset: CurrencyId) -> Option<CurrencyId>;
 }
+
+/// The price a strategy values its position at, which must not move with the reserves traded
+/// against within a block, such as an oracle price.
+pub trait StrategyPrice<AssetId> {
+	/// Returns the price of `asset` counted in `quote`, scaled by 1e18.
+	fn price(asset: AssetId, quote: AssetId) -> Option<Balance>;
+}
+
+impl<AssetId> StrategyPrice<AssetId> for () {
+	fn price(_asset: AssetId, _quote: AssetId) -> Option<Balance> {
+		None
+	}
+}
+
+/// The fees charged by a vault, scaled by 1e18.
+#[derive(Encode, Decode, Clone, PartialEq, Eq, Debug, TypeInfo)]
+pub struct FeeConfig<AccountId> {
+	/// The ratio of the assets charged per year.
+	pub management_fee: Balance,
+	/// The ratio of the profit above the high water mark charged.
+	pub performance_fee: Balance,
+	/// The account receiving the fee shares.
+	pub receiver: AccountId,
+}
+
+/// The accrual state of the fees of a vault.
+#[derive(Encode, Decode, Clone, Default, PartialEq, Eq, Debug, TypeInfo)]
+pub struct FeeState<BlockNumber> {
+	/// The block the fees last accrued at.
+	pub last_accrued: BlockNumber,
+	/// The highest price of a share the performance fee was charged at, scaled by 1e18.
+	pub high_water_mark: Balance,
+}
+
+/// The fee shares of a vault not minted yet.
+#[derive(Clone, Default, PartialEq, Eq, Debug)]
+pub struct PendingFees {
+	pub management_fee_shares: Balance,
+	pub performance_fee_shares: Balance,
+	pub high_water_mark: Balance,
+}
@@ -0,0 +1,644 @@
+// Copyright 2021-2022 Zenlink.
+// Licensed under Apache 2.0.
+
+use super::*;
+use sp_core::U256;
+use sp_runtime::traits::Convert;
+use sp_std::{marker::PhantomData, vec};
+use zenlink_protocol::{ExportZenlink, MultiAssetsHandler, PairStatus::Trading};
+use zenlink_stable_amm::{
+	traits::{StableAmmApi, StableAmmOracle},
+	MAX_POOL_CURRENCIES,
+};
+
+/// A strategy which deploys the idle underlying asset of the vault to earn yield.
+pub trait VaultStrategy<AccountId, AssetId> {
+	/// Returns the value of the position of the strategy, counted in the underlying asset.
+	fn total_value(underlying_asset_id: AssetId) -> Balance;
+
+	/// Moves the amounts of the underlying asset from the vault account into the position.
+	fn deploy(
+		vault_account: &AccountId,
+		underlying_asset_id: AssetId,
+		amounts: Balance,
+	) -> DispatchResult;
+
+	/// Takes the amounts of the underlying asset out of the position back to the vault account,
+	/// returns the amounts actually freed.
+	fn free(
+		vault_account: &AccountId,
+		underlying_asset_id: AssetId,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError>;
+
+	/// Collects the yield of the position into the vault account, returns the amounts collected.
+	fn harvest(
+		vault_account: &AccountId,
+		underlying_asset_id: AssetId,
+	) -> Result<Balance, DispatchError>;
+
+	/// The weight of `total_value`.
+	fn total_value_weight() -> Weight;
+
+	/// The weight of `free`.
+	fn free_weight() -> Weight;
+}
+
+impl<AccountId, AssetId> VaultStrategy<AccountId, AssetId> for () {
+	fn total_value(_underlying_asset_id: AssetId) -> Balance {
+		Zero::zero()
+	}
+
+	fn deploy(
+		_vault_account: &AccountId,
+		_underlying_asset_id: AssetId,
+		_amounts: Balance,
+	) -> DispatchResult {
+		Err(DispatchError::Other("no vault strategy"))
+	}
+
+	fn free(
+		_vault_account: &AccountId,
+		_underlying_asset_id: AssetId,
+		_amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		Ok(Zero::zero())
+	}
+
+	fn harvest(
+		_vault_account: &AccountId,
+		_underlying_asset_id: AssetId,
+	) -> Result<Balance, DispatchError> {
+		Ok(Zero::zero())
+	}
+
+	fn total_value_weight() -> Weight {
+		Weight::zero()
+	}
+
+	fn free_weight() -> Weight {
+		Weight::zero()
+	}
+}
+
+/// The least `amount` may fall to with the slippage of a strategy.
+fn min_with_slippage(amount: Balance) -> Option<Balance> {
+	balance_mul_div(
+		amount,
+		FEE_DENOMINATOR.saturating_sub(MAX_STRATEGY_SLIPPAGE),
+		FEE_DENOMINATOR,
+		Rounding::Up,
+	)
+}
+
+/// The most `amount` may rise to with the slippage of a strategy.
+fn max_with_slippage(amount: Balance) -> Option<Balance> {
+	balance_mul_div(
+		amount,
+		FEE_DENOMINATOR.saturating_add(MAX_STRATEGY_SLIPPAGE),
+		FEE_DENOMINATOR,
+		Rounding::Up,
+	)
+}
+
+type StableAmmOf<T> = zenlink_stable_amm::Pallet<T>;
+type StablePoolIdOf<T> = <T as zenlink_stable_amm::Config>::PoolId;
+
+/// Deploys the underlying asset into the stable-amm pool `PoolOf` maps it to, as single sided
+/// liquidity. The lp currency is held by a sub account of `StrategyId` for each underlying asset
+/// and valued as the amount of the underlying asset it withdraws. Adding and removing liquidity
+/// is bounded by the moving average virtual price of the pool, counting its currencies at par.
+/// The swap fees of the pool accrue in the value of the lp currency, so there is nothing to
+/// harvest.
+pub struct StableAmmStrategy<T, StrategyId, PoolOf>(PhantomData<(T, StrategyId, PoolOf)>);
+
+impl<T, StrategyId, PoolOf> StableAmmStrategy<T, StrategyId, PoolOf>
+where
+	T: Config + zenlink_stable_amm::Config<CurrencyId = <T as Config>::AssetId>,
+	StrategyId: Get<PalletId>,
+	PoolOf: Convert<<T as Config>::AssetId, Option<StablePoolIdOf<T>>>,
+{
+	fn position_account(underlying_asset_id: <T as Config>::AssetId) -> T::AccountId {
+		StrategyId::get().into_sub_account_truncating(underlying_asset_id)
+	}
+
+	/// Returns the pool, the index of the underlying asset in it and the lp currency of it.
+	fn pool_of(
+		underlying_asset_id: <T as Config>::AssetId,
+	) -> Result<(StablePoolIdOf<T>, u32, <T as Config>::AssetId), DispatchError> {
+		let pool_id =
+			PoolOf::convert(underlying_asset_id).ok_or(Error::<T>::InvalidStrategyPool)?;
+		let index = StableAmmOf::<T>::get_currency_index(pool_id, underlying_asset_id)
+			.ok_or(Error::<T>::InvalidStrategyPool)?;
+		let lp_currency_id =
+			StableAmmOf::<T>::get_lp_currency(pool_id).ok_or(Error::<T>::InvalidStrategyPool)?;
+		Ok((pool_id, index, lp_currency_id))
+	}
+
+	/// Returns the amount of the lp currency worth `amounts` of the underlying asset at the
+	/// moving average virtual price.
+	fn lp_value(
+		pool_id: StablePoolIdOf<T>,
+		index: u32,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		let (virtual_price, multiplier) = Self::par_price(pool_id, index)?;
+		amounts
+			.checked_mul(multiplier)
+			.and_then(|amounts| {
+				balance_mul_div(amounts, FEE_DENOMINATOR, virtual_price, Rounding::Down)
+			})
+			.ok_or_else(|| Error::<T>::Math.into())
+	}
+
+	/// Returns the amount of the underlying asset `lp_amount` is worth at the moving average
+	/// virtual price.
+	fn underlying_value(
+		pool_id: StablePoolIdOf<T>,
+		index: u32,
+		lp_amount: Balance,
+	) -> Result<Balance, DispatchError> {
+		let (virtual_price, multiplier) = Self::par_price(pool_id, index)?;
+		balance_mul_div(lp_amount, virtual_price, FEE_DENOMINATOR, Rounding::Down)
+			.and_then(|amounts| amounts.checked_div(multiplier))
+			.ok_or_else(|| Error::<T>::Math.into())
+	}
+
+	/// Returns the moving average virtual price of the pool and the precision multiplier of the
+	/// underlying asset.
+	fn par_price(
+		pool_id: StablePoolIdOf<T>,
+		index: u32,
+	) -> Result<(Balance, Balance), DispatchError> {
+		let virtual_price =
+			<StableAmmOf<T> as StableAmmOracle<StablePoolIdOf<T>, Balance>>::virtual_price_oracle(
+				pool_id,
+			)
+			.ok_or(Error::<T>::NoStrategyPrice)?;
+		let multiplier = StableAmmOf::<T>::get_currency_precision_multipliers(pool_id)
+			.get(index as usize)
+			.copied()
+			.filter(|multiplier| !multiplier.is_zero())
+			.ok_or(Error::<T>::InvalidStrategyPool)?;
+		Ok((virtual_price, multiplier))
+	}
+
+	/// The amounts of each currency of the pool, holding `amounts` at the index of the underlying
+	/// asset.
+	fn single_amounts(pool_id: StablePoolIdOf<T>, index: u32, amounts: Balance) -> Vec<Balance> {
+		let currencies = StableAmmOf::<T>::get_currencies(pool_id);
+		let mut single_amounts = vec![Zero::zero(); currencies.len()];
+		if let Some(amount) = single_amounts.get_mut(index as usize) {
+			*amount = amounts;
+		}
+		single_amounts
+	}
+}
+
+impl<T, StrategyId, PoolOf> VaultStrategy<T::AccountId, <T as Config>::AssetId>
+	for StableAmmStrategy<T, StrategyId, PoolOf>
+where
+	T: Config + zenlink_stable_amm::Config<CurrencyId = <T as Config>::AssetId>,
+	StrategyId: Get<PalletId>,
+	PoolOf: Convert<<T as Config>::AssetId, Option<StablePoolIdOf<T>>>,
+{
+	fn total_value(underlying_asset_id: <T as Config>::AssetId) -> Balance {
+		let (pool_id, index, lp_currency_id) = match Self::pool_of(underlying_asset_id) {
+			Ok(pool) => pool,
+			Err(_) => return Zero::zero(),
+		};
+		let lp_amount = <T as Config>::MultiAsset::free_balance(
+			lp_currency_id,
+			&Self::position_account(underlying_asset_id),
+		);
+		if lp_amount.is_zero() {
+			return Zero::zero()
+		}
+		<StableAmmOf<T> as StableAmmApi<
+			StablePoolIdOf<T>,
+			<T as Config>::AssetId,
+			T::AccountId,
+			Balance,
+		>>::stable_amm_calculate_remove_liquidity_one_currency(pool_id, lp_amount, index)
+		.unwrap_or_default()
+	}
+
+	fn deploy(
+		vault_account: &T::AccountId,
+		underlying_asset_id: <T as Config>::AssetId,
+		amounts: Balance,
+	) -> DispatchResult {
+		let (pool_id, index, _) = Self::pool_of(underlying_asset_id)?;
+		let position_account = Self::position_account(underlying_asset_id);
+		let min_mint_amount =
+			min_with_slippage(Self::lp_value(pool_id, index, amounts)?).ok_or(Error::<T>::Math)?;
+		<T as Config>::MultiAsset::transfer(
+			underlying_asset_id,
+			vault_account,
+			&position_account,
+			amounts,
+		)?;
+
+		<StableAmmOf<T> as StableAmmApi<
+			StablePoolIdOf<T>,
+			<T as Config>::AssetId,
+			T::AccountId,
+			Balance,
+		>>::add_liquidity(
+			&position_account,
+			pool_id,
+			&Self::single_amounts(pool_id, index, amounts),
+			min_mint_amount,
+			&position_account,
+		)?;
+		Ok(())
+	}
+
+	fn free(
+		vault_account: &T::AccountId,
+		underlying_asset_id: <T as Config>::AssetId,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		let (pool_id, index, lp_currency_id) = Self::pool_of(underlying_asset_id)?;
+		let position_account = Self::position_account(underlying_asset_id);
+		let lp_amount = <T as Config>::MultiAsset::free_balance(lp_currency_id, &position_account);
+		if lp_amount.is_zero() || amounts.is_zero() {
+			return Ok(Zero::zero())
+		}
+
+		// the whole position is freed when it falls short of the amounts
+		if amounts >= Self::total_value(underlying_asset_id) {
+			let min_amount = min_with_slippage(Self::underlying_value(pool_id, index, lp_amount)?)
+				.ok_or(Error::<T>::Math)?;
+			return <StableAmmOf<T> as StableAmmApi<
+				StablePoolIdOf<T>,
+				<T as Config>::AssetId,
+				T::AccountId,
+				Balance,
+			>>::remove_liquidity_one_currency(
+				&position_account,
+				pool_id,
+				lp_amount,
+				index,
+				min_amount,
+				vault_account,
+			)
+		}
+
+		let max_burn_amount = max_with_slippage(Self::lp_value(pool_id, index, amounts)?)
+			.ok_or(Error::<T>::Math)?
+			.min(lp_amount);
+
+		<StableAmmOf<T> as StableAmmApi<
+			StablePoolIdOf<T>,
+			<T as Config>::AssetId,
+			T::AccountId,
+			Balance,
+		>>::remove_liquidity_imbalance(
+			&position_account,
+			pool_id,
+			&Self::single_amounts(pool_id, index, amounts),
+			max_burn_amount,
+			vault_account,
+		)?;
+		Ok(amounts)
+	}
+
+	fn harvest(
+		_vault_account: &T::AccountId,
+		_underlying_asset_id: <T as Config>::AssetId,
+	) -> Result<Balance, DispatchError> {
+		Ok(Zero::zero())
+	}
+
+	fn total_value_weight() -> Weight {
+		<T as zenlink_stable_amm::Config>::WeightInfo::remove_liquidity_one_currency(
+			MAX_POOL_CURRENCIES,
+			1,
+		)
+	}
+
+	fn free_weight() -> Weight {
+		<T as zenlink_stable_amm::Config>::WeightInfo::remove_liquidity_imbalance(
+			MAX_POOL_CURRENCIES,
+			1,
+		)
+		.max(<T as zenlink_stable_amm::Config>::WeightInfo::remove_liquidity_one_currency(
+			MAX_POOL_CURRENCIES,
+			1,
+		))
+		.saturating_add(Self::total_value_weight())
+		// the oracle and the pool for the virtual price and the precision multiplier
+		.saturating_add(T::DbWeight::get().reads(2))
+	}
+}
+
+type ZenlinkOf<T> = zenlink_protocol::Pallet<T>;
+type ZenlinkAssetIdOf<T> = <T as zenlink_protocol::Config>::AssetId;
+type ZenlinkAssetsOf<T> = <T as zenlink_protocol::Config>::MultiAssetsHandler;
+
+/// Deploys the underlying asset into the zenlink-protocol pair `PairOf` maps it to, which also
+/// names the underlying asset in zenlink-protocol. Half of the deployed asset is sold for the
+/// paired asset and both are added as liquidity. The position is held by a sub account of
+/// `StrategyId` for each underlying asset and valued at the paired asset price of `Price`, with
+/// the reserves of the pair rebalanced to that price, so trading against the pair doesn't move
+/// it. Without a price the position is valued at the debt of the strategy. The swap fees accrue
+/// in the share of the reserves, so there is nothing to harvest.
+pub struct ZenlinkPairStrategy<T, StrategyId, PairOf, Price>(
+	PhantomData<(T, StrategyId, PairOf, Price)>,
+);
+
+impl<T, StrategyId, PairOf, Price> ZenlinkPairStrategy<T, StrategyId, PairOf, Price>
+where
+	T: Config + zenlink_protocol::Config,
+	StrategyId: Get<PalletId>,
+	PairOf: Convert<<T as Config>::AssetId, Option<(ZenlinkAssetIdOf<T>, ZenlinkAssetIdOf<T>)>>,
+	Price: StrategyPrice<ZenlinkAssetIdOf<T>>,
+{
+	fn position_account(underlying_asset_id: <T as Config>::AssetId) -> T::AccountId {
+		StrategyId::get().into_sub_account_truncating(underlying_asset_id)
+	}
+
+	/// Returns the underlying asset and the paired asset in zenlink-protocol.
+	fn pair_of(
+		underlying_asset_id: <T as Config>::AssetId,
+	) -> Result<(ZenlinkAssetIdOf<T>, ZenlinkAssetIdOf<T>), DispatchError> {
+		PairOf::convert(underlying_asset_id).ok_or_else(|| Error::<T>::InvalidStrategyPool.into())
+	}
+
+	/// Returns the reserve of the underlying asset with the reserves of the pair rebalanced to the
+	/// paired asset at `price`, keeping their product, which only grows by trading. The rebalanced
+	/// paired reserve is worth as much. Also returns the total supply of the lp asset.
+	fn fair_reserve(
+		asset: ZenlinkAssetIdOf<T>,
+		paired_asset: ZenlinkAssetIdOf<T>,
+		price: Balance,
+	) -> Option<(Balance, Balance)> {
+		match ZenlinkOf::<T>::pair_status(ZenlinkOf::<T>::sort_asset_id(asset, paired_asset)) {
+			Trading(pair) if !pair.total_supply.is_zero() => {
+				let reserve = ZenlinkAssetsOf::<T>::balance_of(asset, &pair.pair_account);
+				let paired_reserve =
+					ZenlinkAssetsOf::<T>::balance_of(paired_asset, &pair.pair_account);
+				let paired_value = U256::from(paired_reserve)
+					.checked_mul(U256::from(price))?
+					.checked_div(U256::from(FEE_DENOMINATOR))?;
+				let fair_reserve = U256::from(reserve)
+					.checked_mul(paired_value)?
+					.integer_sqrt()
+					.try_into()
+					.ok()?;
+				Some((fair_reserve, pair.total_supply))
+			},
+			_ => None,
+		}
+	}
+
+	/// Returns the least amounts of both assets removing `lp_amount` may take, with the reserves of
+	/// the pair near the rebalanced ones.
+	fn removal_min(
+		asset: ZenlinkAssetIdOf<T>,
+		paired_asset: ZenlinkAssetIdOf<T>,
+		lp_amount: Balance,
+		price: Balance,
+	) -> Option<(Balance, Balance)> {
+		let (fair_reserve, total_supply) = Self::fair_reserve(asset, paired_asset, price)?;
+		let share = balance_mul_div(lp_amount, fair_reserve, total_supply, Rounding::Down)?;
+		let paired_share = balance_mul_div(share, FEE_DENOMINATOR, price, Rounding::Down)?;
+		Some((min_with_slippage(share)?, min_with_slippage(paired_share)?))
+	}
+
+	/// Returns the value of the position with the paired asset at `price` and the reserves of the
+	/// pair rebalanced to it.
+	fn fair_value(
+		asset: ZenlinkAssetIdOf<T>,
+		paired_asset: ZenlinkAssetIdOf<T>,
+		position_account: &T::AccountId,
+		price: Balance,
+	) -> Option<Balance> {
+		let amount = ZenlinkAssetsOf::<T>::balance_of(asset, position_account);
+		let paired_amount = ZenlinkAssetsOf::<T>::balance_of(paired_asset, position_account);
+		let mut value = balance_mul_div(paired_amount, price, FEE_DENOMINATOR, Rounding::Down)?
+			.checked_add(amount)?;
+
+		let lp_amount = ZenlinkOf::<T>::lp_asset_id(&asset, &paired_asset)
+			.map(|lp_asset| ZenlinkAssetsOf::<T>::balance_of(lp_asset, position_account))
+			.unwrap_or_default();
+		if !lp_amount.is_zero() {
+			if let Some((fair_reserve, total_supply)) =
+				Self::fair_reserve(asset, paired_asset, price)
+			{
+				let lp_value = balance_mul_div(
+					fair_reserve.checked_mul(2)?,
+					lp_amount,
+					total_supply,
+					Rounding::Down,
+				)?;
+				value = value.checked_add(lp_value)?;
+			}
+		}
+		Some(value)
+	}
+}
+
+impl<T, StrategyId, PairOf, Price> VaultStrategy<T::AccountId, <T as Config>::AssetId>
+	for ZenlinkPairStrategy<T, StrategyId, PairOf, Price>
+where
+	T: Config + zenlink_protocol::Config,
+	StrategyId: Get<PalletId>,
+	PairOf: Convert<<T as Config>::AssetId, Option<(ZenlinkAssetIdOf<T>, ZenlinkAssetIdOf<T>)>>,
+	Price: StrategyPrice<ZenlinkAssetIdOf<T>>,
+{
+	fn total_value(underlying_asset_id: <T as Config>::AssetId) -> Balance {
+		let (asset, paired_asset) = match Self::pair_of(underlying_asset_id) {
+			Ok(pair) => pair,
+			Err(_) => return Zero::zero(),
+		};
+		Price::price(paired_asset, asset)
+			.and_then(|price| {
+				Self::fair_value(
+					asset,
+					paired_asset,
+					&Self::position_account(underlying_asset_id),
+					price,
+				)
+			})
+			.unwrap_or_else(|| Pallet::<T>::strategy_debt(underlying_asset_id))
+	}
+
+	fn deploy(
+		vault_account: &T::AccountId,
+		underlying_asset_id: <T as Config>::AssetId,
+		amounts: Balance,
+	) -> DispatchResult {
+		let (asset, paired_asset) = Self::pair_of(underlying_asset_id)?;
+		let position_account = Self::position_account(underlying_asset_id);
+		let price = Price::price(paired_asset, asset).ok_or(Error::<T>::NoStrategyPrice)?;
+		let value = Self::fair_value(asset, paired_asset, &position_account, price)
+			.ok_or(Error::<T>::Math)?;
+		<T as Config>::MultiAsset::transfer(
+			underlying_asset_id,
+			vault_account,
+			&position_account,
+			amounts,
+		)?;
+
+		let sold = amounts / 2;
+		let min_bought = balance_mul_div(sold, FEE_DENOMINATOR, price, Rounding::Down)
+			.and_then(min_with_slippage)
+			.ok_or(Error::<T>::Math)?;
+		<ZenlinkOf<T> as ExportZenlink<_, _>>::inner_swap_exact_assets_for_assets(
+			&position_account,
+			sold,
+			min_bought,
+			&[asset, paired_asset],
+			&position_account,
+		)?;
+
+		let amount = ZenlinkAssetsOf::<T>::balance_of(asset, &position_account);
+		let paired_amount = ZenlinkAssetsOf::<T>::balance_of(paired_asset, &position_account);
+		<ZenlinkOf<T> as ExportZenlink<_, _>>::inner_add_liquidity(
+			&position_account,
+			asset,
+			paired_asset,
+			amount,
+			paired_amount,
+			min_with_slippage(amount).ok_or(Error::<T>::Math)?,
+			min_with_slippage(paired_amount).ok_or(Error::<T>::Math)?,
+		)?;
+
+		// the position must gain the amounts deployed, at the price
+		let added = Self::fair_value(asset, paired_asset, &position_account, price)
+			.ok_or(Error::<T>::Math)?
+			.saturating_sub(value);
+		ensure!(
+			added >= min_with_slippage(amounts).ok_or(Error::<T>::Math)?,
+			Error::<T>::StrategySlippage
+		);
+		Ok(())
+	}
+
+	fn free(
+		vault_account: &T::AccountId,
+		underlying_asset_id: <T as Config>::AssetId,
+		amounts: Balance,
+	) -> Result<Balance, DispatchError> {
+		let (asset, paired_asset) = Self::pair_of(underlying_asset_id)?;
+		let position_account = Self::position_account(underlying_asset_id);
+		let value = Self::total_value(underlying_asset_id);
+		if value.is_zero() || amounts.is_zero() {
+			return Ok(Zero::zero())
+		}
+		// without a price only the amounts freed are bounded, by the debt
+		let price = Price::price(paired_asset, asset);
+
+		// remove the share of the liquidity backing the amounts, with room for the slippage of
+		// realizing them below the value
+		if let Some(lp_asset) = ZenlinkOf::<T>::lp_asset_id(&asset, &paired_asset) {
+			let lp_amount = ZenlinkAssetsOf::<T>::balance_of(lp_asset, &position_account);
+			let removed = balance_mul_div(lp_amount, amounts.min(value), value, Rounding::Up)
+				.and_then(max_with_slippage)
+				.ok_or(Error::<T>::Math)?
+				.min(lp_amount);
+			if !removed.is_zero() {
+				let (amount_min, paired_amount_min) = match price {
+					Some(price) => Self::removal_min(asset, paired_asset, removed, price)
+						.ok_or(Error::<T>::Math)?,
+					None => (Zero::zero(), Zero::zero()),
+				};
+				<ZenlinkOf<T> as ExportZenlink<_, _>>::inner_remove_liquidity(
+					&position_account,
+					asset,
+					paired_asset,
+					removed,
+					amount_min,
+					paired_amount_min,
+					&position_account,
+				)?;
+			}
+		}
+
+		// buy the lacking underlying asset with the paired asset, or sell all of it if it falls
+		// short
+		let amount = ZenlinkAssetsOf::<T>::balance_of(asset, &position_account);
+		let paired_amount = ZenlinkAssetsOf::<T>::balance_of(paired_asset, &position_account);
+		if amount < amounts && !paired_amount.is_zero() {
+			let lacking = amounts - amount;
+			let path = [paired_asset, asset];
+			let paired_needed = ZenlinkOf::<T>::get_amount_in_by_path(lacking, &path)
+				.ok()
+				.and_then(|amounts| amounts.first().copied());
+			match paired_needed {
+				Some(paired_needed) if paired_needed <= paired_amount => {
+					let max_sold = match price {
+						Some(price) =>
+							balance_mul_div(lacking, FEE_DENOMINATOR, price, Rounding::Up)
+								.and_then(max_with_slippage)
+								.ok_or(Error::<T>::Math)?
+								.min(paired_amount),
+						None => paired_amount,
+					};
+					<ZenlinkOf<T> as ExportZenlink<_, _>>::inner_swap_assets_for_exact_assets(
+						&position_account,
+						lacking,
+						max_sold,
+						&path,
+						&position_account,
+					)?
+				},
+				_ => {
+					let min_bought = match price {
+						Some(price) =>
+							balance_mul_div(paired_amount, price, FEE_DENOMINATOR, Rounding::Down)
+								.and_then(min_with_slippage)
+								.ok_or(Error::<T>::Math)?,
+						None => Zero::zero(),
+					};
+					<ZenlinkOf<T> as ExportZenlink<_, _>>::inner_swap_exact_assets_for_assets(
+						&position_account,
+						paired_amount,
+						min_bought,
+						&path,
+						&position_account,
+					)?
+				},
+			}
+		}
+
+		let freed = ZenlinkAssetsOf::<T>::balance_of(asset, &position_account).min(amounts);
+		ensure!(
+			freed >= min_with_slippage(amounts.min(value)).ok_or(Error::<T>::Math)?,
+			Error::<T>::StrategySlippage
+		);
+		<T as Config>::MultiAsset::transfer(
+			underlying_asset_id,
+			&position_account,
+			vault_account,
+			freed,
+		)?;
+		Ok(freed)
+	}
+
+	fn harvest(
+		_vault_account: &T::AccountId,
+		_underlying_asset_id: <T as Config>::AssetId,
+	) -> Result<Balance, DispatchError> {
+		Ok(Zero::zero())
+	}
+
+	fn total_value_weight() -> Weight {
+		// the pair status, the lp balance, both reserves, both idle balances, the price and the
+		// debt
+		T::DbWeight::get().reads(8)
+	}
+
+	fn free_weight() -> Weight {
+		<T as zenlink_protocol::Config>::WeightInfo::remove_liquidity()
+			.saturating_add(
+				<T as zenlink_protocol::Config>::WeightInfo::swap_assets_for_exact_assets().max(
+					<T as zenlink_protocol::Config>::WeightInfo::swap_exact_assets_for_assets(),
+				),
+			)
+			.saturating_add(T::DbWeight::get().reads_writes(2, 2))
+			.saturating_add(Self::total_value_weight())
+	}
+}
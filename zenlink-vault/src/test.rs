@@ -602,6 +602,10 @@ fn redeem_with_ongoing_vault_should_work() {
 
 		assert_eq!(VaultPallet::max_redeem(underlying_asset_id, &ALICE), Ok(shares));
 		assert_eq!(VaultPallet::preview_redeem(underlying_asset_id, shares), Ok(amounts));
+		assert_eq!(
+			VaultPallet::preview_redeem_after_penalty(underlying_asset_id, shares),
+			Ok(expected_underlying_asset_received)
+		);
 
 		let underlying_balance_before = get_user_balance(underlying_asset_id, &BOB);
 
@@ -772,3 +776,452 @@ pub fn overflow_checked() {
 	assert_eq!(balance_mul_div(a, b, c, sp_arithmetic::Rounding::Down), Some(u128::MAX / 2));
 	assert_eq!(balance_mul_div(a, b, c, sp_arithmetic::Rounding::Up), Some(u128::MAX / 2 + 1));
 }
+
+#[test]
+fn strategy_allocate_and_harvest_should_work() {
+	new_test_ext().execute_with(|| {
+		assert_ok!(VaultPallet::create_vault_asset(
+			RawOrigin::Root.into(),
+			ASSET1,
+			TOKEN1_DECIMAL,
+			TOKEN1_DECIMAL,
+			0,
+			0,
+		));
+		assert_ok!(VaultPallet::deposit(
+			RawOrigin::Signed(ALICE).into(),
+			ASSET1,
+			10 * TOKEN1_UNIT,
+			ALICE
+		));
+		assert_eq!(get_user_balance(VAULT_ASSET1, &ALICE), 10 * TOKEN1_UNIT);
+
+		assert_noop!(
+			VaultPallet::allocate(RawOrigin::Root.into(), ASSET1, 4 * TOKEN1_UNIT),
+			Error::<Test>::ExceedDebtLimit
+		);
+		assert_noop!(
+			VaultPallet::set_debt_limit(RawOrigin::Signed(ALICE).into(), ASSET1, 6 * TOKEN1_UNIT),
+			BadOrigin
+		);
+		assert_ok!(VaultPallet::set_debt_limit(RawOrigin::Root.into(), ASSET1, 6 * TOKEN1_UNIT));
+
+		// the deployed assets still count in the total assets
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), ASSET1, 4 * TOKEN1_UNIT));
+		assert_eq!(VaultPallet::strategy_debt(ASSET1), 4 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(ASSET1, &STRATEGY), 4 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(ASSET1, &VAULT_PALLET_ACCOUNT), 6 * TOKEN1_UNIT);
+		assert_eq!(VaultPallet::total_assets(ASSET1), Ok(10 * TOKEN1_UNIT));
+		assert_noop!(
+			VaultPallet::allocate(RawOrigin::Root.into(), ASSET1, 3 * TOKEN1_UNIT),
+			Error::<Test>::ExceedDebtLimit
+		);
+
+		// the harvest raises the share price
+		HarvestYield::set(2 * TOKEN1_UNIT);
+		assert_ok!(VaultPallet::harvest(RawOrigin::Signed(BOB).into(), ASSET1));
+		assert_eq!(VaultPallet::total_assets(ASSET1), Ok(12 * TOKEN1_UNIT));
+		assert_eq!(VaultPallet::convert_to_assets(ASSET1, TOKEN1_UNIT), Ok(12 * TOKEN1_UNIT / 10));
+
+		assert_ok!(VaultPallet::deallocate(RawOrigin::Root.into(), ASSET1, TOKEN1_UNIT));
+		assert_eq!(VaultPallet::strategy_debt(ASSET1), 3 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(ASSET1, &VAULT_PALLET_ACCOUNT), 9 * TOKEN1_UNIT);
+
+		// a strategy freeing less than the withdrawal lacks fails it
+		FreeLimit::set(Some(TOKEN1_UNIT));
+		assert_noop!(
+			VaultPallet::redeem(RawOrigin::Signed(ALICE).into(), ASSET1, 10 * TOKEN1_UNIT, ALICE),
+			Error::<Test>::StrategyShortfall
+		);
+		FreeLimit::set(None);
+
+		// the lacking assets are taken back from the strategy
+		assert_ok!(VaultPallet::redeem(
+			RawOrigin::Signed(ALICE).into(),
+			ASSET1,
+			10 * TOKEN1_UNIT,
+			ALICE
+		));
+		assert_eq!(get_user_balance(ASSET1, &ALICE), 52 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(ASSET1, &STRATEGY), 0);
+		assert_eq!(VaultPallet::strategy_debt(ASSET1), 0);
+	});
+}
+
+#[test]
+fn vault_fees_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		assert_ok!(VaultPallet::create_vault_asset(
+			RawOrigin::Root.into(),
+			ASSET1,
+			TOKEN1_DECIMAL,
+			TOKEN1_DECIMAL,
+			0,
+			0,
+		));
+		assert_ok!(VaultPallet::deposit(
+			RawOrigin::Signed(ALICE).into(),
+			ASSET1,
+			10 * TOKEN1_UNIT,
+			ALICE
+		));
+
+		assert_noop!(
+			VaultPallet::set_vault_fees(RawOrigin::Signed(ALICE).into(), ASSET1, 0, 0, CHARLIE),
+			BadOrigin
+		);
+		assert_noop!(
+			VaultPallet::set_vault_fees(
+				RawOrigin::Root.into(),
+				ASSET1,
+				0,
+				2e18 as Balance,
+				CHARLIE
+			),
+			Error::<Test>::InvalidFee
+		);
+		assert_noop!(
+			VaultPallet::set_vault_fees(
+				RawOrigin::Root.into(),
+				ASSET1,
+				2e17 as Balance,
+				0,
+				CHARLIE
+			),
+			Error::<Test>::InvalidFee
+		);
+		// 10% management fee and 20% performance fee
+		assert_ok!(VaultPallet::set_vault_fees(
+			RawOrigin::Root.into(),
+			ASSET1,
+			1e17 as Balance,
+			2e17 as Balance,
+			CHARLIE
+		));
+		assert_eq!(VaultPallet::fee_state(ASSET1).high_water_mark, 1e18 as Balance);
+
+		// a tenth of a year charges 1% of the assets, already respected by the previews
+		System::set_block_number(1 + BlocksPerYear::get() / 10);
+		let pending_fees = VaultPallet::pending_fees(ASSET1).unwrap();
+		assert_eq!(pending_fees.management_fee_shares, 101010101010101010);
+		assert_eq!(pending_fees.performance_fee_shares, 0);
+		assert_eq!(
+			VaultPallet::preview_redeem(ASSET1, 10 * TOKEN1_UNIT),
+			Ok(99 * TOKEN1_UNIT / 10)
+		);
+
+		assert_ok!(VaultPallet::harvest(RawOrigin::Signed(BOB).into(), ASSET1));
+		System::assert_has_event(RuntimeEvent::Vaults(crate::Event::CollectFees {
+			underlying_asset: ASSET1,
+			receiver: CHARLIE,
+			management_fee_shares: 101010101010101010,
+			performance_fee_shares: 0,
+		}));
+		assert_eq!(get_user_balance(VAULT_ASSET1, &CHARLIE), 101010101010101010);
+		assert_eq!(
+			VaultPallet::preview_redeem(ASSET1, 10 * TOKEN1_UNIT),
+			Ok(99 * TOKEN1_UNIT / 10)
+		);
+		let pending_fees = VaultPallet::pending_fees(ASSET1).unwrap();
+		assert_eq!(pending_fees.management_fee_shares, 0);
+		assert_eq!(pending_fees.high_water_mark, 1e18 as Balance);
+
+		// the profit above the high water mark is charged
+		HarvestYield::set(2 * TOKEN1_UNIT);
+		assert_ok!(VaultPallet::harvest(RawOrigin::Signed(BOB).into(), ASSET1));
+		let pending_fees = VaultPallet::pending_fees(ASSET1).unwrap();
+		assert_eq!(pending_fees.management_fee_shares, 0);
+		assert_eq!(pending_fees.performance_fee_shares, 330144280074739044);
+
+		HarvestYield::set(0);
+		assert_ok!(VaultPallet::redeem(
+			RawOrigin::Signed(CHARLIE).into(),
+			ASSET1,
+			101010101010101010,
+			CHARLIE
+		));
+		assert_eq!(get_user_balance(VAULT_ASSET1, &CHARLIE), 330144280074739044);
+		assert_eq!(VaultPallet::fee_state(ASSET1).high_water_mark, 1150400000000000000);
+
+		// a vault left idle for years only charges the management fee of one year
+		let last_accrued = VaultPallet::fee_state(ASSET1).last_accrued;
+		System::set_block_number(last_accrued + BlocksPerYear::get());
+		let yearly_fees = VaultPallet::pending_fees(ASSET1).unwrap();
+		assert!(yearly_fees.management_fee_shares > 0);
+		System::set_block_number(last_accrued + BlocksPerYear::get() * 5);
+		assert_eq!(VaultPallet::pending_fees(ASSET1), Ok(yearly_fees));
+	});
+}
+
+fn setup_strategy_pools() {
+	assert_ok!(StableAmm::create_base_pool(
+		RawOrigin::Root.into(),
+		vec![STABLE_ASSET, Token(TOKEN5_SYMBOL)],
+		vec![18, 18],
+		50,
+		1e7 as Balance,
+		0,
+		ALICE,
+		Vec::from("stable_pool_lp"),
+	));
+	assert_ok!(StableAmm::add_liquidity(
+		RawOrigin::Signed(ALICE).into(),
+		0,
+		vec![100 * TOKEN1_UNIT, 100 * TOKEN1_UNIT],
+		0,
+		ALICE,
+		u64::MAX,
+	));
+
+	assert_ok!(Zenlink::create_pair(RawOrigin::Root.into(), TOKEN4_ASSET_ID, TOKEN5_ASSET_ID));
+	assert_ok!(Zenlink::add_liquidity(
+		RawOrigin::Signed(ALICE).into(),
+		TOKEN4_ASSET_ID,
+		TOKEN5_ASSET_ID,
+		500 * TOKEN1_UNIT,
+		500 * TOKEN1_UNIT,
+		0,
+		0,
+		u64::MAX
+	));
+}
+
+fn prepare_strategy_vault(underlying_asset_id: CurrencyId) {
+	assert_ok!(VaultPallet::create_vault_asset(
+		RawOrigin::Root.into(),
+		underlying_asset_id,
+		TOKEN1_DECIMAL,
+		TOKEN1_DECIMAL,
+		0,
+		0,
+	));
+	assert_ok!(VaultPallet::deposit(
+		RawOrigin::Signed(ALICE).into(),
+		underlying_asset_id,
+		10 * TOKEN1_UNIT,
+		ALICE
+	));
+	assert_ok!(VaultPallet::set_debt_limit(
+		RawOrigin::Root.into(),
+		underlying_asset_id,
+		10 * TOKEN1_UNIT
+	));
+}
+
+#[test]
+fn stable_amm_strategy_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_strategy_pools();
+		prepare_strategy_vault(STABLE_ASSET);
+
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), STABLE_ASSET, 4 * TOKEN1_UNIT));
+		assert_eq!(VaultPallet::strategy_debt(STABLE_ASSET), 4 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(STABLE_ASSET, &VAULT_PALLET_ACCOUNT), 6 * TOKEN1_UNIT);
+		// single sided liquidity pays the imbalance fee
+		let total_assets = VaultPallet::total_assets(STABLE_ASSET).unwrap();
+		assert!(total_assets < 10 * TOKEN1_UNIT && total_assets > 10 * TOKEN1_UNIT * 999 / 1000);
+
+		// the strategy funds the part of a withdrawal the idle assets lack
+		assert_ok!(VaultPallet::withdraw(
+			RawOrigin::Signed(ALICE).into(),
+			STABLE_ASSET,
+			8 * TOKEN1_UNIT,
+			BOB
+		));
+		assert_eq!(get_user_balance(STABLE_ASSET, &BOB), 8 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(STABLE_ASSET, &VAULT_PALLET_ACCOUNT), 0);
+		assert_eq!(VaultPallet::strategy_debt(STABLE_ASSET), 2 * TOKEN1_UNIT);
+
+		// the swap fees accrue in the position, so there is nothing to harvest
+		assert_ok!(VaultPallet::harvest(RawOrigin::Signed(BOB).into(), STABLE_ASSET));
+		System::assert_last_event(RuntimeEvent::Vaults(crate::Event::Harvest {
+			underlying_asset: STABLE_ASSET,
+			amounts: 0,
+		}));
+
+		// redeeming the rest frees the whole position
+		let value = StableStrategy::total_value(STABLE_ASSET);
+		let balance_before = get_user_balance(STABLE_ASSET, &ALICE);
+		let shares = get_user_balance(VaultToken(TOKEN3_SYMBOL), &ALICE);
+		assert_ok!(VaultPallet::redeem(
+			RawOrigin::Signed(ALICE).into(),
+			STABLE_ASSET,
+			shares,
+			ALICE
+		));
+		assert_eq!(get_user_balance(STABLE_ASSET, &ALICE), balance_before + value);
+		assert_eq!(StableStrategy::total_value(STABLE_ASSET), 0);
+
+		assert_noop!(
+			StableStrategy::deploy(&VAULT_PALLET_ACCOUNT, PAIR_ASSET, TOKEN1_UNIT),
+			Error::<Test>::InvalidStrategyPool
+		);
+	});
+}
+
+#[test]
+fn zenlink_pair_strategy_should_work() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_strategy_pools();
+		prepare_strategy_vault(PAIR_ASSET);
+
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), PAIR_ASSET, 4 * TOKEN1_UNIT));
+		assert_eq!(VaultPallet::strategy_debt(PAIR_ASSET), 4 * TOKEN1_UNIT);
+		assert_eq!(get_user_balance(PAIR_ASSET, &VAULT_PALLET_ACCOUNT), 6 * TOKEN1_UNIT);
+		// the swap into the pair pays the swap fee
+		let total_assets = VaultPallet::total_assets(PAIR_ASSET).unwrap();
+		assert!(total_assets < 10 * TOKEN1_UNIT && total_assets > 10 * TOKEN1_UNIT * 99 / 100);
+
+		// the strategy funds the part of a redeem the idle assets lack
+		let amounts = VaultPallet::preview_redeem(PAIR_ASSET, 8 * TOKEN1_UNIT).unwrap();
+		assert_ok!(VaultPallet::redeem(
+			RawOrigin::Signed(ALICE).into(),
+			PAIR_ASSET,
+			8 * TOKEN1_UNIT,
+			BOB
+		));
+		assert_eq!(get_user_balance(PAIR_ASSET, &BOB), amounts);
+		assert_eq!(get_user_balance(PAIR_ASSET, &VAULT_PALLET_ACCOUNT), 0);
+		assert_eq!(
+			VaultPallet::strategy_debt(PAIR_ASSET),
+			4 * TOKEN1_UNIT - (amounts - 6 * TOKEN1_UNIT)
+		);
+
+		// the swap fees accrue in the position, so there is nothing to harvest
+		assert_ok!(VaultPallet::harvest(RawOrigin::Signed(BOB).into(), PAIR_ASSET));
+		System::assert_last_event(RuntimeEvent::Vaults(crate::Event::Harvest {
+			underlying_asset: PAIR_ASSET,
+			amounts: 0,
+		}));
+
+		// selling the paired asset back costs the swap fee again
+		let value = PairStrategy::total_value(PAIR_ASSET);
+		assert_ok!(VaultPallet::deallocate(RawOrigin::Root.into(), PAIR_ASSET, value));
+		let freed = get_user_balance(PAIR_ASSET, &VAULT_PALLET_ACCOUNT);
+		assert!(freed <= value && freed > value * 99 / 100);
+
+		assert_noop!(
+			PairStrategy::deploy(&VAULT_PALLET_ACCOUNT, STABLE_ASSET, TOKEN1_UNIT),
+			Error::<Test>::InvalidStrategyPool
+		);
+	});
+}
+
+#[test]
+fn zenlink_pair_strategy_value_should_not_follow_spot_price() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_strategy_pools();
+		prepare_strategy_vault(PAIR_ASSET);
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), PAIR_ASSET, 4 * TOKEN1_UNIT));
+
+		// pumping the paired asset only adds the swap fee to the value
+		let value = PairStrategy::total_value(PAIR_ASSET);
+		assert_ok!(Zenlink::swap_exact_assets_for_assets(
+			RawOrigin::Signed(ALICE).into(),
+			100 * TOKEN1_UNIT,
+			0,
+			vec![TOKEN4_ASSET_ID, TOKEN5_ASSET_ID],
+			ALICE,
+			u64::MAX
+		));
+		let pumped = PairStrategy::total_value(PAIR_ASSET);
+		assert!(pumped >= value && pumped < value * 101 / 100);
+
+		// without a price the position is valued at the debt
+		PairPrice::set(None);
+		assert_eq!(PairStrategy::total_value(PAIR_ASSET), 4 * TOKEN1_UNIT);
+	});
+}
+
+#[test]
+fn stable_amm_strategy_should_bound_slippage() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_strategy_pools();
+		prepare_strategy_vault(STABLE_ASSET);
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), STABLE_ASSET, 4 * TOKEN1_UNIT));
+
+		// the pool pushed off par burns more lp for the scarce underlying asset than the moving
+		// average virtual price bounds
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			1,
+			0,
+			90 * TOKEN1_UNIT,
+			0,
+			ALICE,
+			u64::MAX
+		));
+		assert_noop!(
+			VaultPallet::withdraw(
+				RawOrigin::Signed(ALICE).into(),
+				STABLE_ASSET,
+				8 * TOKEN1_UNIT,
+				BOB
+			),
+			zenlink_stable_amm::Error::<Test>::AmountSlippage
+		);
+
+		// and mints less lp for the abundant one
+		assert_ok!(StableAmm::swap(
+			RawOrigin::Signed(ALICE).into(),
+			0,
+			0,
+			1,
+			180 * TOKEN1_UNIT,
+			0,
+			ALICE,
+			u64::MAX
+		));
+		assert_noop!(
+			VaultPallet::allocate(RawOrigin::Root.into(), STABLE_ASSET, 2 * TOKEN1_UNIT),
+			zenlink_stable_amm::Error::<Test>::AmountSlippage
+		);
+	});
+}
+
+#[test]
+fn zenlink_pair_strategy_should_bound_slippage() {
+	new_test_ext().execute_with(|| {
+		System::set_block_number(1);
+		setup_strategy_pools();
+		prepare_strategy_vault(PAIR_ASSET);
+		assert_ok!(VaultPallet::allocate(RawOrigin::Root.into(), PAIR_ASSET, 4 * TOKEN1_UNIT));
+
+		// pumping the paired asset away from the price fails both the swap into the pair and the
+		// removal of the liquidity
+		assert_ok!(Zenlink::swap_exact_assets_for_assets(
+			RawOrigin::Signed(ALICE).into(),
+			100 * TOKEN1_UNIT,
+			0,
+			vec![TOKEN4_ASSET_ID, TOKEN5_ASSET_ID],
+			ALICE,
+			u64::MAX
+		));
+		assert_noop!(
+			VaultPallet::allocate(RawOrigin::Root.into(), PAIR_ASSET, 2 * TOKEN1_UNIT),
+			zenlink_protocol::Error::<Test>::InsufficientTargetAmount
+		);
+		assert_noop!(
+			VaultPallet::withdraw(
+				RawOrigin::Signed(ALICE).into(),
+				PAIR_ASSET,
+				8 * TOKEN1_UNIT,
+				BOB
+			),
+			zenlink_protocol::Error::<Test>::InsufficientTargetAmount
+		);
+
+		// nor can it deploy without a price
+		PairPrice::set(None);
+		assert_noop!(
+			VaultPallet::allocate(RawOrigin::Root.into(), PAIR_ASSET, 2 * TOKEN1_UNIT),
+			Error::<Test>::NoStrategyPrice
+		);
+	});
+}
@@ -80,6 +80,13 @@ pub trait Vault<T: Config> {
 		shares: Balance,
 	) -> Result<Balance, DispatchError>;
 
+	/// Like `preview_redeem`, but net of the withdraw penalty, which is what a redeem call pays
+	/// out.
+	fn preview_redeem_after_penalty(
+		underlying_asset_id: T::AssetId,
+		shares: Balance,
+	) -> Result<Balance, DispatchError>;
+
 	/// Mints shares Vault shares to receiver by depositing exactly amount of underlying tokens.
 	fn deposit(
 		who: &T::AccountId,